@@ -0,0 +1,14 @@
+use std::path::Path;
+
+/// CJK font compiled in by the `bundled-font` feature. It is not part of the
+/// repository; see the readme for where to get it.
+const BUNDLED_FONT: &str = "assets/fonts/NotoSansTC-Regular.otf";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", BUNDLED_FONT);
+    println!("cargo:rustc-check-cfg=cfg(bundled_font_missing)");
+    if std::env::var_os("CARGO_FEATURE_BUNDLED_FONT").is_some() && !Path::new(BUNDLED_FONT).exists()
+    {
+        println!("cargo:rustc-cfg=bundled_font_missing");
+    }
+}
@@ -0,0 +1,124 @@
+use crate::error::LoadError;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Returns `true` for archive bundles such as `.zip`.
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+/// Lists the entries of a zip archive that one of the loaders can open.
+pub fn list_archive_entries(path: &Path) -> Result<Vec<String>, LoadError> {
+    let archive = open_archive(path)?;
+
+    let mut entries = Vec::new();
+    for name in archive.file_names() {
+        let name = name.map_err(|e| LoadError::OpenArchive(e.to_string()))?;
+        if !name.ends_with('/') && crate::data_loader::is_supported(Path::new(name.as_ref())) {
+            entries.push(name.into_owned());
+        }
+    }
+    entries.sort();
+
+    Ok(entries)
+}
+
+/// An archive entry extracted into a temporary directory of its own. The
+/// directory is removed when this is dropped, so it is kept for as long as
+/// the entry is open.
+#[derive(Debug)]
+pub struct ExtractedEntry {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl ExtractedEntry {
+    /// The extracted file, which can be opened like any other file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ExtractedEntry {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Extracts one archive entry into a new temporary directory.
+pub fn extract_archive_entry(path: &Path, entry: &str) -> Result<ExtractedEntry, LoadError> {
+    static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+    let mut archive = open_archive(path)?;
+    let extract_error = |detail: String| LoadError::ExtractEntry {
+        entry: entry.to_string(),
+        detail,
+    };
+    let mut file = archive
+        .by_name(entry)
+        .map_err(|e| extract_error(e.to_string()))?;
+
+    let file_name = file
+        .enclosed_name()
+        .and_then(|p| p.file_name().map(|n| n.to_owned()))
+        .ok_or_else(|| LoadError::InvalidEntryName(entry.to_string()))?;
+
+    let dir = std::env::temp_dir().join("rust_excel_reader").join(format!(
+        "entry_{}_{}",
+        std::process::id(),
+        NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).map_err(|e| extract_error(e.to_string()))?;
+    // Owned from here on, so the directory is removed if extraction fails
+    let extracted = ExtractedEntry {
+        path: dir.join(file_name),
+        dir,
+    };
+    let mut out = File::create(&extracted.path).map_err(|e| extract_error(e.to_string()))?;
+    io::copy(&mut file, &mut out).map_err(|e| extract_error(e.to_string()))?;
+
+    Ok(extracted)
+}
+
+fn open_archive(path: &Path) -> Result<zip::ZipArchive<File>, LoadError> {
+    let file = File::open(path).map_err(|e| LoadError::Io(e.to_string()))?;
+    zip::ZipArchive::new(file).map_err(|e| LoadError::OpenArchive(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Scratch;
+    use std::io::Write;
+
+    #[test]
+    fn extracted_entries_are_removed_on_drop() {
+        let scratch = Scratch::new("archive_extract");
+        let archive = scratch.path("lots.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("data/lot.csv", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"wafer,bin\nW01,1\n").unwrap();
+        zip.finish().unwrap();
+
+        assert_eq!(list_archive_entries(&archive).unwrap(), ["data/lot.csv"]);
+        let first = extract_archive_entry(&archive, "data/lot.csv").unwrap();
+        let second = extract_archive_entry(&archive, "data/lot.csv").unwrap();
+        assert_ne!(first.path(), second.path());
+        assert_eq!(first.path().file_name().unwrap(), "lot.csv");
+        assert_eq!(
+            std::fs::read_to_string(first.path()).unwrap(),
+            "wafer,bin\nW01,1\n"
+        );
+
+        let dir = first.path().parent().unwrap().to_path_buf();
+        drop(first);
+        assert!(!dir.exists());
+        assert!(second.path().exists());
+        assert!(extract_archive_entry(&archive, "missing.csv").is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::compressed::FileStamp;
+use crate::data_loader::{ColumnType, DataLoadResult, DataPage};
+use crate::error::LoadError;
+use arrow::array::RecordBatch;
+use arrow::datatypes::{DataType, Schema};
+use arrow::ipc::reader::FileReader;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use once_cell::sync::Lazy;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Row counts of the record batches in an Arrow IPC file, which unlike
+/// Parquet row groups are not recorded in the footer.
+type BatchRowCache = HashMap<PathBuf, (FileStamp, Vec<usize>)>;
+
+static IPC_BATCH_ROWS: Lazy<Mutex<BatchRowCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Loads `num_rows` rows from a Parquet (`.parquet`) or Arrow IPC
+/// (`.arrow`, `.feather`) file, starting at `start_row`.
+///
+/// The sheet list holds "All rows" followed by one entry per row group (or
+/// record batch for IPC files); `sheet_index` selects among them. Only the
+/// row groups that overlap the requested page are decoded.
+pub async fn load_columnar_data(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+) -> DataLoadResult {
+    let result = tokio::task::spawn_blocking(move || {
+        if is_parquet(&path) {
+            load_parquet_data_sync(path, start_row, num_rows, sheet_index)
+        } else {
+            load_ipc_data_sync(path, start_row, num_rows, sheet_index)
+        }
+    })
+    .await;
+
+    result.map_err(|e| LoadError::Task(e.to_string()))?
+}
+
+/// Blocking Parquet variant of [`load_columnar_data`].
+pub fn load_parquet_data_sync(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+) -> DataLoadResult {
+    let file = File::open(&path).map_err(|e| e.to_string())?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+        .map_err(|e| format!("Failed to open Parquet file: {}", e))?;
+
+    let group_rows: Vec<usize> = builder
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|group| group.num_rows() as usize)
+        .collect();
+    let schema = builder.schema().clone();
+    let window = RowWindow::new(&group_rows, sheet_index, start_row, num_rows);
+
+    let mut rows = Vec::new();
+    if !window.groups.is_empty() {
+        let reader = builder
+            .with_row_groups(window.groups.clone())
+            .with_offset(window.offset)
+            .with_limit(num_rows)
+            .with_batch_size(num_rows.clamp(1, 8192))
+            .build()
+            .map_err(|e| format!("Failed to read Parquet file: {}", e))?;
+
+        for batch in reader {
+            let batch = batch.map_err(|e| format!("Failed to read Parquet file: {}", e))?;
+            append_rows(&batch, 0, batch.num_rows(), &mut rows)?;
+        }
+    }
+
+    Ok(page(&schema, rows, "Row group", &group_rows, start_row, window.total))
+}
+
+/// Blocking Arrow IPC variant of [`load_columnar_data`].
+pub fn load_ipc_data_sync(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+) -> DataLoadResult {
+    let batch_rows = ipc_batch_rows(&path)?;
+    let window = RowWindow::new(&batch_rows, sheet_index, start_row, num_rows);
+
+    let file = File::open(&path).map_err(|e| e.to_string())?;
+    let mut reader = FileReader::try_new(file, None).map_err(ipc_error)?;
+    let schema = reader.schema();
+
+    let mut rows = Vec::new();
+    let mut offset = window.offset;
+    for &index in &window.groups {
+        reader.set_index(index).map_err(ipc_error)?;
+        let Some(batch) = reader.next() else { break };
+        let batch = batch.map_err(ipc_error)?;
+
+        let wanted = num_rows - rows.len();
+        let end = batch.num_rows().min(offset.saturating_add(wanted));
+        append_rows(&batch, offset, end, &mut rows)?;
+        offset = 0;
+    }
+
+    Ok(page(&schema, rows, "Batch", &batch_rows, start_row, window.total))
+}
+
+/// Number of rows in sheet `sheet_index` of a Parquet or Arrow IPC file,
+/// read from the footer and batch headers without decoding any column.
+pub fn columnar_row_count(path: &Path, sheet_index: usize) -> Result<usize, String> {
+    let group_rows = if is_parquet(path) {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+            .map_err(|e| format!("Failed to open Parquet file: {}", e))?;
+        builder
+            .metadata()
+            .row_groups()
+            .iter()
+            .map(|group| group.num_rows() as usize)
+            .collect()
+    } else {
+        ipc_batch_rows(path)?
+    };
+    Ok(RowWindow::new(&group_rows, sheet_index, 0, 0).total)
+}
+
+/// Returns `true` for Parquet files; everything else handled by this module
+/// is Arrow IPC.
+pub fn is_parquet(path: &Path) -> bool {
+    path.extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("parquet"))
+}
+
+/// Maps an Arrow data type to the column type shown in the table header.
+pub fn column_type(data_type: &DataType) -> ColumnType {
+    match data_type {
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => ColumnType::Text,
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => ColumnType::Integer,
+        DataType::Float16
+        | DataType::Float32
+        | DataType::Float64
+        | DataType::Decimal32(_, _)
+        | DataType::Decimal64(_, _)
+        | DataType::Decimal128(_, _)
+        | DataType::Decimal256(_, _) => ColumnType::Float,
+        DataType::Boolean => ColumnType::Boolean,
+        DataType::Date32 | DataType::Date64 => ColumnType::Date,
+        DataType::Timestamp(_, _) => ColumnType::DateTime,
+        DataType::Dictionary(_, value_type) => column_type(value_type),
+        _ => ColumnType::Other,
+    }
+}
+
+/// The row groups that overlap a requested page.
+struct RowWindow {
+    /// Indices of the row groups to decode, in file order.
+    groups: Vec<usize>,
+    /// Rows to skip in the first of `groups`.
+    offset: usize,
+    /// Total rows in the selected sheet.
+    total: usize,
+}
+
+impl RowWindow {
+    /// `sheet_index` 0 covers every group; `n > 0` covers group `n - 1` only.
+    fn new(group_rows: &[usize], sheet_index: usize, start_row: usize, num_rows: usize) -> Self {
+        let selected: Vec<usize> = match sheet_index {
+            index if index > 0 && index <= group_rows.len() => vec![index - 1],
+            _ => (0..group_rows.len()).collect(),
+        };
+
+        let total = selected.iter().map(|&g| group_rows[g]).sum();
+        let end_row = start_row.saturating_add(num_rows);
+        let mut groups = Vec::new();
+        let mut offset = 0;
+        let mut group_start = 0;
+
+        for g in selected {
+            let group_end = group_start + group_rows[g];
+            if group_end > start_row && group_start < end_row {
+                if groups.is_empty() {
+                    offset = start_row - group_start;
+                }
+                groups.push(g);
+            }
+            group_start = group_end;
+        }
+
+        Self { groups, offset, total }
+    }
+}
+
+fn page(
+    schema: &Schema,
+    rows: Vec<Vec<String>>,
+    group_label: &str,
+    group_rows: &[usize],
+    start_row: usize,
+    total: usize,
+) -> DataPage {
+    let mut sheet_names = vec!["All rows".to_string()];
+    sheet_names.extend(
+        group_rows
+            .iter()
+            .enumerate()
+            .map(|(i, count)| format!("{} {} ({} rows)", group_label, i + 1, count)),
+    );
+
+    let (headers, column_types) = if start_row == 0 {
+        schema
+            .fields()
+            .iter()
+            .map(|field| (field.name().clone(), column_type(field.data_type())))
+            .unzip()
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    DataPage {
+        end_of_file: start_row + rows.len() >= total,
+        headers,
+        rows,
+        sheet_names,
+        column_types,
+        ..DataPage::default()
+    }
+}
+
+/// Formats rows `start..end` of `batch` as text.
+fn append_rows(
+    batch: &RecordBatch,
+    start: usize,
+    end: usize,
+    rows: &mut Vec<Vec<String>>,
+) -> Result<(), String> {
+    let options = FormatOptions::default();
+    let formatters = batch
+        .columns()
+        .iter()
+        .map(|column| ArrayFormatter::try_new(column.as_ref(), &options))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for row in start..end {
+        rows.push(
+            formatters
+                .iter()
+                .map(|formatter| formatter.value(row).to_string())
+                .collect(),
+        );
+    }
+
+    Ok(())
+}
+
+fn ipc_batch_rows(path: &Path) -> Result<Vec<usize>, String> {
+    let stamp = FileStamp::read(path)?;
+    let mut cache = IPC_BATCH_ROWS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_stamp, rows)) = cache.get(path) {
+        if *cached_stamp == stamp {
+            return Ok(rows.clone());
+        }
+    }
+
+    // An empty projection reads the batch headers without decoding columns
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let reader = FileReader::try_new(file, Some(Vec::new())).map_err(ipc_error)?;
+    let rows = reader
+        .map(|batch| batch.map(|b| b.num_rows()).map_err(ipc_error))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    cache.insert(path.to_path_buf(), (stamp, rows.clone()));
+    Ok(rows)
+}
+
+fn ipc_error(e: arrow::error::ArrowError) -> String {
+    format!("Failed to read Arrow file: {}", e)
+}
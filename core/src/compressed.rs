@@ -0,0 +1,354 @@
+use crate::csv_loader::{csv_reader, read_header, read_rows, CsvRows, ParseIssue, Tolerance};
+use crate::data_loader::{DataLoadResult, DataPage};
+use crate::progress::Progress;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Number of data rows between two checkpoints in a [`StreamIndex`].
+const CHECKPOINT_INTERVAL: usize = 10_000;
+
+/// Maximum number of compressed files whose index is kept in memory.
+const MAX_CACHED_INDEXES: usize = 8;
+
+static INDEXES: Lazy<Mutex<HashMap<PathBuf, StreamIndex>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Compression formats that are decompressed transparently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// Detects the compression format from the last extension of `path`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            "bz2" => Some(Compression::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// Opens `path` and returns a reader over the decompressed bytes.
+    pub fn open(self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        self.decoder(BufReader::new(File::open(path)?))
+    }
+
+    /// Returns a reader over the decompressed bytes of `file`, which yields
+    /// the compressed bytes.
+    pub fn decoder<R>(self, file: R) -> io::Result<Box<dyn Read + Send>>
+    where
+        R: BufRead + Send + 'static,
+    {
+        Ok(match self {
+            // Multi-member streams are common when logs are appended with `gzip >>`
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+            Compression::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+            Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(file)),
+        })
+    }
+}
+
+/// Returns the extension of a compressed file once the compression suffix is
+/// removed, e.g. `"csv"` for `lot.csv.gz`.
+pub fn inner_extension(path: &Path) -> Option<String> {
+    Compression::from_path(path)?;
+    let stem = Path::new(path.file_stem()?);
+    stem.extension()?.to_str().map(|s| s.to_ascii_lowercase())
+}
+
+/// Loads a page of a compressed CSV file.
+///
+/// Compressed streams cannot be seeked, so each file gets a [`StreamIndex`]
+/// that remembers the decompressed byte offset of every
+/// [`CHECKPOINT_INTERVAL`]th data row, plus the open cursor of the last page.
+/// Sequential paging continues from the cursor; a jump backwards restarts
+/// decompression but skips to the nearest checkpoint without CSV parsing.
+/// The index is built for one `tolerance`, as skipped and repaired records
+/// change the row numbers. Rows parsed on the way are added to `progress`.
+pub fn load_compressed_csv_sync(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    tolerance: Tolerance,
+    progress: &Progress,
+) -> DataLoadResult {
+    let compression = Compression::from_path(&path)
+        .ok_or_else(|| format!("'{}' is not a compressed file", path.display()))?;
+    let stamp = FileStamp::read(&path)?;
+
+    // The index is taken out while the page is read, so that loads of other
+    // files do not wait for the decompression
+    let cached = INDEXES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&path)
+        .filter(|index| index.stamp == stamp && index.tolerance == tolerance);
+    let mut index = match cached {
+        Some(index) => index,
+        None => StreamIndex::build(&path, compression, stamp, tolerance)?,
+    };
+
+    let page = index.read_page(&path, compression, start_row, num_rows, progress)?;
+    let headers = if start_row == 0 {
+        index.headers.clone()
+    } else {
+        Vec::new()
+    };
+
+    let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+    if indexes.len() >= MAX_CACHED_INDEXES {
+        indexes.clear();
+    }
+    indexes.insert(path, index);
+    drop(indexes);
+
+    Ok(DataPage {
+        headers,
+        rows: page.rows,
+        sheet_names: vec!["CSV".to_string()],
+        end_of_file: page.end_of_file,
+        parse_issues: page.issues,
+        ..DataPage::default()
+    })
+}
+
+/// Modification time and size, used to notice that a cached index is stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    pub(crate) fn read(path: &Path) -> Result<Self, String> {
+        let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+        Ok(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+/// Rows read by [`StreamIndex::read_page`], with the issues found in them.
+struct PageRows {
+    rows: Vec<Vec<String>>,
+    end_of_file: bool,
+    issues: Vec<ParseIssue>,
+}
+
+/// An open decompression stream positioned at data row `next_row`.
+struct StreamCursor {
+    rows: CsvRows<Box<dyn Read + Send>>,
+    next_row: usize,
+}
+
+struct StreamIndex {
+    stamp: FileStamp,
+    tolerance: Tolerance,
+    headers: Vec<String>,
+    /// `(data_row, lines_before, decompressed_byte_offset)`, sorted by row.
+    checkpoints: Vec<(usize, u64, u64)>,
+    cursor: Option<StreamCursor>,
+}
+
+impl StreamIndex {
+    fn build(
+        path: &Path,
+        compression: Compression,
+        stamp: FileStamp,
+        tolerance: Tolerance,
+    ) -> Result<Self, String> {
+        let open = || compression.open(path).map_err(|e| e.to_string());
+        let (header_line, headers) = read_header(open)?;
+
+        // Find where the first data row starts in the decompressed stream
+        let mut rdr = csv_reader(open()?);
+        for _ in 0..=header_line {
+            if rdr.records().next().is_none() {
+                break;
+            }
+        }
+        let data_offset = rdr.position().byte();
+        let data_line = rdr.position().line().saturating_sub(1);
+        let width = headers.len();
+
+        Ok(Self {
+            stamp,
+            tolerance,
+            headers,
+            checkpoints: vec![(0, data_line, data_offset)],
+            cursor: Some(StreamCursor {
+                rows: CsvRows::new(rdr, tolerance, width),
+                next_row: 0,
+            }),
+        })
+    }
+
+    fn read_page(
+        &mut self,
+        path: &Path,
+        compression: Compression,
+        start_row: usize,
+        num_rows: usize,
+        progress: &Progress,
+    ) -> Result<PageRows, String> {
+        let mut cursor = self.cursor_for(path, compression, start_row)?;
+        if num_rows != usize::MAX {
+            progress.set_total_rows((start_row - cursor.next_row).saturating_add(num_rows));
+        }
+
+        // Walk forward to the requested row, recording checkpoints on the way
+        while cursor.next_row < start_row {
+            match cursor.rows.next() {
+                Some(row) => row?,
+                None => {
+                    self.cursor = Some(cursor);
+                    return Ok(PageRows {
+                        rows: Vec::new(),
+                        end_of_file: true,
+                        issues: Vec::new(),
+                    });
+                }
+            };
+            cursor.next_row += 1;
+            progress.add_rows(1);
+            self.record_checkpoint(&cursor);
+        }
+        cursor.rows.take_issues();
+
+        let (data, end_of_file) = read_rows(&mut cursor.rows, num_rows)?;
+        cursor.next_row += data.len();
+        progress.add_rows(data.len());
+        self.record_checkpoint(&cursor);
+        let issues = cursor.rows.take_issues();
+        self.cursor = Some(cursor);
+
+        Ok(PageRows {
+            rows: data,
+            end_of_file,
+            issues,
+        })
+    }
+
+    /// Returns the cached cursor if it has not passed `row` yet, otherwise
+    /// opens a new stream at the last checkpoint before `row`.
+    fn cursor_for(
+        &mut self,
+        path: &Path,
+        compression: Compression,
+        row: usize,
+    ) -> Result<StreamCursor, String> {
+        let (checkpoint_row, checkpoint_line, checkpoint_offset) = self
+            .checkpoints
+            .iter()
+            .rev()
+            .find(|(checkpoint_row, _, _)| *checkpoint_row <= row)
+            .copied()
+            .unwrap_or(self.checkpoints[0]);
+
+        if let Some(cursor) = self.cursor.take() {
+            if cursor.next_row <= row && cursor.next_row >= checkpoint_row {
+                return Ok(cursor);
+            }
+        }
+
+        let mut stream = compression.open(path).map_err(|e| e.to_string())?;
+        io::copy(&mut (&mut stream).take(checkpoint_offset), &mut io::sink())
+            .map_err(|e| e.to_string())?;
+
+        let rows = CsvRows::new(csv_reader(stream), self.tolerance, self.headers.len())
+            .starting_at(checkpoint_row, checkpoint_line, checkpoint_offset);
+        Ok(StreamCursor {
+            rows,
+            next_row: checkpoint_row,
+        })
+    }
+
+    /// Rows split off a broken record are still pending in the reader
+    /// between record boundaries, where no checkpoint can be taken.
+    fn record_checkpoint(&mut self, cursor: &StreamCursor) {
+        let last_row = self.checkpoints.last().map_or(0, |(row, _, _)| *row);
+        if cursor.next_row >= last_row + CHECKPOINT_INTERVAL {
+            if let Some(checkpoint) = cursor.rows.boundary() {
+                self.checkpoints.push(checkpoint);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_loader::load_csv_data_sync;
+    use crate::test_support::Scratch;
+    use std::io::Write;
+
+    #[test]
+    fn pages_past_a_checkpoint_match_the_plain_file() {
+        let scratch = Scratch::new("compressed_checkpoints");
+        let mut text = String::from("id,name,note\n");
+        for row in 0..25_000 {
+            match row {
+                // Line breaks in quotes and a short record move the line
+                // numbers away from the row numbers
+                7 | 12_345 => text.push_str(&format!("{row},\"two\nlines\",x\n")),
+                15_000 => text.push_str(&format!("{row},short\n")),
+                _ => text.push_str(&format!("{row},name {row},\n")),
+            }
+        }
+        let plain = scratch.path("rows.csv");
+        std::fs::write(&plain, &text).unwrap();
+        let gz = scratch.path("rows.csv.gz");
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&gz).unwrap(), flate2::Compression::fast());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let load = |path: &Path, start: usize| {
+            let progress = Progress::new();
+            let page = if Compression::from_path(path).is_some() {
+                load_compressed_csv_sync(
+                    path.to_path_buf(),
+                    start,
+                    100,
+                    Tolerance::Repair,
+                    &progress,
+                )
+            } else {
+                load_csv_data_sync(path.to_path_buf(), start, 100, Tolerance::Repair, &progress)
+            };
+            page.unwrap()
+        };
+        // Walking to the end records the checkpoints; the pages read after
+        // that start from the one at row 10,000
+        for start in [0, 24_950, 14_950, 12_300] {
+            let expected = load(&plain, start);
+            let page = load(&gz, start);
+            assert_eq!(page.rows, expected.rows, "page at {start}");
+            assert_eq!(page.end_of_file, expected.end_of_file);
+            assert_eq!(page.parse_issues, expected.parse_issues);
+        }
+        let checkpoints = INDEXES
+            .lock()
+            .unwrap()
+            .get(&gz)
+            .unwrap()
+            .checkpoints
+            .clone();
+        assert!(checkpoints.iter().any(|&(row, _, _)| row == 10_000));
+
+        let page = load(&gz, 14_950);
+        assert_eq!(page.rows[50], ["15000", "short", ""]);
+        assert_eq!(page.parse_issues.len(), 1);
+        assert_eq!(page.parse_issues[0].line, 15_004);
+    }
+}
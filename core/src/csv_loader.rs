@@ -0,0 +1,820 @@
+use crate::data_loader::{DataLoadResult, DataPage};
+use crate::error::LoadError;
+use crate::progress::{Progress, ProgressReader};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tokio::task;
+use regex::Regex;
+
+/// How records that do not parse cleanly are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Tolerance {
+    /// Invalid UTF-8 stops the load; other issues are only reported.
+    #[default]
+    Strict,
+    /// Bad records are left out and reported.
+    Skip,
+    /// Bad records are repaired as far as possible and reported: invalid
+    /// bytes are replaced, short rows padded with empty cells and the lines
+    /// swallowed by an unterminated quote read as records of their own.
+    Repair,
+}
+
+/// What is wrong with a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// A quote opened a field that ran on over the following `lines` lines,
+    /// which look like records of their own.
+    UnterminatedQuote { lines: usize },
+    InvalidUtf8,
+    /// The record has a different number of fields than the header.
+    FieldCount { expected: usize, found: usize },
+}
+
+/// A record of a CSV file that did not parse cleanly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseIssue {
+    /// Data row the record became or, when it was skipped, the data row
+    /// that follows it.
+    pub row: usize,
+    /// Line of the file the record starts on, from 1.
+    pub line: u64,
+    /// Byte offset of the record in the file; in the decompressed stream
+    /// for compressed files.
+    pub byte: u64,
+    pub kind: IssueKind,
+    /// The record was left out of the table.
+    pub skipped: bool,
+}
+
+/// Loads `num_rows` data rows from a CSV file, starting at `start_row`.
+///
+/// Headers are only returned when `start_row == 0`. Files with a preamble are
+/// handled through [`detect_file_structure`]. Records that do not parse
+/// cleanly are handled as `tolerance` says and listed in the page.
+pub async fn load_csv_data(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    tolerance: Tolerance,
+) -> DataLoadResult {
+    let result = task::spawn_blocking(move || {
+        load_csv_data_sync(path, start_row, num_rows, tolerance, &Progress::new())
+    }).await;
+
+    result.map_err(|e| LoadError::Task(e.to_string()))?
+}
+
+/// Blocking variant of [`load_csv_data`].
+///
+/// Compressed files (`.csv.gz`, `.csv.zst`, `.csv.bz2`) are handed to
+/// [`crate::compressed`], which keeps a checkpoint index so later pages do not
+/// have to re-parse the whole stream. Rows skipped to reach `start_row` and
+/// rows read are added to `progress`.
+pub fn load_csv_data_sync(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    tolerance: Tolerance,
+    progress: &Progress,
+) -> DataLoadResult {
+    if crate::compressed::Compression::from_path(&path).is_some() {
+        return crate::compressed::load_compressed_csv_sync(
+            path, start_row, num_rows, tolerance, progress,
+        );
+    }
+
+    let open = || std::fs::File::open(&path).map_err(|e| LoadError::Io(e.to_string()));
+    let (header_line, header_fields) = read_header(open)?;
+
+    let mut rdr = csv_reader(open()?);
+
+    // Skip the preamble and the header line
+    for _ in 0..header_line + 1 {
+        if rdr.records().next().is_none() {
+            break;
+        }
+    }
+
+    // Skipped and repaired records change the row numbers, so the rows that
+    // were already loaded are skipped the same way they were read
+    let mut rows = CsvRows::new(rdr, tolerance, header_fields.len());
+    if num_rows != usize::MAX {
+        progress.set_total_rows(start_row.saturating_add(num_rows));
+    }
+    for row in rows.by_ref().take(start_row) {
+        row?;
+        progress.add_rows(1);
+    }
+    rows.take_issues();
+
+    let (data, end_of_file) = read_rows(&mut rows, num_rows)?;
+    progress.add_rows(data.len());
+    let headers = if start_row == 0 { header_fields } else { Vec::new() };
+
+    Ok(DataPage {
+        headers,
+        rows: data,
+        sheet_names: vec!["CSV".to_string()],
+        end_of_file,
+        parse_issues: rows.take_issues(),
+        ..DataPage::default()
+    })
+}
+
+/// Builds a CSV reader with the settings every loader in this crate uses:
+/// no implicit header row and tolerance for preamble lines of any width.
+pub(crate) fn csv_reader<R: Read>(reader: R) -> csv::Reader<R> {
+    csv::ReaderBuilder::new()
+        .flexible(true)
+        .has_headers(false)
+        .from_reader(reader)
+}
+
+/// Detects the file structure and returns `(header_line, header_fields)`.
+///
+/// `open` is called twice: once to sample the structure and once to read the
+/// header record itself.
+pub(crate) fn read_header<R, F, E>(open: F) -> Result<(usize, Vec<String>), LoadError>
+where
+    R: Read,
+    F: Fn() -> Result<R, E>,
+    E: Into<LoadError>,
+{
+    let mut sample = csv_reader(open().map_err(Into::into)?);
+    let header_line = match detect_file_structure(&mut sample)? {
+        FileStructure::Simple => 0,
+        FileStructure::Mixed { header_line } => header_line,
+    };
+
+    let mut rdr = csv_reader(open().map_err(Into::into)?);
+    let headers = match rdr.records().nth(header_line) {
+        Some(record) => record
+            .map_err(|e| LoadError::Parse(e.to_string()))?
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    Ok((header_line, headers))
+}
+
+/// Opens a plain or compressed CSV file for reading.
+pub(crate) fn open_csv_source(path: &Path) -> Result<Box<dyn Read + Send>, String> {
+    match crate::compressed::Compression::from_path(path) {
+        Some(compression) => compression.open(path).map_err(|e| e.to_string()),
+        None => Ok(Box::new(
+            std::fs::File::open(path).map_err(|e| e.to_string())?,
+        )),
+    }
+}
+
+/// [`open_csv_source`] that adds the bytes read from the file to `progress`
+/// and sets its total to the size of the file. For compressed files these
+/// are the compressed bytes.
+pub(crate) fn open_tracked_csv_source(
+    path: &Path,
+    progress: &Progress,
+) -> Result<Box<dyn Read + Send>, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    if let Ok(metadata) = file.metadata() {
+        progress.set_total_bytes(metadata.len());
+    }
+    let file = ProgressReader::new(file, progress.clone());
+    match crate::compressed::Compression::from_path(path) {
+        Some(compression) => compression
+            .decoder(std::io::BufReader::new(file))
+            .map_err(|e| e.to_string()),
+        None => Ok(Box::new(file)),
+    }
+}
+
+/// Records that did not parse cleanly in a whole file, as found by
+/// [`scan_parse_issues`].
+#[derive(Debug, Clone, Default)]
+pub struct IssueScan {
+    /// The first issues, up to the limit of the scan.
+    pub issues: Vec<ParseIssue>,
+    /// Issues found in all, listed or not.
+    pub total: usize,
+}
+
+/// Reads a whole plain or compressed CSV file the way the loader does with
+/// `tolerance` and lists the records that do not parse cleanly, keeping the
+/// first `limit`. Bytes and rows read are reported to `progress`.
+pub fn scan_parse_issues(
+    path: &Path,
+    tolerance: Tolerance,
+    limit: usize,
+    progress: &Progress,
+) -> Result<IssueScan, String> {
+    let (header_line, headers) = read_header(|| open_csv_source(path))?;
+    let mut rdr = csv_reader(open_tracked_csv_source(path, progress)?);
+    for _ in 0..=header_line {
+        if rdr.records().next().is_none() {
+            break;
+        }
+    }
+
+    let mut rows = CsvRows::new(rdr, tolerance, headers.len());
+    let mut scan = IssueScan::default();
+    loop {
+        let row = rows.next().transpose()?;
+        for issue in rows.take_issues() {
+            scan.total += 1;
+            if scan.issues.len() < limit {
+                scan.issues.push(issue);
+            }
+        }
+        if row.is_none() {
+            return Ok(scan);
+        }
+        progress.add_rows(1);
+    }
+}
+
+/// Returns the `key,value` lines before the header of a mixed file, such as
+/// `Wafer ID,W01`. Lines without a key or value are skipped; simple files
+/// have no preamble.
+pub fn read_preamble(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let (header_line, _) = read_header(|| open_csv_source(path))?;
+    let mut preamble = Vec::new();
+    for record in csv_reader(open_csv_source(path)?).records().take(header_line) {
+        let record = record.map_err(|e| e.to_string())?;
+        let mut fields = record.iter().map(str::trim);
+        let key = fields.next().unwrap_or_default();
+        let value = fields.filter(|f| !f.is_empty()).collect::<Vec<_>>().join(", ");
+        if !key.is_empty() && !value.is_empty() {
+            preamble.push((key.to_string(), value));
+        }
+    }
+    Ok(preamble)
+}
+
+/// Reads up to `num_rows` rows; the flag is `true` when the input ran out.
+pub(crate) fn read_rows<R: Read>(
+    rows: &mut CsvRows<R>,
+    num_rows: usize,
+) -> Result<(Vec<Vec<String>>, bool), LoadError> {
+    let mut data = Vec::new();
+
+    for _ in 0..num_rows {
+        match rows.next() {
+            Some(row) => data.push(row?),
+            None => return Ok((data, true)),
+        }
+    }
+
+    Ok((data, false))
+}
+
+/// The data rows of a CSV reader positioned after the header, with records
+/// that do not parse cleanly handled as a [`Tolerance`] says. What was
+/// found is kept until [`CsvRows::take_issues`].
+pub(crate) struct CsvRows<R> {
+    rdr: csv::Reader<R>,
+    tolerance: Tolerance,
+    /// Fields of the header; 0 turns the field-count check off.
+    width: usize,
+    /// Data row the next row returned becomes.
+    next_row: usize,
+    /// Lines and bytes of the file before the point `rdr` started reading.
+    base_line: u64,
+    base_byte: u64,
+    /// Records split off a record with an unterminated quote, with their
+    /// line and byte offset, not returned yet.
+    pending: VecDeque<(csv::ByteRecord, u64, u64)>,
+    issues: Vec<ParseIssue>,
+}
+
+impl<R: Read> CsvRows<R> {
+    pub(crate) fn new(rdr: csv::Reader<R>, tolerance: Tolerance, width: usize) -> Self {
+        Self {
+            rdr,
+            tolerance,
+            width,
+            next_row: 0,
+            base_line: 0,
+            base_byte: 0,
+            pending: VecDeque::new(),
+            issues: Vec::new(),
+        }
+    }
+
+    /// Rows of a reader that starts at data row `row`, after `line` lines
+    /// and `byte` bytes of the file, e.g. at a checkpoint.
+    pub(crate) fn starting_at(mut self, row: usize, line: u64, byte: u64) -> Self {
+        self.next_row = row;
+        self.base_line = line;
+        self.base_byte = byte;
+        self
+    }
+
+    /// Data row, line and byte offset of the next record, or `None` while
+    /// rows split off an earlier record are still pending.
+    pub(crate) fn boundary(&self) -> Option<(usize, u64, u64)> {
+        let position = self.rdr.position();
+        self.pending.is_empty().then(|| {
+            (
+                self.next_row,
+                self.base_line + position.line().saturating_sub(1),
+                self.base_byte + position.byte(),
+            )
+        })
+    }
+
+    pub(crate) fn take_issues(&mut self) -> Vec<ParseIssue> {
+        std::mem::take(&mut self.issues)
+    }
+
+    /// Turns a record into a row, or `None` when it is skipped.
+    fn accept(
+        &mut self,
+        record: &csv::ByteRecord,
+        line: u64,
+        byte: u64,
+    ) -> Result<Option<Vec<String>>, LoadError> {
+        let mut kinds = Vec::new();
+        let fields = record
+            .iter()
+            .map(std::str::from_utf8)
+            .collect::<Result<Vec<_>, _>>();
+        let mut cells = match fields {
+            Ok(fields) => fields.into_iter().map(str::to_string).collect::<Vec<_>>(),
+            Err(e) if self.tolerance == Tolerance::Strict => {
+                return Err(LoadError::Parse(format!("line {}: {}", line, e)));
+            }
+            Err(_) => {
+                kinds.push(IssueKind::InvalidUtf8);
+                record
+                    .iter()
+                    .map(|field| String::from_utf8_lossy(field).into_owned())
+                    .collect()
+            }
+        };
+        if self.width > 0 && cells.len() != self.width {
+            kinds.push(IssueKind::FieldCount {
+                expected: self.width,
+                found: cells.len(),
+            });
+            if self.tolerance == Tolerance::Repair && cells.len() < self.width {
+                cells.resize(self.width, String::new());
+            }
+        }
+
+        let skipped = self.tolerance == Tolerance::Skip && !kinds.is_empty();
+        for kind in kinds {
+            self.report(line, byte, kind, skipped);
+        }
+        if skipped {
+            return Ok(None);
+        }
+        self.next_row += 1;
+        Ok(Some(cells))
+    }
+
+    fn report(&mut self, line: u64, byte: u64, kind: IssueKind, skipped: bool) {
+        self.issues.push(ParseIssue {
+            row: self.next_row,
+            line,
+            byte,
+            kind,
+            skipped,
+        });
+    }
+
+    /// The lines of a record in which a quote ran on over whole records,
+    /// with their line and byte offsets in the record; `None` for sound
+    /// records. Blank lines are left out but counted.
+    ///
+    /// A quoted field may hold line breaks, so only a line that has as many
+    /// delimiters as a record has counts as swallowed.
+    fn swallowed_lines(&self, record: &csv::ByteRecord) -> Option<Vec<(u64, u64, Vec<u8>)>> {
+        if self.width < 2 {
+            return None;
+        }
+        let looks_swallowed = record.iter().any(|field| {
+            field.split(|&b| b == b'\n').skip(1).any(|line| {
+                line.iter().filter(|&&b| b == b',').count() >= self.width - 1
+            })
+        });
+        if !looks_swallowed {
+            return None;
+        }
+
+        // The record as written, less the quote that opened the field
+        let raw = record.iter().collect::<Vec<_>>().join(&b","[..]);
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for (index, line) in raw.split(|&b| b == b'\n').enumerate() {
+            let text = line.strip_suffix(b"\r").unwrap_or(line);
+            if !text.is_empty() {
+                // Lines after the first come after the dropped quote
+                let byte = if offset == 0 { 0 } else { offset + 1 };
+                lines.push((index as u64, byte as u64, text.to_vec()));
+            }
+            offset += line.len() + 1;
+        }
+        Some(lines)
+    }
+}
+
+impl<R: Read> Iterator for CsvRows<R> {
+    type Item = Result<Vec<String>, LoadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (record, line, byte) = match self.pending.pop_front() {
+                Some(pending) => pending,
+                None => {
+                    let mut record = csv::ByteRecord::new();
+                    match self.rdr.read_byte_record(&mut record) {
+                        Ok(true) => {}
+                        Ok(false) => return None,
+                        Err(e) => return Some(Err(LoadError::Parse(e.to_string()))),
+                    }
+                    let (line, byte) = record
+                        .position()
+                        .map_or((0, 0), |position| (position.line(), position.byte()));
+                    let (line, byte) = (self.base_line + line, self.base_byte + byte);
+
+                    if let Some(lines) = self.swallowed_lines(&record) {
+                        let kind = IssueKind::UnterminatedQuote { lines: lines.len() };
+                        let skipped = self.tolerance == Tolerance::Skip;
+                        self.report(line, byte, kind, skipped);
+                        if self.tolerance != Tolerance::Strict {
+                            // Each line is read again as a record, without
+                            // quoting; skipping leaves out only the first
+                            for (line_offset, byte_offset, text) in
+                                lines.into_iter().skip(usize::from(skipped))
+                            {
+                                let record = unquoted_record(&text);
+                                self.pending.push_back((
+                                    record,
+                                    line + line_offset,
+                                    byte + byte_offset,
+                                ));
+                            }
+                            continue;
+                        }
+                    }
+                    (record, line, byte)
+                }
+            };
+
+            match self.accept(&record, line, byte) {
+                Ok(Some(row)) => return Some(Ok(row)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Splits one line of a CSV file into fields, with quotes taken literally.
+fn unquoted_record(line: &[u8]) -> csv::ByteRecord {
+    let mut record = csv::ByteRecord::new();
+    let _ = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .quoting(false)
+        .from_reader(line)
+        .read_byte_record(&mut record);
+    record
+}
+
+/// Layout of a CSV file as seen by [`detect_file_structure`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStructure {
+    /// The first record is the header line.
+    Simple,
+    /// The file starts with preamble lines; the header is on record `header_line`.
+    Mixed { header_line: usize },
+}
+
+/// Samples the first 50 records of `rdr` and decides where the header line is.
+///
+/// The reader must be built with `has_headers(false)` and `flexible(true)` so
+/// preamble lines with a different field count are returned as records.
+pub fn detect_file_structure<R: Read>(
+    rdr: &mut csv::Reader<R>,
+) -> Result<FileStructure, LoadError> {
+    let mut sample_lines = Vec::new();
+    let max_sample_lines = 50;
+
+    for (line_count, record) in rdr.records().enumerate() {
+        if line_count >= max_sample_lines {
+            break;
+        }
+
+        let record = record.map_err(|e| LoadError::Parse(e.to_string()))?;
+        let fields: Vec<String> = record.iter().map(|s| s.to_string()).collect();
+        sample_lines.push((line_count, fields));
+    }
+
+    if sample_lines.is_empty() {
+        return Err(LoadError::EmptyFile);
+    }
+
+    let is_mixed = is_mixed_structure(&sample_lines);
+
+    if is_mixed {
+        if let Some(header_line) = find_header_with_regex(&sample_lines) {
+            return Ok(FileStructure::Mixed { header_line });
+        }
+    }
+
+    Ok(FileStructure::Simple)
+}
+
+/// Returns `true` when the first lines look like a `key,value` preamble rather
+/// than a table: wildly different field counts, known preamble keys, or
+/// single-letter marker lines.
+pub fn is_mixed_structure(sample_lines: &[(usize, Vec<String>)]) -> bool {
+    if sample_lines.len() < 3 {
+        return false;
+    }
+
+    let first_line_fields = sample_lines[0].1.len();
+    let second_line_fields = sample_lines[1].1.len();
+    let third_line_fields = sample_lines[2].1.len();
+
+    let max_fields = first_line_fields.max(second_line_fields).max(third_line_fields);
+    let min_fields = first_line_fields.min(second_line_fields).min(third_line_fields);
+
+    if max_fields > min_fields * 2 {
+        return true;
+    }
+
+    for (_, fields) in sample_lines[..3].iter() {
+        if fields.len() == 2 {
+            let first_field = fields[0].to_lowercase();
+
+            if first_field.contains("user") ||
+                first_field.contains("supplier") ||
+                first_field.contains("wafer") ||
+                first_field.contains("led") ||
+                first_field.contains("date") ||
+                first_field.contains("time") ||
+                first_field.contains("version") ||
+                first_field.contains("id") {
+                return true;
+            }
+        }
+    }
+
+    for (_, fields) in sample_lines[..3].iter() {
+        if fields.len() == 1 {
+            let field = fields[0].to_lowercase();
+            if field.len() == 1 && field.chars().next().unwrap().is_alphabetic() {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Finds the record that looks like the data header of a mixed file, scoring
+/// fields on `INDEX`, underscores and measurement keywords (`wp`, `fwhm`, ...).
+pub fn find_header_with_regex(sample_lines: &[(usize, Vec<String>)]) -> Option<usize> {
+    let index_pattern = Regex::new(r"INDEX").unwrap();
+    let underscore_pattern = Regex::new(r".*_.*").unwrap();
+
+    for (line_num, fields) in sample_lines {
+        let mut pattern_matches = 0;
+        let mut keyword_matches = 0;
+        let mut underscore_count = 0;
+
+        if fields.len() < 5 {
+            continue;
+        }
+
+        for field in fields {
+            let field_upper = field.to_uppercase();
+            let field_lower = field.to_lowercase();
+
+            if index_pattern.is_match(&field_upper) {
+                pattern_matches += 3;
+            }
+
+            if underscore_pattern.is_match(field) {
+                pattern_matches += 1;
+                underscore_count += 1;
+            }
+
+            let data_keywords = ["index", "upl", "epi", "aoi", "chip", "wp", "wd", "fwhm"];
+            for keyword in &data_keywords {
+                if field_lower.contains(keyword) {
+                    keyword_matches += 1;
+                }
+            }
+        }
+
+        let is_likely_header =
+            fields.len() >= 10 &&
+            underscore_count >= 5 &&
+            (pattern_matches >= 5 || keyword_matches >= 5);
+
+        if is_likely_header {
+            return Some(*line_num);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads the data rows of `text` after its header line.
+    fn read(text: &str, tolerance: Tolerance) -> (Vec<Vec<String>>, Vec<ParseIssue>) {
+        let mut rdr = csv_reader(text.as_bytes());
+        let width = rdr.records().next().unwrap().unwrap().len();
+        let mut rows = CsvRows::new(rdr, tolerance, width);
+        let data = rows.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        (data, rows.take_issues())
+    }
+
+    fn issue(row: usize, line: u64, byte: u64, kind: IssueKind, skipped: bool) -> ParseIssue {
+        ParseIssue {
+            row,
+            line,
+            byte,
+            kind,
+            skipped,
+        }
+    }
+
+    const RAGGED: &str = "a,b,c\n1,2,3\n4,5\n6,7,8,9\n10,11,12\n";
+
+    #[test]
+    fn strict_reports_field_counts() {
+        let (rows, issues) = read(RAGGED, Tolerance::Strict);
+        assert_eq!(
+            rows,
+            [
+                vec!["1", "2", "3"],
+                vec!["4", "5"],
+                vec!["6", "7", "8", "9"],
+                vec!["10", "11", "12"]
+            ]
+        );
+        let short = IssueKind::FieldCount {
+            expected: 3,
+            found: 2,
+        };
+        let long = IssueKind::FieldCount {
+            expected: 3,
+            found: 4,
+        };
+        assert_eq!(
+            issues,
+            [issue(1, 3, 12, short, false), issue(2, 4, 16, long, false)]
+        );
+    }
+
+    #[test]
+    fn skip_leaves_bad_records_out() {
+        let (rows, issues) = read(RAGGED, Tolerance::Skip);
+        assert_eq!(rows, [vec!["1", "2", "3"], vec!["10", "11", "12"]]);
+        let short = IssueKind::FieldCount {
+            expected: 3,
+            found: 2,
+        };
+        let long = IssueKind::FieldCount {
+            expected: 3,
+            found: 4,
+        };
+        assert_eq!(
+            issues,
+            [issue(1, 3, 12, short, true), issue(1, 4, 16, long, true)]
+        );
+    }
+
+    #[test]
+    fn repair_pads_short_records() {
+        let (rows, issues) = read(RAGGED, Tolerance::Repair);
+        assert_eq!(
+            rows,
+            [
+                vec!["1", "2", "3"],
+                vec!["4", "5", ""],
+                vec!["6", "7", "8", "9"],
+                vec!["10", "11", "12"]
+            ]
+        );
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|issue| !issue.skipped));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let text = b"a,b\n1,\xff\n2,3\n";
+        let mut rdr = csv_reader(&text[..]);
+        rdr.records().next();
+        let mut rows = CsvRows::new(rdr, Tolerance::Strict, 2);
+        assert!(matches!(rows.next(), Some(Err(LoadError::Parse(_)))));
+
+        let mut rdr = csv_reader(&text[..]);
+        rdr.records().next();
+        let mut rows = CsvRows::new(rdr, Tolerance::Repair, 2);
+        let data = rows.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(data, [vec!["1", "\u{fffd}"], vec!["2", "3"]]);
+        assert_eq!(
+            rows.take_issues(),
+            [issue(0, 2, 4, IssueKind::InvalidUtf8, false)]
+        );
+    }
+
+    /// A quote left open on line 3 swallows the rest of the file, which has
+    /// a blank line.
+    const UNTERMINATED: &str = "a,b,c\n1,2,3\n4,\"5,6\n7,8,9\n\n10,11,12\n";
+
+    #[test]
+    fn unterminated_quote_strict() {
+        let (rows, issues) = read(UNTERMINATED, Tolerance::Strict);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][1], "5,6\n7,8,9\n\n10,11,12\n");
+        let quote = IssueKind::UnterminatedQuote { lines: 3 };
+        let short = IssueKind::FieldCount {
+            expected: 3,
+            found: 2,
+        };
+        assert_eq!(
+            issues,
+            [issue(1, 3, 12, quote, false), issue(1, 3, 12, short, false)]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_repair() {
+        let (rows, issues) = read(UNTERMINATED, Tolerance::Repair);
+        assert_eq!(
+            rows,
+            [
+                vec!["1", "2", "3"],
+                vec!["4", "5", "6"],
+                vec!["7", "8", "9"],
+                vec!["10", "11", "12"]
+            ]
+        );
+        let kind = IssueKind::UnterminatedQuote { lines: 3 };
+        assert_eq!(issues, [issue(1, 3, 12, kind, false)]);
+    }
+
+    #[test]
+    fn unterminated_quote_skip() {
+        let (rows, issues) = read(UNTERMINATED, Tolerance::Skip);
+        assert_eq!(
+            rows,
+            [
+                vec!["1", "2", "3"],
+                vec!["7", "8", "9"],
+                vec!["10", "11", "12"]
+            ]
+        );
+        let kind = IssueKind::UnterminatedQuote { lines: 3 };
+        assert_eq!(issues, [issue(1, 3, 12, kind, true)]);
+    }
+
+    #[test]
+    fn swallowed_lines_keep_their_line_numbers() {
+        // The third swallowed line is short, so it is reported on its own
+        // line, after the blank one
+        let text = "a,b,c\n\"1,2,3\n4,5,6\n\n7,8\n";
+        let (rows, issues) = read(text, Tolerance::Repair);
+        assert_eq!(
+            rows,
+            [vec!["1", "2", "3"], vec!["4", "5", "6"], vec!["7", "8", ""]]
+        );
+        assert_eq!(
+            issues,
+            [
+                issue(0, 2, 6, IssueKind::UnterminatedQuote { lines: 3 }, false),
+                issue(
+                    2,
+                    5,
+                    20,
+                    IssueKind::FieldCount {
+                        expected: 3,
+                        found: 2
+                    },
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_line_breaks_are_not_swallowed_records() {
+        let text = "a,b\n\"one\ntwo\",1\n2,3\n";
+        let (rows, issues) = read(text, Tolerance::Repair);
+        assert_eq!(rows, [vec!["one\ntwo", "1"], vec!["2", "3"]]);
+        assert!(issues.is_empty());
+    }
+}
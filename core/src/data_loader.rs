@@ -0,0 +1,324 @@
+use crate::csv_loader::{ParseIssue, Tolerance};
+use crate::error::LoadError;
+use crate::progress::Progress;
+use crate::row_count::{count_rows, RowCount};
+use once_cell::sync::Lazy;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use tokio::runtime::Runtime;
+
+static RT: Lazy<Runtime> = Lazy::new(|| Runtime::new().expect("Unable to create Runtime"));
+
+/// One page of rows returned by a loader.
+#[derive(Debug, Clone, Default)]
+pub struct DataPage {
+    /// Column names; only filled in when the page starts at row 0.
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Sheets the user can switch between. Single-table formats report one
+    /// pseudo sheet named after the format (see [`is_pseudo_sheet`]);
+    /// columnar files list their row groups.
+    pub sheet_names: Vec<String>,
+    pub end_of_file: bool,
+    /// Declared column types for formats that carry a schema, empty otherwise.
+    pub column_types: Vec<ColumnType>,
+    /// CSV records of this page that did not parse cleanly.
+    pub parse_issues: Vec<ParseIssue>,
+}
+
+/// Column type declared by the file's schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Text,
+    Integer,
+    Float,
+    Boolean,
+    Date,
+    DateTime,
+    /// Binary, nested and other types that are shown as formatted text.
+    Other,
+}
+
+impl ColumnType {
+    /// Short name shown under column headers.
+    pub fn label(self) -> &'static str {
+        match self {
+            ColumnType::Text => "text",
+            ColumnType::Integer => "int",
+            ColumnType::Float => "float",
+            ColumnType::Boolean => "bool",
+            ColumnType::Date => "date",
+            ColumnType::DateTime => "datetime",
+            ColumnType::Other => "other",
+        }
+    }
+}
+
+/// Result of loading one page of data.
+pub type DataLoadResult = Result<DataPage, LoadError>;
+
+/// Returns `true` for the sheet name reported by formats that have no sheets.
+pub fn is_pseudo_sheet(name: &str) -> bool {
+    matches!(name, "CSV" | "JSON" | "Fixed width")
+}
+
+/// Loads pages of spreadsheet data in the background.
+#[derive(Default)]
+pub struct DataLoader {
+    options: LoadOptions,
+    /// Progress of the last row count started.
+    count_progress: Progress,
+    /// Progress of the last page load started.
+    load_progress: Progress,
+}
+
+/// Per-file settings that change how a file is parsed.
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// Column layout for fixed-width text files; guessed when `None`.
+    pub fixed_width: Option<crate::fixed_width_loader::FixedWidthLayout>,
+    /// How CSV records that do not parse cleanly are handled.
+    pub tolerance: Tolerance,
+}
+
+impl DataLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options applied to every following load.
+    pub fn options(&self) -> &LoadOptions {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: LoadOptions) {
+        self.options = options;
+    }
+
+    /// Loads `num_rows` data rows starting at `start_row` (0 = first row after
+    /// the header) and sends the result on `tx`.
+    ///
+    /// `on_ready` is called after the result has been sent, e.g. to wake up a
+    /// GUI event loop. Rows read on the way are reported to
+    /// [`DataLoader::load_progress`] until the next load starts.
+    pub fn load_data_async<F>(
+        &mut self,
+        path: PathBuf,
+        start_row: usize,
+        num_rows: usize,
+        sheet_index: usize,
+        tx: mpsc::Sender<DataLoadResult>,
+        on_ready: F,
+    ) where
+        F: FnOnce() + Send + 'static,
+    {
+        let options = self.options.clone();
+        self.load_progress = Progress::new();
+        let progress = self.load_progress.clone();
+        RT.spawn(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                load_data_tracked(path, start_row, num_rows, sheet_index, &options, &progress)
+            })
+            .await
+            .map_err(|e| LoadError::Task(e.to_string()))
+            .and_then(|result| result);
+            // The receiver is gone when the caller has moved on to another file.
+            let _ = tx.send(result);
+            on_ready();
+        });
+    }
+
+    /// Rows skipped and read by the last page load.
+    pub fn load_progress(&self) -> &Progress {
+        &self.load_progress
+    }
+
+    /// Counts the data rows of a sheet in the background and sends the
+    /// result on `tx`; see [`count_rows`]. How far the count has got is
+    /// reported to [`DataLoader::count_progress`] until the next count
+    /// starts.
+    pub fn count_rows_async<F>(
+        &mut self,
+        path: PathBuf,
+        sheet_index: usize,
+        tx: mpsc::Sender<RowCount>,
+        on_ready: F,
+    ) where
+        F: FnOnce() + Send + 'static,
+    {
+        let options = self.options.clone();
+        // A fresh counter, so a count still running for the previous file
+        // cannot move the bar of this one
+        self.count_progress = Progress::new();
+        let progress = self.count_progress.clone();
+        spawn_task(
+            move || RowCount {
+                rows: count_rows(&path, sheet_index, &options, &progress),
+                path,
+                sheet_index,
+            },
+            tx,
+            on_ready,
+        );
+    }
+
+    /// Bytes read and rows counted by the last row count.
+    pub fn count_progress(&self) -> &Progress {
+        &self.count_progress
+    }
+}
+
+/// Runs a blocking `task` on the shared background runtime, sends its result
+/// on `tx` and then calls `on_ready`. Used for whole-sheet work such as
+/// queries and reports that must not block the caller.
+pub fn spawn_task<T, F, R>(task: F, tx: mpsc::Sender<T>, on_ready: R)
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+    R: FnOnce() + Send + 'static,
+{
+    RT.spawn_blocking(move || {
+        let _ = tx.send(task());
+        on_ready();
+    });
+}
+
+/// Loads one page of data from `path`, choosing the loader by file extension.
+pub async fn load_data(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+) -> DataLoadResult {
+    load_data_with_options(path, start_row, num_rows, sheet_index, &LoadOptions::default()).await
+}
+
+/// [`load_data`] with explicit [`LoadOptions`].
+pub async fn load_data_with_options(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+    options: &LoadOptions,
+) -> DataLoadResult {
+    let options = options.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        load_data_sync(path, start_row, num_rows, sheet_index, &options)
+    })
+    .await;
+
+    result.map_err(|e| LoadError::Task(e.to_string()))?
+}
+
+/// Blocking variant of [`load_data_with_options`], for code that already
+/// runs on a worker thread. `num_rows` may be `usize::MAX` to read the rest
+/// of the sheet.
+pub fn load_data_sync(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+    options: &LoadOptions,
+) -> DataLoadResult {
+    load_data_tracked(path, start_row, num_rows, sheet_index, options, &Progress::new())
+}
+
+/// [`load_data_sync`] that reports the rows it skips and reads to
+/// `progress`. Only CSV files, plain or compressed, report progress; they
+/// are the large files whose far pages take long to reach.
+pub fn load_data_tracked(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+    options: &LoadOptions,
+    progress: &Progress,
+) -> DataLoadResult {
+    if crate::archive::is_archive(&path) {
+        return Err(LoadError::ArchiveNeedsEntry);
+    }
+
+    match file_extension(&path).as_deref() {
+        Some("csv") => {
+            crate::csv_loader::load_csv_data_sync(
+                path,
+                start_row,
+                num_rows,
+                options.tolerance,
+                progress,
+            )
+        }
+        Some("xlsx") | Some("xls") | Some("ods") => {
+            crate::excel_loader::load_excel_data_sync(path, start_row, num_rows, sheet_index)
+        }
+        Some("parquet") => {
+            crate::columnar_loader::load_parquet_data_sync(path, start_row, num_rows, sheet_index)
+        }
+        Some("arrow") | Some("feather") => {
+            crate::columnar_loader::load_ipc_data_sync(path, start_row, num_rows, sheet_index)
+        }
+        Some("json") | Some("ndjson") | Some("jsonl") => {
+            crate::json_loader::load_json_data_sync(path, start_row, num_rows)
+        }
+        Some("db") | Some("sqlite") | Some("sqlite3") => {
+            crate::sqlite_loader::load_sqlite_data_sync(path, start_row, num_rows, sheet_index)
+        }
+        Some("txt") | Some("dat") | Some("prn") | Some("fwf") => {
+            let layout = options.fixed_width.clone();
+            crate::fixed_width_loader::load_fixed_width_data_sync(path, start_row, num_rows, layout)
+        }
+        _ => Err(LoadError::UnsupportedFormat),
+    }
+}
+
+/// Returns `true` if [`load_data`] can read `path`.
+pub fn is_supported(path: &Path) -> bool {
+    matches!(
+        file_extension(path).as_deref(),
+        Some("csv")
+            | Some("xlsx")
+            | Some("xls")
+            | Some("ods")
+            | Some("parquet")
+            | Some("arrow")
+            | Some("feather")
+            | Some("json")
+            | Some("ndjson")
+            | Some("jsonl")
+            | Some("db")
+            | Some("sqlite")
+            | Some("sqlite3")
+            | Some("txt")
+            | Some("dat")
+            | Some("prn")
+            | Some("fwf")
+    )
+}
+
+/// Returns `true` for plain and compressed CSV files, the files read with a
+/// [`Tolerance`].
+pub fn is_csv(path: &Path) -> bool {
+    file_extension(path).as_deref() == Some("csv")
+}
+
+/// Returns `true` for text files read by the fixed-width loader.
+pub fn is_fixed_width(path: &Path) -> bool {
+    matches!(
+        file_extension(path).as_deref(),
+        Some("txt") | Some("dat") | Some("prn") | Some("fwf")
+    )
+}
+
+/// The lowercase extension that decides the loader. For compressed files
+/// this is the extension underneath the compression suffix, so `lot.csv.gz`
+/// is treated as `csv`.
+pub(crate) fn file_extension(path: &Path) -> Option<String> {
+    if crate::compressed::Compression::from_path(path).is_some() {
+        // Only CSV can be streamed; the Excel readers need a seekable file
+        return crate::compressed::inner_extension(path).filter(|ext| ext == "csv");
+    }
+
+    path.extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .map(|ext| ext.to_ascii_lowercase())
+}
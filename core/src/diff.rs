@@ -0,0 +1,521 @@
+use crate::error::LoadError;
+use crate::table_reader::{find_column, SheetSource};
+use crate::values::{compare_cells, format_number, parse_number};
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+
+/// Upper limit on differing rows kept for display. Rows past it are still
+/// counted in the summary.
+const MAX_DIFF_ROWS: usize = 100_000;
+
+/// How rows of the two sources are paired up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffAlignment {
+    /// Row N of one source is compared with row N of the other.
+    Position,
+    /// Rows with the same values in these columns are compared, wherever
+    /// they are in the files.
+    Key(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffSpec {
+    pub alignment: DiffAlignment,
+    /// Numeric cells that differ by at most this much are not a change.
+    pub tolerance: f64,
+    /// Leading and trailing whitespace of cells and keys is ignored.
+    pub trim: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffStatus {
+    /// Only in the second source.
+    Added,
+    /// Only in the first source.
+    Removed,
+    Changed,
+}
+
+impl DiffStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            DiffStatus::Added => "added",
+            DiffStatus::Removed => "removed",
+            DiffStatus::Changed => "changed",
+        }
+    }
+}
+
+/// How a cell of a changed row compares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellChange {
+    Same,
+    Changed,
+    /// Numbers that differ by no more than the tolerance.
+    WithinTolerance,
+}
+
+/// A row that differs between the sources.
+#[derive(Debug, Clone)]
+pub struct DiffRow {
+    pub status: DiffStatus,
+    /// The key values, or the row number when aligned by position.
+    pub key: Vec<String>,
+    /// One cell per compared column. Changed cells read "old → new", with
+    /// the difference for numbers.
+    pub cells: Vec<String>,
+    /// One entry per compared column; all `Same` for added and removed rows.
+    pub changes: Vec<CellChange>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiffSummary {
+    pub left_rows: usize,
+    pub right_rows: usize,
+    pub unchanged: usize,
+    pub changed: usize,
+    pub added: usize,
+    pub removed: usize,
+    pub changed_cells: usize,
+    pub cells_within_tolerance: usize,
+}
+
+/// Result of [`diff_sheets`].
+#[derive(Debug, Clone, Default)]
+pub struct DiffResult {
+    /// Name of the key column of the output, e.g. "INDEX" or "row".
+    pub key_header: String,
+    /// Columns present in both sources, in the order of the first one.
+    pub headers: Vec<String>,
+    /// Differing rows: changed rows in the order they were found, then
+    /// removed and added rows ordered by key.
+    pub rows: Vec<DiffRow>,
+    pub summary: DiffSummary,
+    pub left_only_columns: Vec<String>,
+    pub right_only_columns: Vec<String>,
+    /// `true` if more rows differ than are kept in `rows`.
+    pub truncated: bool,
+}
+
+/// Compares two sheets row by row and cell by cell.
+///
+/// Columns are matched by header name. Both sheets are streamed side by
+/// side; with key alignment, a row is held in memory only until its partner
+/// turns up in the other sheet, so files that are mostly in the same order
+/// compare in little memory however large they are.
+pub fn diff_sheets(
+    left: &SheetSource,
+    right: &SheetSource,
+    spec: &DiffSpec,
+) -> Result<DiffResult, LoadError> {
+    let mut left_reader = left.open()?;
+    let mut right_reader = right.open()?;
+    let left_headers = left_reader.headers().to_vec();
+    let right_headers = right_reader.headers().to_vec();
+
+    // (left index, right index) of each compared column
+    let mut columns = Vec::new();
+    let mut result = DiffResult::default();
+    for (left_index, header) in left_headers.iter().enumerate() {
+        match find_column(&right_headers, header) {
+            Some(right_index) => {
+                columns.push((left_index, right_index));
+                result.headers.push(header.clone());
+            }
+            None => result.left_only_columns.push(header.clone()),
+        }
+    }
+    result.right_only_columns = right_headers
+        .iter()
+        .filter(|h| find_column(&left_headers, h).is_none())
+        .cloned()
+        .collect();
+    if columns.is_empty() {
+        return Err(LoadError::NoCommonColumns);
+    }
+
+    let keys = match &spec.alignment {
+        DiffAlignment::Position => None,
+        DiffAlignment::Key(names) => {
+            if names.is_empty() {
+                return Err(LoadError::NoKeyColumns);
+            }
+            let mut keys = Vec::new();
+            for name in names {
+                let missing = |source: &SheetSource| LoadError::MissingKeyColumn {
+                    column: name.clone(),
+                    file: source.file_name(),
+                };
+                let left_index = find_column(&left_headers, name).ok_or_else(|| missing(left))?;
+                let right_index =
+                    find_column(&right_headers, name).ok_or_else(|| missing(right))?;
+                keys.push((left_index, right_index));
+            }
+            Some(keys)
+        }
+    };
+    result.key_header = match &spec.alignment {
+        DiffAlignment::Position => "row".to_string(),
+        DiffAlignment::Key(names) => names.join(" · "),
+    };
+
+    let mut differ = Differ {
+        columns,
+        tolerance: spec.tolerance.abs(),
+        trim: spec.trim,
+        result,
+    };
+    let mut left_pending: Pending = HashMap::new();
+    let mut right_pending: Pending = HashMap::new();
+    let mut left_done = false;
+    let mut right_done = false;
+
+    while !(left_done && right_done) {
+        let left_row = if left_done {
+            None
+        } else {
+            left_reader.next().transpose()?
+        };
+        let right_row = if right_done {
+            None
+        } else {
+            right_reader.next().transpose()?
+        };
+        left_done |= left_row.is_none();
+        right_done |= right_row.is_none();
+
+        let Some(keys) = &keys else {
+            let row_number = differ
+                .result
+                .summary
+                .left_rows
+                .max(differ.result.summary.right_rows)
+                + 1;
+            if left_row.is_some() {
+                differ.result.summary.left_rows += 1;
+            }
+            if right_row.is_some() {
+                differ.result.summary.right_rows += 1;
+            }
+            let key = vec![row_number.to_string()];
+            match (left_row, right_row) {
+                (Some(l), Some(r)) => differ.compare(key, &l, &r),
+                (Some(l), None) => differ.one_sided(DiffStatus::Removed, key, &l, false),
+                (None, Some(r)) => differ.one_sided(DiffStatus::Added, key, &r, true),
+                (None, None) => {}
+            }
+            continue;
+        };
+
+        if let Some(row) = left_row {
+            differ.result.summary.left_rows += 1;
+            let key = differ.row_key(&row, keys.iter().map(|&(l, _)| l));
+            match take_pending(&mut right_pending, &key) {
+                Some(partner) => differ.compare(key, &row, &partner),
+                None => left_pending.entry(key).or_default().push_back(row),
+            }
+        }
+        if let Some(row) = right_row {
+            differ.result.summary.right_rows += 1;
+            let key = differ.row_key(&row, keys.iter().map(|&(_, r)| r));
+            match take_pending(&mut left_pending, &key) {
+                Some(partner) => differ.compare(key, &partner, &row),
+                None => right_pending.entry(key).or_default().push_back(row),
+            }
+        }
+    }
+
+    // Whatever found no partner exists in one sheet only
+    for (status, pending, is_right) in [
+        (DiffStatus::Removed, left_pending, false),
+        (DiffStatus::Added, right_pending, true),
+    ] {
+        let mut pending: Vec<(Vec<String>, VecDeque<Vec<String>>)> = pending.into_iter().collect();
+        pending.sort_by(|a, b| compare_keys(&a.0, &b.0));
+        for (key, rows) in pending {
+            for row in rows {
+                differ.one_sided(status, key.clone(), &row, is_right);
+            }
+        }
+    }
+
+    Ok(differ.result)
+}
+
+/// Rows waiting for their partner in the other sheet, by key.
+type Pending = HashMap<Vec<String>, VecDeque<Vec<String>>>;
+
+struct Differ {
+    columns: Vec<(usize, usize)>,
+    tolerance: f64,
+    trim: bool,
+    result: DiffResult,
+}
+
+impl Differ {
+    /// Cell `index` of `row` as compared, empty if the row is short.
+    fn cell<'a>(&self, row: &'a [String], index: usize) -> &'a str {
+        let cell = row.get(index).map_or("", String::as_str);
+        if self.trim {
+            cell.trim()
+        } else {
+            cell
+        }
+    }
+
+    fn row_key(&self, row: &[String], columns: impl Iterator<Item = usize>) -> Vec<String> {
+        columns.map(|c| self.cell(row, c).to_string()).collect()
+    }
+
+    fn compare(&mut self, key: Vec<String>, left: &[String], right: &[String]) {
+        let mut cells = Vec::with_capacity(self.columns.len());
+        let mut changes = Vec::with_capacity(self.columns.len());
+        for &(l, r) in &self.columns {
+            let old = self.cell(left, l);
+            let new = self.cell(right, r);
+            let (cell, change) = self.compare_cell(old, new);
+            match change {
+                CellChange::Same => {}
+                CellChange::Changed => self.result.summary.changed_cells += 1,
+                CellChange::WithinTolerance => self.result.summary.cells_within_tolerance += 1,
+            }
+            cells.push(cell);
+            changes.push(change);
+        }
+
+        if !changes.contains(&CellChange::Changed) {
+            self.result.summary.unchanged += 1;
+            return;
+        }
+        self.result.summary.changed += 1;
+        self.push(DiffRow {
+            status: DiffStatus::Changed,
+            key,
+            cells,
+            changes,
+        });
+    }
+
+    fn compare_cell(&self, old: &str, new: &str) -> (String, CellChange) {
+        if old == new {
+            return (new.to_string(), CellChange::Same);
+        }
+        match (parse_number(old), parse_number(new)) {
+            (Some(x), Some(y)) => {
+                let delta = y - x;
+                if delta == 0.0 {
+                    (new.to_string(), CellChange::Same)
+                } else if delta.abs() <= self.tolerance {
+                    (new.to_string(), CellChange::WithinTolerance)
+                } else {
+                    let sign = if delta > 0.0 { "+" } else { "" };
+                    (
+                        format!("{} → {} (Δ {}{})", old, new, sign, format_number(delta)),
+                        CellChange::Changed,
+                    )
+                }
+            }
+            _ => (format!("{} → {}", old, new), CellChange::Changed),
+        }
+    }
+
+    fn one_sided(&mut self, status: DiffStatus, key: Vec<String>, row: &[String], is_right: bool) {
+        match status {
+            DiffStatus::Added => self.result.summary.added += 1,
+            DiffStatus::Removed => self.result.summary.removed += 1,
+            DiffStatus::Changed => {}
+        }
+        let cells = self
+            .columns
+            .iter()
+            .map(|&(l, r)| {
+                let index = if is_right { r } else { l };
+                self.cell(row, index).to_string()
+            })
+            .collect();
+        self.push(DiffRow {
+            status,
+            key,
+            cells,
+            changes: vec![CellChange::Same; self.columns.len()],
+        });
+    }
+
+    fn push(&mut self, row: DiffRow) {
+        if self.result.rows.len() < MAX_DIFF_ROWS {
+            self.result.rows.push(row);
+        } else {
+            self.result.truncated = true;
+        }
+    }
+}
+
+/// Orders keys column by column, numbers by value.
+fn compare_keys(a: &[String], b: &[String]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| compare_cells(a, b))
+        .find(|order| order.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn take_pending(pending: &mut Pending, key: &[String]) -> Option<Vec<String>> {
+    let rows = pending.get_mut(key)?;
+    let row = rows.pop_front();
+    if rows.is_empty() {
+        pending.remove(key);
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Scratch;
+
+    fn diff(scratch: &Scratch, left: &str, right: &str, spec: &DiffSpec) -> DiffResult {
+        let source = |name: &str, text: &str| {
+            let path = scratch.path(name);
+            std::fs::write(&path, text).unwrap();
+            SheetSource {
+                path,
+                sheet_index: 0,
+                options: Default::default(),
+            }
+        };
+        diff_sheets(&source("left.csv", left), &source("right.csv", right), spec).unwrap()
+    }
+
+    fn by_key(columns: &[&str]) -> DiffSpec {
+        DiffSpec {
+            alignment: DiffAlignment::Key(columns.iter().map(|c| c.to_string()).collect()),
+            tolerance: 0.0,
+            trim: false,
+        }
+    }
+
+    fn statuses(result: &DiffResult) -> Vec<(DiffStatus, Vec<&str>)> {
+        result
+            .rows
+            .iter()
+            .map(|row| (row.status, row.key.iter().map(String::as_str).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn key_alignment_pairs_reordered_rows() {
+        let scratch = Scratch::new("diff_reordered");
+        let result = diff(
+            &scratch,
+            "lot,unit,value\nA,1,10\nA,2,20\nB,1,30\n",
+            "lot,unit,value\nB,1,30\nA,2,25\nA,1,10\nC,1,40\n",
+            &by_key(&["lot", "unit"]),
+        );
+        assert_eq!(result.key_header, "lot · unit");
+        assert_eq!(
+            statuses(&result),
+            [
+                (DiffStatus::Changed, vec!["A", "2"]),
+                (DiffStatus::Added, vec!["C", "1"]),
+            ]
+        );
+        assert_eq!(result.rows[0].cells[2], "20 → 25 (Δ +5)");
+        assert_eq!(result.summary.unchanged, 2);
+    }
+
+    #[test]
+    fn key_parts_are_not_joined() {
+        // Joined with a separator, both rows would have the key "a · b · c"
+        let scratch = Scratch::new("diff_key_parts");
+        let result = diff(
+            &scratch,
+            "k1,k2,value\na · b,c,1\n",
+            "k1,k2,value\na,b · c,1\n",
+            &by_key(&["k1", "k2"]),
+        );
+        assert_eq!(
+            statuses(&result),
+            [
+                (DiffStatus::Removed, vec!["a · b", "c"]),
+                (DiffStatus::Added, vec!["a", "b · c"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_pair_in_order() {
+        let scratch = Scratch::new("diff_duplicates");
+        let result = diff(
+            &scratch,
+            "id,value\n1,a\n1,b\n1,c\n",
+            "id,value\n1,a\n1,x\n",
+            &by_key(&["id"]),
+        );
+        assert_eq!(
+            statuses(&result),
+            [
+                (DiffStatus::Changed, vec!["1"]),
+                (DiffStatus::Removed, vec!["1"]),
+            ]
+        );
+        assert_eq!(result.rows[0].cells[1], "b → x");
+        assert_eq!(result.rows[1].cells[1], "c");
+    }
+
+    #[test]
+    fn numeric_tolerance() {
+        let scratch = Scratch::new("diff_tolerance");
+        let spec = DiffSpec {
+            alignment: DiffAlignment::Position,
+            tolerance: 0.01,
+            trim: false,
+        };
+        let result = diff(
+            &scratch,
+            "a,b\n1.000,2\n5,x\n",
+            "a,b\n1.005,2.00\n5.5,x\n",
+            &spec,
+        );
+        assert_eq!(statuses(&result), [(DiffStatus::Changed, vec!["2"])]);
+        assert_eq!(
+            result.rows[0].changes,
+            [CellChange::Changed, CellChange::Same]
+        );
+        assert_eq!(result.summary.unchanged, 1);
+        assert_eq!(result.summary.cells_within_tolerance, 1);
+        assert_eq!(result.summary.changed_cells, 1);
+    }
+
+    #[test]
+    fn spaces_count_unless_trimmed() {
+        let scratch = Scratch::new("diff_trim");
+        let (left, right) = ("id,value\n1,a\n2 ,b\n", "id,value\n1,a \n2,b\n");
+        let result = diff(&scratch, left, right, &by_key(&["id"]));
+        assert_eq!(result.summary.changed, 1);
+        assert_eq!(result.summary.added, 1);
+        assert_eq!(result.summary.removed, 1);
+
+        let spec = DiffSpec {
+            trim: true,
+            ..by_key(&["id"])
+        };
+        let result = diff(&scratch, left, right, &spec);
+        assert!(result.rows.is_empty());
+        assert_eq!(result.summary.unchanged, 2);
+    }
+
+    #[test]
+    fn columns_in_one_sheet_only() {
+        let scratch = Scratch::new("diff_columns");
+        let result = diff(
+            &scratch,
+            "id,old,value\n1,x,2\n",
+            "id,value,new\n1,3,y\n",
+            &by_key(&["id"]),
+        );
+        assert_eq!(result.headers, ["id", "value"]);
+        assert_eq!(result.left_only_columns, ["old"]);
+        assert_eq!(result.right_only_columns, ["new"]);
+        assert_eq!(result.rows[0].cells, ["1", "2 → 3 (Δ +1)"]);
+    }
+}
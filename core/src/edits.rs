@@ -0,0 +1,533 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Anchor of rows inserted after the last row of the sheet.
+pub const END: usize = usize::MAX;
+
+/// Identifies a row of an edited sheet: a data row of the file, by its
+/// index, or a row the user inserted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RowId {
+    Source(usize),
+    Inserted(usize),
+}
+
+/// One change to a sheet, with enough detail to undo it.
+#[derive(Debug, Clone)]
+pub enum Edit {
+    SetCell {
+        row: RowId,
+        column: usize,
+        old: String,
+        new: String,
+    },
+    /// Inserts `cells` as row `position` of the rows inserted before source
+    /// row `anchor` (or [`END`]).
+    InsertRow {
+        row: RowId,
+        anchor: usize,
+        position: usize,
+        cells: Vec<String>,
+    },
+    /// Deletes a row. For an inserted row, `anchor` and `position` say where
+    /// it was, so undo can put it back.
+    DeleteRow {
+        row: RowId,
+        anchor: usize,
+        position: usize,
+        cells: Vec<String>,
+    },
+}
+
+impl Edit {
+    fn inverse(&self) -> Edit {
+        match self.clone() {
+            Edit::SetCell {
+                row,
+                column,
+                old,
+                new,
+            } => Edit::SetCell {
+                row,
+                column,
+                old: new,
+                new: old,
+            },
+            Edit::InsertRow {
+                row,
+                anchor,
+                position,
+                cells,
+            } => Edit::DeleteRow {
+                row,
+                anchor,
+                position,
+                cells,
+            },
+            Edit::DeleteRow {
+                row,
+                anchor,
+                position,
+                cells,
+            } => Edit::InsertRow {
+                row,
+                anchor,
+                position,
+                cells,
+            },
+        }
+    }
+}
+
+/// Edits to a sheet, kept apart from the rows read from the file.
+///
+/// The file is never loaded in full to edit it: pages read later go through
+/// [`EditOverlay::page_rows`], exports through [`EditOverlay::apply_to_rows`],
+/// and saving streams the file with the overlay applied (see
+/// [`crate::save`]).
+#[derive(Debug, Clone, Default)]
+pub struct EditOverlay {
+    /// Source row → column → (value in the file, edited value).
+    cells: HashMap<usize, BTreeMap<usize, (String, String)>>,
+    deleted: BTreeSet<usize>,
+    /// Anchor → rows inserted before that source row, in order, with ids.
+    inserted: BTreeMap<usize, Vec<(usize, Vec<String>)>>,
+    next_id: usize,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl EditOverlay {
+    /// `true` if the sheet is unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.deleted.is_empty() && self.inserted.is_empty()
+    }
+
+    /// `true` if rows were inserted or deleted, which renumbers the rows
+    /// below them.
+    pub fn has_row_changes(&self) -> bool {
+        !self.deleted.is_empty() || !self.inserted.is_empty()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// An id for a row about to be inserted.
+    pub fn new_row_id(&mut self) -> RowId {
+        self.next_id += 1;
+        RowId::Inserted(self.next_id)
+    }
+
+    /// Applies `edit` and makes it the next one to undo.
+    pub fn record(&mut self, edit: Edit) {
+        self.apply(&edit);
+        self.undo.push(edit);
+        self.redo.clear();
+    }
+
+    /// Reverts the last edit and returns the change that was made, so the
+    /// caller can mirror it in the rows on screen.
+    pub fn undo(&mut self) -> Option<Edit> {
+        let edit = self.undo.pop()?;
+        let inverse = edit.inverse();
+        self.apply(&inverse);
+        self.redo.push(edit);
+        Some(inverse)
+    }
+
+    /// Applies the last undone edit again and returns it.
+    pub fn redo(&mut self) -> Option<Edit> {
+        let edit = self.redo.pop()?;
+        self.apply(&edit);
+        self.undo.push(edit.clone());
+        Some(edit)
+    }
+
+    fn apply(&mut self, edit: &Edit) {
+        match edit {
+            Edit::SetCell {
+                row: RowId::Source(index),
+                column,
+                old,
+                new,
+            } => {
+                let row = self.cells.entry(*index).or_default();
+                let original = row
+                    .get(column)
+                    .map(|(original, _)| original.clone())
+                    .unwrap_or_else(|| old.clone());
+                if *new == original {
+                    row.remove(column);
+                } else {
+                    row.insert(*column, (original, new.clone()));
+                }
+                if row.is_empty() {
+                    self.cells.remove(index);
+                }
+            }
+            Edit::SetCell {
+                row: RowId::Inserted(id),
+                column,
+                new,
+                ..
+            } => {
+                if let Some(cells) = self.inserted_row_mut(*id) {
+                    if cells.len() <= *column {
+                        cells.resize(column + 1, String::new());
+                    }
+                    cells[*column] = new.clone();
+                }
+            }
+            Edit::InsertRow {
+                row: RowId::Source(index),
+                ..
+            } => {
+                self.deleted.remove(index);
+            }
+            Edit::InsertRow {
+                row: RowId::Inserted(id),
+                anchor,
+                position,
+                cells,
+            } => {
+                let group = self.inserted.entry(*anchor).or_default();
+                let position = (*position).min(group.len());
+                group.insert(position, (*id, cells.clone()));
+            }
+            Edit::DeleteRow {
+                row: RowId::Source(index),
+                ..
+            } => {
+                self.deleted.insert(*index);
+            }
+            Edit::DeleteRow {
+                row: RowId::Inserted(id),
+                anchor,
+                ..
+            } => {
+                if let Some(group) = self.inserted.get_mut(anchor) {
+                    group.retain(|(row_id, _)| row_id != id);
+                    if group.is_empty() {
+                        self.inserted.remove(anchor);
+                    }
+                }
+            }
+        }
+    }
+
+    fn inserted_row_mut(&mut self, id: usize) -> Option<&mut Vec<String>> {
+        self.inserted
+            .values_mut()
+            .flat_map(|group| group.iter_mut())
+            .find(|(row_id, _)| *row_id == id)
+            .map(|(_, cells)| cells)
+    }
+
+    /// `true` if the cell differs from the file.
+    pub fn is_edited(&self, row: RowId, column: usize) -> bool {
+        match row {
+            RowId::Source(index) => self
+                .cells
+                .get(&index)
+                .is_some_and(|cells| cells.contains_key(&column)),
+            RowId::Inserted(_) => true,
+        }
+    }
+
+    /// Sort key giving the order rows appear in: inserted rows come right
+    /// before their anchor, in the order they were placed.
+    pub fn row_order(&self, row: RowId) -> (usize, usize, usize) {
+        match row {
+            RowId::Source(index) => (index, 1, 0),
+            RowId::Inserted(id) => self
+                .inserted
+                .iter()
+                .find_map(|(&anchor, group)| {
+                    let position = group.iter().position(|(row_id, _)| *row_id == id)?;
+                    Some((anchor, 0, position))
+                })
+                .unwrap_or((END, 0, usize::MAX)),
+        }
+    }
+
+    /// Applies the edits to a page of rows read from the file, starting at
+    /// source row `start`. Rows inserted at the end are added once the
+    /// last page has been read.
+    pub fn page_rows(
+        &self,
+        start: usize,
+        rows: Vec<Vec<String>>,
+        end_of_file: bool,
+    ) -> Vec<(RowId, Vec<String>)> {
+        let end = start + rows.len();
+        let mut page = Vec::with_capacity(rows.len());
+        for (index, row) in (start..).zip(rows) {
+            self.push_inserted(index, &mut page);
+            if let Some(row) = self.edit_row(index, row) {
+                page.push((RowId::Source(index), row));
+            }
+        }
+        if end_of_file {
+            for (_, group) in self.inserted.range(end..) {
+                page.extend(
+                    group
+                        .iter()
+                        .map(|(id, cells)| (RowId::Inserted(*id), cells.clone())),
+                );
+            }
+        }
+        page
+    }
+
+    fn push_inserted(&self, anchor: usize, page: &mut Vec<(RowId, Vec<String>)>) {
+        if let Some(group) = self.inserted.get(&anchor) {
+            page.extend(
+                group
+                    .iter()
+                    .map(|(id, cells)| (RowId::Inserted(*id), cells.clone())),
+            );
+        }
+    }
+
+    /// The row with its edits, or `None` if it was deleted.
+    pub fn edit_row(&self, index: usize, mut row: Vec<String>) -> Option<Vec<String>> {
+        if self.deleted.contains(&index) {
+            return None;
+        }
+        if let Some(cells) = self.cells.get(&index) {
+            for (&column, (_, value)) in cells {
+                if row.len() <= column {
+                    row.resize(column + 1, String::new());
+                }
+                row[column] = value.clone();
+            }
+        }
+        Some(row)
+    }
+
+    /// `true` if source row `index` has edited cells.
+    pub fn has_cell_edits(&self, index: usize) -> bool {
+        self.cells.contains_key(&index)
+    }
+
+    /// Edited cells of source row `index` as `(column, value)`.
+    pub fn cell_edits(&self, index: usize) -> impl Iterator<Item = (usize, &str)> {
+        self.cells.get(&index).into_iter().flat_map(|cells| {
+            cells
+                .iter()
+                .map(|(&column, (_, value))| (column, value.as_str()))
+        })
+    }
+
+    pub fn is_deleted(&self, index: usize) -> bool {
+        self.deleted.contains(&index)
+    }
+
+    /// Rows inserted before source row `anchor`.
+    pub fn inserted_before(&self, anchor: usize) -> impl Iterator<Item = &Vec<String>> {
+        self.inserted
+            .get(&anchor)
+            .into_iter()
+            .flat_map(|group| group.iter().map(|(_, cells)| cells))
+    }
+
+    /// Rows inserted at or after source row `anchor`, for the end of a file
+    /// that has `anchor` data rows.
+    pub fn inserted_from(&self, anchor: usize) -> impl Iterator<Item = &Vec<String>> {
+        self.inserted
+            .range(anchor..)
+            .flat_map(|(_, group)| group.iter().map(|(_, cells)| cells))
+    }
+
+    /// Applies the edits to every data row of a sheet, read front to back.
+    pub fn apply_to_rows<'a, I>(
+        &'a self,
+        rows: I,
+    ) -> impl Iterator<Item = Result<Vec<String>, String>> + 'a
+    where
+        I: Iterator<Item = Result<Vec<String>, String>> + 'a,
+    {
+        let mut next_index = 0;
+        let mut rows = rows.fuse();
+        let mut tail: Option<Vec<Vec<String>>> = None;
+        let mut pending: std::collections::VecDeque<Result<Vec<String>, String>> =
+            std::collections::VecDeque::new();
+
+        std::iter::from_fn(move || loop {
+            if let Some(row) = pending.pop_front() {
+                return Some(row);
+            }
+            if let Some(tail) = &mut tail {
+                return tail.pop().map(Ok);
+            }
+            match rows.next() {
+                Some(Ok(row)) => {
+                    let index = next_index;
+                    next_index += 1;
+                    pending.extend(self.inserted_before(index).cloned().map(Ok));
+                    if let Some(row) = self.edit_row(index, row) {
+                        pending.push_back(Ok(row));
+                    }
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    // Reversed, so `pop` yields them in order
+                    let mut rows: Vec<Vec<String>> =
+                        self.inserted_from(next_index).cloned().collect();
+                    rows.reverse();
+                    tail = Some(rows);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> Vec<Vec<String>> {
+        (0..4)
+            .map(|row| vec![format!("a{row}"), format!("b{row}")])
+            .collect()
+    }
+
+    fn set_cell(row: RowId, column: usize, old: &str, new: &str) -> Edit {
+        Edit::SetCell {
+            row,
+            column,
+            old: old.to_string(),
+            new: new.to_string(),
+        }
+    }
+
+    fn cells(page: &[(RowId, Vec<String>)]) -> Vec<Vec<String>> {
+        page.iter().map(|(_, row)| row.clone()).collect()
+    }
+
+    #[test]
+    fn setting_a_cell_back_clears_the_edit() {
+        let mut overlay = EditOverlay::default();
+        overlay.record(set_cell(RowId::Source(1), 1, "b1", "x"));
+        overlay.record(set_cell(RowId::Source(1), 1, "x", "b1"));
+        assert!(overlay.is_empty());
+
+        assert!(overlay.undo().is_some());
+        assert!(overlay.is_edited(RowId::Source(1), 1));
+        assert_eq!(overlay.cell_edits(1).collect::<Vec<_>>(), [(1, "x")]);
+        assert!(overlay.undo().is_some());
+        assert!(overlay.is_empty());
+        assert!(!overlay.can_undo());
+
+        assert!(overlay.redo().is_some());
+        assert!(overlay.redo().is_some());
+        assert!(overlay.is_empty());
+        assert!(!overlay.can_redo());
+    }
+
+    #[test]
+    fn deleting_an_inserted_row_removes_it() {
+        let mut overlay = EditOverlay::default();
+        let row = overlay.new_row_id();
+        let new = vec!["new".to_string(), String::new()];
+        overlay.record(Edit::InsertRow {
+            row,
+            anchor: 2,
+            position: 0,
+            cells: new.clone(),
+        });
+        overlay.record(set_cell(row, 1, "", "y"));
+        let page = overlay.page_rows(0, source(), true);
+        assert_eq!(page[2], (row, vec!["new".to_string(), "y".to_string()]));
+        assert!(overlay.has_row_changes());
+
+        overlay.record(Edit::DeleteRow {
+            row,
+            anchor: 2,
+            position: 0,
+            cells: vec!["new".to_string(), "y".to_string()],
+        });
+        assert!(overlay.is_empty());
+        assert_eq!(cells(&overlay.page_rows(0, source(), true)), source());
+
+        overlay.undo();
+        let page = overlay.page_rows(0, source(), true);
+        assert_eq!(page[2], (row, vec!["new".to_string(), "y".to_string()]));
+    }
+
+    #[test]
+    fn undoing_a_deleted_source_row_restores_it() {
+        let mut overlay = EditOverlay::default();
+        overlay.record(Edit::DeleteRow {
+            row: RowId::Source(1),
+            anchor: 1,
+            position: 0,
+            cells: source()[1].clone(),
+        });
+        assert!(overlay.is_deleted(1));
+        let page = overlay.page_rows(0, source(), true);
+        assert_eq!(
+            page.iter().map(|(row, _)| *row).collect::<Vec<_>>(),
+            [RowId::Source(0), RowId::Source(2), RowId::Source(3)]
+        );
+
+        assert!(matches!(
+            overlay.undo(),
+            Some(Edit::InsertRow {
+                row: RowId::Source(1),
+                ..
+            })
+        ));
+        assert!(overlay.is_empty());
+        assert_eq!(cells(&overlay.page_rows(0, source(), true)), source());
+    }
+
+    #[test]
+    fn apply_to_rows_matches_page_rows() {
+        let mut overlay = EditOverlay::default();
+        for (anchor, position, text) in [
+            (0, 0, "first"),
+            (2, 0, "mid"),
+            (2, 1, "mid2"),
+            (END, 0, "last"),
+        ] {
+            let row = overlay.new_row_id();
+            overlay.record(Edit::InsertRow {
+                row,
+                anchor,
+                position,
+                cells: vec![text.to_string()],
+            });
+        }
+        overlay.record(Edit::DeleteRow {
+            row: RowId::Source(1),
+            anchor: 1,
+            position: 0,
+            cells: source()[1].clone(),
+        });
+        overlay.record(set_cell(RowId::Source(3), 0, "a3", "z"));
+
+        let mut paged = overlay.page_rows(0, source()[..2].to_vec(), false);
+        paged.extend(overlay.page_rows(2, source()[2..].to_vec(), true));
+        let applied = overlay
+            .apply_to_rows(source().into_iter().map(Ok))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(applied, cells(&paged));
+        assert_eq!(
+            applied
+                .iter()
+                .map(|row| row[0].as_str())
+                .collect::<Vec<_>>(),
+            ["first", "a0", "mid", "mid2", "a2", "z", "last"]
+        );
+
+        let mut order = paged.iter().map(|(row, _)| *row).collect::<Vec<_>>();
+        order.sort_by_key(|&row| overlay.row_order(row));
+        assert_eq!(order, paged.iter().map(|(row, _)| *row).collect::<Vec<_>>());
+    }
+}
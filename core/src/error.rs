@@ -0,0 +1,170 @@
+use std::fmt;
+
+/// Why a page of data could not be loaded, or a file opened, compared,
+/// merged or queried.
+///
+/// Each kind is a separate variant so a user interface can describe it in
+/// its own language; [`fmt::Display`] gives the English text. Details that
+/// come from other libraries (I/O, parser and workbook errors) are kept as
+/// text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// The background task running the loader failed.
+    Task(String),
+    /// The file could not be opened or read.
+    Io(String),
+    /// The file contains no records at all.
+    EmptyFile,
+    /// A record of a text file could not be parsed.
+    Parse(String),
+    /// The workbook could not be opened.
+    OpenWorkbook(String),
+    /// The workbook has no worksheets.
+    NoWorksheets,
+    ReadWorksheet {
+        sheet: String,
+        detail: String,
+    },
+    /// Archives are listed and extracted, not loaded directly.
+    ArchiveNeedsEntry,
+    UnsupportedFormat,
+    /// The zip archive could not be opened or listed.
+    OpenArchive(String),
+    ExtractEntry {
+        entry: String,
+        detail: String,
+    },
+    /// The entry's name has no file name, or would leave the directory it
+    /// is extracted into.
+    InvalidEntryName(String),
+    /// A JSON array file is not valid JSON.
+    InvalidJson(String),
+    /// A line of an NDJSON file is not valid JSON.
+    InvalidJsonLine {
+        line: usize,
+        detail: String,
+    },
+    OpenDatabase(String),
+    /// The SQLite database has no tables or views.
+    NoTables,
+    ReadTable {
+        table: String,
+        detail: String,
+    },
+    /// An output file, such as the result of a merge, could not be created.
+    CreateFile {
+        path: String,
+        detail: String,
+    },
+    /// The sheets being compared share no column names.
+    NoCommonColumns,
+    /// A compare or merge by key was started without key columns.
+    NoKeyColumns,
+    MissingKeyColumn {
+        column: String,
+        file: String,
+    },
+    /// An input of a merge has a different number of key columns than the
+    /// first one.
+    KeyCountMismatch {
+        file: String,
+        count: usize,
+        first_file: String,
+        first_count: usize,
+    },
+    /// A merge needs at least two inputs.
+    TooFewInputs,
+    /// The in-memory database of the SQL console could not be created.
+    QueryEngine(String),
+    AttachDatabase {
+        path: String,
+        detail: String,
+    },
+    /// Errors of the other loaders, already described in English.
+    Other(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Task(detail) => write!(f, "Task execution error: {}", detail),
+            LoadError::Io(detail) => write!(f, "Cannot read the file: {}", detail),
+            LoadError::EmptyFile => write!(f, "The file is empty"),
+            LoadError::Parse(detail) => write!(f, "Cannot parse the file: {}", detail),
+            LoadError::OpenWorkbook(detail) => write!(f, "Failed to open Excel file: {}", detail),
+            LoadError::NoWorksheets => write!(f, "No worksheets found in the file"),
+            LoadError::ReadWorksheet { sheet, detail } => {
+                write!(f, "Failed to read worksheet '{}': {}", sheet, detail)
+            }
+            LoadError::ArchiveNeedsEntry => {
+                write!(f, "Archives must be opened through one of their entries.")
+            }
+            LoadError::UnsupportedFormat => write!(
+                f,
+                "Unsupported file format. Please select a CSV, Excel, ODS, Parquet, Arrow, JSON, SQLite, or fixed-width text file."
+            ),
+            LoadError::OpenArchive(detail) => write!(f, "Failed to open archive: {}", detail),
+            LoadError::ExtractEntry { entry, detail } => {
+                write!(f, "Failed to extract '{}': {}", entry, detail)
+            }
+            LoadError::InvalidEntryName(entry) => {
+                write!(f, "Invalid file name in archive: '{}'", entry)
+            }
+            LoadError::InvalidJson(detail) => write!(f, "Invalid JSON: {}", detail),
+            LoadError::InvalidJsonLine { line, detail } => {
+                write!(f, "Invalid JSON on line {}: {}", line, detail)
+            }
+            LoadError::OpenDatabase(detail) => {
+                write!(f, "Failed to open SQLite database: {}", detail)
+            }
+            LoadError::NoTables => write!(f, "No tables or views found in the database"),
+            LoadError::ReadTable { table, detail } => {
+                write!(f, "Failed to read '{}': {}", table, detail)
+            }
+            LoadError::CreateFile { path, detail } => {
+                write!(f, "Failed to create {}: {}", path, detail)
+            }
+            LoadError::NoCommonColumns => {
+                write!(f, "The two sheets have no column names in common.")
+            }
+            LoadError::NoKeyColumns => write!(f, "Pick at least one key column."),
+            LoadError::MissingKeyColumn { column, file } => {
+                write!(f, "Key column '{}' is not in {}", column, file)
+            }
+            LoadError::KeyCountMismatch {
+                file,
+                count,
+                first_file,
+                first_count,
+            } => write!(
+                f,
+                "{} has {} key columns, but {} has {}",
+                file, count, first_file, first_count
+            ),
+            LoadError::TooFewInputs => write!(f, "Pick at least two files to merge."),
+            LoadError::QueryEngine(detail) => {
+                write!(f, "Failed to start the query engine: {}", detail)
+            }
+            LoadError::AttachDatabase { path, detail } => {
+                write!(f, "Failed to attach '{}': {}", path, detail)
+            }
+            LoadError::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Lets loaders that describe their errors as text use `?`.
+impl From<String> for LoadError {
+    fn from(message: String) -> Self {
+        LoadError::Other(message)
+    }
+}
+
+/// Lets code that reports errors as text use `?` on loader results.
+impl From<LoadError> for String {
+    fn from(error: LoadError) -> Self {
+        error.to_string()
+    }
+}
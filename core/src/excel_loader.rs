@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+use crate::data_loader::{DataLoadResult, DataPage};
+use crate::error::LoadError;
+use calamine::{open_workbook_auto, Reader};
+
+/// Loads `num_rows` data rows from worksheet `sheet_index`, starting at
+/// `start_row`. The first row of the sheet is treated as the header.
+///
+/// An out-of-range `sheet_index` falls back to the first sheet. The returned
+/// sheet names list every worksheet in the workbook.
+pub async fn load_excel_data(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+) -> DataLoadResult {
+    // Excel operations are CPU-bound, so we run them in a blocking task
+    let result = tokio::task::spawn_blocking(move || {
+        load_excel_data_sync(path, start_row, num_rows, sheet_index)
+    })
+    .await;
+
+    result.map_err(|e| LoadError::Task(e.to_string()))?
+}
+
+/// Blocking variant of [`load_excel_data`].
+pub fn load_excel_data_sync(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+) -> DataLoadResult {
+    let mut workbook =
+        open_workbook_auto(&path).map_err(|e| LoadError::OpenWorkbook(e.to_string()))?;
+
+    // Get the first worksheet
+    let sheet_names = workbook.sheet_names().to_owned();
+    if sheet_names.is_empty() {
+        return Err(LoadError::NoWorksheets);
+    }
+
+    // Use the specified sheet index, fallback to first sheet if index is invalid
+    let sheet_index = if sheet_index < sheet_names.len() {
+        sheet_index
+    } else {
+        0
+    };
+    let sheet_name = &sheet_names[sheet_index];
+    let range = workbook
+        .worksheet_range(sheet_name)
+        .map_err(|e| LoadError::ReadWorksheet {
+            sheet: sheet_name.clone(),
+            detail: e.to_string(),
+        })?;
+
+    let mut headers = Vec::new();
+    let mut data = Vec::new();
+
+    // Convert range to rows
+    let rows: Vec<Vec<_>> = range.rows().map(|row| row.to_vec()).collect();
+
+    if rows.is_empty() {
+        return Ok(DataPage {
+            headers,
+            rows: data,
+            sheet_names,
+            end_of_file: true,
+            ..DataPage::default()
+        });
+    }
+
+    // Extract headers if this is the first load
+    if start_row == 0 && !rows.is_empty() {
+        headers = rows[0].iter().map(|cell| cell.to_string()).collect();
+    }
+
+    // Calculate the actual start row (skip header if start_row == 0)
+    let data_start = if start_row == 0 { 1 } else { start_row + 1 };
+    let data_end = std::cmp::min(data_start.saturating_add(num_rows), rows.len());
+
+    // Extract the requested rows
+    for row_idx in data_start..data_end {
+        if row_idx < rows.len() {
+            let row_data: Vec<String> = rows[row_idx]
+                .iter()
+                .map(|cell| cell.to_string())
+                .collect();
+            data.push(row_data);
+        }
+    }
+
+    // Check if we've reached the end of the sheet
+    let end_of_file = data_end >= rows.len();
+
+    Ok(DataPage {
+        headers,
+        rows: data,
+        sheet_names,
+        end_of_file,
+        ..DataPage::default()
+    })
+}
+
+/// Number of data rows in worksheet `sheet_index`, from the dimensions of
+/// its used range; the first row is the header.
+pub fn excel_row_count(path: &Path, sheet_index: usize) -> Result<usize, LoadError> {
+    let mut workbook =
+        open_workbook_auto(path).map_err(|e| LoadError::OpenWorkbook(e.to_string()))?;
+    let sheet_names = workbook.sheet_names().to_owned();
+    let Some(sheet_name) = sheet_names.get(sheet_index).or(sheet_names.first()) else {
+        return Err(LoadError::NoWorksheets);
+    };
+    let range = workbook
+        .worksheet_range(sheet_name)
+        .map_err(|e| LoadError::ReadWorksheet {
+            sheet: sheet_name.clone(),
+            detail: e.to_string(),
+        })?;
+    Ok(range.height().saturating_sub(1))
+}
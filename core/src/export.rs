@@ -0,0 +1,59 @@
+use std::path::Path;
+
+/// Writes `headers` and `rows` to a CSV file at `path`, replacing it.
+pub fn write_csv(path: &Path, headers: &[String], rows: &[Vec<String>]) -> Result<(), String> {
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to create '{}': {}", path.display(), e))?;
+
+    if !headers.is_empty() {
+        writer.write_record(headers).map_err(|e| e.to_string())?;
+    }
+    for row in rows {
+        writer.write_record(row).map_err(|e| e.to_string())?;
+    }
+
+    writer.flush().map_err(|e| e.to_string())
+}
+
+/// Streams `rows` into a CSV file at `path` and returns the number of rows
+/// written. Stops at the first row error.
+pub fn write_csv_rows<I>(path: &Path, headers: &[String], rows: I) -> Result<usize, String>
+where
+    I: IntoIterator<Item = Result<Vec<String>, String>>,
+{
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to create '{}': {}", path.display(), e))?;
+
+    if !headers.is_empty() {
+        writer.write_record(headers).map_err(|e| e.to_string())?;
+    }
+    let mut count = 0;
+    for row in rows {
+        writer.write_record(row?).map_err(|e| e.to_string())?;
+        count += 1;
+    }
+
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// Formats one row as a JSON object of header to value, in column order.
+/// Columns without a header are named by their 1-based position.
+pub fn row_json(headers: &[String], row: &[String]) -> String {
+    let object: serde_json::Map<String, serde_json::Value> = row
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let name = match headers.get(index) {
+                Some(header) if !header.is_empty() => header.clone(),
+                _ => format!("column {}", index + 1),
+            };
+            (name, serde_json::Value::String(value.clone()))
+        })
+        .collect();
+    serde_json::to_string_pretty(&object).unwrap_or_default()
+}
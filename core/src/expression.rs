@@ -0,0 +1,1103 @@
+use crate::values::{format_number, parse_number};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Timelike};
+use std::cmp::Ordering;
+
+/// Date and date-time layouts accepted wherever text is used as a date.
+const DATE_TIME_FORMATS: [&str; 6] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y/%m/%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M",
+    "%m/%d/%Y %H:%M:%S",
+];
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%Y/%m/%d", "%Y%m%d", "%m/%d/%Y"];
+
+/// How deeply parentheses, function calls and prefix operators may nest,
+/// so a pathological expression is refused instead of overflowing the
+/// stack.
+const MAX_DEPTH: usize = 256;
+
+/// A compiled expression over the cells of one row.
+///
+/// Columns are referenced by header name: plain names like `wd - wp`, or
+/// `[Die X]` / `` `Die X` `` for names with spaces or symbols. Supported are
+/// arithmetic (`+ - * / % ^`), comparison (`= == != <> < <= > >=`), logic
+/// (`and or not`, `&& || !`), text concatenation with `&`, string literals
+/// in single or double quotes, and the functions listed in [`FUNCTIONS`].
+#[derive(Debug, Clone)]
+pub struct Expression {
+    root: Node,
+}
+
+/// Function names with a short signature, for help text in the GUI.
+pub const FUNCTIONS: &[(&str, &str)] = &[
+    ("if", "if(condition, then, else)"),
+    ("coalesce", "coalesce(a, b, ...) — first non-empty value"),
+    ("is_empty", "is_empty(x)"),
+    ("abs", "abs(x)"),
+    ("round", "round(x, digits = 0)"),
+    ("floor", "floor(x)"),
+    ("ceil", "ceil(x)"),
+    ("sqrt", "sqrt(x)"),
+    ("pow", "pow(x, y)"),
+    ("exp", "exp(x)"),
+    ("ln", "ln(x)"),
+    ("log10", "log10(x)"),
+    ("min", "min(a, b, ...)"),
+    ("max", "max(a, b, ...)"),
+    ("number", "number(text)"),
+    ("text", "text(x)"),
+    ("len", "len(text)"),
+    ("upper", "upper(text)"),
+    ("lower", "lower(text)"),
+    ("trim", "trim(text)"),
+    ("concat", "concat(a, b, ...)"),
+    ("substr", "substr(text, start, length) — start at 1"),
+    ("left", "left(text, n)"),
+    ("right", "right(text, n)"),
+    ("contains", "contains(text, part)"),
+    ("starts_with", "starts_with(text, prefix)"),
+    ("ends_with", "ends_with(text, suffix)"),
+    ("replace", "replace(text, from, to)"),
+    ("date", "date(text) — parse a date or date-time"),
+    ("today", "today()"),
+    ("now", "now()"),
+    ("year", "year(date)"),
+    ("month", "month(date)"),
+    ("day", "day(date)"),
+    ("hour", "hour(date)"),
+    ("minute", "minute(date)"),
+    ("second", "second(date)"),
+    ("weekday", "weekday(date) — 1 = Monday"),
+    ("add_days", "add_days(date, n)"),
+    ("days_between", "days_between(from, to)"),
+    ("format_date", "format_date(date, \"%Y-%m-%d\")"),
+];
+
+/// A value produced while evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Date(NaiveDateTime),
+}
+
+impl Value {
+    /// Interprets a cell: blanks are null and numbers are numbers; anything
+    /// else stays text until a date function asks for a date.
+    pub fn from_cell(cell: &str) -> Self {
+        if cell.trim().is_empty() {
+            Value::Null
+        } else if let Some(number) = parse_number(cell) {
+            Value::Number(number)
+        } else {
+            Value::Text(cell.to_string())
+        }
+    }
+
+    /// Formats the value as cell text.
+    pub fn to_cell(&self) -> String {
+        match self {
+            Value::Null => String::new(),
+            Value::Number(n) => format_number(*n),
+            Value::Text(s) => s.clone(),
+            Value::Bool(b) => b.to_string(),
+            Value::Date(d) if d.time() == chrono::NaiveTime::MIN => {
+                d.format("%Y-%m-%d").to_string()
+            }
+            Value::Date(d) => d.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+
+    /// Truthiness used by `if`, `and`, `or` and row filters.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Number(n) => *n != 0.0 && !n.is_nan(),
+            Value::Text(s) => !s.is_empty() && !s.eq_ignore_ascii_case("false"),
+            Value::Bool(b) => *b,
+            Value::Date(_) => true,
+        }
+    }
+
+    fn as_number(&self) -> Result<f64, String> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            Value::Bool(b) => Ok(if *b { 1.0 } else { 0.0 }),
+            Value::Text(s) => parse_number(s).ok_or_else(|| format!("'{}' is not a number", s)),
+            Value::Null => Err("empty value".to_string()),
+            Value::Date(_) => Err("a date is not a number".to_string()),
+        }
+    }
+
+    fn as_text(&self) -> String {
+        self.to_cell()
+    }
+
+    fn as_date(&self) -> Result<NaiveDateTime, String> {
+        match self {
+            Value::Date(d) => Ok(*d),
+            Value::Text(s) => parse_date(s).ok_or_else(|| format!("'{}' is not a date", s)),
+            // Compact dates such as 20240131 parse as numbers first
+            Value::Number(n) if n.fract() == 0.0 => parse_date(&format!("{}", *n as i64))
+                .ok_or_else(|| format!("'{}' is not a date", format_number(*n))),
+            other => Err(format!("'{}' is not a date", other.to_cell())),
+        }
+    }
+}
+
+/// Parses the date and date-time layouts in [`DATE_TIME_FORMATS`] and
+/// [`DATE_FORMATS`].
+pub fn parse_date(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+                .map(|date| date.and_time(chrono::NaiveTime::MIN))
+        })
+}
+
+impl Expression {
+    /// Parses `source`, resolving column names against `headers`. Names are
+    /// matched exactly first, then case-insensitively.
+    pub fn compile(source: &str, headers: &[String]) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+            headers,
+        };
+        let root = parser.expression()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!(
+                "Unexpected {} at position {}",
+                token.kind,
+                token.offset + 1
+            ));
+        }
+        Ok(Self { root })
+    }
+
+    /// Evaluates the expression for one row.
+    pub fn evaluate(&self, row: &[String]) -> Result<Value, String> {
+        self.root.evaluate(row)
+    }
+
+    /// Evaluates the expression and formats the result as a cell; errors
+    /// are shown in the cell as `#ERR: ...`.
+    pub fn evaluate_cell(&self, row: &[String]) -> String {
+        match self.evaluate(row) {
+            Ok(value) => value.to_cell(),
+            Err(e) => format!("#ERR: {}", e),
+        }
+    }
+
+    /// `true` if the expression is truthy for the row; errors count as false.
+    pub fn matches(&self, row: &[String]) -> bool {
+        self.evaluate(row).is_ok_and(|value| value.is_truthy())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Literal(Value),
+    Column(usize),
+    Unary(UnaryOp, Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum UnaryOp {
+    Negate,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+    Concat,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    If,
+    Coalesce,
+    IsEmpty,
+    Abs,
+    Round,
+    Floor,
+    Ceil,
+    Sqrt,
+    Pow,
+    Exp,
+    Ln,
+    Log10,
+    Min,
+    Max,
+    Number,
+    Text,
+    Len,
+    Upper,
+    Lower,
+    Trim,
+    Concat,
+    Substr,
+    Left,
+    Right,
+    Contains,
+    StartsWith,
+    EndsWith,
+    Replace,
+    Date,
+    Today,
+    Now,
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Weekday,
+    AddDays,
+    DaysBetween,
+    FormatDate,
+}
+
+impl Function {
+    /// Looks up a function by name and returns it with its argument range.
+    fn lookup(name: &str) -> Option<(Self, usize, usize)> {
+        let many = usize::MAX;
+        Some(match name.to_ascii_lowercase().as_str() {
+            "if" => (Function::If, 3, 3),
+            "coalesce" => (Function::Coalesce, 1, many),
+            "is_empty" => (Function::IsEmpty, 1, 1),
+            "abs" => (Function::Abs, 1, 1),
+            "round" => (Function::Round, 1, 2),
+            "floor" => (Function::Floor, 1, 1),
+            "ceil" => (Function::Ceil, 1, 1),
+            "sqrt" => (Function::Sqrt, 1, 1),
+            "pow" => (Function::Pow, 2, 2),
+            "exp" => (Function::Exp, 1, 1),
+            "ln" => (Function::Ln, 1, 1),
+            "log10" => (Function::Log10, 1, 1),
+            "min" => (Function::Min, 1, many),
+            "max" => (Function::Max, 1, many),
+            "number" => (Function::Number, 1, 1),
+            "text" => (Function::Text, 1, 1),
+            "len" => (Function::Len, 1, 1),
+            "upper" => (Function::Upper, 1, 1),
+            "lower" => (Function::Lower, 1, 1),
+            "trim" => (Function::Trim, 1, 1),
+            "concat" => (Function::Concat, 1, many),
+            "substr" => (Function::Substr, 2, 3),
+            "left" => (Function::Left, 2, 2),
+            "right" => (Function::Right, 2, 2),
+            "contains" => (Function::Contains, 2, 2),
+            "starts_with" => (Function::StartsWith, 2, 2),
+            "ends_with" => (Function::EndsWith, 2, 2),
+            "replace" => (Function::Replace, 3, 3),
+            "date" => (Function::Date, 1, 1),
+            "today" => (Function::Today, 0, 0),
+            "now" => (Function::Now, 0, 0),
+            "year" => (Function::Year, 1, 1),
+            "month" => (Function::Month, 1, 1),
+            "day" => (Function::Day, 1, 1),
+            "hour" => (Function::Hour, 1, 1),
+            "minute" => (Function::Minute, 1, 1),
+            "second" => (Function::Second, 1, 1),
+            "weekday" => (Function::Weekday, 1, 1),
+            "add_days" => (Function::AddDays, 2, 2),
+            "days_between" => (Function::DaysBetween, 2, 2),
+            "format_date" => (Function::FormatDate, 2, 2),
+            _ => return None,
+        })
+    }
+}
+
+impl Node {
+    fn evaluate(&self, row: &[String]) -> Result<Value, String> {
+        match self {
+            Node::Literal(value) => Ok(value.clone()),
+            Node::Column(index) => Ok(Value::from_cell(
+                row.get(*index).map(String::as_str).unwrap_or_default(),
+            )),
+            Node::Unary(UnaryOp::Negate, operand) => match operand.evaluate(row)? {
+                Value::Null => Ok(Value::Null),
+                value => Ok(Value::Number(-value.as_number()?)),
+            },
+            Node::Unary(UnaryOp::Not, operand) => {
+                Ok(Value::Bool(!operand.evaluate(row)?.is_truthy()))
+            }
+            Node::Binary(BinaryOp::And, left, right) => Ok(Value::Bool(
+                left.evaluate(row)?.is_truthy() && right.evaluate(row)?.is_truthy(),
+            )),
+            Node::Binary(BinaryOp::Or, left, right) => Ok(Value::Bool(
+                left.evaluate(row)?.is_truthy() || right.evaluate(row)?.is_truthy(),
+            )),
+            Node::Binary(op, left, right) => binary(*op, left.evaluate(row)?, right.evaluate(row)?),
+            Node::Call(Function::If, args) => {
+                if args[0].evaluate(row)?.is_truthy() {
+                    args[1].evaluate(row)
+                } else {
+                    args[2].evaluate(row)
+                }
+            }
+            Node::Call(Function::Coalesce, args) => {
+                for arg in args {
+                    let value = arg.evaluate(row)?;
+                    if value != Value::Null && value != Value::Text(String::new()) {
+                        return Ok(value);
+                    }
+                }
+                Ok(Value::Null)
+            }
+            Node::Call(function, args) => {
+                let values = args
+                    .iter()
+                    .map(|arg| arg.evaluate(row))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call(*function, &values)
+            }
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value, String> {
+    use BinaryOp::*;
+
+    match op {
+        Concat => return Ok(Value::Text(left.as_text() + &right.as_text())),
+        Equal => return Ok(Value::Bool(compare(&left, &right) == Some(Ordering::Equal))),
+        NotEqual => return Ok(Value::Bool(compare(&left, &right) != Some(Ordering::Equal))),
+        Less | LessEqual | Greater | GreaterEqual => {
+            let Some(ordering) = compare(&left, &right) else {
+                return Ok(Value::Bool(false));
+            };
+            return Ok(Value::Bool(match op {
+                Less => ordering.is_lt(),
+                LessEqual => ordering.is_le(),
+                Greater => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }));
+        }
+        _ => {}
+    }
+
+    // Arithmetic with an empty cell gives an empty result, like a spreadsheet
+    // that leaves the formula cell blank.
+    if left == Value::Null || right == Value::Null {
+        return Ok(Value::Null);
+    }
+
+    match (op, &left, &right) {
+        (Add, Value::Date(d), other) | (Add, other, Value::Date(d)) => {
+            return add_days(*d, other.as_number()?).map(Value::Date);
+        }
+        (Subtract, Value::Date(d), Value::Date(e)) => {
+            return Ok(Value::Number((*d - *e).num_seconds() as f64 / 86_400.0));
+        }
+        (Subtract, Value::Date(d), other) => {
+            return add_days(*d, -other.as_number()?).map(Value::Date);
+        }
+        // `+` on two non-numeric texts joins them
+        (Add, Value::Text(a), Value::Text(b))
+            if parse_number(a).is_none() && parse_number(b).is_none() =>
+        {
+            return Ok(Value::Text(format!("{}{}", a, b)));
+        }
+        _ => {}
+    }
+
+    let (a, b) = (left.as_number()?, right.as_number()?);
+    let result = match op {
+        Add => a + b,
+        Subtract => a - b,
+        Multiply => a * b,
+        Divide if b == 0.0 => return Err("division by zero".to_string()),
+        Divide => a / b,
+        Remainder if b == 0.0 => return Err("division by zero".to_string()),
+        Remainder => a % b,
+        Power => a.powf(b),
+        _ => unreachable!("handled above"),
+    };
+    Ok(Value::Number(result))
+}
+
+/// Compares two values: numerically when both are numbers, as dates when
+/// either is a date, as text otherwise. Returns `None` when a date cannot be
+/// compared with the other value.
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Null, Value::Null) => Some(Ordering::Equal),
+        (Value::Null, _) | (_, Value::Null) => {
+            // A blank cell equals an empty string and nothing else
+            let other = if *left == Value::Null { right } else { left };
+            if *other == Value::Text(String::new()) {
+                Some(Ordering::Equal)
+            } else if *left == Value::Null {
+                Some(Ordering::Less)
+            } else {
+                Some(Ordering::Greater)
+            }
+        }
+        (Value::Date(_), _) | (_, Value::Date(_)) => {
+            Some(left.as_date().ok()?.cmp(&right.as_date().ok()?))
+        }
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => match (left.as_number(), right.as_number()) {
+            (Ok(a), Ok(b)) => Some(a.total_cmp(&b)),
+            _ => Some(left.as_text().cmp(&right.as_text())),
+        },
+    }
+}
+
+/// Moves `date` by `n` days, which may be fractional or negative.
+fn add_days(date: NaiveDateTime, n: f64) -> Result<NaiveDateTime, String> {
+    Duration::try_milliseconds((n * 86_400_000.0).round() as i64)
+        .and_then(|days| date.checked_add_signed(days))
+        .ok_or_else(|| "date out of range".to_string())
+}
+
+/// Formats `date` with a strftime `format`. Dates carry no time zone, so
+/// offset and zone specifiers such as `%z` are errors rather than a panic
+/// inside chrono.
+fn format_date(date: NaiveDateTime, format: &str) -> Result<String, String> {
+    use std::fmt::Write as _;
+
+    let items = chrono::format::StrftimeItems::new(format)
+        .parse()
+        .map_err(|_| format!("invalid date format '{}'", format))?;
+    let mut text = String::new();
+    if write!(text, "{}", date.format_with_items(items.into_iter())).is_err() {
+        let specifier = ["%:::z", "%::z", "%:z", "%#z", "%z", "%Z"]
+            .into_iter()
+            .find(|specifier| format.contains(specifier));
+        return Err(match specifier {
+            Some(specifier) => format!("'{}' needs a time zone, which dates do not have", specifier),
+            None => format!("unsupported date format '{}'", format),
+        });
+    }
+    Ok(text)
+}
+
+fn call(function: Function, args: &[Value]) -> Result<Value, String> {
+    use Function::*;
+
+    let number = |i: usize| args[i].as_number();
+    let text = |i: usize| args[i].as_text();
+    let date = |i: usize| args[i].as_date();
+    let numeric = |f: fn(f64) -> f64| -> Result<Value, String> {
+        match &args[0] {
+            Value::Null => Ok(Value::Null),
+            value => Ok(Value::Number(f(value.as_number()?))),
+        }
+    };
+    // Date parts of an empty cell are empty
+    if matches!(
+        function,
+        Year | Month | Day | Hour | Minute | Second | Weekday | FormatDate
+    ) && args[0] == Value::Null
+    {
+        return Ok(Value::Null);
+    }
+
+    Ok(match function {
+        If | Coalesce => unreachable!("evaluated lazily"),
+        IsEmpty => Value::Bool(args[0] == Value::Null || text(0).trim().is_empty()),
+        Abs => return numeric(f64::abs),
+        Floor => return numeric(f64::floor),
+        Ceil => return numeric(f64::ceil),
+        Sqrt => return numeric(f64::sqrt),
+        Exp => return numeric(f64::exp),
+        Ln => return numeric(f64::ln),
+        Log10 => return numeric(f64::log10),
+        Round => {
+            if args[0] == Value::Null {
+                return Ok(Value::Null);
+            }
+            let digits = if args.len() > 1 { number(1)? as i32 } else { 0 };
+            let factor = 10f64.powi(digits);
+            Value::Number((number(0)? * factor).round() / factor)
+        }
+        Pow => Value::Number(number(0)?.powf(number(1)?)),
+        Min | Max => {
+            let mut best: Option<f64> = None;
+            for value in args.iter().filter(|v| **v != Value::Null) {
+                let n = value.as_number()?;
+                best = Some(match best {
+                    None => n,
+                    Some(b) if matches!(function, Min) => b.min(n),
+                    Some(b) => b.max(n),
+                });
+            }
+            best.map_or(Value::Null, Value::Number)
+        }
+        Number => match &args[0] {
+            Value::Null => Value::Null,
+            value => Value::Number(value.as_number()?),
+        },
+        Text => Value::Text(text(0)),
+        Len => Value::Number(text(0).chars().count() as f64),
+        Upper => Value::Text(text(0).to_uppercase()),
+        Lower => Value::Text(text(0).to_lowercase()),
+        Trim => Value::Text(text(0).trim().to_string()),
+        Concat => Value::Text(args.iter().map(Value::as_text).collect()),
+        Substr => {
+            let start = (number(1)?.max(1.0) as usize) - 1;
+            let s = text(0);
+            let chars = s.chars().skip(start);
+            Value::Text(if args.len() > 2 {
+                chars.take(number(2)?.max(0.0) as usize).collect()
+            } else {
+                chars.collect()
+            })
+        }
+        Left => Value::Text(text(0).chars().take(number(1)?.max(0.0) as usize).collect()),
+        Right => {
+            let s = text(0);
+            let count = s.chars().count();
+            let n = (number(1)?.max(0.0) as usize).min(count);
+            Value::Text(s.chars().skip(count - n).collect())
+        }
+        Contains => Value::Bool(text(0).contains(&text(1))),
+        StartsWith => Value::Bool(text(0).starts_with(&text(1))),
+        EndsWith => Value::Bool(text(0).ends_with(&text(1))),
+        Replace => Value::Text(text(0).replace(&text(1), &text(2))),
+        Date => match &args[0] {
+            Value::Null => Value::Null,
+            _ => Value::Date(date(0)?),
+        },
+        Today => Value::Date(Local::now().date_naive().and_time(chrono::NaiveTime::MIN)),
+        Now => Value::Date(Local::now().naive_local()),
+        Year => Value::Number(date(0)?.year() as f64),
+        Month => Value::Number(date(0)?.month() as f64),
+        Day => Value::Number(date(0)?.day() as f64),
+        Hour => Value::Number(date(0)?.hour() as f64),
+        Minute => Value::Number(date(0)?.minute() as f64),
+        Second => Value::Number(date(0)?.second() as f64),
+        Weekday => Value::Number(date(0)?.weekday().number_from_monday() as f64),
+        AddDays => Value::Date(add_days(date(0)?, number(1)?)?),
+        DaysBetween => Value::Number((date(1)? - date(0)?).num_seconds() as f64 / 86_400.0),
+        FormatDate => Value::Text(format_date(date(0)?, &text(1))?),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Text(String),
+    Identifier(String),
+    /// A `[bracketed]` or `` `quoted` `` column name.
+    QuotedName(String),
+    Symbol(&'static str),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Number(n) => write!(f, "number {}", n),
+            TokenKind::Text(s) => write!(f, "text \"{}\"", s),
+            TokenKind::Identifier(s) | TokenKind::QuotedName(s) => write!(f, "'{}'", s),
+            TokenKind::Symbol(s) => write!(f, "'{}'", s),
+            TokenKind::LeftParen => write!(f, "'('"),
+            TokenKind::RightParen => write!(f, "')'"),
+            TokenKind::Comma => write!(f, "','"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// Character offset in the source, for error messages.
+    offset: usize,
+}
+
+/// Operators, longest first so `<=` wins over `<`.
+const SYMBOLS: [&str; 18] = [
+    "==", "!=", "<>", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "^", "&", "=", "<", ">", "!",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let offset = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()))
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponent, as in 1e-3
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            TokenKind::Number(
+                text.parse()
+                    .map_err(|_| format!("Invalid number '{}' at position {}", text, offset + 1))?,
+            )
+        } else if c == '"' || c == '\'' {
+            let (text, end) = read_delimited(&chars, i, c)?;
+            i = end;
+            TokenKind::Text(text)
+        } else if c == '[' || c == '`' {
+            let close = if c == '[' { ']' } else { '`' };
+            let (name, end) = read_delimited(&chars, i, close)?;
+            i = end;
+            TokenKind::QuotedName(name)
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            TokenKind::Identifier(chars[start..i].iter().collect())
+        } else if c == '(' {
+            i += 1;
+            TokenKind::LeftParen
+        } else if c == ')' {
+            i += 1;
+            TokenKind::RightParen
+        } else if c == ',' {
+            i += 1;
+            TokenKind::Comma
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(**s))
+                .ok_or_else(|| format!("Unexpected '{}' at position {}", c, offset + 1))?;
+            i += symbol.chars().count();
+            TokenKind::Symbol(symbol)
+        };
+
+        tokens.push(Token { kind, offset });
+    }
+
+    Ok(tokens)
+}
+
+/// Reads text from the opening delimiter at `start` up to `close`. A doubled
+/// closing delimiter stands for itself. Returns the text and the index after
+/// the closing delimiter.
+fn read_delimited(chars: &[char], start: usize, close: char) -> Result<(String, usize), String> {
+    let mut text = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == close {
+            if chars.get(i + 1) == Some(&close) && close != ']' {
+                text.push(close);
+                i += 2;
+                continue;
+            }
+            return Ok((text, i + 1));
+        }
+        text.push(chars[i]);
+        i += 1;
+    }
+    Err(format!(
+        "Missing closing {} for position {}",
+        close,
+        start + 1
+    ))
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    /// Current nesting, see [`MAX_DEPTH`].
+    depth: usize,
+    headers: &'a [String],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// Consumes the next token if it is one of `symbols` (or a keyword
+    /// operator standing for one of them) and returns the symbol.
+    fn eat_symbol(&mut self, symbols: &[&'static str]) -> Option<&'static str> {
+        let symbol = match &self.peek()?.kind {
+            TokenKind::Symbol(s) => *s,
+            TokenKind::Identifier(word) => match word.to_ascii_lowercase().as_str() {
+                "and" => "&&",
+                "or" => "||",
+                "not" => "!",
+                _ => return None,
+            },
+            _ => return None,
+        };
+        if symbols.contains(&symbol) {
+            self.position += 1;
+            Some(symbol)
+        } else {
+            None
+        }
+    }
+
+    /// Parses one nested part with `parse`, failing once the nesting goes
+    /// past [`MAX_DEPTH`].
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Node, String>,
+    ) -> Result<Node, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err(format!(
+                "Expression is nested more than {} levels deep",
+                MAX_DEPTH
+            ));
+        }
+        self.depth += 1;
+        let node = parse(self);
+        self.depth -= 1;
+        node
+    }
+
+    fn expression(&mut self) -> Result<Node, String> {
+        self.or()
+    }
+
+    fn or(&mut self) -> Result<Node, String> {
+        let mut left = self.and()?;
+        while self.eat_symbol(&["||"]).is_some() {
+            left = Node::Binary(BinaryOp::Or, Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Node, String> {
+        let mut left = self.not()?;
+        while self.eat_symbol(&["&&"]).is_some() {
+            left = Node::Binary(BinaryOp::And, Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Node, String> {
+        if self.eat_symbol(&["!"]).is_some() {
+            return Ok(Node::Unary(UnaryOp::Not, Box::new(self.nested(Self::not)?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Node, String> {
+        let left = self.additive()?;
+        let op = match self.eat_symbol(&["==", "=", "!=", "<>", "<", "<=", ">", ">="]) {
+            Some("==") | Some("=") => BinaryOp::Equal,
+            Some("!=") | Some("<>") => BinaryOp::NotEqual,
+            Some("<") => BinaryOp::Less,
+            Some("<=") => BinaryOp::LessEqual,
+            Some(">") => BinaryOp::Greater,
+            Some(">=") => BinaryOp::GreaterEqual,
+            _ => return Ok(left),
+        };
+        Ok(Node::Binary(op, Box::new(left), Box::new(self.additive()?)))
+    }
+
+    fn additive(&mut self) -> Result<Node, String> {
+        let mut left = self.multiplicative()?;
+        while let Some(symbol) = self.eat_symbol(&["+", "-", "&"]) {
+            let op = match symbol {
+                "+" => BinaryOp::Add,
+                "-" => BinaryOp::Subtract,
+                _ => BinaryOp::Concat,
+            };
+            left = Node::Binary(op, Box::new(left), Box::new(self.multiplicative()?));
+        }
+        Ok(left)
+    }
+
+    fn multiplicative(&mut self) -> Result<Node, String> {
+        let mut left = self.unary()?;
+        while let Some(symbol) = self.eat_symbol(&["*", "/", "%"]) {
+            let op = match symbol {
+                "*" => BinaryOp::Multiply,
+                "/" => BinaryOp::Divide,
+                _ => BinaryOp::Remainder,
+            };
+            left = Node::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        match self.eat_symbol(&["-", "+"]) {
+            Some("-") => Ok(Node::Unary(
+                UnaryOp::Negate,
+                Box::new(self.nested(Self::unary)?),
+            )),
+            Some(_) => self.nested(Self::unary),
+            None => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Node, String> {
+        let base = self.primary()?;
+        if self.eat_symbol(&["^"]).is_some() {
+            // Right-associative, and binds tighter than a leading minus
+            return Ok(Node::Binary(
+                BinaryOp::Power,
+                Box::new(base),
+                Box::new(self.nested(Self::unary)?),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        let Some(token) = self.next() else {
+            return Err("Unexpected end of expression".to_string());
+        };
+
+        match token.kind {
+            TokenKind::Number(n) => Ok(Node::Literal(Value::Number(n))),
+            TokenKind::Text(s) => Ok(Node::Literal(Value::Text(s))),
+            TokenKind::QuotedName(name) => self.column(&name, token.offset),
+            TokenKind::LeftParen => {
+                let inner = self.nested(Self::expression)?;
+                self.expect_right_paren()?;
+                Ok(inner)
+            }
+            TokenKind::Identifier(name) => {
+                if matches!(self.peek().map(|t| &t.kind), Some(TokenKind::LeftParen)) {
+                    return self.call(&name, token.offset);
+                }
+                match name.to_ascii_lowercase().as_str() {
+                    "true" if self.find_column(&name).is_none() => {
+                        Ok(Node::Literal(Value::Bool(true)))
+                    }
+                    "false" if self.find_column(&name).is_none() => {
+                        Ok(Node::Literal(Value::Bool(false)))
+                    }
+                    "null" if self.find_column(&name).is_none() => Ok(Node::Literal(Value::Null)),
+                    _ => self.column(&name, token.offset),
+                }
+            }
+            kind => Err(format!(
+                "Unexpected {} at position {}",
+                kind,
+                token.offset + 1
+            )),
+        }
+    }
+
+    fn call(&mut self, name: &str, offset: usize) -> Result<Node, String> {
+        let (function, min_args, max_args) = Function::lookup(name)
+            .ok_or_else(|| format!("Unknown function '{}' at position {}", name, offset + 1))?;
+        self.next(); // '('
+
+        let mut args = Vec::new();
+        if !matches!(self.peek().map(|t| &t.kind), Some(TokenKind::RightParen)) {
+            loop {
+                args.push(self.nested(Self::expression)?);
+                if matches!(self.peek().map(|t| &t.kind), Some(TokenKind::Comma)) {
+                    self.next();
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect_right_paren()?;
+
+        if args.len() < min_args || args.len() > max_args {
+            let expected = if min_args == max_args {
+                min_args.to_string()
+            } else if max_args == usize::MAX {
+                format!("at least {}", min_args)
+            } else {
+                format!("{} to {}", min_args, max_args)
+            };
+            let noun = if max_args == 1 { "argument" } else { "arguments" };
+            return Err(format!(
+                "{}() takes {} {}, got {}",
+                name,
+                expected,
+                noun,
+                args.len()
+            ));
+        }
+        Ok(Node::Call(function, args))
+    }
+
+    fn expect_right_paren(&mut self) -> Result<(), String> {
+        match self.next() {
+            Some(Token {
+                kind: TokenKind::RightParen,
+                ..
+            }) => Ok(()),
+            Some(token) => Err(format!(
+                "Expected ')' but found {} at position {}",
+                token.kind,
+                token.offset + 1
+            )),
+            None => Err("Missing ')'".to_string()),
+        }
+    }
+
+    fn find_column(&self, name: &str) -> Option<usize> {
+        self.headers
+            .iter()
+            .position(|h| h.trim() == name)
+            .or_else(|| {
+                self.headers
+                    .iter()
+                    .position(|h| h.trim().eq_ignore_ascii_case(name))
+            })
+    }
+
+    fn column(&self, name: &str, offset: usize) -> Result<Node, String> {
+        self.find_column(name)
+            .map(Node::Column)
+            .ok_or_else(|| format!("Unknown column '{}' at position {}", name, offset + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, headers: &[&str], row: &[&str]) -> Result<Value, String> {
+        let headers: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+        let row: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+        Expression::compile(source, &headers)?.evaluate(&row)
+    }
+
+    fn cell(source: &str) -> String {
+        Expression::compile(source, &[]).unwrap().evaluate_cell(&[])
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(cell("1 + 2 * 3"), "7");
+        assert_eq!(cell("(1 + 2) * 3"), "9");
+        assert_eq!(cell("2 ^ 3 * 2"), "16");
+        assert_eq!(cell("10 - 4 - 3"), "3");
+        assert_eq!(cell("-2 + 5"), "3");
+        assert_eq!(cell("1 + 1 = 2 and 3 > 2"), "true");
+        assert_eq!(cell("not 1 > 2 or false"), "true");
+        assert_eq!(cell("\"n=\" & (1 + 2)"), "n=3");
+        assert_eq!(cell("1 + 2 & \"x\""), "3x");
+    }
+
+    #[test]
+    fn columns() {
+        let headers = ["Die X", "wd"];
+        assert_eq!(eval("[Die X] * 2 + wd", &headers, &["3", "1"]), Ok(Value::Number(7.0)));
+        assert_eq!(eval("WD + 1", &headers, &["3", "1"]), Ok(Value::Number(2.0)));
+        assert_eq!(eval("wd + 1", &headers, &["3", ""]), Ok(Value::Null));
+        assert!(Expression::compile("missing + 1", &[]).is_err());
+    }
+
+    #[test]
+    fn date_arithmetic() {
+        assert_eq!(cell("date(\"2024-02-28\") + 2"), "2024-03-01");
+        assert_eq!(cell("date(\"2024-03-01\") - 1"), "2024-02-29");
+        assert_eq!(cell("add_days(\"2024-01-01\", 0.5)"), "2024-01-01 12:00:00");
+        assert_eq!(cell("date(\"2024-03-01\") - date(\"2024-02-01\")"), "29");
+        assert_eq!(cell("days_between(\"2024-01-01\", \"2024-01-31\")"), "30");
+        assert_eq!(cell("weekday(\"2024-01-01\")"), "1");
+    }
+
+    #[test]
+    fn date_out_of_range() {
+        let error = Err("date out of range".to_string());
+        assert_eq!(eval("date(\"2024-01-01\") + 1e9", &[], &[]), error);
+        assert_eq!(eval("date(\"2024-01-01\") - 1e9", &[], &[]), error);
+        assert_eq!(eval("add_days(\"2024-01-01\", 1e9)", &[], &[]), error);
+        assert_eq!(eval("add_days(\"2024-01-01\", 1e300)", &[], &[]), error);
+    }
+
+    #[test]
+    fn format_date_errors() {
+        assert_eq!(cell("format_date(\"2024-01-31\", \"%d/%m/%Y\")"), "31/01/2024");
+        assert!(eval("format_date(\"2024-01-31\", \"%Q\")", &[], &[])
+            .unwrap_err()
+            .contains("invalid date format"));
+        for specifier in ["%z", "%:z", "%Z"] {
+            let format = format!("format_date(\"2024-01-31\", \"%Y {}\")", specifier);
+            let error = eval(&format, &[], &[]).unwrap_err();
+            assert!(error.contains(specifier), "{}", error);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(eval("1 / 0", &[], &[]), Err("division by zero".to_string()));
+        assert_eq!(eval("5 % 0", &[], &[]), Err("division by zero".to_string()));
+        assert!(eval("\"abc\" * 2", &[], &[]).is_err());
+        assert!(eval("year(\"soon\")", &[], &[]).is_err());
+        assert!(Expression::compile("1 +", &[]).is_err());
+        assert!(Expression::compile("(1 + 2", &[]).is_err());
+        assert!(Expression::compile("1 2", &[]).is_err());
+        assert_eq!(cell("1 / 0"), "#ERR: division by zero");
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let parens = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(eval(&parens(MAX_DEPTH), &[], &[]), Ok(Value::Number(1.0)));
+        let too_deep = format!("Expression is nested more than {} levels deep", MAX_DEPTH);
+        for source in [
+            parens(100_000),
+            format!("{}1", "-".repeat(100_000)),
+            format!("{}true", "!".repeat(100_000)),
+            format!("{}1", "2^".repeat(100_000)),
+            format!("{}1{}", "abs(".repeat(100_000), ")".repeat(100_000)),
+        ] {
+            assert_eq!(Expression::compile(&source, &[]).unwrap_err(), too_deep);
+        }
+    }
+}
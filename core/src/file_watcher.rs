@@ -0,0 +1,58 @@
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+
+/// Watches a single file and calls back whenever it is written, replaced or
+/// recreated. Dropping the `FileWatcher` stops watching.
+pub struct FileWatcher {
+    _watcher: notify::RecommendedWatcher,
+    path: PathBuf,
+}
+
+impl FileWatcher {
+    /// Starts watching `path`. `on_change` runs on the watcher thread, so it
+    /// should only signal the owner (e.g. send on a channel) and return.
+    ///
+    /// The parent directory is watched rather than the file itself, because
+    /// many tools save by writing a temporary file and renaming it over the
+    /// original, which would otherwise detach a file-level watch.
+    pub fn watch<F>(path: &Path, on_change: F) -> Result<Self, String>
+    where
+        F: Fn() + Send + 'static,
+    {
+        let path = path
+            .canonicalize()
+            .map_err(|e| format!("Failed to watch '{}': {}", path.display(), e))?;
+        let dir = path
+            .parent()
+            .ok_or_else(|| format!("Failed to watch '{}': no parent directory", path.display()))?
+            .to_path_buf();
+
+        let target = path.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else { return };
+
+            let is_change = matches!(
+                event.kind,
+                EventKind::Modify(_) | EventKind::Create(_) | EventKind::Any
+            );
+            if is_change && event.paths.iter().any(|p| p == &target) {
+                on_change();
+            }
+        })
+        .map_err(|e| format!("Failed to watch '{}': {}", path.display(), e))?;
+
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch '{}': {}", path.display(), e))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            path,
+        })
+    }
+
+    /// The canonical path being watched.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
@@ -0,0 +1,277 @@
+use crate::data_loader::{DataLoadResult, DataPage};
+use crate::error::LoadError;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Width of a tab stop when tabs are expanded to spaces.
+const TAB_WIDTH: usize = 8;
+
+/// Number of lines used to guess column boundaries.
+const GUESS_SAMPLE_LINES: usize = 200;
+
+/// Column layout of a fixed-width text file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedWidthLayout {
+    /// Character positions where a new column starts, in increasing order.
+    /// The first column always starts at 0, which is not listed.
+    pub boundaries: Vec<usize>,
+}
+
+impl FixedWidthLayout {
+    /// Splits `line` into trimmed fields at the layout's boundaries.
+    pub fn split_line(&self, line: &str) -> Vec<String> {
+        let chars: Vec<char> = expand_tabs(line).chars().collect();
+        let mut fields = Vec::with_capacity(self.boundaries.len() + 1);
+        let mut start = 0;
+
+        for &end in self.boundaries.iter().chain(std::iter::once(&usize::MAX)) {
+            let end = end.min(chars.len());
+            let field: String = chars.get(start.min(end)..end).unwrap_or_default().iter().collect();
+            fields.push(field.trim().to_string());
+            start = end;
+        }
+
+        fields
+    }
+
+    /// Adds a boundary at `position`, keeping the list sorted and unique.
+    pub fn insert_boundary(&mut self, position: usize) {
+        if position == 0 {
+            return;
+        }
+        if let Err(index) = self.boundaries.binary_search(&position) {
+            self.boundaries.insert(index, position);
+        }
+    }
+}
+
+/// A saved layout, applied automatically to files whose first line equals
+/// `header_line`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedWidthProfile {
+    pub name: String,
+    pub header_line: String,
+    pub layout: FixedWidthLayout,
+}
+
+/// Finds the profile whose header line matches the first line of a file.
+pub fn find_profile<'a>(
+    profiles: &'a [FixedWidthProfile],
+    header_line: &str,
+) -> Option<&'a FixedWidthProfile> {
+    let header_line = header_line.trim_end();
+    profiles
+        .iter()
+        .find(|profile| profile.header_line.trim_end() == header_line)
+}
+
+/// Loads `num_rows` rows of a fixed-width text file, starting at
+/// `start_row`. The first non-empty line is the header.
+///
+/// Without a `layout`, boundaries are guessed with [`guess_layout`].
+pub async fn load_fixed_width_data(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    layout: Option<FixedWidthLayout>,
+) -> DataLoadResult {
+    let result = tokio::task::spawn_blocking(move || {
+        load_fixed_width_data_sync(path, start_row, num_rows, layout)
+    })
+    .await;
+
+    result.map_err(|e| LoadError::Task(e.to_string()))?
+}
+
+/// Blocking variant of [`load_fixed_width_data`].
+pub fn load_fixed_width_data_sync(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    layout: Option<FixedWidthLayout>,
+) -> DataLoadResult {
+    let layout = match layout {
+        Some(layout) => layout,
+        None => guess_layout(&read_preview_lines(&path, GUESS_SAMPLE_LINES)?),
+    };
+
+    let mut lines = non_empty_lines(&path)?;
+    let header_line = match lines.next() {
+        Some(line) => line?,
+        None => return Err(LoadError::EmptyFile),
+    };
+
+    for _ in 0..start_row {
+        if lines.next().transpose()?.is_none() {
+            break;
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut end_of_file = false;
+    for _ in 0..num_rows {
+        match lines.next().transpose()? {
+            Some(line) => rows.push(layout.split_line(&line)),
+            None => {
+                end_of_file = true;
+                break;
+            }
+        }
+    }
+
+    Ok(DataPage {
+        headers: if start_row == 0 {
+            layout.split_line(&header_line)
+        } else {
+            Vec::new()
+        },
+        rows,
+        sheet_names: vec!["Fixed width".to_string()],
+        end_of_file,
+        ..DataPage::default()
+    })
+}
+
+/// Reads up to `max_lines` non-empty lines with tabs expanded, for guessing
+/// boundaries and for the import preview.
+pub fn read_preview_lines(path: &Path, max_lines: usize) -> Result<Vec<String>, String> {
+    non_empty_lines(path)?
+        .take(max_lines)
+        .map(|line| line.map(|l| expand_tabs(&l)))
+        .collect()
+}
+
+/// Guesses column boundaries from whitespace alignment.
+///
+/// A character position that is blank in every sample line separates two
+/// columns; a boundary is placed where such a blank gap ends.
+pub fn guess_layout(lines: &[String]) -> FixedWidthLayout {
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut blank = vec![true; width];
+
+    for line in lines {
+        for (position, c) in line.chars().enumerate() {
+            if !c.is_whitespace() {
+                blank[position] = false;
+            }
+        }
+    }
+
+    let mut layout = FixedWidthLayout::default();
+    for position in 1..width {
+        if blank[position - 1] && !blank[position] {
+            layout.insert_boundary(position);
+        }
+    }
+
+    layout
+}
+
+/// Non-empty lines of a file, decoded lossily so stray bytes from legacy
+/// encodings do not abort the load.
+fn non_empty_lines(path: &Path) -> Result<impl Iterator<Item = Result<String, String>>, String> {
+    let reader = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+
+    Ok(reader
+        .split(b'\n')
+        .map(|line| {
+            line.map(|bytes| {
+                String::from_utf8_lossy(&bytes)
+                    .trim_end_matches(['\r', '\n'])
+                    .to_string()
+            })
+            .map_err(|e| e.to_string())
+        })
+        .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty())))
+}
+
+/// Replaces tabs with spaces up to the next multiple of [`TAB_WIDTH`].
+fn expand_tabs(line: &str) -> String {
+    if !line.contains('\t') {
+        return line.to_string();
+    }
+
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = TAB_WIDTH - column % TAB_WIDTH;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Scratch;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn guesses_boundaries_from_blank_columns() {
+        let sample = lines(&["ID  NAME   VALUE", "1   alpha  10", "22  b      3"]);
+        assert_eq!(guess_layout(&sample).boundaries, [4, 11]);
+        // A gap blank in only some lines is not a boundary
+        let sample = lines(&["AB CD", "ABXCD"]);
+        assert!(guess_layout(&sample).boundaries.is_empty());
+        assert!(guess_layout(&[]).boundaries.is_empty());
+    }
+
+    #[test]
+    fn expands_tabs_to_the_next_stop() {
+        assert_eq!(expand_tabs("a\tb"), format!("a{}b", " ".repeat(7)));
+        assert_eq!(expand_tabs("\tb"), format!("{}b", " ".repeat(8)));
+        assert_eq!(
+            expand_tabs("abcdefgh\tx"),
+            format!("abcdefgh{}x", " ".repeat(8))
+        );
+        assert_eq!(expand_tabs("名\tx"), format!("名{}x", " ".repeat(7)));
+    }
+
+    #[test]
+    fn splits_on_characters_not_bytes() {
+        let layout = FixedWidthLayout {
+            boundaries: vec![4, 8],
+        };
+        assert_eq!(
+            layout.split_line("名前  數量  備註"),
+            ["名前", "數量", "備註"]
+        );
+        assert_eq!(layout.split_line("名前"), ["名前", "", ""]);
+        assert_eq!(layout.split_line("id\tvalue"), ["id", "", "value"]);
+        let layout = FixedWidthLayout {
+            boundaries: vec![8],
+        };
+        assert_eq!(layout.split_line("id\tvalue"), ["id", "value"]);
+    }
+
+    #[test]
+    fn inserts_boundaries_sorted_and_once() {
+        let mut layout = FixedWidthLayout::default();
+        for position in [8, 0, 3, 8, 5] {
+            layout.insert_boundary(position);
+        }
+        assert_eq!(layout.boundaries, [3, 5, 8]);
+    }
+
+    #[test]
+    fn preview_skips_blank_lines_and_expands_tabs() {
+        let scratch = Scratch::new("fixed_width_preview");
+        let path = scratch.path("data.txt");
+        std::fs::write(&path, "a\tb\n\n   \nc\r\nd\n").unwrap();
+        assert_eq!(
+            read_preview_lines(&path, 2).unwrap(),
+            [format!("a{}b", " ".repeat(7)), "c".to_string()]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use crate::csv_loader::read_preamble;
+use crate::data_loader::{file_extension, is_supported, LoadOptions};
+use crate::export::write_csv_rows;
+use crate::table_reader::{find_column, TableReader};
+use crate::values::compare_cells;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the column that tells which file a concatenated row came from.
+pub const SOURCE_FILE_COLUMN: &str = "source_file";
+
+/// A file found by [`scan_folder`], with what is needed to line it up with
+/// the others.
+#[derive(Debug, Clone)]
+pub struct FolderFile {
+    pub path: PathBuf,
+    pub headers: Vec<String>,
+    /// Preamble fields of a mixed CSV file, e.g. `("Wafer ID", "W01")`.
+    pub preamble: Vec<(String, String)>,
+    /// Set if the file could not be read; it is left out of the table.
+    pub error: Option<String>,
+}
+
+impl FolderFile {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Describes how the headers differ from `reference`, or `None` if they
+    /// are the same columns in the same order.
+    pub fn header_mismatch(&self, reference: &[String]) -> Option<String> {
+        if self.error.is_some() || same_headers(&self.headers, reference) {
+            return None;
+        }
+
+        let missing: Vec<&str> = reference
+            .iter()
+            .filter(|h| find_column(&self.headers, h).is_none())
+            .map(String::as_str)
+            .collect();
+        let extra: Vec<&str> = self
+            .headers
+            .iter()
+            .filter(|h| find_column(reference, h).is_none())
+            .map(String::as_str)
+            .collect();
+
+        let mut problems = Vec::new();
+        if !missing.is_empty() {
+            problems.push(format!("missing {}", missing.join(", ")));
+        }
+        if !extra.is_empty() {
+            problems.push(format!("extra {}", extra.join(", ")));
+        }
+        if problems.is_empty() {
+            problems.push("columns in a different order".to_string());
+        }
+        Some(problems.join("; "))
+    }
+}
+
+/// Lists the supported files in `dir` whose names match `pattern`, e.g.
+/// `*.csv` or `lot7_*.csv;*.csv.gz`, in natural order, and reads the header
+/// and preamble of each.
+pub fn scan_folder(dir: &Path, pattern: &str) -> Result<Vec<FolderFile>, String> {
+    let patterns: Vec<String> = pattern
+        .split(';')
+        .map(|p| p.trim().to_lowercase())
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    let mut paths = Vec::new();
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read folder {}: {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+        let matches = patterns.is_empty() || patterns.iter().any(|p| wildcard_match(p, &name));
+        if path.is_file() && matches && is_supported(&path) {
+            paths.push(path);
+        }
+    }
+    paths.sort_by(|a, b| compare_cells(&a.to_string_lossy(), &b.to_string_lossy()));
+
+    Ok(paths.into_iter().map(scan_file).collect())
+}
+
+fn scan_file(path: PathBuf) -> FolderFile {
+    let mut file = FolderFile {
+        path,
+        headers: Vec::new(),
+        preamble: Vec::new(),
+        error: None,
+    };
+    match TableReader::open(&file.path, 0, &LoadOptions::default()) {
+        Ok(reader) => file.headers = reader.headers().to_vec(),
+        Err(e) => file.error = Some(e),
+    }
+    if file.error.is_none() && file_extension(&file.path).as_deref() == Some("csv") {
+        match read_preamble(&file.path) {
+            Ok(preamble) => file.preamble = preamble,
+            Err(e) => file.error = Some(e),
+        }
+    }
+    file
+}
+
+/// The headers most of the readable files share, which the others are
+/// checked against.
+pub fn reference_headers(files: &[FolderFile]) -> Vec<String> {
+    let mut best: Option<(&[String], usize)> = None;
+    for file in files.iter().filter(|f| f.error.is_none()) {
+        let count = files
+            .iter()
+            .filter(|f| f.error.is_none() && same_headers(&f.headers, &file.headers))
+            .count();
+        if best.is_none_or(|(_, best_count)| count > best_count) {
+            best = Some((&file.headers, count));
+        }
+    }
+    best.map(|(headers, _)| headers.to_vec()).unwrap_or_default()
+}
+
+/// Writes the rows of `files`, one after the other, to `target` as CSV and
+/// returns the number of rows written.
+///
+/// The output starts with a `source_file` column and one column per
+/// preamble field, then the data columns. Columns are lined up by name, so
+/// a file with its columns in another order, or with columns missing, still
+/// lands in the right place; columns only some files have are added at the
+/// end and left blank for the others.
+///
+/// Data columns follow [`reference_headers`].
+pub fn concat_files(files: &[FolderFile], target: &Path) -> Result<usize, String> {
+    let mut data_headers = reference_headers(files);
+    let files: Vec<&FolderFile> = files.iter().filter(|f| f.error.is_none()).collect();
+    if files.is_empty() {
+        return Err("No readable files to combine.".to_string());
+    }
+
+    let mut preamble_keys: Vec<String> = Vec::new();
+    for file in &files {
+        for header in &file.headers {
+            if find_column(&data_headers, header).is_none() {
+                data_headers.push(header.clone());
+            }
+        }
+        for (key, _) in &file.preamble {
+            if !preamble_keys.contains(key) {
+                preamble_keys.push(key.clone());
+            }
+        }
+    }
+
+    let mut headers = vec![SOURCE_FILE_COLUMN.to_string()];
+    for key in &preamble_keys {
+        // A preamble field must not shadow a data column
+        let name = if find_column(&data_headers, key).is_some() || key == SOURCE_FILE_COLUMN {
+            format!("{} (preamble)", key)
+        } else {
+            key.clone()
+        };
+        headers.push(name);
+    }
+    let fixed_width = headers.len();
+    headers.extend(data_headers.iter().cloned());
+
+    let rows = files.iter().flat_map(|file| {
+        let mut prefix = vec![file.file_name()];
+        let preamble: HashMap<&str, &str> = file
+            .preamble
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        prefix.extend(
+            preamble_keys
+                .iter()
+                .map(|key| preamble.get(key.as_str()).unwrap_or(&"").to_string()),
+        );
+        // Output position of each of this file's columns
+        let positions: Vec<usize> = file
+            .headers
+            .iter()
+            .map(|h| fixed_width + find_column(&data_headers, h).unwrap_or_default())
+            .collect();
+        let width = headers.len();
+
+        let reader = match TableReader::open(&file.path, 0, &LoadOptions::default()) {
+            Ok(reader) => reader,
+            Err(e) => {
+                let e = format!("{}: {}", file.file_name(), e);
+                return Box::new(std::iter::once(Err(e)))
+                    as Box<dyn Iterator<Item = Result<Vec<String>, String>>>;
+            }
+        };
+        Box::new(reader.map(move |row| {
+            let row = row?;
+            let mut out = prefix.clone();
+            out.resize(width, String::new());
+            for (cell, &position) in row.into_iter().zip(&positions) {
+                out[position] = cell;
+            }
+            Ok(out)
+        }))
+    });
+
+    write_csv_rows(target, &headers, rows)
+}
+
+fn same_headers(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.trim() == y.trim())
+}
+
+/// Matches `name` against a pattern with `*` (any run of characters) and
+/// `?` (one character).
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it was tried at
+    let mut backtrack = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p + 1, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,374 @@
+use crate::stats::NumericRange;
+use crate::table_reader::find_column;
+use crate::values::parse_number;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// An sRGB color.
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    pub const ALL: [Comparison; 6] = [
+        Comparison::Greater,
+        Comparison::GreaterOrEqual,
+        Comparison::Less,
+        Comparison::LessOrEqual,
+        Comparison::Equal,
+        Comparison::NotEqual,
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "!=",
+        }
+    }
+
+    fn holds(self, a: f64, b: f64) -> bool {
+        match self {
+            Comparison::Greater => a > b,
+            Comparison::GreaterOrEqual => a >= b,
+            Comparison::Less => a < b,
+            Comparison::LessOrEqual => a <= b,
+            Comparison::Equal => a == b,
+            Comparison::NotEqual => a != b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextMatch {
+    Contains,
+    Equals,
+    StartsWith,
+    Regex,
+}
+
+impl TextMatch {
+    pub const ALL: [TextMatch; 4] = [
+        TextMatch::Contains,
+        TextMatch::Equals,
+        TextMatch::StartsWith,
+        TextMatch::Regex,
+    ];
+}
+
+/// What a rule does to the cells of its column.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RuleKind {
+    /// Background blended from `low` at the column minimum to `high` at the
+    /// maximum.
+    ColorScale { low: Rgb, high: Rgb },
+    /// Background for numbers that compare true against `value`, e.g.
+    /// `FWHM > 25`.
+    Threshold {
+        comparison: Comparison,
+        value: f64,
+        color: Rgb,
+    },
+    /// Background for text matching `pattern`, ignoring case except for
+    /// regular expressions.
+    Text {
+        mode: TextMatch,
+        pattern: String,
+        color: Rgb,
+    },
+    /// A bar as long as the number is large, relative to the column range.
+    DataBar { color: Rgb },
+}
+
+impl RuleKind {
+    /// One rule of each kind with default settings.
+    pub fn defaults() -> [RuleKind; 4] {
+        [
+            RuleKind::ColorScale {
+                low: [38, 84, 140],
+                high: [170, 60, 50],
+            },
+            RuleKind::Threshold {
+                comparison: Comparison::Greater,
+                value: 0.0,
+                color: [150, 45, 45],
+            },
+            RuleKind::Text {
+                mode: TextMatch::Contains,
+                pattern: String::new(),
+                color: [120, 90, 20],
+            },
+            RuleKind::DataBar {
+                color: [66, 133, 244],
+            },
+        ]
+    }
+
+    /// `true` for rules that need the minimum and maximum of the column.
+    pub fn needs_range(&self) -> bool {
+        matches!(self, RuleKind::ColorScale { .. } | RuleKind::DataBar { .. })
+    }
+}
+
+/// A formatting rule for one column, referred to by name so it still
+/// applies when a file with the same columns is opened again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormatRule {
+    pub column: String,
+    pub kind: RuleKind,
+}
+
+/// Lower and upper spec limits of a measurement column, either of which may
+/// be left open. Saved per header signature like [`FormatRule`]s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpecLimit {
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+}
+
+/// Where a value lies relative to its [`SpecLimit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitCheck {
+    Within,
+    Below,
+    Above,
+}
+
+impl SpecLimit {
+    pub fn is_empty(&self) -> bool {
+        self.low.is_none() && self.high.is_none()
+    }
+
+    /// Checks a cell against the limits; `None` for cells that are not
+    /// numbers. Values on a limit are within it.
+    pub fn check(&self, cell: &str) -> Option<LimitCheck> {
+        let value = parse_number(cell)?;
+        Some(match (self.low, self.high) {
+            (Some(low), _) if value < low => LimitCheck::Below,
+            (_, Some(high)) if value > high => LimitCheck::Above,
+            _ => LimitCheck::Within,
+        })
+    }
+}
+
+/// Identifies files with the same columns, so rules saved for one apply to
+/// the others.
+pub fn header_signature(headers: &[String]) -> String {
+    headers
+        .iter()
+        .map(|h| h.trim())
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+/// How a cell is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CellFormat {
+    pub fill: Option<Rgb>,
+    /// Length of the data bar as a fraction of the cell width, and its color.
+    pub bar: Option<(f32, Rgb)>,
+}
+
+struct CompiledRule {
+    column: usize,
+    kind: RuleKind,
+    regex: Option<Regex>,
+    pattern: String,
+    range: Option<NumericRange>,
+}
+
+/// Rules resolved against the headers of a sheet, ready to be applied cell
+/// by cell as pages are drawn.
+///
+/// Threshold and text rules only look at the cell itself. Color scales and
+/// data bars need the range of their column over the whole sheet, which is
+/// computed once with [`crate::stats::numeric_ranges`] and handed in with
+/// [`ConditionalFormat::set_range`]; until then they are not drawn.
+#[derive(Default)]
+pub struct ConditionalFormat {
+    rules: Vec<CompiledRule>,
+    /// Problems with the rules, by rule index, e.g. an invalid regex.
+    errors: Vec<(usize, String)>,
+}
+
+impl ConditionalFormat {
+    pub fn compile(rules: &[FormatRule], headers: &[String]) -> Self {
+        let mut format = ConditionalFormat::default();
+        for (index, rule) in rules.iter().enumerate() {
+            let Some(column) = find_column(headers, &rule.column) else {
+                format
+                    .errors
+                    .push((index, format!("No column named '{}'", rule.column)));
+                continue;
+            };
+            // A text rule whose pattern has not been typed yet would color
+            // every cell
+            if matches!(&rule.kind, RuleKind::Text { pattern, .. } if pattern.is_empty()) {
+                continue;
+            }
+            let (regex, pattern) = match &rule.kind {
+                RuleKind::Text {
+                    mode: TextMatch::Regex,
+                    pattern,
+                    ..
+                } => match Regex::new(pattern) {
+                    Ok(regex) => (Some(regex), String::new()),
+                    Err(e) => {
+                        format.errors.push((index, format!("Invalid regex: {}", e)));
+                        continue;
+                    }
+                },
+                RuleKind::Text { pattern, .. } => (None, pattern.to_lowercase()),
+                _ => (None, String::new()),
+            };
+            format.rules.push(CompiledRule {
+                column,
+                kind: rule.kind.clone(),
+                regex,
+                pattern,
+                range: None,
+            });
+        }
+        format
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn errors(&self) -> &[(usize, String)] {
+        &self.errors
+    }
+
+    /// Columns of color scales and data bars, whose range is still missing.
+    pub fn columns_needing_range(&self) -> Vec<usize> {
+        let mut columns: Vec<usize> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.kind.needs_range() && rule.range.is_none())
+            .map(|rule| rule.column)
+            .collect();
+        columns.sort_unstable();
+        columns.dedup();
+        columns
+    }
+
+    pub fn set_range(&mut self, column: usize, range: NumericRange) {
+        for rule in self.rules.iter_mut().filter(|rule| rule.column == column) {
+            rule.range = Some(range);
+        }
+    }
+
+    /// The format of `row[column]`. The first rule that colors the cell
+    /// wins; a data bar can be drawn on top.
+    pub fn format(&self, row: &[String], column: usize) -> CellFormat {
+        let mut format = CellFormat::default();
+        let Some(cell) = row.get(column) else {
+            return format;
+        };
+        for rule in self.rules.iter().filter(|rule| rule.column == column) {
+            match &rule.kind {
+                RuleKind::DataBar { color } if format.bar.is_none() => {
+                    format.bar = rule
+                        .range
+                        .zip(parse_number(cell))
+                        .map(|(range, value)| (range.position(value), *color));
+                }
+                RuleKind::DataBar { .. } => {}
+                kind if format.fill.is_none() => format.fill = rule.fill(kind, cell),
+                _ => {}
+            }
+        }
+        format
+    }
+}
+
+impl CompiledRule {
+    fn fill(&self, kind: &RuleKind, cell: &str) -> Option<Rgb> {
+        match kind {
+            RuleKind::ColorScale { low, high } => {
+                let t = self.range?.position(parse_number(cell)?);
+                Some(blend(*low, *high, t))
+            }
+            RuleKind::Threshold {
+                comparison,
+                value,
+                color,
+            } => comparison
+                .holds(parse_number(cell)?, *value)
+                .then_some(*color),
+            RuleKind::Text { mode, color, .. } => {
+                let matched = match (mode, &self.regex) {
+                    (_, Some(regex)) => regex.is_match(cell),
+                    (TextMatch::Equals, None) => cell.trim().to_lowercase() == self.pattern,
+                    (TextMatch::StartsWith, None) => {
+                        cell.trim_start().to_lowercase().starts_with(&self.pattern)
+                    }
+                    (_, None) => cell.to_lowercase().contains(&self.pattern),
+                };
+                (matched && !cell.is_empty()).then_some(*color)
+            }
+            RuleKind::DataBar { .. } => None,
+        }
+    }
+}
+
+fn blend(low: Rgb, high: Rgb, t: f32) -> Rgb {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    [
+        mix(low[0], high[0]),
+        mix(low[1], high[1]),
+        mix(low[2], high[2]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_rule(mode: TextMatch, pattern: &str) -> FormatRule {
+        FormatRule {
+            column: "bin".to_string(),
+            kind: RuleKind::Text {
+                mode,
+                pattern: pattern.to_string(),
+                color: [1, 2, 3],
+            },
+        }
+    }
+
+    #[test]
+    fn text_rules() {
+        let headers = vec!["bin".to_string()];
+        let row = |cell: &str| vec![cell.to_string()];
+        let rules = [text_rule(TextMatch::StartsWith, "fail")];
+        let format = ConditionalFormat::compile(&rules, &headers);
+        assert_eq!(format.format(&row("FAIL open"), 0).fill, Some([1, 2, 3]));
+        assert_eq!(format.format(&row("pass"), 0).fill, None);
+    }
+
+    #[test]
+    fn empty_text_patterns_are_skipped() {
+        let headers = vec!["bin".to_string()];
+        for mode in TextMatch::ALL {
+            let format = ConditionalFormat::compile(&[text_rule(mode, "")], &headers);
+            assert!(format.is_empty(), "{:?}", mode);
+            assert!(format.errors().is_empty());
+            assert_eq!(
+                format.format(&["pass".to_string()], 0),
+                CellFormat::default()
+            );
+        }
+    }
+}
@@ -0,0 +1,585 @@
+use crate::error::LoadError;
+use crate::table_reader::{find_column, SheetSource};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Memory the rows of one build side may take before the join partitions
+/// both sides to disk.
+pub const DEFAULT_MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Partition files per side when a join spills to disk.
+const PARTITIONS: usize = 64;
+
+/// Rough per-cell overhead of a `String` in a row, for the memory estimate.
+const CELL_OVERHEAD: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind {
+    /// Only keys present in every source.
+    Inner,
+    /// Every row of the first source, with blanks where a later source has
+    /// no matching row.
+    Left,
+    /// Every key of every source.
+    Outer,
+}
+
+impl JoinKind {
+    pub const ALL: [JoinKind; 3] = [JoinKind::Inner, JoinKind::Left, JoinKind::Outer];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            JoinKind::Inner => "inner",
+            JoinKind::Left => "left",
+            JoinKind::Outer => "outer",
+        }
+    }
+}
+
+/// A source of a join and the names of its key columns. Every input needs
+/// the same number of key columns; the names may differ, e.g. `INDEX` in
+/// one file and `chip_id` in another.
+#[derive(Debug, Clone)]
+pub struct JoinInput {
+    pub source: SheetSource,
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JoinSpec {
+    pub inputs: Vec<JoinInput>,
+    pub kind: JoinKind,
+    /// See [`DEFAULT_MEMORY_LIMIT`].
+    pub memory_limit: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JoinSummary {
+    pub rows: usize,
+    /// `true` if a build side did not fit in memory and was partitioned to
+    /// disk.
+    pub spilled: bool,
+}
+
+/// Joins the inputs on their key columns and writes the result to `target`
+/// as CSV.
+///
+/// The output has the key columns first, named as in the first input, then
+/// the other columns of each input in turn. Column names that repeat get the
+/// file name as a prefix, e.g. `aoi.DEFECT`.
+///
+/// Inputs are joined one after the other with a hash join: the next input is
+/// loaded into a hash table and the result so far is streamed past it. When
+/// that table outgrows `memory_limit`, both sides are partitioned by key
+/// into temporary files and joined one partition at a time.
+pub fn join_sheets(spec: &JoinSpec, target: &Path) -> Result<JoinSummary, LoadError> {
+    if spec.inputs.len() < 2 {
+        return Err(LoadError::TooFewInputs);
+    }
+    let first = &spec.inputs[0];
+    let key_count = first.keys.len();
+    if key_count == 0 {
+        return Err(LoadError::NoKeyColumns);
+    }
+    if let Some(input) = spec.inputs.iter().find(|i| i.keys.len() != key_count) {
+        return Err(LoadError::KeyCountMismatch {
+            file: input.source.file_name(),
+            count: input.keys.len(),
+            first_file: first.source.file_name(),
+            first_count: key_count,
+        });
+    }
+
+    let work_dir = WorkDir::create()?;
+    let mut summary = JoinSummary::default();
+
+    // The result so far: key columns first, then the value columns
+    let first_reader = first.source.open()?;
+    let (first_keys, first_values) = split_columns(first_reader.headers(), first)?;
+    let mut headers: Vec<String> = first_keys
+        .iter()
+        .map(|&k| first_reader.headers()[k].clone())
+        .collect();
+    headers.extend(
+        first_values
+            .iter()
+            .map(|&v| first_reader.headers()[v].clone()),
+    );
+    let mut left: Box<dyn Iterator<Item = Result<Vec<String>, String>>> =
+        Box::new(first_reader.map(move |row| {
+            row.map(|row| {
+                first_keys
+                    .iter()
+                    .chain(&first_values)
+                    .map(|&c| cell(&row, c))
+                    .collect()
+            })
+        }));
+
+    for (stage, input) in spec.inputs.iter().enumerate().skip(1) {
+        let reader = input.source.open()?;
+        let (keys, values) = split_columns(reader.headers(), input)?;
+        let prefix = input
+            .source
+            .path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let left_width = headers.len();
+        for &v in &values {
+            let name = unique_name(&headers, &reader.headers()[v], &prefix);
+            headers.push(name);
+        }
+        let right = reader.map(move |row| {
+            row.map(|row| {
+                let key: Vec<String> = keys
+                    .iter()
+                    .map(|&c| cell(&row, c).trim().to_string())
+                    .collect();
+                let values: Vec<String> = values.iter().map(|&c| cell(&row, c)).collect();
+                (key, values)
+            })
+        });
+
+        let last = stage + 1 == spec.inputs.len();
+        let output = if last {
+            target.to_path_buf()
+        } else {
+            work_dir.file(&format!("stage_{}.csv", stage))
+        };
+        let mut writer = csv_writer(&output)?;
+        write_record(&mut writer, &headers)?;
+
+        let mut rows = 0;
+        let stage_input = Stage {
+            key_count,
+            left_width,
+            right_width: headers.len() - left_width,
+            kind: spec.kind,
+            memory_limit: spec.memory_limit,
+        };
+        summary.spilled |= stage_input.run(left, right, &work_dir, stage, &mut |row| {
+            rows += 1;
+            write_record(&mut writer, &row)
+        })?;
+        writer.flush().map_err(|e| LoadError::Io(e.to_string()))?;
+        drop(writer);
+        summary.rows = rows;
+
+        if !last {
+            let mut reader = csv_file_reader(&output, true)?;
+            left = Box::new(std::iter::from_fn(move || read_record(&mut reader)));
+        } else {
+            left = Box::new(std::iter::empty());
+        }
+    }
+
+    Ok(summary)
+}
+
+/// One pairwise join: the result so far (`left`, keys first) with the next
+/// input (`right`, split into key and value cells).
+struct Stage {
+    key_count: usize,
+    left_width: usize,
+    right_width: usize,
+    kind: JoinKind,
+    memory_limit: usize,
+}
+
+type KeyedRow = (Vec<String>, Vec<String>);
+
+/// Right rows by key, with a flag telling whether a left row matched them.
+type BuildTable = HashMap<Vec<String>, (Vec<Vec<String>>, bool)>;
+
+impl Stage {
+    /// Returns `true` if the join spilled to disk.
+    fn run(
+        &self,
+        left: impl Iterator<Item = Result<Vec<String>, String>>,
+        mut right: impl Iterator<Item = Result<KeyedRow, String>>,
+        work_dir: &WorkDir,
+        stage: usize,
+        emit: &mut dyn FnMut(Vec<String>) -> Result<(), LoadError>,
+    ) -> Result<bool, LoadError> {
+        let mut table = BuildTable::new();
+        let mut size = 0;
+        for row in right.by_ref() {
+            let (key, values) = row?;
+            size += row_size(&key) + row_size(&values);
+            table.entry(key).or_default().0.push(values);
+            if size > self.memory_limit {
+                break;
+            }
+        }
+
+        if size <= self.memory_limit {
+            self.probe(&mut table, left, emit)?;
+            self.finish(table, emit)?;
+            return Ok(false);
+        }
+
+        // Too large for memory: partition both sides by key and join each
+        // pair of partitions on its own
+        let partition = |kind: &str, index: usize| {
+            work_dir.file(&format!("stage_{}_{}_{}.csv", stage, kind, index))
+        };
+        let mut right_parts = (0..PARTITIONS)
+            .map(|i| csv_writer(&partition("right", i)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let spilled = table.into_iter().flat_map(|(key, (rows, _))| {
+            rows.into_iter()
+                .map(move |values| Ok((key.clone(), values)))
+        });
+        for row in spilled.chain(right) {
+            let (mut key, values) = row?;
+            let part = partition_of(&key);
+            key.extend(values);
+            write_record(&mut right_parts[part], &key)?;
+        }
+        drop(right_parts);
+
+        let mut left_parts = (0..PARTITIONS)
+            .map(|i| csv_writer(&partition("left", i)))
+            .collect::<Result<Vec<_>, _>>()?;
+        for row in left {
+            let row = row?;
+            let part = partition_of(&row[..self.key_count.min(row.len())]);
+            write_record(&mut left_parts[part], &row)?;
+        }
+        drop(left_parts);
+
+        for index in 0..PARTITIONS {
+            let mut table = BuildTable::new();
+            let mut reader = csv_file_reader(&partition("right", index), false)?;
+            while let Some(row) = read_record(&mut reader) {
+                let mut key = row?;
+                let values = key.split_off(self.key_count.min(key.len()));
+                table.entry(key).or_default().0.push(values);
+            }
+            let mut reader = csv_file_reader(&partition("left", index), false)?;
+            let left = std::iter::from_fn(move || read_record(&mut reader));
+            self.probe(&mut table, left, emit)?;
+            self.finish(table, emit)?;
+        }
+        Ok(true)
+    }
+
+    /// Streams the left rows past the hash table.
+    fn probe(
+        &self,
+        table: &mut BuildTable,
+        left: impl Iterator<Item = Result<Vec<String>, String>>,
+        emit: &mut dyn FnMut(Vec<String>) -> Result<(), LoadError>,
+    ) -> Result<(), LoadError> {
+        for row in left {
+            let mut row = row?;
+            row.resize(self.left_width, String::new());
+            let key: Vec<String> = row[..self.key_count]
+                .iter()
+                .map(|k| k.trim().to_string())
+                .collect();
+            match table.get_mut(&key) {
+                Some((matches, matched)) => {
+                    *matched = true;
+                    for values in matches.iter() {
+                        let mut joined = row.clone();
+                        joined.extend(values.iter().cloned());
+                        joined.resize(self.left_width + self.right_width, String::new());
+                        emit(joined)?;
+                    }
+                }
+                None if self.kind == JoinKind::Inner => {}
+                None => {
+                    row.resize(self.left_width + self.right_width, String::new());
+                    emit(row)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// For an outer join, emits the right rows no left row matched.
+    fn finish(
+        &self,
+        table: BuildTable,
+        emit: &mut dyn FnMut(Vec<String>) -> Result<(), LoadError>,
+    ) -> Result<(), LoadError> {
+        if self.kind != JoinKind::Outer {
+            return Ok(());
+        }
+        for (key, (rows, matched)) in table {
+            if matched {
+                continue;
+            }
+            for values in rows {
+                let mut joined = key.clone();
+                joined.resize(self.left_width, String::new());
+                joined.extend(values);
+                joined.resize(self.left_width + self.right_width, String::new());
+                emit(joined)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Indexes of the key columns of an input and of its other columns.
+fn split_columns(
+    headers: &[String],
+    input: &JoinInput,
+) -> Result<(Vec<usize>, Vec<usize>), LoadError> {
+    let keys = input
+        .keys
+        .iter()
+        .map(|name| {
+            find_column(headers, name).ok_or_else(|| LoadError::MissingKeyColumn {
+                column: name.clone(),
+                file: input.source.file_name(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let values = (0..headers.len()).filter(|c| !keys.contains(c)).collect();
+    Ok((keys, values))
+}
+
+/// `name`, or `prefix.name` if the output already has a column called
+/// `name`, numbered further if that is taken as well.
+fn unique_name(headers: &[String], name: &str, prefix: &str) -> String {
+    let taken = |candidate: &str| headers.iter().any(|h| h.eq_ignore_ascii_case(candidate));
+    if !taken(name) {
+        return name.to_string();
+    }
+    let prefixed = format!("{}.{}", prefix, name);
+    let mut candidate = prefixed.clone();
+    let mut number = 2;
+    while taken(&candidate) {
+        candidate = format!("{} ({})", prefixed, number);
+        number += 1;
+    }
+    candidate
+}
+
+fn cell(row: &[String], column: usize) -> String {
+    row.get(column).cloned().unwrap_or_default()
+}
+
+fn row_size(cells: &[String]) -> usize {
+    cells.iter().map(|c| c.len() + CELL_OVERHEAD).sum()
+}
+
+fn partition_of(key: &[String]) -> usize {
+    let mut hasher = DefaultHasher::new();
+    for part in key {
+        part.trim().hash(&mut hasher);
+    }
+    (hasher.finish() % PARTITIONS as u64) as usize
+}
+
+fn csv_writer(path: &Path) -> Result<csv::Writer<BufWriter<File>>, LoadError> {
+    let file = File::create(path).map_err(|e| LoadError::CreateFile {
+        path: path.display().to_string(),
+        detail: e.to_string(),
+    })?;
+    Ok(csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(BufWriter::new(file)))
+}
+
+fn write_record(
+    writer: &mut csv::Writer<BufWriter<File>>,
+    row: &[String],
+) -> Result<(), LoadError> {
+    writer
+        .write_record(row)
+        .map_err(|e| LoadError::Io(e.to_string()))
+}
+
+fn csv_file_reader(
+    path: &Path,
+    has_headers: bool,
+) -> Result<csv::Reader<BufReader<File>>, LoadError> {
+    let file = File::open(path).map_err(|e| LoadError::Io(e.to_string()))?;
+    Ok(csv::ReaderBuilder::new()
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_reader(BufReader::new(file)))
+}
+
+fn read_record(reader: &mut csv::Reader<BufReader<File>>) -> Option<Result<Vec<String>, String>> {
+    let mut record = csv::StringRecord::new();
+    match reader.read_record(&mut record) {
+        Ok(true) => Some(Ok(record.iter().map(str::to_string).collect())),
+        Ok(false) => None,
+        Err(e) => Some(Err(e.to_string())),
+    }
+}
+
+/// Temporary directory for intermediate results and spill files, removed
+/// when the join finishes.
+struct WorkDir(PathBuf);
+
+impl WorkDir {
+    fn create() -> Result<Self, LoadError> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join("rust_excel_reader").join(format!(
+            "join_{}_{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path).map_err(|e| LoadError::CreateFile {
+            path: path.display().to_string(),
+            detail: e.to_string(),
+        })?;
+        Ok(Self(path))
+    }
+
+    fn file(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Scratch;
+
+    const LOTS: &str = "id,x\n1,a1\n2,a2\n3,a3\n3,a3b\n5,a5\n";
+    const TESTS: &str = "ID,y\n 1 ,b1\n2,b2\n3,b3\n4,b4\n";
+
+    fn input(scratch: &Scratch, name: &str, text: &str, key: &str) -> JoinInput {
+        let path = scratch.path(name);
+        std::fs::write(&path, text).unwrap();
+        JoinInput {
+            source: SheetSource {
+                path,
+                sheet_index: 0,
+                options: Default::default(),
+            },
+            keys: vec![key.to_string()],
+        }
+    }
+
+    /// Joins the inputs and returns the summary, the headers and the rows
+    /// sorted, as partitions change the order.
+    fn join(
+        scratch: &Scratch,
+        inputs: Vec<JoinInput>,
+        kind: JoinKind,
+        memory_limit: usize,
+    ) -> (JoinSummary, Vec<String>, Vec<Vec<String>>) {
+        let target = scratch.path(&format!("{}_{}.csv", kind.label(), memory_limit));
+        let spec = JoinSpec {
+            inputs,
+            kind,
+            memory_limit,
+        };
+        let summary = join_sheets(&spec, &target).unwrap();
+        let mut reader = csv::Reader::from_path(&target).unwrap();
+        let headers = reader.headers().unwrap().iter().map(String::from).collect();
+        let mut rows: Vec<Vec<String>> = reader
+            .records()
+            .map(|record| record.unwrap().iter().map(String::from).collect())
+            .collect();
+        rows.sort();
+        (summary, headers, rows)
+    }
+
+    fn rows(rows: &[[&str; 3]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn each_kind_in_memory_and_spilled() {
+        let scratch = Scratch::new("join_kinds");
+        let matched = [
+            ["1", "a1", "b1"],
+            ["2", "a2", "b2"],
+            ["3", "a3", "b3"],
+            ["3", "a3b", "b3"],
+        ];
+        let left_only = ["5", "a5", ""];
+        let right_only = ["4", "", "b4"];
+        let mut left = matched.to_vec();
+        left.push(left_only);
+        let mut outer = left.clone();
+        outer.insert(4, right_only);
+
+        for (kind, expected) in [
+            (JoinKind::Inner, matched.to_vec()),
+            (JoinKind::Left, left),
+            (JoinKind::Outer, outer),
+        ] {
+            for memory_limit in [DEFAULT_MEMORY_LIMIT, 0] {
+                let inputs = vec![
+                    input(&scratch, "lots.csv", LOTS, "id"),
+                    input(&scratch, "tests.csv", TESTS, "ID"),
+                ];
+                let (summary, headers, joined) = join(&scratch, inputs, kind, memory_limit);
+                assert_eq!(headers, ["id", "x", "y"]);
+                assert_eq!(joined, rows(&expected), "{} join", kind.label());
+                assert_eq!(summary.rows, expected.len());
+                assert_eq!(summary.spilled, memory_limit == 0);
+            }
+        }
+    }
+
+    #[test]
+    fn three_inputs_with_repeated_column_names() {
+        let scratch = Scratch::new("join_three");
+        let inputs = vec![
+            input(&scratch, "lots.csv", LOTS, "id"),
+            input(&scratch, "tests.csv", TESTS, "ID"),
+            input(&scratch, "aoi.csv", "id,x\n2,c2\n4,c4\n", "id"),
+        ];
+        let (summary, headers, joined) = join(&scratch, inputs, JoinKind::Inner, 0);
+        assert_eq!(headers, ["id", "x", "y", "aoi.x"]);
+        assert_eq!(joined, [["2", "a2", "b2", "c2"]]);
+        assert!(summary.spilled);
+    }
+
+    #[test]
+    fn mismatched_key_counts() {
+        let scratch = Scratch::new("join_key_counts");
+        let mut tests = input(&scratch, "tests.csv", TESTS, "ID");
+        tests.keys.push("y".to_string());
+        let spec = JoinSpec {
+            inputs: vec![input(&scratch, "lots.csv", LOTS, "id"), tests],
+            kind: JoinKind::Inner,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        };
+        assert_eq!(
+            join_sheets(&spec, &scratch.path("out.csv")).unwrap_err(),
+            LoadError::KeyCountMismatch {
+                file: "tests.csv".to_string(),
+                count: 2,
+                first_file: "lots.csv".to_string(),
+                first_count: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn work_dir_is_removed() {
+        let work_dir = WorkDir::create().unwrap();
+        let path = work_dir.file("stage_1_left_0.csv");
+        std::fs::write(&path, "1\n").unwrap();
+        let dir = work_dir.0.clone();
+        drop(work_dir);
+        assert!(!dir.exists());
+    }
+}
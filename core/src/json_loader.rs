@@ -0,0 +1,445 @@
+use crate::compressed::FileStamp;
+use crate::data_loader::{ColumnType, DataLoadResult, DataPage};
+use crate::error::LoadError;
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// The most recently parsed JSON array. Arrays have to be parsed in full, so
+/// keeping the last one avoids re-parsing the file for every page.
+type ParsedArray = (PathBuf, FileStamp, Arc<Vec<Value>>);
+
+static PARSED_ARRAY: Lazy<Mutex<Option<ParsedArray>>> = Lazy::new(|| Mutex::new(None));
+
+/// The schema of the most recently loaded JSON file, which takes a pass over
+/// every record to infer.
+type CachedSchema = (PathBuf, FileStamp, Arc<Schema>);
+
+static SCHEMA: Lazy<Mutex<Option<CachedSchema>>> = Lazy::new(|| Mutex::new(None));
+
+/// Loads `num_rows` records from a JSON array (`.json`) or NDJSON
+/// (`.ndjson`, `.jsonl`) file, starting at `start_row`.
+///
+/// Columns are the union of keys over all records, with nested objects
+/// flattened into dotted names (`meas.wp`) and arrays shown as JSON text.
+/// The keys are collected once per version of the file; after that, NDJSON
+/// is read line by line, so only the requested page is parsed.
+pub async fn load_json_data(path: PathBuf, start_row: usize, num_rows: usize) -> DataLoadResult {
+    let result = tokio::task::spawn_blocking(move || {
+        load_json_data_sync(path, start_row, num_rows)
+    })
+    .await;
+
+    result.map_err(|e| LoadError::Task(e.to_string()))?
+}
+
+/// Blocking variant of [`load_json_data`].
+pub fn load_json_data_sync(path: PathBuf, start_row: usize, num_rows: usize) -> DataLoadResult {
+    if is_json_array(&path)? {
+        load_array_page(&path, start_row, num_rows)
+    } else {
+        load_ndjson_page(&path, start_row, num_rows)
+    }
+}
+
+/// Flattens one JSON record into `(column, value)` pairs.
+///
+/// Nested objects become dotted column names; a record that is not an object
+/// is returned as a single `value` column.
+pub fn flatten_record(record: &Value) -> Vec<(String, Value)> {
+    let mut fields = Vec::new();
+    match record {
+        Value::Object(map) => flatten_object("", map, &mut fields),
+        other => fields.push(("value".to_string(), other.clone())),
+    }
+    fields
+}
+
+fn flatten_object(prefix: &str, map: &Map<String, Value>, fields: &mut Vec<(String, Value)>) {
+    for (key, value) in map {
+        let name = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+
+        match value {
+            Value::Object(nested) if !nested.is_empty() => flatten_object(&name, nested, fields),
+            _ => fields.push((name, value.clone())),
+        }
+    }
+}
+
+/// Formats a flattened value as cell text.
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// `true` if the first non-whitespace byte is `[`.
+fn is_json_array(path: &Path) -> Result<bool, LoadError> {
+    let io = |e: std::io::Error| LoadError::Io(e.to_string());
+    let reader = BufReader::new(File::open(path).map_err(io)?);
+    for byte in reader.bytes() {
+        let byte = byte.map_err(io)?;
+        if !byte.is_ascii_whitespace() {
+            return Ok(byte == b'[');
+        }
+    }
+    Err(LoadError::EmptyFile)
+}
+
+fn load_array_page(path: &Path, start_row: usize, num_rows: usize) -> DataLoadResult {
+    let records = parsed_array(path)?;
+    let schema = cached_schema(path, || {
+        let mut schema = Schema::default();
+        for record in records.iter() {
+            schema.add(record);
+        }
+        Ok(schema)
+    })?;
+
+    let end = records.len().min(start_row.saturating_add(num_rows));
+    let rows = records
+        .get(start_row..end)
+        .unwrap_or_default()
+        .iter()
+        .map(|record| schema.row(record))
+        .collect();
+
+    Ok(schema.page(rows, start_row, end >= records.len()))
+}
+
+fn parsed_array(path: &Path) -> Result<Arc<Vec<Value>>, LoadError> {
+    let stamp = FileStamp::read(path)?;
+    let mut cache = PARSED_ARRAY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_path, cached_stamp, records)) = cache.as_ref() {
+        if cached_path == path && *cached_stamp == stamp {
+            return Ok(records.clone());
+        }
+    }
+
+    let reader = BufReader::new(File::open(path).map_err(|e| LoadError::Io(e.to_string()))?);
+    let records: Vec<Value> =
+        serde_json::from_reader(reader).map_err(|e| LoadError::InvalidJson(e.to_string()))?;
+    let records = Arc::new(records);
+    *cache = Some((path.to_path_buf(), stamp, records.clone()));
+
+    Ok(records)
+}
+
+fn load_ndjson_page(path: &Path, start_row: usize, num_rows: usize) -> DataLoadResult {
+    let open = || -> Result<NdjsonRecords, LoadError> {
+        let file = File::open(path).map_err(|e| LoadError::Io(e.to_string()))?;
+        Ok(NdjsonRecords::new(BufReader::new(file)))
+    };
+
+    let schema = cached_schema(path, || {
+        let mut schema = Schema::default();
+        let mut records = 0;
+        for record in open()? {
+            schema.add(&record?);
+            records += 1;
+        }
+        if records == 0 {
+            return Err(LoadError::EmptyFile);
+        }
+        Ok(schema)
+    })?;
+
+    let mut records = open()?;
+    records.skip_records(start_row)?;
+    let mut rows = Vec::new();
+    let mut end_of_file = false;
+    for _ in 0..num_rows {
+        match records.next() {
+            Some(record) => rows.push(schema.row(&record?)),
+            None => {
+                end_of_file = true;
+                break;
+            }
+        }
+    }
+
+    Ok(schema.page(rows, start_row, end_of_file))
+}
+
+/// The schema of `path`, inferred by `infer` unless the file is unchanged
+/// since it was last inferred.
+fn cached_schema(
+    path: &Path,
+    infer: impl FnOnce() -> Result<Schema, LoadError>,
+) -> Result<Arc<Schema>, LoadError> {
+    let stamp = FileStamp::read(path)?;
+    if let Some((cached_path, cached_stamp, schema)) =
+        SCHEMA.lock().unwrap_or_else(|e| e.into_inner()).as_ref()
+    {
+        if cached_path == path && *cached_stamp == stamp {
+            return Ok(schema.clone());
+        }
+    }
+
+    // Inferred without the lock, so other files do not wait for the pass
+    let schema = Arc::new(infer()?);
+    *SCHEMA.lock().unwrap_or_else(|e| e.into_inner()) =
+        Some((path.to_path_buf(), stamp, schema.clone()));
+    Ok(schema)
+}
+
+/// Iterates over the records of an NDJSON stream, skipping blank lines.
+struct NdjsonRecords {
+    lines: std::io::Lines<BufReader<File>>,
+    line_number: usize,
+}
+
+impl NdjsonRecords {
+    fn new(reader: BufReader<File>) -> Self {
+        Self {
+            lines: reader.lines(),
+            line_number: 0,
+        }
+    }
+
+    /// Skips `count` records without parsing them.
+    fn skip_records(&mut self, count: usize) -> Result<(), LoadError> {
+        let mut skipped = 0;
+        while skipped < count {
+            let Some(line) = self.lines.next() else { break };
+            let line = line.map_err(|e| LoadError::Io(e.to_string()))?;
+            self.line_number += 1;
+            if !line.trim().is_empty() {
+                skipped += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for NdjsonRecords {
+    type Item = Result<Value, LoadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(LoadError::Io(e.to_string()))),
+            };
+            self.line_number += 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return Some(
+                serde_json::from_str(&line).map_err(|e| LoadError::InvalidJsonLine {
+                    line: self.line_number,
+                    detail: e.to_string(),
+                }),
+            );
+        }
+    }
+}
+
+/// Column layout inferred from the records of a file.
+#[derive(Debug, Default)]
+struct Schema {
+    columns: Vec<String>,
+    /// Column name → index into `columns`.
+    positions: HashMap<String, usize>,
+    /// Type of each column; `None` while only nulls were seen.
+    types: Vec<Option<ColumnType>>,
+}
+
+impl Schema {
+    /// Adds the fields of one record, in the order they first appear.
+    fn add(&mut self, record: &Value) {
+        for (name, value) in flatten_record(record) {
+            let index = match self.positions.get(&name) {
+                Some(&index) => index,
+                None => {
+                    self.positions.insert(name.clone(), self.columns.len());
+                    self.columns.push(name);
+                    self.types.push(None);
+                    self.columns.len() - 1
+                }
+            };
+            self.types[index] = merge_type(self.types[index], &value);
+        }
+    }
+
+    fn row(&self, record: &Value) -> Vec<String> {
+        let mut row = vec![String::new(); self.columns.len()];
+        for (name, value) in flatten_record(record) {
+            if let Some(&index) = self.positions.get(&name) {
+                row[index] = cell_text(&value);
+            }
+        }
+        row
+    }
+
+    fn page(&self, rows: Vec<Vec<String>>, start_row: usize, end_of_file: bool) -> DataPage {
+        let (headers, column_types) = if start_row == 0 {
+            let types = self.types.iter().map(|t| t.unwrap_or(ColumnType::Text));
+            (self.columns.clone(), types.collect())
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        DataPage {
+            headers,
+            rows,
+            sheet_names: vec!["JSON".to_string()],
+            end_of_file,
+            column_types,
+            ..DataPage::default()
+        }
+    }
+}
+
+/// Widens the type seen so far for a column with one more value. Nulls do
+/// not change the type; mixed types fall back to text.
+fn merge_type(current: Option<ColumnType>, value: &Value) -> Option<ColumnType> {
+    let value_type = match value {
+        Value::Null => return current,
+        Value::Bool(_) => ColumnType::Boolean,
+        Value::Number(n) if n.is_i64() || n.is_u64() => ColumnType::Integer,
+        Value::Number(_) => ColumnType::Float,
+        Value::String(_) => ColumnType::Text,
+        Value::Array(_) | Value::Object(_) => ColumnType::Other,
+    };
+
+    Some(match (current, value_type) {
+        (None, t) => t,
+        (Some(a), b) if a == b => a,
+        (Some(ColumnType::Integer), ColumnType::Float)
+        | (Some(ColumnType::Float), ColumnType::Integer) => ColumnType::Float,
+        _ => ColumnType::Text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Scratch;
+    use serde_json::json;
+
+    #[test]
+    fn flattens_nested_objects() {
+        let record = json!({
+            "id": 1,
+            "meas": {"wp": 1.5, "limits": {"low": 0}},
+            "empty": {},
+            "bins": [1, 2],
+        });
+        let names: Vec<String> = flatten_record(&record)
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(names, ["id", "meas.wp", "meas.limits.low", "empty", "bins"]);
+
+        let mut schema = Schema::default();
+        schema.add(&record);
+        assert_eq!(schema.row(&record), ["1", "1.5", "0", "{}", "[1,2]"]);
+        assert_eq!(
+            flatten_record(&json!("text")),
+            [("value".to_string(), json!("text"))]
+        );
+    }
+
+    #[test]
+    fn merges_types() {
+        let mut schema = Schema::default();
+        for record in [
+            json!({"int": 1, "float": 1, "mixed": 1, "flag": true, "null": null}),
+            json!({"int": null, "float": 2.5, "mixed": "x", "flag": false, "null": null}),
+        ] {
+            schema.add(&record);
+        }
+        let page = schema.page(Vec::new(), 0, true);
+        assert_eq!(page.headers, ["int", "float", "mixed", "flag", "null"]);
+        assert_eq!(
+            page.column_types,
+            [
+                ColumnType::Integer,
+                ColumnType::Float,
+                ColumnType::Text,
+                ColumnType::Boolean,
+                ColumnType::Text
+            ]
+        );
+    }
+
+    /// An NDJSON file whose last record has a field no other record has.
+    fn late_field(records: usize) -> String {
+        let mut text = String::new();
+        for id in 0..records {
+            text.push_str(&format!("{{\"id\": {id}}}\n"));
+            if id % 100 == 0 {
+                text.push('\n');
+            }
+        }
+        text.push_str(&format!("{{\"id\": {records}, \"note\": \"late\"}}\n"));
+        text
+    }
+
+    #[test]
+    fn ndjson_keeps_fields_after_the_first_records() {
+        let scratch = Scratch::new("json_late_field");
+        let path = scratch.path("lots.ndjson");
+        std::fs::write(&path, late_field(5_000)).unwrap();
+
+        let first = load_json_data_sync(path.clone(), 0, 10).unwrap();
+        assert_eq!(first.headers, ["id", "note"]);
+        assert_eq!(first.rows[3], ["3", ""]);
+        assert!(!first.end_of_file);
+
+        let last = load_json_data_sync(path.clone(), 4_999, 10).unwrap();
+        assert!(last.headers.is_empty());
+        assert_eq!(last.rows, [["4999", ""], ["5000", "late"]]);
+        assert!(last.end_of_file);
+    }
+
+    #[test]
+    fn arrays_keep_fields_after_the_first_records() {
+        let scratch = Scratch::new("json_array_late_field");
+        let path = scratch.path("lots.json");
+        let mut records: Vec<Value> = (0..2_000).map(|id| json!({ "id": id })).collect();
+        records.push(json!({"id": 2_000, "meas": {"wp": 1.5}}));
+        std::fs::write(&path, Value::Array(records).to_string()).unwrap();
+
+        let first = load_json_data_sync(path.clone(), 0, 1).unwrap();
+        assert_eq!(first.headers, ["id", "meas.wp"]);
+        assert_eq!(first.column_types, [ColumnType::Integer, ColumnType::Float]);
+        let last = load_json_data_sync(path, 2_000, 10).unwrap();
+        assert_eq!(last.rows, [["2000", "1.5"]]);
+    }
+
+    #[test]
+    fn schema_follows_changes_to_the_file() {
+        let scratch = Scratch::new("json_changed");
+        let path = scratch.path("lots.ndjson");
+        std::fs::write(&path, "{\"a\": 1}\n").unwrap();
+        assert_eq!(
+            load_json_data_sync(path.clone(), 0, 10).unwrap().headers,
+            ["a"]
+        );
+
+        std::fs::write(&path, "{\"a\": 1}\n{\"b\": 2}\n").unwrap();
+        let page = load_json_data_sync(path.clone(), 0, 10).unwrap();
+        assert_eq!(page.headers, ["a", "b"]);
+        assert_eq!(page.rows, [["1", ""], ["", "2"]]);
+
+        std::fs::write(&path, "\n").unwrap();
+        assert!(matches!(
+            load_json_data_sync(path, 0, 10),
+            Err(LoadError::EmptyFile)
+        ));
+    }
+}
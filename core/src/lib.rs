@@ -0,0 +1,109 @@
+//! Core data loading for `rust_excel_reader`.
+//!
+//! This crate contains everything that reads spreadsheet data and has no GUI
+//! dependencies, so it can be reused by other tools:
+//!
+//! - [`csv_loader`]: paged CSV reading, including detection of "mixed" files
+//!   that carry a preamble (wafer ID, date, ...) before the real header line,
+//!   and tolerant reading that skips or repairs malformed records.
+//! - [`excel_loader`]: paged reading of XLSX / XLS / ODS worksheets.
+//! - [`columnar_loader`]: Parquet and Arrow IPC files, paged by row group.
+//! - [`json_loader`]: JSON arrays and NDJSON, with nested objects flattened.
+//! - [`sqlite_loader`]: tables and views of SQLite databases.
+//! - [`fixed_width_loader`]: fixed-width text with boundary guessing and
+//!   saved layout profiles.
+//! - [`compressed`]: transparent gzip / zstd / bzip2 decompression for CSV,
+//!   with a checkpoint index for paging.
+//! - [`archive`]: listing and extracting spreadsheets inside `.zip` bundles.
+//! - [`folder`]: a folder of same-schema files combined into one table,
+//!   with the source file and preamble fields as extra columns.
+//! - [`export`]: writing derived tables back out as CSV, and single rows
+//!   as JSON.
+//! - [`expression`]: the expression language of computed columns and row
+//!   filters.
+//! - [`edits`]: [`edits::EditOverlay`], cell edits and inserted / deleted
+//!   rows kept on top of the paged data, with undo and redo.
+//! - [`save`]: writing edits back to CSV and XLSX files, copying everything
+//!   that was not edited unchanged.
+//! - [`sheet_view`]: [`sheet_view::SheetView`], computed columns, filter and
+//!   sort applied to a sheet, page by page or for a full export.
+//! - [`pivot`]: group-by / pivot tables over a whole sheet.
+//! - [`diff`]: cell-level comparison of two sheets, aligned by position or
+//!   by key columns.
+//! - [`join`]: inner / left / outer joins of several sheets on key
+//!   columns, spilling to disk for large inputs.
+//! - [`formatting`]: conditional formatting rules (color scales,
+//!   thresholds, text matches, data bars) evaluated cell by cell, and spec
+//!   limits of measurement columns.
+//! - [`stats`]: column statistics over a whole sheet, such as the numeric
+//!   range a color scale spans.
+//! - [`quality`]: a whole-sheet quality report of empty, constant and
+//!   mixed-type columns, duplicate keys, out-of-range values and trailing
+//!   whitespace, exportable as JSON or HTML.
+//! - [`query`]: [`query::QueryEngine`], SQL over registered files through an
+//!   in-memory SQLite database.
+//! - [`table_reader`]: [`table_reader::TableReader`], a row iterator over a
+//!   whole sheet for queries and reports.
+//! - [`row_count`]: the total row count of a sheet, found without loading
+//!   its rows where the format allows it.
+//! - [`progress`]: [`progress::Progress`], bytes and rows read by a
+//!   background task, for progress bars.
+//! - [`pretty`]: pretty-printing of JSON and XML cell values for the cell
+//!   viewer.
+//! - [`values`]: number parsing, cell ordering and number formatting shared
+//!   by the derived-table features.
+//! - [`error`]: [`LoadError`], the kinds of load failures, so the GUI can
+//!   describe them in the user's language.
+//! - [`data_loader`]: [`DataLoader`], which dispatches on the file extension
+//!   and runs loads on a shared background runtime.
+//! - [`file_watcher`]: [`FileWatcher`], change notifications for an open file.
+//!
+//! ```no_run
+//! use rust_excel_reader_core::load_data;
+//!
+//! # async fn run() -> Result<(), String> {
+//! let page = load_data("lot.csv".into(), 0, 100, 0).await?;
+//! println!("{:?}: {} rows", page.headers, page.rows.len());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod archive;
+pub mod columnar_loader;
+pub mod compressed;
+pub mod csv_loader;
+pub mod diff;
+pub mod edits;
+pub mod data_loader;
+pub mod error;
+pub mod excel_loader;
+pub mod export;
+pub mod expression;
+pub mod file_watcher;
+pub mod fixed_width_loader;
+pub mod folder;
+pub mod formatting;
+pub mod join;
+pub mod json_loader;
+pub mod pivot;
+pub mod pretty;
+pub mod progress;
+pub mod quality;
+pub mod query;
+pub mod row_count;
+pub mod save;
+pub mod sheet_view;
+pub mod sqlite_loader;
+pub mod stats;
+pub mod table_reader;
+#[cfg(test)]
+mod test_support;
+pub mod values;
+
+pub use csv_loader::{detect_file_structure, FileStructure};
+pub use data_loader::{
+    is_csv, is_fixed_width, is_pseudo_sheet, is_supported, load_data, load_data_with_options,
+    ColumnType, DataLoadResult, DataLoader, DataPage, LoadOptions,
+};
+pub use error::LoadError;
+pub use file_watcher::FileWatcher;
@@ -0,0 +1,377 @@
+use crate::data_loader::{ColumnType, LoadOptions};
+use crate::table_reader::TableReader;
+use crate::values::{compare_cells, format_number, parse_number};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Upper limit on distinct column-group keys, which become output columns.
+const MAX_COLUMN_KEYS: usize = 500;
+
+/// Aggregate applied to a value column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// Non-blank cells.
+    Count,
+    Sum,
+    Mean,
+    Min,
+    Max,
+    /// Sample standard deviation.
+    Std,
+    /// Distinct non-blank cells.
+    DistinctCount,
+}
+
+impl Aggregate {
+    pub const ALL: [Aggregate; 7] = [
+        Aggregate::Count,
+        Aggregate::Sum,
+        Aggregate::Mean,
+        Aggregate::Min,
+        Aggregate::Max,
+        Aggregate::Std,
+        Aggregate::DistinctCount,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Aggregate::Count => "count",
+            Aggregate::Sum => "sum",
+            Aggregate::Mean => "mean",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::Std => "std",
+            Aggregate::DistinctCount => "distinct",
+        }
+    }
+
+    fn column_type(self) -> ColumnType {
+        match self {
+            Aggregate::Count | Aggregate::DistinctCount => ColumnType::Integer,
+            _ => ColumnType::Float,
+        }
+    }
+}
+
+/// What to group by and what to aggregate. Columns are indexes into the
+/// sheet's headers.
+#[derive(Debug, Clone, Default)]
+pub struct PivotSpec {
+    /// Each distinct combination becomes an output row.
+    pub row_groups: Vec<usize>,
+    /// Each distinct combination becomes a block of output columns.
+    pub column_groups: Vec<usize>,
+    pub values: Vec<(usize, Aggregate)>,
+}
+
+/// Result of [`compute_pivot`], ready to be shown as a table.
+#[derive(Debug, Clone, Default)]
+pub struct PivotTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub column_types: Vec<ColumnType>,
+}
+
+/// Groups every row of a sheet by `spec` and aggregates the value columns.
+///
+/// Numeric aggregates skip cells that are not numbers. Output rows are sorted
+/// by their group keys, numbers by value; column groups are sorted the same
+/// way and each contributes one column per value.
+pub fn compute_pivot(
+    path: &Path,
+    sheet_index: usize,
+    options: &LoadOptions,
+    spec: &PivotSpec,
+) -> Result<PivotTable, String> {
+    if spec.row_groups.is_empty() && spec.column_groups.is_empty() && spec.values.is_empty() {
+        return Err("Pick at least one group or value column.".to_string());
+    }
+
+    let reader = TableReader::open(path, sheet_index, options)?;
+    let headers = reader.headers().to_vec();
+    let column_name = |index: usize| {
+        headers
+            .get(index)
+            .cloned()
+            .ok_or_else(|| format!("Column {} does not exist in this sheet", index + 1))
+    };
+
+    let mut row_keys: HashMap<Vec<String>, usize> = HashMap::new();
+    let mut column_keys: HashMap<Vec<String>, usize> = HashMap::new();
+    // (row key, column key) → one accumulator per value column
+    let mut cells: HashMap<(usize, usize), Vec<Accumulator>> = HashMap::new();
+    let mut group_sizes: HashMap<(usize, usize), usize> = HashMap::new();
+
+    for row in reader {
+        let row = row?;
+        let key = |columns: &[usize]| -> Vec<String> {
+            columns
+                .iter()
+                .map(|&c| row.get(c).map(|v| v.trim().to_string()).unwrap_or_default())
+                .collect()
+        };
+
+        let next_row = row_keys.len();
+        let row_id = *row_keys.entry(key(&spec.row_groups)).or_insert(next_row);
+        let next_column = column_keys.len();
+        let column_id = *column_keys
+            .entry(key(&spec.column_groups))
+            .or_insert(next_column);
+        if column_keys.len() > MAX_COLUMN_KEYS {
+            return Err(format!(
+                "The column groups have more than {} distinct values.",
+                MAX_COLUMN_KEYS
+            ));
+        }
+
+        *group_sizes.entry((row_id, column_id)).or_default() += 1;
+        let accumulators = cells.entry((row_id, column_id)).or_insert_with(|| {
+            spec.values
+                .iter()
+                .map(|&(_, aggregate)| Accumulator::new(aggregate))
+                .collect()
+        });
+        for (accumulator, &(column, _)) in accumulators.iter_mut().zip(&spec.values) {
+            accumulator.add(row.get(column).map(String::as_str).unwrap_or_default());
+        }
+    }
+
+    let row_keys = sorted_keys(row_keys);
+    let column_keys = sorted_keys(column_keys);
+
+    let mut table = PivotTable::default();
+    for &column in &spec.row_groups {
+        table.headers.push(column_name(column)?);
+        table.column_types.push(ColumnType::Text);
+    }
+    // Without value columns each group shows its number of rows
+    let mut value_names = Vec::new();
+    for &(column, aggregate) in &spec.values {
+        value_names.push((
+            format!("{}({})", aggregate.label(), column_name(column)?),
+            aggregate.column_type(),
+        ));
+    }
+    if value_names.is_empty() {
+        value_names.push(("rows".to_string(), ColumnType::Integer));
+    }
+
+    for (column_key, _) in &column_keys {
+        for (value_name, column_type) in &value_names {
+            table.headers.push(if spec.column_groups.is_empty() {
+                value_name.clone()
+            } else {
+                format!("{} · {}", column_key.join(" / "), value_name)
+            });
+            table.column_types.push(*column_type);
+        }
+    }
+
+    for (row_key, row_id) in &row_keys {
+        let mut output = row_key.clone();
+        for (_, column_id) in &column_keys {
+            let group = (*row_id, *column_id);
+            match cells.get(&group) {
+                Some(_) if spec.values.is_empty() => output.push(group_sizes[&group].to_string()),
+                Some(accumulators) => output.extend(accumulators.iter().map(Accumulator::result)),
+                None => output.extend(std::iter::repeat_n(String::new(), value_names.len())),
+            }
+        }
+        table.rows.push(output);
+    }
+
+    Ok(table)
+}
+
+/// Keys in display order, each with its id.
+fn sorted_keys(keys: HashMap<Vec<String>, usize>) -> Vec<(Vec<String>, usize)> {
+    let mut keys: Vec<_> = keys.into_iter().collect();
+    keys.sort_by(|(a, _), (b, _)| {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| compare_cells(x, y))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    keys
+}
+
+/// Running state of one aggregate over one group.
+struct Accumulator {
+    aggregate: Aggregate,
+    count: usize,
+    numbers: usize,
+    sum: f64,
+    // Welford's running mean and squared deviation, for a stable `std`
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+    distinct: HashSet<String>,
+}
+
+impl Accumulator {
+    fn new(aggregate: Aggregate) -> Self {
+        Self {
+            aggregate,
+            count: 0,
+            numbers: 0,
+            sum: 0.0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            distinct: HashSet::new(),
+        }
+    }
+
+    fn add(&mut self, cell: &str) {
+        let cell = cell.trim();
+        if cell.is_empty() {
+            return;
+        }
+        self.count += 1;
+
+        match self.aggregate {
+            Aggregate::Count => {}
+            Aggregate::DistinctCount => {
+                if !self.distinct.contains(cell) {
+                    self.distinct.insert(cell.to_string());
+                }
+            }
+            _ => {
+                if let Some(value) = parse_number(cell) {
+                    self.numbers += 1;
+                    self.sum += value;
+                    let delta = value - self.mean;
+                    self.mean += delta / self.numbers as f64;
+                    self.m2 += delta * (value - self.mean);
+                    self.min = self.min.min(value);
+                    self.max = self.max.max(value);
+                }
+            }
+        }
+    }
+
+    fn result(&self) -> String {
+        let numeric = |value: f64| {
+            if self.numbers == 0 {
+                String::new()
+            } else {
+                format_number(value)
+            }
+        };
+
+        match self.aggregate {
+            Aggregate::Count => self.count.to_string(),
+            Aggregate::DistinctCount => self.distinct.len().to_string(),
+            Aggregate::Sum => numeric(self.sum),
+            Aggregate::Mean => numeric(self.mean),
+            Aggregate::Min => numeric(self.min),
+            Aggregate::Max => numeric(self.max),
+            Aggregate::Std if self.numbers < 2 => String::new(),
+            Aggregate::Std => format_number((self.m2 / (self.numbers - 1) as f64).sqrt()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Scratch;
+
+    fn aggregate(aggregate: Aggregate, cells: &[&str]) -> String {
+        let mut accumulator = Accumulator::new(aggregate);
+        for cell in cells {
+            accumulator.add(cell);
+        }
+        accumulator.result()
+    }
+
+    #[test]
+    fn aggregates_skip_blanks_and_text() {
+        let cells = ["4", " ", "x", "1", "", "7", "x"];
+        assert_eq!(aggregate(Aggregate::Count, &cells), "5");
+        assert_eq!(aggregate(Aggregate::DistinctCount, &cells), "4");
+        assert_eq!(aggregate(Aggregate::Sum, &cells), "12");
+        assert_eq!(aggregate(Aggregate::Mean, &cells), "4");
+        assert_eq!(aggregate(Aggregate::Min, &cells), "1");
+        assert_eq!(aggregate(Aggregate::Max, &cells), "7");
+        assert_eq!(aggregate(Aggregate::Std, &cells), "3");
+    }
+
+    #[test]
+    fn aggregates_without_numbers_are_blank() {
+        assert_eq!(aggregate(Aggregate::Sum, &["x", ""]), "");
+        assert_eq!(aggregate(Aggregate::Min, &[]), "");
+        assert_eq!(aggregate(Aggregate::Std, &["5"]), "");
+        assert_eq!(aggregate(Aggregate::Count, &[]), "0");
+    }
+
+    #[test]
+    fn std_is_stable_for_large_offsets() {
+        // Deviations of -6, -3, 3 and 6: a sample variance of 30. Summing
+        // squares of values this large would lose the deviations entirely.
+        let cells = ["1000000004", "1000000007", "1000000013", "1000000016"];
+        assert_eq!(aggregate(Aggregate::Std, &cells), "5.477226");
+        assert_eq!(aggregate(Aggregate::Mean, &cells), "1000000010");
+    }
+
+    #[test]
+    fn groups_are_ordered_like_a_sorted_column() {
+        let scratch = Scratch::new("pivot_groups");
+        let path = scratch.path("lots.csv");
+        std::fs::write(
+            &path,
+            "wafer,bin,value\nW10,1,5\nW2,2,3\n,1,1\nW2,1,4\nw1,10,2\nW10,2,6\n",
+        )
+        .unwrap();
+        let spec = PivotSpec {
+            row_groups: vec![0],
+            column_groups: vec![1],
+            values: vec![(2, Aggregate::Sum)],
+        };
+        let table = compute_pivot(&path, 0, &LoadOptions::default(), &spec).unwrap();
+        assert_eq!(
+            table.headers,
+            [
+                "wafer",
+                "1 · sum(value)",
+                "2 · sum(value)",
+                "10 · sum(value)"
+            ]
+        );
+        assert_eq!(
+            table.rows,
+            [
+                ["w1", "", "", "2"],
+                ["W2", "4", "3", ""],
+                ["W10", "5", "6", ""],
+                ["", "1", "", ""],
+            ]
+        );
+        assert_eq!(
+            table.column_types,
+            [
+                ColumnType::Text,
+                ColumnType::Float,
+                ColumnType::Float,
+                ColumnType::Float
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_without_values_count_rows() {
+        let scratch = Scratch::new("pivot_rows");
+        let path = scratch.path("lots.csv");
+        std::fs::write(&path, "wafer\nW1\nW2\nW1\n").unwrap();
+        let spec = PivotSpec {
+            row_groups: vec![0],
+            ..PivotSpec::default()
+        };
+        let table = compute_pivot(&path, 0, &LoadOptions::default(), &spec).unwrap();
+        assert_eq!(table.headers, ["wafer", "rows"]);
+        assert_eq!(table.rows, [["W1", "2"], ["W2", "1"]]);
+    }
+}
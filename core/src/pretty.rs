@@ -0,0 +1,112 @@
+use quick_xml::events::Event;
+use quick_xml::{Reader, Writer};
+
+/// Structured text found in a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Structure {
+    Json,
+    Xml,
+}
+
+impl Structure {
+    pub fn label(self) -> &'static str {
+        match self {
+            Structure::Json => "JSON",
+            Structure::Xml => "XML",
+        }
+    }
+}
+
+/// Reformats a cell holding a JSON object or array, or an XML fragment,
+/// one element per line and indented. `None` for anything else, including
+/// malformed JSON and XML with unbalanced tags.
+pub fn pretty_print(text: &str) -> Option<(Structure, String)> {
+    let text = text.trim();
+    if text.starts_with('{') || text.starts_with('[') {
+        let value: serde_json::Value = serde_json::from_str(text).ok()?;
+        return Some((Structure::Json, serde_json::to_string_pretty(&value).ok()?));
+    }
+    if text.starts_with('<') && text.ends_with('>') {
+        return pretty_xml(text).map(|xml| (Structure::Xml, xml));
+    }
+    None
+}
+
+/// `true` for text better read in a viewer than in a one-line cell:
+/// several lines, or JSON or XML.
+pub fn is_structured(text: &str) -> bool {
+    text.contains('\n') || pretty_print(text).is_some()
+}
+
+/// Puts every tag on its own line, indented by depth. Elements holding only
+/// text stay on one line, as in `<name>W01</name>`.
+fn pretty_xml(text: &str) -> Option<String> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    // The reader checks that end tags match; an element left open is only
+    // noticed by counting
+    let mut depth = 0usize;
+    loop {
+        let event = reader.read_event().ok()?;
+        match &event {
+            Event::Eof => break,
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+        writer.write_event(event).ok()?;
+    }
+    if depth > 0 {
+        return None;
+    }
+    String::from_utf8(writer.into_inner()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json() {
+        let (structure, text) = pretty_print(r#" {"wafer":"W01","bins":[1,2]} "#).unwrap();
+        assert_eq!(structure, Structure::Json);
+        assert_eq!(
+            text,
+            "{\n  \"wafer\": \"W01\",\n  \"bins\": [\n    1,\n    2\n  ]\n}"
+        );
+        assert_eq!(pretty_print("{not json"), None);
+        assert_eq!(pretty_print("[1, 2"), None);
+    }
+
+    #[test]
+    fn xml() {
+        let (structure, text) =
+            pretty_print(r#"<lot id="L1"><wafer n="1"><name>W01</name><flag/></wafer></lot>"#)
+                .unwrap();
+        assert_eq!(structure, Structure::Xml);
+        assert_eq!(
+            text,
+            "<lot id=\"L1\">\n  <wafer n=\"1\">\n    <name>W01</name>\n    <flag/>\n  </wafer>\n</lot>"
+        );
+    }
+
+    #[test]
+    fn xml_keeps_comments_and_attributes() {
+        let (_, text) =
+            pretty_print(r#"<?xml version="1.0"?><a b="x > y"><!-- note --><c>1</c></a>"#).unwrap();
+        assert_eq!(
+            text,
+            "<?xml version=\"1.0\"?>\n<a b=\"x > y\">\n  <!-- note -->\n  <c>1</c>\n</a>"
+        );
+    }
+
+    #[test]
+    fn malformed_xml() {
+        assert_eq!(pretty_print("<a><b></a>"), None);
+        assert_eq!(pretty_print("<a><b></b>"), None);
+        assert_eq!(pretty_print("<a></b>"), None);
+        assert!(!is_structured("<not closed"));
+        assert!(is_structured("line one\nline two"));
+    }
+}
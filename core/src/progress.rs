@@ -0,0 +1,102 @@
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Counters a background task advances as it reads a file, polled by the
+/// GUI to draw a progress bar. Clones share the same counters, so the task
+/// gets a clone and the caller keeps the original.
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    counters: Arc<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    bytes_read: AtomicU64,
+    /// Size of the input in bytes, 0 while unknown.
+    total_bytes: AtomicU64,
+    rows: AtomicU64,
+    /// Rows the task will go through, 0 while unknown.
+    total_rows: AtomicU64,
+}
+
+/// The state of a [`Progress`] at one moment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProgressEvent {
+    pub bytes_read: u64,
+    pub total_bytes: Option<u64>,
+    /// Rows parsed so far.
+    pub rows: u64,
+    pub total_rows: Option<u64>,
+}
+
+impl ProgressEvent {
+    /// How far the task has got, from 0 to 1: by bytes when the size of the
+    /// input is known, else by rows. `None` when neither total is known.
+    pub fn fraction(&self) -> Option<f32> {
+        let (done, total) = match (self.total_bytes, self.total_rows) {
+            (Some(total), _) => (self.bytes_read, total),
+            (None, Some(total)) => (self.rows, total),
+            (None, None) => return None,
+        };
+        Some((done as f64 / total.max(1) as f64).min(1.0) as f32)
+    }
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the size of the input, for the fraction read.
+    pub fn set_total_bytes(&self, total: u64) {
+        self.counters.total_bytes.store(total, Ordering::Relaxed);
+    }
+
+    /// Sets the number of rows the task will go through, when it is known
+    /// beforehand, e.g. from the row counter.
+    pub fn set_total_rows(&self, total: usize) {
+        self.counters.total_rows.store(total as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_bytes(&self, count: usize) {
+        self.counters
+            .bytes_read
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_rows(&self, count: usize) {
+        self.counters.rows.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ProgressEvent {
+        let known = |value: u64| (value > 0).then_some(value);
+        let counters = &self.counters;
+        ProgressEvent {
+            bytes_read: counters.bytes_read.load(Ordering::Relaxed),
+            total_bytes: known(counters.total_bytes.load(Ordering::Relaxed)),
+            rows: counters.rows.load(Ordering::Relaxed),
+            total_rows: known(counters.total_rows.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// A reader that adds the bytes passing through it to a [`Progress`].
+pub struct ProgressReader<R> {
+    inner: R,
+    progress: Progress,
+}
+
+impl<R> ProgressReader<R> {
+    pub fn new(inner: R, progress: Progress) -> Self {
+        Self { inner, progress }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.progress.add_bytes(count);
+        Ok(count)
+    }
+}
@@ -0,0 +1,383 @@
+use crate::formatting::{LimitCheck, SpecLimit};
+use crate::progress::Progress;
+use crate::table_reader::SheetSource;
+use crate::values::{format_number, parse_number};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+/// Example rows kept per finding.
+pub const MAX_EXAMPLES: usize = 20;
+
+/// What a quality scan checks besides the per-column checks that always run.
+#[derive(Debug, Clone, Default)]
+pub struct QualityOptions {
+    /// Columns whose values together identify a row, such as the X and Y
+    /// of a die; empty to skip the duplicate key check.
+    pub key_columns: Vec<usize>,
+    /// Allowed range of coordinate and measurement columns, by header.
+    pub limits: BTreeMap<String, SpecLimit>,
+}
+
+/// A problem found in a column.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FindingKind {
+    /// No cell of the column has a value.
+    Empty,
+    /// Every cell with a value has this one.
+    Constant { value: String },
+    /// Rows share a key with an earlier row; `values` is the number of
+    /// distinct keys that occur more than once.
+    DuplicateKeys { columns: Vec<String>, values: usize },
+    /// Both numbers and text, with the cells of the rarer kind as examples.
+    MixedTypes { numbers: usize, text: usize },
+    /// Numbers outside the range set for the column.
+    OutOfRange {
+        low: Option<f64>,
+        high: Option<f64>,
+    },
+    /// Values that end in spaces or tabs.
+    TrailingWhitespace,
+}
+
+impl FindingKind {
+    /// Describes the finding in English, for exported reports.
+    pub fn describe(&self) -> String {
+        match self {
+            FindingKind::Empty => "Empty column".to_string(),
+            FindingKind::Constant { value } => format!("Constant value '{}'", value),
+            FindingKind::DuplicateKeys { columns, values } => format!(
+                "Duplicate keys ({}): {} values occur more than once",
+                columns.join(", "),
+                values
+            ),
+            FindingKind::MixedTypes { numbers, text } => {
+                format!("Mixed types: {} numbers, {} text values", numbers, text)
+            }
+            FindingKind::OutOfRange { low, high } => {
+                let bound = |value: &Option<f64>| value.map(format_number).unwrap_or_default();
+                format!("Out of range [{}, {}]", bound(low), bound(high))
+            }
+            FindingKind::TrailingWhitespace => "Trailing whitespace".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    #[serde(flatten)]
+    pub kind: FindingKind,
+    /// Cells the finding applies to; rows for duplicate keys.
+    pub count: usize,
+    /// Data rows showing the finding, 0-based. Exports number them from 1
+    /// like the table does.
+    #[serde(serialize_with = "row_numbers")]
+    pub example_rows: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnReport {
+    pub name: String,
+    /// Cells with a value other than whitespace.
+    pub filled: usize,
+    pub empty: usize,
+    pub findings: Vec<Finding>,
+}
+
+/// Findings of a whole-sheet scan, column by column.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QualityReport {
+    pub rows: usize,
+    pub columns: Vec<ColumnReport>,
+}
+
+impl QualityReport {
+    pub fn finding_count(&self) -> usize {
+        self.columns.iter().map(|column| column.findings.len()).sum()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// A standalone HTML page with one table row per finding.
+    pub fn to_html(&self, title: &str) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>\nbody {{ font-family: sans-serif; }}\n\
+             table {{ border-collapse: collapse; }}\n\
+             th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }}\n\
+             th {{ background: #eee; }}\n</style>\n</head>\n<body>\n\
+             <h1>{title}</h1>\n<p>{rows} rows, {findings} findings</p>\n",
+            title = escape_html(title),
+            rows = self.rows,
+            findings = self.finding_count(),
+        );
+        html.push_str(
+            "<table>\n<tr><th>Column</th><th>Filled</th><th>Empty</th><th>Finding</th>\
+             <th>Count</th><th>Example rows</th></tr>\n",
+        );
+        for column in &self.columns {
+            if column.findings.is_empty() {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>OK</td><td></td><td></td></tr>",
+                    escape_html(&column.name),
+                    column.filled,
+                    column.empty
+                );
+            }
+            for finding in &column.findings {
+                let examples: Vec<String> = finding
+                    .example_rows
+                    .iter()
+                    .map(|row| (row + 1).to_string())
+                    .collect();
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&column.name),
+                    column.filled,
+                    column.empty,
+                    escape_html(&finding.kind.describe()),
+                    finding.count,
+                    examples.join(", ")
+                );
+            }
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+fn row_numbers<S: Serializer>(rows: &[usize], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(rows.iter().map(|row| row + 1))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Cells with one kind of problem: how many, and the first few rows.
+#[derive(Debug, Default)]
+struct Tally {
+    count: usize,
+    rows: Vec<usize>,
+}
+
+impl Tally {
+    fn add(&mut self, row: usize) {
+        self.count += 1;
+        if self.rows.len() < MAX_EXAMPLES {
+            self.rows.push(row);
+        }
+    }
+
+    fn finding(self, kind: FindingKind) -> Option<Finding> {
+        (self.count > 0).then_some(Finding {
+            kind,
+            count: self.count,
+            example_rows: self.rows,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct ColumnScan {
+    filled: usize,
+    /// The first value, and whether every later one has matched it.
+    first: Option<String>,
+    constant: bool,
+    numbers: Tally,
+    text: Tally,
+    out_of_range: Tally,
+    whitespace: Tally,
+    limit: Option<SpecLimit>,
+}
+
+impl ColumnScan {
+    fn add(&mut self, row: usize, cell: &str) {
+        if cell.trim().is_empty() {
+            return;
+        }
+        self.filled += 1;
+        match &self.first {
+            None => {
+                self.first = Some(cell.to_string());
+                self.constant = true;
+            }
+            Some(first) => self.constant &= first == cell,
+        }
+        if parse_number(cell).is_some() {
+            self.numbers.add(row);
+        } else {
+            self.text.add(row);
+        }
+        if let Some(limit) = &self.limit {
+            if matches!(limit.check(cell), Some(LimitCheck::Below | LimitCheck::Above)) {
+                self.out_of_range.add(row);
+            }
+        }
+        if cell.len() != cell.trim_end().len() {
+            self.whitespace.add(row);
+        }
+    }
+
+    fn finish(self, name: String, rows: usize) -> ColumnReport {
+        let mut findings = Vec::new();
+        if self.filled == 0 && rows > 0 {
+            findings.push(Finding {
+                kind: FindingKind::Empty,
+                count: rows,
+                example_rows: Vec::new(),
+            });
+        }
+        if self.constant && self.filled > 1 {
+            findings.push(Finding {
+                kind: FindingKind::Constant {
+                    value: self.first.unwrap_or_default(),
+                },
+                count: self.filled,
+                example_rows: Vec::new(),
+            });
+        }
+        if self.numbers.count > 0 && self.text.count > 0 {
+            let kind = FindingKind::MixedTypes {
+                numbers: self.numbers.count,
+                text: self.text.count,
+            };
+            let rarer = if self.numbers.count < self.text.count {
+                self.numbers
+            } else {
+                self.text
+            };
+            findings.extend(rarer.finding(kind));
+        }
+        if let Some(limit) = self.limit {
+            findings.extend(self.out_of_range.finding(FindingKind::OutOfRange {
+                low: limit.low,
+                high: limit.high,
+            }));
+        }
+        findings.extend(self.whitespace.finding(FindingKind::TrailingWhitespace));
+        ColumnReport {
+            name,
+            filled: self.filled,
+            empty: rows - self.filled,
+            findings,
+        }
+    }
+}
+
+/// Rows sharing their key with an earlier row.
+#[derive(Debug, Default)]
+struct KeyScan {
+    /// First row of each key, and whether the key has repeated.
+    seen: HashMap<Vec<String>, (usize, bool)>,
+    duplicates: Tally,
+    values: usize,
+}
+
+impl KeyScan {
+    fn add(&mut self, row: usize, key: Vec<String>) {
+        if key.iter().all(|value| value.is_empty()) {
+            return;
+        }
+        match self.seen.get_mut(&key) {
+            Some((first, repeated)) => {
+                if !*repeated {
+                    *repeated = true;
+                    self.values += 1;
+                    // The first occurrence is an example too
+                    if self.duplicates.rows.len() < MAX_EXAMPLES {
+                        self.duplicates.rows.push(*first);
+                    }
+                }
+                self.duplicates.add(row);
+            }
+            None => {
+                self.seen.insert(key, (row, false));
+            }
+        }
+    }
+}
+
+/// Reads the whole sheet and checks every column for being empty or
+/// constant, for mixing numbers with text, for values outside the range set
+/// in `options` and for trailing whitespace, and the key columns for
+/// duplicate keys. Cells are taken as stored in the file, without edits.
+pub fn scan_quality(
+    source: &SheetSource,
+    options: &QualityOptions,
+    progress: &Progress,
+) -> Result<QualityReport, String> {
+    let reader = source.open_tracked(progress)?;
+    let mut names = reader.headers().to_vec();
+    let limit_of = |name: &str| {
+        options
+            .limits
+            .get(name)
+            .filter(|limit| !limit.is_empty())
+            .copied()
+    };
+    let mut columns: Vec<ColumnScan> = names
+        .iter()
+        .map(|name| ColumnScan {
+            limit: limit_of(name),
+            ..ColumnScan::default()
+        })
+        .collect();
+    let mut keys = KeyScan::default();
+
+    let mut rows = 0;
+    for row in reader {
+        let row = row?;
+        while columns.len() < row.len() {
+            names.push(format!("column {}", columns.len() + 1));
+            columns.push(ColumnScan::default());
+        }
+        for (column, cell) in columns.iter_mut().zip(&row) {
+            column.add(rows, cell);
+        }
+        if !options.key_columns.is_empty() {
+            let key = options
+                .key_columns
+                .iter()
+                .map(|&index| row.get(index).map_or("", |cell| cell.trim()).to_string())
+                .collect();
+            keys.add(rows, key);
+        }
+        rows += 1;
+    }
+
+    let mut report = QualityReport {
+        rows,
+        columns: columns
+            .into_iter()
+            .zip(names.iter().cloned())
+            .map(|(column, name)| column.finish(name, rows))
+            .collect(),
+    };
+    // Duplicate keys are listed under the first key column
+    let key_column = options.key_columns.first().and_then(|&index| report.columns.get_mut(index));
+    if let Some(column) = key_column {
+        let kind = FindingKind::DuplicateKeys {
+            columns: options
+                .key_columns
+                .iter()
+                .filter_map(|&index| names.get(index).cloned())
+                .collect(),
+            values: keys.values,
+        };
+        if let Some(finding) = keys.duplicates.finding(kind) {
+            column.findings.insert(0, finding);
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,525 @@
+use crate::compressed::FileStamp;
+use crate::data_loader::{file_extension, load_data_sync, ColumnType, LoadOptions};
+use crate::error::LoadError;
+use crate::sqlite_loader::{cell_text, list_objects, open_database, quote_identifier};
+use crate::table_reader::TableReader;
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+/// Runs SQL over the files the user has open.
+///
+/// Each sheet of a registered file becomes a table in an in-memory SQLite
+/// database, named after the file (`lot_a`, or `lot_a_sheet1` for workbooks
+/// with several sheets). SQLite databases are attached instead of copied.
+/// Cell values are stored as integers or reals where they parse as numbers,
+/// so `WHERE x > 1.5` and `SUM(x)` behave as expected.
+pub struct QueryEngine {
+    conn: Connection,
+    tables: Vec<RegisteredTable>,
+    sources: Vec<RegisteredSource>,
+    attached_count: usize,
+}
+
+/// A table the user can query.
+#[derive(Debug, Clone)]
+pub struct RegisteredTable {
+    /// Name to use in SQL.
+    pub name: String,
+    pub path: PathBuf,
+    /// Sheet the table was read from; empty for single-table formats.
+    pub sheet: String,
+    pub columns: Vec<String>,
+}
+
+/// A registered file, kept to notice when it changes on disk.
+struct RegisteredSource {
+    path: PathBuf,
+    stamp: FileStamp,
+    /// Schema name for attached SQLite databases.
+    attached_as: Option<String>,
+}
+
+/// Result of [`QueryEngine::query`].
+#[derive(Debug, Clone, Default)]
+pub struct QueryResult {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Types of the returned values, inferred per column.
+    pub column_types: Vec<ColumnType>,
+}
+
+impl QueryEngine {
+    pub fn new() -> Result<Self, LoadError> {
+        let conn =
+            Connection::open_in_memory().map_err(|e| LoadError::QueryEngine(e.to_string()))?;
+        Ok(Self {
+            conn,
+            tables: Vec::new(),
+            sources: Vec::new(),
+            attached_count: 0,
+        })
+    }
+
+    /// Tables available to queries, in registration order.
+    pub fn tables(&self) -> &[RegisteredTable] {
+        &self.tables
+    }
+
+    /// Makes every sheet of `path` available as a table and returns the
+    /// table names. Sheets without columns, such as an empty sheet of a
+    /// workbook, are left out.
+    ///
+    /// Registering a file again is cheap while it is unchanged on disk; a
+    /// changed file is re-imported under the same names.
+    pub fn register_file(
+        &mut self,
+        path: &Path,
+        options: &LoadOptions,
+    ) -> Result<Vec<String>, LoadError> {
+        let stamp = FileStamp::read(path)?;
+        if let Some(source) = self.sources.iter().find(|s| s.path == path) {
+            if source.stamp == stamp {
+                return Ok(self.table_names(path));
+            }
+        }
+        let previous_names = self.table_names(path);
+        self.unregister_file(path)?;
+
+        let extension = file_extension(path);
+        let (attached_as, imported) = match extension.as_deref() {
+            Some("db") | Some("sqlite") | Some("sqlite3") => {
+                self.attached_count += 1;
+                let schema = format!("source_{}", self.attached_count);
+                let attached = self.attach_database(path, &schema, &previous_names);
+                (Some(schema), attached)
+            }
+            _ => (None, self.import_file(path, options, &previous_names)),
+        };
+        // Tables of the sheets read before a failure would otherwise keep
+        // their names taken with no source to drop them
+        if let Err(e) = imported {
+            let _ = self.drop_tables(path, attached_as.as_deref());
+            return Err(e);
+        }
+
+        self.sources.push(RegisteredSource {
+            path: path.to_path_buf(),
+            stamp,
+            attached_as,
+        });
+        Ok(self.table_names(path))
+    }
+
+    /// Runs `sql` and returns every row it produces.
+    pub fn query(&self, sql: &str) -> Result<QueryResult, String> {
+        let mut stmt = self.conn.prepare(sql).map_err(|e| e.to_string())?;
+        let headers: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+        let column_count = headers.len();
+        let mut types: Vec<Option<ColumnType>> = vec![None; column_count];
+
+        let mut rows = Vec::new();
+        let mut result_rows = stmt.query([]).map_err(|e| e.to_string())?;
+        while let Some(row) = result_rows.next().map_err(|e| e.to_string())? {
+            let mut values = Vec::with_capacity(column_count);
+            for (index, column_type) in types.iter_mut().enumerate() {
+                let value = row.get_ref(index).map_err(|e| e.to_string())?;
+                *column_type = merge_type(*column_type, value);
+                values.push(cell_text(value));
+            }
+            rows.push(values);
+        }
+
+        Ok(QueryResult {
+            headers,
+            rows,
+            column_types: types
+                .into_iter()
+                .map(|t| t.unwrap_or(ColumnType::Text))
+                .collect(),
+        })
+    }
+
+    fn table_names(&self, path: &Path) -> Vec<String> {
+        self.tables
+            .iter()
+            .filter(|t| t.path == path)
+            .map(|t| t.name.clone())
+            .collect()
+    }
+
+    fn unregister_file(&mut self, path: &Path) -> Result<(), String> {
+        let Some(index) = self.sources.iter().position(|s| s.path == path) else {
+            return Ok(());
+        };
+        let source = self.sources.remove(index);
+        self.drop_tables(path, source.attached_as.as_deref())
+    }
+
+    /// Drops the tables of `path`, or its views and the database attached
+    /// as `attached_as`.
+    fn drop_tables(&mut self, path: &Path, attached_as: Option<&str>) -> Result<(), String> {
+        let names = self.table_names(path);
+        self.tables.retain(|t| t.path != path);
+
+        let kind = if attached_as.is_some() { "VIEW" } else { "TABLE" };
+        for name in names {
+            self.conn
+                .execute_batch(&format!("DROP {} IF EXISTS {}", kind, quote_identifier(&name)))
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(schema) = attached_as {
+            self.conn
+                .execute_batch(&format!("DETACH DATABASE {}", quote_identifier(schema)))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Attaches a SQLite database as `schema` and exposes each of its tables
+    /// and views through a temporary view, so it can be joined with other
+    /// files.
+    fn attach_database(
+        &mut self,
+        path: &Path,
+        schema: &str,
+        previous_names: &[String],
+    ) -> Result<(), LoadError> {
+        self.conn
+            .execute(
+                &format!("ATTACH DATABASE ?1 AS {}", quote_identifier(schema)),
+                [path.to_string_lossy()],
+            )
+            .map_err(|e| LoadError::AttachDatabase {
+                path: path.display().to_string(),
+                detail: e.to_string(),
+            })?;
+
+        let objects = list_objects(&open_database(path)?)?;
+
+        for (index, object) in objects.iter().enumerate() {
+            let name =
+                self.table_name(path, &object.name, objects.len(), previous_names.get(index));
+            self.conn
+                .execute_batch(&format!(
+                    "CREATE TEMP VIEW {} AS SELECT * FROM {}.{}",
+                    quote_identifier(&name),
+                    quote_identifier(schema),
+                    quote_identifier(&object.name)
+                ))
+                .map_err(|e| e.to_string())?;
+
+            let columns = self.view_columns(&name)?;
+            self.tables.push(RegisteredTable {
+                name,
+                path: path.to_path_buf(),
+                sheet: object.name.clone(),
+                columns,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn view_columns(&self, name: &str) -> Result<Vec<String>, String> {
+        let stmt = self
+            .conn
+            .prepare(&format!("SELECT * FROM {} LIMIT 0", quote_identifier(name)))
+            .map_err(|e| e.to_string())?;
+        Ok(stmt.column_names().iter().map(|s| s.to_string()).collect())
+    }
+
+    fn import_file(
+        &mut self,
+        path: &Path,
+        options: &LoadOptions,
+        previous_names: &[String],
+    ) -> Result<(), LoadError> {
+        let first = load_data_sync(path.to_path_buf(), 0, 0, 0, options)?;
+        // Row groups of a columnar file are views of the same table
+        let sheet_count = match file_extension(path).as_deref() {
+            Some("parquet") | Some("arrow") | Some("feather") => 1,
+            _ => first.sheet_names.len().max(1),
+        };
+
+        for sheet_index in 0..sheet_count {
+            let sheet = first
+                .sheet_names
+                .get(sheet_index)
+                .cloned()
+                .unwrap_or_default();
+            let reader = TableReader::open(path, sheet_index, options)?;
+            let columns = column_names(reader.headers());
+            // SQLite has no tables without columns
+            if columns.is_empty() {
+                continue;
+            }
+            let name = self.table_name(path, &sheet, sheet_count, previous_names.get(sheet_index));
+            self.import_rows(&name, &columns, reader)?;
+
+            self.tables.push(RegisteredTable {
+                name,
+                path: path.to_path_buf(),
+                sheet: if sheet_count > 1 {
+                    sheet
+                } else {
+                    String::new()
+                },
+                columns,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn import_rows(
+        &mut self,
+        name: &str,
+        columns: &[String],
+        rows: TableReader,
+    ) -> Result<(), String> {
+        let column_list = columns
+            .iter()
+            .map(|c| quote_identifier(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let placeholders = vec!["?"; columns.len()].join(", ");
+
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute_batch(&format!(
+            "CREATE TABLE {} ({})",
+            quote_identifier(name),
+            column_list
+        ))
+        .map_err(|e| e.to_string())?;
+
+        {
+            let mut insert = tx
+                .prepare(&format!(
+                    "INSERT INTO {} VALUES ({})",
+                    quote_identifier(name),
+                    placeholders
+                ))
+                .map_err(|e| e.to_string())?;
+            let mut values = Vec::with_capacity(columns.len());
+            for row in rows {
+                let row = row?;
+                values.clear();
+                values.extend(
+                    (0..columns.len()).map(|i| typed_value(row.get(i).map_or("", String::as_str))),
+                );
+                insert
+                    .execute(rusqlite::params_from_iter(values.iter()))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// SQL name for a sheet: the file stem, plus the sheet name for
+    /// workbooks with several sheets, made unique among the registered tables.
+    fn table_name(
+        &self,
+        path: &Path,
+        sheet: &str,
+        sheet_count: usize,
+        previous: Option<&String>,
+    ) -> String {
+        if let Some(previous) = previous {
+            return previous.clone();
+        }
+
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let stem = stem.split('.').next().unwrap_or_default();
+        let base = if sheet_count > 1 {
+            sanitize(&format!("{}_{}", stem, sheet))
+        } else {
+            sanitize(stem)
+        };
+
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while self
+            .tables
+            .iter()
+            .any(|t| t.name.eq_ignore_ascii_case(&name))
+        {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        name
+    }
+}
+
+/// Turns arbitrary text into a lowercase identifier that needs no quoting.
+fn sanitize(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    sanitized = sanitized.trim_matches('_').to_string();
+    if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert_str(0, "t_");
+    }
+    sanitized
+}
+
+/// Column names for a header row: blanks become `column_N` and duplicates get
+/// a numeric suffix, since SQLite rejects both.
+fn column_names(headers: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(headers.len());
+    for (index, header) in headers.iter().enumerate() {
+        let base = match header.trim() {
+            "" => format!("column_{}", index + 1),
+            trimmed => trimmed.to_string(),
+        };
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while names.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// Stores a cell as an integer or real when it is one, as text otherwise.
+/// Values with a leading zero, such as lot or part numbers, stay text.
+fn typed_value(cell: &str) -> Value {
+    let trimmed = cell.trim();
+    if trimmed.is_empty() {
+        return Value::Null;
+    }
+
+    let digits = trimmed.strip_prefix('-').unwrap_or(trimmed);
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    if !leading_zero && !trimmed.starts_with('+') {
+        if let Ok(i) = trimmed.parse::<i64>() {
+            return Value::Integer(i);
+        }
+        // `f64::from_str` also accepts "inf" and "NaN"; require a digit
+        if trimmed.bytes().any(|b| b.is_ascii_digit()) {
+            if let Ok(f) = trimmed.parse::<f64>() {
+                return Value::Real(f);
+            }
+        }
+    }
+
+    Value::Text(cell.to_string())
+}
+
+/// Widens the type seen so far for a result column with one more value.
+fn merge_type(current: Option<ColumnType>, value: ValueRef<'_>) -> Option<ColumnType> {
+    let value_type = match value {
+        ValueRef::Null => return current,
+        ValueRef::Integer(_) => ColumnType::Integer,
+        ValueRef::Real(_) => ColumnType::Float,
+        ValueRef::Text(_) => ColumnType::Text,
+        ValueRef::Blob(_) => ColumnType::Other,
+    };
+
+    Some(match (current, value_type) {
+        (None, t) => t,
+        (Some(a), b) if a == b => a,
+        (Some(ColumnType::Integer), ColumnType::Float)
+        | (Some(ColumnType::Float), ColumnType::Integer) => ColumnType::Float,
+        _ => ColumnType::Text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_xlsx, Scratch, TestSheet};
+
+    fn attached_databases(engine: &QueryEngine) -> usize {
+        engine
+            .query("SELECT name FROM pragma_database_list")
+            .unwrap()
+            .rows
+            .iter()
+            .filter(|row| row[0].starts_with("source_"))
+            .count()
+    }
+
+    #[test]
+    fn failed_import_drops_the_sheets_read_before() {
+        let scratch = Scratch::new("query_import");
+        let path = scratch.path("lot.xlsx");
+        let rows: &[&[&str]] = &[&["x"], &["1"]];
+        write_xlsx(
+            &path,
+            &[
+                TestSheet { name: "a", rows, extra: "" },
+                TestSheet { name: "b", rows, extra: "" },
+            ],
+        );
+        let options = LoadOptions::default();
+        let mut engine = QueryEngine::new().unwrap();
+
+        // The second sheet's table name is taken
+        engine.query("CREATE TABLE lot_b (y)").unwrap();
+        assert!(engine.register_file(&path, &options).is_err());
+        assert!(engine.tables().is_empty());
+        assert!(engine.query("SELECT * FROM lot_a").is_err());
+
+        engine.query("DROP TABLE lot_b").unwrap();
+        assert_eq!(
+            engine.register_file(&path, &options),
+            Ok(vec!["lot_a".to_string(), "lot_b".to_string()])
+        );
+        assert_eq!(engine.query("SELECT x FROM lot_b").unwrap().rows, vec![vec!["1"]]);
+    }
+
+    #[test]
+    fn failed_attach_is_detached() {
+        let scratch = Scratch::new("query_attach");
+        let path = scratch.path("lot.db");
+        std::fs::write(&path, "not a database, just text that is long enough").unwrap();
+        let options = LoadOptions::default();
+        let mut engine = QueryEngine::new().unwrap();
+
+        assert!(engine.register_file(&path, &options).is_err());
+        assert_eq!(attached_databases(&engine), 0);
+
+        std::fs::remove_file(&path).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE wafers (id); INSERT INTO wafers VALUES (7);")
+            .unwrap();
+        drop(conn);
+        assert_eq!(engine.register_file(&path, &options), Ok(vec!["lot".to_string()]));
+        assert_eq!(engine.query("SELECT id FROM lot").unwrap().rows, vec![vec!["7"]]);
+        assert_eq!(attached_databases(&engine), 1);
+    }
+
+    #[test]
+    fn sheets_without_columns_are_left_out() {
+        let scratch = Scratch::new("query_empty");
+        let workbook = scratch.path("lot.xlsx");
+        write_xlsx(
+            &workbook,
+            &[
+                TestSheet { name: "empty", rows: &[], extra: "" },
+                TestSheet { name: "bins", rows: &[&["bin"], &["3"]], extra: "" },
+            ],
+        );
+        let csv = scratch.path("blank.csv");
+        std::fs::write(&csv, "").unwrap();
+        let options = LoadOptions::default();
+        let mut engine = QueryEngine::new().unwrap();
+
+        assert_eq!(engine.register_file(&workbook, &options), Ok(vec!["lot_bins".to_string()]));
+        assert_eq!(engine.query("SELECT bin FROM lot_bins").unwrap().rows, vec![vec!["3"]]);
+        // A file without even a header row is reported, not imported
+        assert_eq!(engine.register_file(&csv, &options), Err(LoadError::EmptyFile));
+        assert_eq!(engine.tables().len(), 1);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::csv_loader::{open_csv_source, open_tracked_csv_source, read_header, Tolerance};
+use crate::data_loader::{file_extension, LoadOptions};
+use crate::progress::Progress;
+use crate::table_reader::TableReader;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Bytes scanned per read when counting CSV records.
+const SCAN_BUFFER: usize = 256 * 1024;
+
+/// Result of a background row count, tagged with the sheet it was counted
+/// for so a count that arrives after the user moved on can be dropped.
+#[derive(Debug, Clone)]
+pub struct RowCount {
+    pub path: PathBuf,
+    pub sheet_index: usize,
+    pub rows: Result<usize, String>,
+}
+
+/// Number of data rows in a sheet, found the cheapest way the format
+/// allows: a scan for record ends in CSV, the used range of Excel sheets,
+/// the footer of columnar files and `COUNT(*)` in SQLite. JSON and
+/// fixed-width files are read through in full, and so are CSV files read
+/// with a tolerance, whose skipped and repaired records change the count.
+pub fn count_rows(
+    path: &Path,
+    sheet_index: usize,
+    options: &LoadOptions,
+    progress: &Progress,
+) -> Result<usize, String> {
+    match file_extension(path).as_deref() {
+        Some("csv") if options.tolerance == Tolerance::Strict => count_csv_rows(path, progress),
+        Some("xlsx") | Some("xls") | Some("ods") => {
+            Ok(crate::excel_loader::excel_row_count(path, sheet_index)?)
+        }
+        Some("parquet") | Some("arrow") | Some("feather") => {
+            crate::columnar_loader::columnar_row_count(path, sheet_index)
+        }
+        Some("db") | Some("sqlite") | Some("sqlite3") => {
+            Ok(crate::sqlite_loader::sqlite_row_count(path, sheet_index)?)
+        }
+        _ => {
+            let mut count = 0;
+            for row in TableReader::open_tracked(path, sheet_index, options, progress.clone())? {
+                row?;
+                count += 1;
+            }
+            Ok(count)
+        }
+    }
+}
+
+/// Counts the records after the header of a plain or compressed CSV file
+/// without parsing fields: line breaks inside quoted fields are skipped
+/// and blank lines are not counted, as the CSV reader does.
+fn count_csv_rows(path: &Path, progress: &Progress) -> Result<usize, String> {
+    let (header_line, _) = read_header(|| open_csv_source(path))?;
+    let mut source = open_tracked_csv_source(path, progress)?;
+
+    let mut scanner = RecordScanner::default();
+    let mut buffer = vec![0; SCAN_BUFFER];
+    loop {
+        let read = source.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        let before = scanner.records;
+        scanner.scan(&buffer[..read]);
+        progress.add_rows(scanner.records - before);
+    }
+    Ok(scanner.finish().saturating_sub(header_line + 1))
+}
+
+/// Where the scanner is within a record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    FieldStart,
+    Unquoted,
+    Quoted,
+    /// After a quote inside a quoted field: the field ends, or a second
+    /// quote makes it a literal one.
+    QuoteInQuoted,
+}
+
+/// Counts CSV records across buffers, following quoting the way the `csv`
+/// crate does: a quote only opens a field at its start.
+#[derive(Debug, Default)]
+struct RecordScanner {
+    state: ScanState,
+    /// The current line has something other than line breaks.
+    content: bool,
+    records: usize,
+}
+
+impl RecordScanner {
+    fn scan(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            let line_break = byte == b'\n' || byte == b'\r';
+            self.state = match (self.state, byte) {
+                (ScanState::Quoted, b'"') => ScanState::QuoteInQuoted,
+                (ScanState::Quoted, _) => ScanState::Quoted,
+                (ScanState::FieldStart, b'"') | (ScanState::QuoteInQuoted, b'"') => {
+                    ScanState::Quoted
+                }
+                (_, b',') => ScanState::FieldStart,
+                (_, _) if line_break => {
+                    if self.content {
+                        self.records += 1;
+                    }
+                    self.content = false;
+                    ScanState::FieldStart
+                }
+                (_, _) => ScanState::Unquoted,
+            };
+            if !line_break || self.state == ScanState::Quoted {
+                self.content = true;
+            }
+        }
+    }
+
+    /// Total records, including a last one without a line break.
+    fn finish(self) -> usize {
+        self.records + usize::from(self.content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_loader::csv_reader;
+
+    /// Counts the records of `text` fed in chunks of `chunk` bytes, so that
+    /// quotes and line breaks fall on buffer boundaries.
+    fn count(text: &str, chunk: usize) -> usize {
+        let mut scanner = RecordScanner::default();
+        for bytes in text.as_bytes().chunks(chunk) {
+            scanner.scan(bytes);
+        }
+        scanner.finish()
+    }
+
+    fn assert_count(text: &str, expected: usize) {
+        assert_eq!(
+            csv_reader(text.as_bytes()).records().count(),
+            expected,
+            "{text:?}"
+        );
+        for chunk in 1..=text.len().max(1) {
+            assert_eq!(
+                count(text, chunk),
+                expected,
+                "{text:?} in chunks of {chunk}"
+            );
+        }
+    }
+
+    #[test]
+    fn line_breaks_in_quotes() {
+        assert_count("a,b\n\"one\ntwo\",1\n2,3\n", 3);
+        assert_count("a,b\n\"one\r\n\r\ntwo\",1\n", 2);
+        assert_count("a,b\n1,\"\n\"\n", 2);
+    }
+
+    #[test]
+    fn crlf_and_blank_lines() {
+        assert_count("a,b\r\n1,2\r\n3,4\r\n", 3);
+        assert_count("a,b\r\n\r\n1,2\r\n\n\n3,4\r\n\r\n", 3);
+        assert_count("a,b\r1,2\r", 2);
+    }
+
+    #[test]
+    fn last_record_without_line_break() {
+        assert_count("a,b\n1,2", 2);
+        assert_count("a,b\n\"1\n2\"", 2);
+        assert_count("", 0);
+        assert_count("\n\r\n", 0);
+    }
+
+    #[test]
+    fn doubled_quotes() {
+        assert_count("a,b\n\"say \"\"hi\"\"\",1\n2,3\n", 3);
+        assert_count("a,b\n\"\"\"\n\",1\n2,3\n", 3);
+        assert_count("a,b\n\"\"\n\"\"\n", 3);
+    }
+
+    #[test]
+    fn quotes_inside_unquoted_fields() {
+        assert_count("a,b\n5\" pipe,1\n2,3\n", 3);
+        assert_count("a,b\nx,y\"\nz,1\n", 3);
+    }
+}
@@ -0,0 +1,971 @@
+use crate::csv_loader::{csv_reader, read_header};
+use crate::data_loader::file_extension;
+use crate::edits::EditOverlay;
+use calamine::{open_workbook_auto, Reader};
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Returns `true` if edits to `path` can be written back with
+/// [`save_sheet`]: plain CSV files and XLSX workbooks.
+pub fn can_save(path: &Path) -> bool {
+    let compressed = crate::compressed::Compression::from_path(path).is_some();
+    !compressed && matches!(file_extension(path).as_deref(), Some("csv") | Some("xlsx"))
+}
+
+/// Parts of a workbook that refer to the cells of a sheet by row number.
+/// Saving keeps them as they are, so once rows are inserted or deleted they
+/// would point at the wrong rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RowReference {
+    Formulas,
+    MergedCells,
+    ConditionalFormats,
+    DataValidations,
+    Hyperlinks,
+    Tables,
+    DefinedNames,
+}
+
+impl RowReference {
+    pub fn label(self) -> &'static str {
+        match self {
+            RowReference::Formulas => "formulas",
+            RowReference::MergedCells => "merged cells",
+            RowReference::ConditionalFormats => "conditional formats",
+            RowReference::DataValidations => "data validations",
+            RowReference::Hyperlinks => "hyperlinks",
+            RowReference::Tables => "tables",
+            RowReference::DefinedNames => "defined names",
+        }
+    }
+}
+
+/// Lists what refers to rows of sheet `sheet_index` of an XLSX workbook by
+/// number: formulas, merged ranges, conditional formats, data validations,
+/// hyperlinks and tables of the sheet, formulas of other sheets naming it,
+/// and defined names. Rows can only be inserted or deleted in a sheet
+/// without any. Other formats have none.
+pub fn row_references(path: &Path, sheet_index: usize) -> Result<Vec<RowReference>, String> {
+    if file_extension(path).as_deref() != Some("xlsx") {
+        return Ok(Vec::new());
+    }
+    let workbook =
+        open_workbook_auto(path).map_err(|e| format!("Failed to open Excel file: {}", e))?;
+    let sheet_name = pick_sheet(&workbook.sheet_names(), sheet_index)?;
+    drop(workbook);
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Failed to open workbook: {}", e))?;
+    let sheet_part = worksheet_part(&mut archive, &sheet_name)?;
+
+    let mut found = BTreeSet::new();
+    let sheet = read_entry(&mut archive, &sheet_part)?;
+    let mut reader = quick_xml::Reader::from_reader(sheet.as_slice());
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) => {
+                let reference = match e.local_name().as_ref() {
+                    b"f" => RowReference::Formulas,
+                    b"mergeCell" => RowReference::MergedCells,
+                    b"conditionalFormatting" => RowReference::ConditionalFormats,
+                    b"dataValidation" => RowReference::DataValidations,
+                    b"hyperlink" => RowReference::Hyperlinks,
+                    b"tablePart" => RowReference::Tables,
+                    _ => continue,
+                };
+                found.insert(reference);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let workbook = read_entry(&mut archive, WORKBOOK)?;
+    if find_element(&workbook, b"definedName", |_| Some(()))?.is_some() {
+        found.insert(RowReference::DefinedNames);
+    }
+
+    let others: Vec<String> = archive
+        .file_names()
+        .flatten()
+        .filter(|name| name.starts_with("xl/worksheets/") && name.ends_with(".xml"))
+        .filter(|name| *name != sheet_part)
+        .map(|name| name.into_owned())
+        .collect();
+    for name in others {
+        if !found.contains(&RowReference::Formulas)
+            && formula_mentions(&read_entry(&mut archive, &name)?, &sheet_name)?
+        {
+            found.insert(RowReference::Formulas);
+        }
+    }
+    Ok(found.into_iter().collect())
+}
+
+/// `true` if a formula of the worksheet `xml` contains `sheet_name`.
+fn formula_mentions(xml: &[u8], sheet_name: &str) -> Result<bool, String> {
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    let mut in_formula = false;
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) if e.local_name().as_ref() == b"f" => in_formula = true,
+            Event::End(e) if e.local_name().as_ref() == b"f" => in_formula = false,
+            Event::Text(text) if in_formula => {
+                let text = text.unescape().map_err(|e| e.to_string())?;
+                if text.contains(sheet_name) {
+                    return Ok(true);
+                }
+            }
+            Event::Eof => return Ok(false),
+            _ => {}
+        }
+    }
+}
+
+/// Writes `edits` to sheet `sheet_index` of `path`, in place.
+///
+/// Only what was edited is rewritten. In a CSV file the preamble, the
+/// header and every untouched row are copied byte for byte, so quoting,
+/// line endings and a byte order mark survive; edited and inserted rows are
+/// written with the file's line ending. In an XLSX workbook the other sheets
+/// and parts are copied as they are, and edited cells keep their style.
+///
+/// Rows are only inserted into or deleted from an XLSX sheet without
+/// [`row_references`], which would be left pointing at the wrong rows.
+///
+/// The new file is written next to the original and then renamed over it,
+/// so a failed save leaves the original untouched.
+pub fn save_sheet(path: &Path, sheet_index: usize, edits: &EditOverlay) -> Result<(), String> {
+    if !can_save(path) {
+        return Err("Only CSV and XLSX files can be saved; use Export CSV instead.".to_string());
+    }
+
+    let target = saving_path(path);
+    let result = match file_extension(path).as_deref() {
+        Some("xlsx") => save_xlsx(path, sheet_index, edits, &target),
+        _ => save_csv(path, edits, &target),
+    };
+    match result {
+        Ok(()) => std::fs::rename(&target, path)
+            .map_err(|e| format!("Failed to replace {}: {}", path.display(), e)),
+        Err(e) => {
+            let _ = std::fs::remove_file(&target);
+            Err(e)
+        }
+    }
+}
+
+fn saving_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.saving", name))
+}
+
+fn save_csv(path: &Path, edits: &EditOverlay, target: &Path) -> Result<(), String> {
+    let open = || File::open(path).map_err(|e| e.to_string());
+    let (header_line, _) = read_header(open)?;
+    let file_len = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+
+    let mut rdr = csv_reader(BufReader::new(open()?));
+    let mut raw = CsvCopy {
+        raw: BufReader::new(open()?),
+        out: BufWriter::new(File::create(target).map_err(|e| e.to_string())?),
+        copied: 0,
+        ends_with_newline: true,
+        line_ending: line_ending(path)?,
+    };
+
+    let mut record = csv::ByteRecord::new();
+    // Record whose bytes are replaced once the start of the next one is known
+    let mut pending: Option<(usize, u64, csv::ByteRecord)> = None;
+    let mut index = 0;
+    loop {
+        let more = rdr
+            .read_byte_record(&mut record)
+            .map_err(|e| e.to_string())?;
+        let start = match record.position() {
+            Some(position) if more => position.byte(),
+            _ => file_len,
+        };
+        if let Some((row, row_start, fields)) = pending.take() {
+            raw.replace(row_start, start, edits.edit_row(row, decode(&fields)))?;
+        }
+        if !more {
+            break;
+        }
+        if index > header_line {
+            let row = index - header_line - 1;
+            let mut inserted = edits.inserted_before(row).peekable();
+            let replaced = edits.is_deleted(row) || edits.has_cell_edits(row);
+            if inserted.peek().is_some() || replaced {
+                let start = raw.copy_to_record(start)?;
+                for cells in inserted {
+                    raw.write_row(cells)?;
+                }
+                if replaced {
+                    pending = Some((row, start, record.clone()));
+                }
+            }
+        }
+        index += 1;
+    }
+
+    raw.copy_to(file_len)?;
+    let rows = index.saturating_sub(header_line + 1);
+    for cells in edits.inserted_from(rows) {
+        raw.write_row(cells)?;
+    }
+    raw.out.flush().map_err(|e| e.to_string())
+}
+
+/// The line ending of the first line, used for inserted rows.
+fn line_ending(path: &Path) -> Result<Vec<u8>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut line = Vec::new();
+    BufReader::new(file)
+        .take(1 << 20)
+        .read_until(b'\n', &mut line)
+        .map_err(|e| e.to_string())?;
+    Ok(if line.ends_with(b"\r\n") {
+        b"\r\n".to_vec()
+    } else {
+        b"\n".to_vec()
+    })
+}
+
+fn decode(record: &csv::ByteRecord) -> Vec<String> {
+    record
+        .iter()
+        .map(|field| String::from_utf8_lossy(field).into_owned())
+        .collect()
+}
+
+/// Copies a CSV file to the output, replacing byte ranges on the way.
+struct CsvCopy {
+    raw: BufReader<File>,
+    out: BufWriter<File>,
+    /// Bytes of the input consumed so far.
+    copied: u64,
+    ends_with_newline: bool,
+    line_ending: Vec<u8>,
+}
+
+impl CsvCopy {
+    fn copy_to(&mut self, offset: u64) -> Result<(), String> {
+        let mut buf = [0u8; 64 * 1024];
+        while self.copied < offset {
+            let want = (offset - self.copied).min(buf.len() as u64) as usize;
+            let n = self.raw.read(&mut buf[..want]).map_err(|e| e.to_string())?;
+            if n == 0 {
+                break;
+            }
+            self.copied += n as u64;
+            self.write(&buf[..n])?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        if let Some(&last) = bytes.last() {
+            self.ends_with_newline = last == b'\n' || last == b'\r';
+        }
+        self.out.write_all(bytes).map_err(|e| e.to_string())
+    }
+
+    /// Copies up to a record that csv reports at `offset`, including the
+    /// line break in front of it, and returns where the record really starts.
+    /// (With `\r\n` line endings csv may report the `\n`.)
+    fn copy_to_record(&mut self, offset: u64) -> Result<u64, String> {
+        self.copy_to(offset)?;
+        let line_break = self.take_line_breaks()?;
+        self.write(&line_break)?;
+        Ok(self.copied)
+    }
+
+    /// Consumes the line break bytes at the read position and returns them.
+    fn take_line_breaks(&mut self) -> Result<Vec<u8>, String> {
+        let mut taken = Vec::new();
+        loop {
+            let buf = self.raw.fill_buf().map_err(|e| e.to_string())?;
+            let n = buf
+                .iter()
+                .take_while(|&&b| b == b'\n' || b == b'\r')
+                .count();
+            if n == 0 {
+                return Ok(taken);
+            }
+            taken.extend_from_slice(&buf[..n]);
+            self.raw.consume(n);
+            self.copied += n as u64;
+        }
+    }
+
+    /// Replaces the record spanning `start..end` with `row`, or drops it,
+    /// with its line break, if `row` is `None`. Blank lines after an edited
+    /// record are kept.
+    fn replace(&mut self, start: u64, end: u64, row: Option<Vec<String>>) -> Result<(), String> {
+        self.copy_to(start)?;
+        let mut span = Vec::new();
+        (&mut self.raw)
+            .take(end.saturating_sub(self.copied))
+            .read_to_end(&mut span)
+            .map_err(|e| e.to_string())?;
+        self.copied = self.copied.max(end);
+        span.extend(self.take_line_breaks()?);
+
+        if let Some(row) = row {
+            let body = span
+                .iter()
+                .rposition(|&b| b != b'\n' && b != b'\r')
+                .map_or(0, |i| i + 1);
+            let mut bytes = serialize(&row)?;
+            bytes.extend_from_slice(&span[body..]);
+            self.write(&bytes)?;
+        }
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<(), String> {
+        let mut bytes = Vec::new();
+        if !self.ends_with_newline {
+            bytes.extend_from_slice(&self.line_ending);
+        }
+        bytes.extend(serialize(row)?);
+        bytes.extend_from_slice(&self.line_ending);
+        self.write(&bytes)
+    }
+}
+
+/// One CSV record without a line ending.
+fn serialize(row: &[String]) -> Result<Vec<u8>, String> {
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .terminator(csv::Terminator::Any(b'\n'))
+        .from_writer(Vec::new());
+    writer.write_record(row).map_err(|e| e.to_string())?;
+    let mut bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    bytes.pop();
+    Ok(bytes)
+}
+
+/// Where the table of a worksheet sits, as calamine reads it: the sheet row
+/// and column of the header's first cell (1-based row, 0-based column) and
+/// the number of data rows below it.
+#[derive(Debug, Clone, Copy)]
+struct SheetLayout {
+    header_row: u32,
+    first_column: u32,
+    rows: usize,
+}
+
+fn save_xlsx(
+    path: &Path,
+    sheet_index: usize,
+    edits: &EditOverlay,
+    target: &Path,
+) -> Result<(), String> {
+    let mut workbook =
+        open_workbook_auto(path).map_err(|e| format!("Failed to open Excel file: {}", e))?;
+    let sheet_name = pick_sheet(&workbook.sheet_names(), sheet_index)?;
+    let range = workbook
+        .worksheet_range(&sheet_name)
+        .map_err(|e| format!("Failed to read worksheet '{}': {}", sheet_name, e))?;
+    let (start_row, first_column) = range.start().unwrap_or((0, 0));
+    let layout = SheetLayout {
+        header_row: start_row + 1,
+        first_column,
+        rows: range.height().saturating_sub(1),
+    };
+    drop(workbook);
+
+    if edits.has_row_changes() {
+        let references = row_references(path, sheet_index)?;
+        if !references.is_empty() {
+            let labels: Vec<_> = references.iter().map(|r| r.label()).collect();
+            return Err(format!(
+                "Rows cannot be inserted or deleted in a sheet with {}; their row numbers \
+                 would no longer match",
+                labels.join(", ")
+            ));
+        }
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Failed to open workbook: {}", e))?;
+    let sheet_part = worksheet_part(&mut archive, &sheet_name)?;
+    // Edited formula cells become plain values, which a stale calculation
+    // chain would trip over; Excel rebuilds it when it is missing
+    let drop_calc_chain = archive.index_for_name(CALC_CHAIN).is_some();
+    let calc_chain_ref = regex::Regex::new(r"<(Override|Relationship)\b[^>]*calcChain[^>]*/>")
+        .map_err(|e| e.to_string())?;
+
+    let out = BufWriter::new(File::create(target).map_err(|e| e.to_string())?);
+    let mut zip = zip::ZipWriter::new(out);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for index in 0..archive.len() {
+        let name = archive
+            .by_index_raw(index)
+            .map_err(|e| e.to_string())?
+            .name()
+            .map_err(|e| e.to_string())?
+            .into_owned();
+        let contents = if name == sheet_part {
+            rewrite_sheet(&read_entry(&mut archive, &name)?, layout, edits)?
+        } else if drop_calc_chain && name == CALC_CHAIN {
+            continue;
+        } else if drop_calc_chain && (name == CONTENT_TYPES || name == WORKBOOK_RELS) {
+            let xml = String::from_utf8_lossy(&read_entry(&mut archive, &name)?).into_owned();
+            calc_chain_ref
+                .replace_all(&xml, "")
+                .into_owned()
+                .into_bytes()
+        } else {
+            let entry = archive.by_index_raw(index).map_err(|e| e.to_string())?;
+            zip.raw_copy_file(entry).map_err(|e| e.to_string())?;
+            continue;
+        };
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&contents).map_err(|e| e.to_string())?;
+    }
+    zip.finish()
+        .map_err(|e| e.to_string())?
+        .flush()
+        .map_err(|e| e.to_string())
+}
+
+const WORKBOOK: &str = "xl/workbook.xml";
+const WORKBOOK_RELS: &str = "xl/_rels/workbook.xml.rels";
+const CONTENT_TYPES: &str = "[Content_Types].xml";
+const CALC_CHAIN: &str = "xl/calcChain.xml";
+
+/// The name of sheet `sheet_index`, or of the first sheet if there is no
+/// such sheet, as the loaders do.
+fn pick_sheet(sheet_names: &[String], sheet_index: usize) -> Result<String, String> {
+    sheet_names
+        .get(sheet_index)
+        .or(sheet_names.first())
+        .cloned()
+        .ok_or_else(|| "No worksheets found in the file".to_string())
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| format!("Failed to read '{}' from workbook: {}", name, e))?;
+    let mut contents = Vec::new();
+    entry
+        .read_to_end(&mut contents)
+        .map_err(|e| e.to_string())?;
+    Ok(contents)
+}
+
+/// Finds the part holding worksheet `sheet_name`, e.g.
+/// `xl/worksheets/sheet2.xml`, through the workbook relationships.
+fn worksheet_part(archive: &mut zip::ZipArchive<File>, sheet_name: &str) -> Result<String, String> {
+    let workbook = read_entry(archive, WORKBOOK)?;
+    let relation = find_element(&workbook, b"sheet", |attrs| {
+        (attribute(attrs, b"name").as_deref() == Some(sheet_name))
+            .then(|| attribute(attrs, b"id"))
+            .flatten()
+    })?
+    .ok_or_else(|| format!("Worksheet '{}' not found in the workbook", sheet_name))?;
+
+    let rels = read_entry(archive, WORKBOOK_RELS)?;
+    let target = find_element(&rels, b"Relationship", |attrs| {
+        (attribute(attrs, b"Id").as_deref() == Some(relation.as_str()))
+            .then(|| attribute(attrs, b"Target"))
+            .flatten()
+    })?
+    .ok_or_else(|| format!("Worksheet '{}' has no part in the workbook", sheet_name))?;
+
+    Ok(match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("xl/{}", target),
+    })
+}
+
+/// Returns the first non-`None` result of `check` over the `local_name`
+/// elements of `xml`.
+fn find_element<T>(
+    xml: &[u8],
+    local_name: &[u8],
+    check: impl Fn(&BytesStart) -> Option<T>,
+) -> Result<Option<T>, String> {
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == local_name => {
+                if let Some(found) = check(&e) {
+                    return Ok(Some(found));
+                }
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Value of the attribute with local name `name`, ignoring its prefix.
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name)
+        .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()))
+}
+
+/// A `<row>` of a worksheet.
+struct XmlRow {
+    element: BytesStart<'static>,
+    cells: Vec<XmlCell>,
+}
+
+/// A `<c>` of a row: its 0-based column and its events, starting with the
+/// `<c>` element itself.
+struct XmlCell {
+    column: u32,
+    events: Vec<Event<'static>>,
+}
+
+impl XmlRow {
+    fn new() -> Self {
+        Self {
+            element: BytesStart::new("row"),
+            cells: Vec::new(),
+        }
+    }
+
+    /// Sets the cell in sheet column `column`, keeping its style. An empty
+    /// value removes the cell unless it is styled.
+    fn set(&mut self, column: u32, value: &str) {
+        let position = self.cells.partition_point(|c| c.column < column);
+        let existing = self.cells.get(position).filter(|c| c.column == column);
+        let style = existing.and_then(|cell| match &cell.events[0] {
+            Event::Start(e) | Event::Empty(e) => attribute(e, b"s"),
+            _ => None,
+        });
+        let cell = new_cell(column, style, value);
+        match (existing.is_some(), cell) {
+            (true, Some(cell)) => self.cells[position] = cell,
+            (true, None) => {
+                self.cells.remove(position);
+            }
+            (false, Some(cell)) => self.cells.insert(position, cell),
+            (false, None) => {}
+        }
+    }
+
+    fn write(&self, number: u32, writer: &mut quick_xml::Writer<Vec<u8>>) -> Result<(), String> {
+        let element = with_reference(&self.element, number.to_string());
+        if self.cells.is_empty() {
+            return write_event(writer, Event::Empty(element));
+        }
+        write_event(writer, Event::Start(element))?;
+        for cell in &self.cells {
+            let reference = format!("{}{}", column_name(cell.column), number);
+            for (index, event) in cell.events.iter().enumerate() {
+                let event = match event {
+                    Event::Start(e) if index == 0 => {
+                        Event::Start(with_reference(e, reference.clone()))
+                    }
+                    Event::Empty(e) if index == 0 => {
+                        Event::Empty(with_reference(e, reference.clone()))
+                    }
+                    other => other.clone(),
+                };
+                write_event(writer, event)?;
+            }
+        }
+        write_event(writer, Event::End(BytesEnd::new("row")))
+    }
+}
+
+/// A `<c>` holding `value`: a number if it reads as one, otherwise an
+/// inline string. `None` for an empty unstyled cell.
+fn new_cell(column: u32, style: Option<String>, value: &str) -> Option<XmlCell> {
+    let mut element = BytesStart::new("c");
+    if let Some(style) = &style {
+        element.push_attribute(("s", style.as_str()));
+    }
+    if value.is_empty() {
+        return style.map(|_| XmlCell {
+            column,
+            events: vec![Event::Empty(element)],
+        });
+    }
+
+    let mut events = Vec::new();
+    if is_number(value) {
+        events.push(Event::Start(element));
+        events.push(Event::Start(BytesStart::new("v")));
+        events.push(Event::Text(BytesText::new(value).into_owned()));
+        events.push(Event::End(BytesEnd::new("v")));
+    } else {
+        element.push_attribute(("t", "inlineStr"));
+        events.push(Event::Start(element));
+        events.push(Event::Start(BytesStart::new("is")));
+        events.push(Event::Start(
+            BytesStart::new("t").with_attributes([("xml:space", "preserve")]),
+        ));
+        events.push(Event::Text(BytesText::new(value).into_owned()));
+        events.push(Event::End(BytesEnd::new("t")));
+        events.push(Event::End(BytesEnd::new("is")));
+    }
+    events.push(Event::End(BytesEnd::new("c")));
+    Some(XmlCell { column, events })
+}
+
+/// Numbers are stored as numbers, but not IDs with leading zeros such as
+/// `007`, which Excel would show as `7`.
+fn is_number(value: &str) -> bool {
+    let digits = value.trim_start_matches('-');
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    value.trim() == value && !leading_zero && value.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+/// `element` with its `r` attribute set to `reference`. The `spans` hint of
+/// a row is dropped, as edits may have changed it.
+fn with_reference(element: &BytesStart, reference: String) -> BytesStart<'static> {
+    let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+    let mut updated = BytesStart::new(name);
+    updated.push_attribute(("r", reference.as_str()));
+    for attr in element.attributes().flatten() {
+        if attr.key.as_ref() != b"r" && attr.key.as_ref() != b"spans" {
+            updated.push_attribute(attr);
+        }
+    }
+    updated.into_owned()
+}
+
+fn write_event(writer: &mut quick_xml::Writer<Vec<u8>>, event: Event) -> Result<(), String> {
+    writer.write_event(event).map_err(|e| e.to_string())
+}
+
+/// Rewrites the `<sheetData>` of a worksheet with `edits` applied. Rows are
+/// renumbered around inserted and deleted rows; everything outside
+/// `<sheetData>` is kept as it is, which is why [`save_sheet`] refuses row
+/// changes in sheets with [`row_references`].
+fn rewrite_sheet(xml: &[u8], layout: SheetLayout, edits: &EditOverlay) -> Result<Vec<u8>, String> {
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    let mut rows: BTreeMap<u32, XmlRow> = BTreeMap::new();
+    let mut current: Option<(u32, XmlRow)> = None;
+    let mut cell: Option<XmlCell> = None;
+    let mut in_data = false;
+
+    loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?.into_owned();
+        let name = match &event {
+            Event::Start(e) | Event::Empty(e) => e.local_name().as_ref().to_vec(),
+            Event::End(e) => e.local_name().as_ref().to_vec(),
+            _ => Vec::new(),
+        };
+        match event {
+            Event::Eof => break,
+            Event::Start(e) if name == b"sheetData" => {
+                write_event(&mut writer, Event::Start(e))?;
+                in_data = true;
+            }
+            Event::Empty(e) if name == b"sheetData" => {
+                write_event(&mut writer, Event::Start(e))?;
+                write_rows(&mut writer, std::mem::take(&mut rows), layout, edits)?;
+                write_event(&mut writer, Event::End(BytesEnd::new("sheetData")))?;
+            }
+            Event::End(e) if name == b"sheetData" => {
+                write_rows(&mut writer, std::mem::take(&mut rows), layout, edits)?;
+                write_event(&mut writer, Event::End(e))?;
+                in_data = false;
+            }
+            event if !in_data => write_event(&mut writer, event)?,
+
+            Event::Start(e) if name == b"row" => {
+                let number = row_number(&e, &rows);
+                current = Some((
+                    number,
+                    XmlRow {
+                        element: e,
+                        cells: Vec::new(),
+                    },
+                ));
+            }
+            Event::Empty(e) if name == b"row" => {
+                let number = row_number(&e, &rows);
+                rows.insert(
+                    number,
+                    XmlRow {
+                        element: e,
+                        cells: Vec::new(),
+                    },
+                );
+            }
+            Event::End(_) if name == b"row" => {
+                if let Some((number, row)) = current.take() {
+                    rows.insert(number, row);
+                }
+            }
+            Event::Start(e) if name == b"c" => {
+                if let Some((_, row)) = &current {
+                    let column = cell_column(&e, row);
+                    cell = Some(XmlCell {
+                        column,
+                        events: vec![Event::Start(e)],
+                    });
+                }
+            }
+            Event::Empty(e) if name == b"c" => {
+                if let Some((_, row)) = current.as_mut() {
+                    let column = cell_column(&e, row);
+                    row.cells.push(XmlCell {
+                        column,
+                        events: vec![Event::Empty(e)],
+                    });
+                }
+            }
+            Event::End(e) if name == b"c" => {
+                if let (Some(mut done), Some((_, row))) = (cell.take(), current.as_mut()) {
+                    done.events.push(Event::End(e));
+                    row.cells.push(done);
+                }
+            }
+            event => {
+                // Contents of a cell; whitespace between rows and cells is dropped
+                if let Some(cell) = cell.as_mut() {
+                    cell.events.push(event);
+                }
+            }
+        }
+    }
+    Ok(writer.into_inner())
+}
+
+/// The `r` of a row, or the one after the previous row if it has none.
+fn row_number(element: &BytesStart, rows: &BTreeMap<u32, XmlRow>) -> u32 {
+    let previous = rows.keys().next_back().copied().unwrap_or(0);
+    attribute(element, b"r")
+        .and_then(|r| r.parse().ok())
+        .unwrap_or(previous + 1)
+}
+
+/// The column of a cell, from its reference or after the previous cell.
+fn cell_column(element: &BytesStart, row: &XmlRow) -> u32 {
+    let previous = row.cells.last().map_or(0, |c| c.column + 1);
+    attribute(element, b"r")
+        .and_then(|r| parse_column(&r))
+        .unwrap_or(previous)
+}
+
+/// Writes the rows of `<sheetData>`: rows above the data are kept, data
+/// rows get their edits, deleted rows are left out and inserted rows are
+/// added, with the rows below renumbered to match.
+fn write_rows(
+    writer: &mut quick_xml::Writer<Vec<u8>>,
+    mut rows: BTreeMap<u32, XmlRow>,
+    layout: SheetLayout,
+    edits: &EditOverlay,
+) -> Result<(), String> {
+    let first_data = layout.header_row + 1;
+    let after_data = first_data + layout.rows as u32;
+    let below = rows.split_off(&after_data);
+    let data = rows.split_off(&first_data);
+    for (&number, row) in &rows {
+        row.write(number, writer)?;
+    }
+
+    let mut data = data;
+    let mut next = first_data;
+    for index in 0..layout.rows {
+        for cells in edits.inserted_before(index) {
+            new_row(cells, layout).write(next, writer)?;
+            next += 1;
+        }
+        let existing = data.remove(&(first_data + index as u32));
+        if edits.is_deleted(index) {
+            continue;
+        }
+        let mut row = existing.unwrap_or_else(XmlRow::new);
+        for (column, value) in edits.cell_edits(index) {
+            row.set(layout.first_column + column as u32, value);
+        }
+        if !row.cells.is_empty() || row.element.attributes().count() > 1 {
+            row.write(next, writer)?;
+        }
+        next += 1;
+    }
+    for cells in edits.inserted_from(layout.rows) {
+        new_row(cells, layout).write(next, writer)?;
+        next += 1;
+    }
+
+    for (number, row) in below {
+        let number = (number + next).saturating_sub(after_data);
+        row.write(number, writer)?;
+    }
+    Ok(())
+}
+
+fn new_row(cells: &[String], layout: SheetLayout) -> XmlRow {
+    let mut row = XmlRow::new();
+    for (column, value) in cells.iter().enumerate() {
+        row.set(layout.first_column + column as u32, value);
+    }
+    row
+}
+
+/// `0` → `A`, `26` → `AA`.
+fn column_name(column: u32) -> String {
+    let mut name = Vec::new();
+    let mut n = column + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        name.push(b'A' + rem as u8);
+        n = (n - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// The 0-based column of a cell reference such as `AB12`.
+fn parse_column(reference: &str) -> Option<u32> {
+    let letters: Vec<u8> = reference
+        .bytes()
+        .take_while(u8::is_ascii_alphabetic)
+        .collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let mut column = 0u32;
+    for letter in letters {
+        column = column * 26 + (letter.to_ascii_uppercase() - b'A') as u32 + 1;
+    }
+    Some(column - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edits::{Edit, RowId, END};
+    use crate::test_support::{self, Scratch, TestSheet};
+
+    fn set_cell(edits: &mut EditOverlay, row: usize, column: usize, old: &str, new: &str) {
+        edits.record(Edit::SetCell {
+            row: RowId::Source(row),
+            column,
+            old: old.to_string(),
+            new: new.to_string(),
+        });
+    }
+
+    fn insert_row(edits: &mut EditOverlay, anchor: usize, cells: &[&str]) {
+        let row = edits.new_row_id();
+        edits.record(Edit::InsertRow {
+            row,
+            anchor,
+            position: 0,
+            cells: cells.iter().map(|c| c.to_string()).collect(),
+        });
+    }
+
+    fn delete_row(edits: &mut EditOverlay, row: usize, cells: &[&str]) {
+        edits.record(Edit::DeleteRow {
+            row: RowId::Source(row),
+            anchor: row,
+            position: 0,
+            cells: cells.iter().map(|c| c.to_string()).collect(),
+        });
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let scratch = Scratch::new("csv");
+        let path = scratch.path("lot.csv");
+        std::fs::write(
+            &path,
+            "\u{feff}a,b\r\n1,\"x,y\"\r\n2,3\r\n\"q\"\"uoted\",4\r\n",
+        )
+        .unwrap();
+
+        let mut edits = EditOverlay::default();
+        set_cell(&mut edits, 0, 0, "1", "10");
+        delete_row(&mut edits, 1, &["2", "3"]);
+        insert_row(&mut edits, END, &["5", "six, 6"]);
+        save_sheet(&path, 0, &edits).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "\u{feff}a,b\r\n10,\"x,y\"\r\n\"q\"\"uoted\",4\r\n5,\"six, 6\"\r\n"
+        );
+        assert!(!saving_path(&path).exists());
+    }
+
+    fn write_xlsx(path: &Path, rows: &[&[&str]], extra: &str) {
+        test_support::write_xlsx(
+            path,
+            &[TestSheet {
+                name: "Data",
+                rows,
+                extra,
+            }],
+        );
+    }
+
+    fn read_xlsx(path: &Path) -> Vec<Vec<String>> {
+        let mut workbook = open_workbook_auto(path).unwrap();
+        let range = workbook.worksheet_range("Data").unwrap();
+        range
+            .rows()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn xlsx_round_trip() {
+        let scratch = Scratch::new("xlsx");
+        let path = scratch.path("lot.xlsx");
+        write_xlsx(&path, &[&["name", "value"], &["a", "x"], &["b", "y"], &["c", "z"]], "");
+        assert_eq!(row_references(&path, 0), Ok(Vec::new()));
+
+        let mut edits = EditOverlay::default();
+        set_cell(&mut edits, 0, 1, "x", "42");
+        delete_row(&mut edits, 1, &["b", "y"]);
+        insert_row(&mut edits, 2, &["new", "row"]);
+        save_sheet(&path, 0, &edits).unwrap();
+
+        assert_eq!(
+            read_xlsx(&path),
+            vec![
+                vec!["name", "value"],
+                vec!["a", "42"],
+                vec!["new", "row"],
+                vec!["c", "z"],
+            ]
+        );
+    }
+
+    #[test]
+    fn xlsx_row_changes_refused_with_merged_cells() {
+        let scratch = Scratch::new("xlsx_merged");
+        let path = scratch.path("lot.xlsx");
+        let merges = "<mergeCells count=\"1\"><mergeCell ref=\"A3:B3\"/></mergeCells>";
+        write_xlsx(&path, &[&["name", "value"], &["a", "x"], &["b", "y"]], merges);
+        assert_eq!(row_references(&path, 0), Ok(vec![RowReference::MergedCells]));
+        let before = std::fs::read(&path).unwrap();
+
+        let mut edits = EditOverlay::default();
+        insert_row(&mut edits, 0, &["new", "row"]);
+        let error = save_sheet(&path, 0, &edits).unwrap_err();
+        assert!(error.contains("merged cells"), "{}", error);
+        assert_eq!(std::fs::read(&path).unwrap(), before);
+
+        // Cell edits keep every row number and are still saved
+        let mut edits = EditOverlay::default();
+        set_cell(&mut edits, 1, 0, "b", "B");
+        save_sheet(&path, 0, &edits).unwrap();
+        assert_eq!(read_xlsx(&path)[2], vec!["B", "y"]);
+        assert_eq!(row_references(&path, 0), Ok(vec![RowReference::MergedCells]));
+    }
+}
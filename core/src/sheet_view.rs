@@ -0,0 +1,180 @@
+use crate::data_loader::LoadOptions;
+use crate::edits::EditOverlay;
+use crate::export::write_csv_rows;
+use crate::expression::Expression;
+use crate::progress::Progress;
+use crate::table_reader::TableReader;
+use crate::values::SortOrder;
+use std::path::Path;
+
+/// A column whose cells are computed from the other cells of the row.
+#[derive(Debug, Clone)]
+pub struct ComputedColumn {
+    pub name: String,
+    /// The expression as the user typed it.
+    pub source: String,
+    expression: Expression,
+}
+
+/// How a sheet is presented: computed columns appended to the file's own
+/// columns, an optional row filter and an optional sort order.
+///
+/// The same view is applied page by page in the GUI and to the whole sheet
+/// by [`SheetView::export_sheet`], so an export matches what is on screen.
+#[derive(Debug, Clone, Default)]
+pub struct SheetView {
+    computed_columns: Vec<ComputedColumn>,
+    /// Filter source and its compiled expression.
+    filter: Option<(String, Expression)>,
+    pub sort: Option<SortOrder>,
+}
+
+impl SheetView {
+    pub fn computed_columns(&self) -> &[ComputedColumn] {
+        &self.computed_columns
+    }
+
+    pub fn filter_source(&self) -> Option<&str> {
+        self.filter.as_ref().map(|(source, _)| source.as_str())
+    }
+
+    /// `true` if the view changes the rows or columns of the sheet.
+    pub fn is_active(&self) -> bool {
+        !self.computed_columns.is_empty() || self.filter.is_some() || self.sort.is_some()
+    }
+
+    /// The file's headers followed by the computed column names.
+    pub fn headers(&self, base_headers: &[String]) -> Vec<String> {
+        base_headers
+            .iter()
+            .cloned()
+            .chain(self.computed_columns.iter().map(|c| c.name.clone()))
+            .collect()
+    }
+
+    /// Adds a computed column. Its expression may refer to the file's
+    /// columns and to computed columns added before it.
+    pub fn add_computed_column(
+        &mut self,
+        base_headers: &[String],
+        name: &str,
+        source: &str,
+    ) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("The column needs a name.".to_string());
+        }
+        let headers = self.headers(base_headers);
+        if headers.iter().any(|h| h.trim().eq_ignore_ascii_case(name)) {
+            return Err(format!("A column named '{}' already exists.", name));
+        }
+
+        let expression = Expression::compile(source, &headers)?;
+        self.computed_columns.push(ComputedColumn {
+            name: name.to_string(),
+            source: source.to_string(),
+            expression,
+        });
+        Ok(())
+    }
+
+    /// Removes computed column `index`, unless a later computed column or
+    /// the filter refers to it.
+    pub fn remove_computed_column(
+        &mut self,
+        base_headers: &[String],
+        index: usize,
+    ) -> Result<(), String> {
+        let mut view = self.clone();
+        let removed = view.computed_columns.remove(index);
+        view.recompile(base_headers)
+            .map_err(|e| format!("'{}' is still in use: {}", removed.name, e))?;
+        *self = view;
+        Ok(())
+    }
+
+    /// Sets the row filter; an empty source removes it.
+    pub fn set_filter(&mut self, base_headers: &[String], source: &str) -> Result<(), String> {
+        if source.trim().is_empty() {
+            self.filter = None;
+            return Ok(());
+        }
+        let expression = Expression::compile(source, &self.headers(base_headers))?;
+        self.filter = Some((source.to_string(), expression));
+        Ok(())
+    }
+
+    /// Compiles every expression again, e.g. after a column was removed,
+    /// so column references point at the right cells.
+    fn recompile(&mut self, base_headers: &[String]) -> Result<(), String> {
+        let mut headers = base_headers.to_vec();
+        for column in &mut self.computed_columns {
+            column.expression = Expression::compile(&column.source, &headers)?;
+            headers.push(column.name.clone());
+        }
+        if let Some((source, expression)) = &mut self.filter {
+            *expression = Expression::compile(source, &headers)?;
+        }
+        Ok(())
+    }
+
+    /// Appends the computed cells to a row read from the file. Short rows are
+    /// padded to `base_width` first, so computed cells line up with their
+    /// headers.
+    pub fn apply(&self, row: &mut Vec<String>, base_width: usize) {
+        if self.computed_columns.is_empty() {
+            return;
+        }
+        row.resize(base_width, String::new());
+        for column in &self.computed_columns {
+            let cell = column.expression.evaluate_cell(row);
+            row.push(cell);
+        }
+    }
+
+    /// `true` if the row passes the filter. Rows must have been through
+    /// [`SheetView::apply`] first.
+    pub fn matches(&self, row: &[String]) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|(_, expression)| expression.matches(row))
+    }
+
+    /// Writes the whole sheet, not only the loaded rows, to a CSV file with
+    /// `edits` and this view applied. Returns the number of rows written.
+    /// The rows read from the file are reported to `progress`.
+    ///
+    /// Without a sort order the sheet is streamed; sorting needs every
+    /// remaining row in memory.
+    pub fn export_sheet(
+        &self,
+        path: &Path,
+        sheet_index: usize,
+        options: &LoadOptions,
+        edits: &EditOverlay,
+        target: &Path,
+        progress: &Progress,
+    ) -> Result<usize, String> {
+        let reader = TableReader::open_tracked(path, sheet_index, options, progress.clone())?;
+        let base_headers = reader.headers().to_vec();
+        let headers = self.headers(&base_headers);
+        let base_width = base_headers.len();
+
+        let rows = edits.apply_to_rows(reader).filter_map(|row| match row {
+            Ok(mut row) => {
+                self.apply(&mut row, base_width);
+                self.matches(&row).then_some(Ok(row))
+            }
+            Err(e) => Some(Err(e)),
+        });
+
+        match self.sort {
+            None => write_csv_rows(target, &headers, rows),
+            Some(order) => {
+                let mut rows = rows.collect::<Result<Vec<_>, _>>()?;
+                rows.sort_by(|a, b| order.compare(a, b));
+                write_csv_rows(target, &headers, rows.into_iter().map(Ok))
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::data_loader::{ColumnType, DataLoadResult, DataPage};
+use crate::error::LoadError;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use std::path::{Path, PathBuf};
+
+/// Loads `num_rows` rows from table or view `sheet_index` of a SQLite
+/// database, starting at `start_row`.
+///
+/// The sheet list holds every user table followed by every view; views are
+/// marked with a `(view)` suffix. Rows are paged with `LIMIT`/`OFFSET` in
+/// `rowid` order for tables and in natural order for views.
+pub async fn load_sqlite_data(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+) -> DataLoadResult {
+    let result = tokio::task::spawn_blocking(move || {
+        load_sqlite_data_sync(path, start_row, num_rows, sheet_index)
+    })
+    .await;
+
+    result.map_err(|e| LoadError::Task(e.to_string()))?
+}
+
+/// Blocking variant of [`load_sqlite_data`].
+pub fn load_sqlite_data_sync(
+    path: PathBuf,
+    start_row: usize,
+    num_rows: usize,
+    sheet_index: usize,
+) -> DataLoadResult {
+    let conn = open_database(&path)?;
+    let objects = list_objects(&conn)?;
+    if objects.is_empty() {
+        return Err(LoadError::NoTables);
+    }
+
+    // Fall back to the first table if the index is invalid, like Excel sheets
+    let object = objects.get(sheet_index).unwrap_or(&objects[0]);
+    let read_error = |e: rusqlite::Error| object.read_error(e);
+    let order = if object.is_view { "" } else { " ORDER BY rowid" };
+    let sql = format!(
+        "SELECT * FROM {}{} LIMIT ?1 OFFSET ?2",
+        quote_identifier(&object.name),
+        order
+    );
+
+    let mut stmt = conn.prepare(&sql).or_else(|e| {
+        // WITHOUT ROWID tables have no rowid to order by
+        if object.is_view {
+            Err(e)
+        } else {
+            conn.prepare(&format!(
+                "SELECT * FROM {} LIMIT ?1 OFFSET ?2",
+                quote_identifier(&object.name)
+            ))
+        }
+    })
+    .map_err(read_error)?;
+
+    let (headers, column_types) = if start_row == 0 {
+        stmt.columns()
+            .iter()
+            .map(|column| {
+                let column_type = column.decl_type().map_or(ColumnType::Text, column_type);
+                (column.name().to_string(), column_type)
+            })
+            .unzip()
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    let column_count = stmt.column_count();
+    let mut rows = Vec::new();
+    let mut result_rows = stmt
+        .query((num_rows as i64, start_row as i64))
+        .map_err(read_error)?;
+    while let Some(row) = result_rows.next().map_err(read_error)? {
+        let mut values = Vec::with_capacity(column_count);
+        for index in 0..column_count {
+            values.push(cell_text(row.get_ref(index).map_err(read_error)?));
+        }
+        rows.push(values);
+    }
+
+    Ok(DataPage {
+        end_of_file: rows.len() < num_rows,
+        headers,
+        rows,
+        sheet_names: objects.iter().map(SqliteObject::display_name).collect(),
+        column_types,
+        ..DataPage::default()
+    })
+}
+
+/// Number of rows in table or view `sheet_index` of a SQLite database.
+pub fn sqlite_row_count(path: &Path, sheet_index: usize) -> Result<usize, LoadError> {
+    let conn = open_database(path)?;
+    let objects = list_objects(&conn)?;
+    let Some(object) = objects.get(sheet_index).or(objects.first()) else {
+        return Ok(0);
+    };
+    let sql = format!("SELECT COUNT(*) FROM {}", quote_identifier(&object.name));
+    conn.query_row(&sql, [], |row| row.get::<_, i64>(0))
+        .map(|count| count as usize)
+        .map_err(|e| object.read_error(e))
+}
+
+/// A table or view listed in the sheet selector.
+pub(crate) struct SqliteObject {
+    pub(crate) name: String,
+    pub(crate) is_view: bool,
+}
+
+impl SqliteObject {
+    fn read_error(&self, error: rusqlite::Error) -> LoadError {
+        LoadError::ReadTable {
+            table: self.name.clone(),
+            detail: error.to_string(),
+        }
+    }
+
+    fn display_name(&self) -> String {
+        if self.is_view {
+            format!("{} (view)", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+pub(crate) fn open_database(path: &Path) -> Result<Connection, LoadError> {
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| LoadError::OpenDatabase(e.to_string()))
+}
+
+pub(crate) fn list_objects(conn: &Connection) -> Result<Vec<SqliteObject>, LoadError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name, type FROM sqlite_master \
+             WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' \
+             ORDER BY type = 'view', name",
+        )
+        .map_err(|e| LoadError::OpenDatabase(e.to_string()))?;
+
+    let objects = stmt
+        .query_map([], |row| {
+            Ok(SqliteObject {
+                name: row.get(0)?,
+                is_view: row.get::<_, String>(1)? == "view",
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| LoadError::OpenDatabase(e.to_string()))?;
+
+    Ok(objects)
+}
+
+/// Maps a declared SQLite column type to a column type, following SQLite's
+/// own type-affinity rules where they apply.
+fn column_type(decl_type: &str) -> ColumnType {
+    let decl = decl_type.to_ascii_uppercase();
+    if decl.contains("INT") {
+        ColumnType::Integer
+    } else if decl.contains("CHAR") || decl.contains("CLOB") || decl.contains("TEXT") {
+        ColumnType::Text
+    } else if decl.contains("BOOL") {
+        ColumnType::Boolean
+    } else if decl.contains("DATETIME") || decl.contains("TIMESTAMP") {
+        ColumnType::DateTime
+    } else if decl.contains("DATE") {
+        ColumnType::Date
+    } else if decl.contains("REAL")
+        || decl.contains("FLOA")
+        || decl.contains("DOUB")
+        || decl.contains("NUMERIC")
+        || decl.contains("DECIMAL")
+    {
+        ColumnType::Float
+    } else {
+        ColumnType::Other
+    }
+}
+
+pub(crate) fn cell_text(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        // `{:?}` keeps the decimal point, so 495.0 is not shown as an integer
+        ValueRef::Real(f) => format!("{:?}", f),
+        ValueRef::Text(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+        ValueRef::Blob(bytes) => format!("<BLOB {} bytes>", bytes.len()),
+    }
+}
+
+/// Quotes an identifier for use in SQL, e.g. `my "table"` → `"my ""table"""`.
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
@@ -0,0 +1,53 @@
+use crate::table_reader::SheetSource;
+use crate::values::parse_number;
+
+/// Smallest and largest number in a column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl NumericRange {
+    /// Where `value` sits between `min` (0.0) and `max` (1.0), clamped.
+    pub fn position(&self, value: f64) -> f32 {
+        if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0) as f32
+        } else {
+            1.0
+        }
+    }
+
+    fn include(range: &mut Option<NumericRange>, value: f64) {
+        match range {
+            Some(range) => {
+                range.min = range.min.min(value);
+                range.max = range.max.max(value);
+            }
+            None => {
+                *range = Some(NumericRange {
+                    min: value,
+                    max: value,
+                })
+            }
+        }
+    }
+}
+
+/// Reads the whole sheet and returns the range of the numeric cells of each
+/// of `columns`, or `None` for a column without numbers.
+pub fn numeric_ranges(
+    source: &SheetSource,
+    columns: &[usize],
+) -> Result<Vec<Option<NumericRange>>, String> {
+    let mut ranges = vec![None; columns.len()];
+    for row in source.open()? {
+        let row = row?;
+        for (range, &column) in ranges.iter_mut().zip(columns) {
+            if let Some(value) = row.get(column).and_then(|cell| parse_number(cell)) {
+                NumericRange::include(range, value);
+            }
+        }
+    }
+    Ok(ranges)
+}
@@ -0,0 +1,233 @@
+use crate::csv_loader::{
+    csv_reader, open_csv_source, open_tracked_csv_source, read_header, CsvRows, Tolerance,
+};
+use crate::data_loader::{file_extension, load_data_sync, ColumnType, LoadOptions};
+use crate::progress::Progress;
+use crate::values::parse_number;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Rows fetched per page for formats that are read through the page loaders.
+const PAGE_SIZE: usize = 10_000;
+
+/// A sheet of a file, with the options it is read with.
+#[derive(Debug, Clone)]
+pub struct SheetSource {
+    pub path: PathBuf,
+    pub sheet_index: usize,
+    pub options: LoadOptions,
+}
+
+impl SheetSource {
+    pub fn open(&self) -> Result<TableReader, String> {
+        TableReader::open(&self.path, self.sheet_index, &self.options)
+    }
+
+    /// [`SheetSource::open`] reporting the rows and bytes read to `progress`.
+    pub fn open_tracked(&self, progress: &Progress) -> Result<TableReader, String> {
+        TableReader::open_tracked(&self.path, self.sheet_index, &self.options, progress.clone())
+    }
+
+    /// The file name of the source, for messages.
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Number of data rows; see [`crate::row_count::count_rows`].
+    pub fn count_rows(&self, progress: &Progress) -> Result<usize, String> {
+        crate::row_count::count_rows(&self.path, self.sheet_index, &self.options, progress)
+    }
+
+    /// Index of the first data row whose `column` holds `value`, ignoring
+    /// surrounding whitespace; numbers match by value, so `12` finds
+    /// `12.0`.
+    pub fn find_row(
+        &self,
+        column: usize,
+        value: &str,
+        progress: &Progress,
+    ) -> Result<Option<usize>, String> {
+        let value = value.trim();
+        let number = parse_number(value);
+        for (index, row) in self.open_tracked(progress)?.enumerate() {
+            let row = row?;
+            let Some(cell) = row.get(column).map(|cell| cell.trim()) else {
+                continue;
+            };
+            if cell == value || (number.is_some() && parse_number(cell) == number) {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Finds a column by name, exactly first and then ignoring case and
+/// surrounding whitespace, so `Wafer ID` matches `wafer id `.
+pub fn find_column(headers: &[String], name: &str) -> Option<usize> {
+    headers.iter().position(|h| h == name).or_else(|| {
+        let name = name.trim();
+        headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+    })
+}
+
+/// Reads every row of one sheet, front to back, without holding the whole
+/// sheet in memory where the format allows it.
+///
+/// CSV (plain or compressed) is streamed record by record. Other formats are
+/// read in pages through the regular loaders; Excel sheets are read in one
+/// go, because the workbook has to be parsed in full anyway.
+pub struct TableReader {
+    headers: Vec<String>,
+    column_types: Vec<ColumnType>,
+    sheet_names: Vec<String>,
+    source: Source,
+    /// Advanced by every row read; not shared with anyone unless the
+    /// reader was opened with [`TableReader::open_tracked`].
+    progress: Progress,
+}
+
+enum Source {
+    Csv(CsvRows<Box<dyn Read + Send>>),
+    Paged {
+        path: PathBuf,
+        sheet_index: usize,
+        options: LoadOptions,
+        page_size: usize,
+        next_row: usize,
+        buffer: VecDeque<Vec<String>>,
+        end_of_file: bool,
+    },
+}
+
+impl TableReader {
+    pub fn open(path: &Path, sheet_index: usize, options: &LoadOptions) -> Result<Self, String> {
+        Self::open_tracked(path, sheet_index, options, Progress::default())
+    }
+
+    /// [`TableReader::open`] adding the rows read, and for CSV the bytes
+    /// read, to `progress`.
+    pub fn open_tracked(
+        path: &Path,
+        sheet_index: usize,
+        options: &LoadOptions,
+        progress: Progress,
+    ) -> Result<Self, String> {
+        let extension = file_extension(path);
+        if extension.as_deref() == Some("csv") {
+            return Self::open_csv(path, options.tolerance, progress);
+        }
+
+        let first = load_data_sync(path.to_path_buf(), 0, 0, sheet_index, options)?;
+        let page_size = match extension.as_deref() {
+            Some("xlsx") | Some("xls") | Some("ods") => usize::MAX,
+            _ => PAGE_SIZE,
+        };
+
+        Ok(Self {
+            headers: first.headers,
+            column_types: first.column_types,
+            sheet_names: first.sheet_names,
+            source: Source::Paged {
+                path: path.to_path_buf(),
+                sheet_index,
+                options: options.clone(),
+                page_size,
+                next_row: 0,
+                buffer: VecDeque::new(),
+                end_of_file: false,
+            },
+            progress,
+        })
+    }
+
+    fn open_csv(path: &Path, tolerance: Tolerance, progress: Progress) -> Result<Self, String> {
+        let (header_line, headers) = read_header(|| open_csv_source(path))?;
+        let mut rdr = csv_reader(open_tracked_csv_source(path, &progress)?);
+        for _ in 0..=header_line {
+            if rdr.records().next().is_none() {
+                break;
+            }
+        }
+        let width = headers.len();
+
+        Ok(Self {
+            headers,
+            column_types: Vec::new(),
+            sheet_names: vec!["CSV".to_string()],
+            source: Source::Csv(CsvRows::new(rdr, tolerance, width)),
+            progress,
+        })
+    }
+
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Declared column types, empty for formats without a schema.
+    pub fn column_types(&self) -> &[ColumnType] {
+        &self.column_types
+    }
+
+    pub fn sheet_names(&self) -> &[String] {
+        &self.sheet_names
+    }
+}
+
+impl Iterator for TableReader {
+    type Item = Result<Vec<String>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = self.read_row();
+        if let Some(Ok(_)) = row {
+            self.progress.add_rows(1);
+        }
+        row
+    }
+}
+
+impl TableReader {
+    fn read_row(&mut self) -> Option<Result<Vec<String>, String>> {
+        match &mut self.source {
+            Source::Csv(rows) => {
+                let row = rows.next().map(|row| row.map_err(String::from));
+                // Issues are listed by the loaders and the scan, not here
+                rows.take_issues();
+                row
+            }
+            Source::Paged {
+                path,
+                sheet_index,
+                options,
+                page_size,
+                next_row,
+                buffer,
+                end_of_file,
+            } => {
+                if buffer.is_empty() && !*end_of_file {
+                    let page =
+                        load_data_sync(path.clone(), *next_row, *page_size, *sheet_index, options);
+                    match page {
+                        Ok(page) => {
+                            *next_row += page.rows.len();
+                            *end_of_file = page.end_of_file || page.rows.is_empty();
+                            buffer.extend(page.rows);
+                        }
+                        Err(e) => {
+                            *end_of_file = true;
+                            return Some(Err(e.into()));
+                        }
+                    }
+                }
+                buffer.pop_front().map(Ok)
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Scratch files for unit tests.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A scratch directory for one test, removed when dropped.
+pub struct Scratch(PathBuf);
+
+impl Scratch {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir()
+            .join(format!("rust_excel_reader_test_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A worksheet for [`write_xlsx`]: its name, its rows as inline strings and
+/// XML to put after `<sheetData>`, such as `<mergeCells>`.
+pub struct TestSheet<'a> {
+    pub name: &'a str,
+    pub rows: &'a [&'a [&'a str]],
+    pub extra: &'a str,
+}
+
+/// Writes a minimal XLSX workbook with `sheets`.
+pub fn write_xlsx(path: &Path, sheets: &[TestSheet]) {
+    let mut overrides = String::new();
+    let mut entries = String::new();
+    let mut relationships = String::new();
+    let mut parts = Vec::new();
+    for (index, sheet) in sheets.iter().enumerate() {
+        let number = index + 1;
+        overrides += &format!(
+            "<Override PartName=\"/xl/worksheets/sheet{}.xml\" \
+             ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>",
+            number
+        );
+        entries += &format!(
+            "<sheet name=\"{}\" sheetId=\"{}\" r:id=\"rId{}\"/>",
+            sheet.name, number, number
+        );
+        relationships += &format!(
+            "<Relationship Id=\"rId{}\" \
+             Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" \
+             Target=\"worksheets/sheet{}.xml\"/>",
+            number, number
+        );
+
+        let mut data = String::new();
+        for (r, row) in sheet.rows.iter().enumerate() {
+            data += &format!("<row r=\"{}\">", r + 1);
+            for (c, value) in row.iter().enumerate() {
+                data += &format!(
+                    "<c r=\"{}{}\" t=\"inlineStr\"><is><t>{}</t></is></c>",
+                    (b'A' + c as u8) as char,
+                    r + 1,
+                    value
+                );
+            }
+            data += "</row>";
+        }
+        parts.push((
+            format!("xl/worksheets/sheet{}.xml", number),
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+                 <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+                 <sheetData>{}</sheetData>{}</worksheet>",
+                data, sheet.extra
+            ),
+        ));
+    }
+
+    parts.push((
+        "[Content_Types].xml".to_string(),
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+             <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+             <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+             <Override PartName=\"/xl/workbook.xml\" \
+             ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+             {}</Types>",
+            overrides
+        ),
+    ));
+    parts.push((
+        "_rels/.rels".to_string(),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+         <Relationship Id=\"rId1\" \
+         Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" \
+         Target=\"xl/workbook.xml\"/></Relationships>"
+            .to_string(),
+    ));
+    parts.push((
+        "xl/workbook.xml".to_string(),
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+             xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+             <sheets>{}</sheets></workbook>",
+            entries
+        ),
+    ));
+    parts.push((
+        "xl/_rels/workbook.xml.rels".to_string(),
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+             {}</Relationships>",
+            relationships
+        ),
+    ));
+
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    for (name, contents) in parts {
+        zip.start_file(name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(contents.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
@@ -0,0 +1,173 @@
+use std::cmp::Ordering;
+
+/// Parses a cell as a number, ignoring surrounding whitespace. Returns
+/// `None` for blanks and text, including "inf" and "NaN".
+pub fn parse_number(cell: &str) -> Option<f64> {
+    let trimmed = cell.trim();
+    if !trimmed.bytes().any(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    trimmed.parse::<f64>().ok()
+}
+
+/// Orders two cells the way a user expects when sorting a column: numbers
+/// by value, before text; text case-insensitively with embedded numbers
+/// compared by value; blanks last.
+pub fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().is_empty(), b.trim().is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {}
+    }
+
+    match (parse_number(a), parse_number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => natural_cmp(&a.to_lowercase(), &b.to_lowercase()).then_with(|| a.cmp(b)),
+    }
+}
+
+/// Compares text with embedded numbers by value, so `W2` sorts before `W10`.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(x), Some(y)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let a_end = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+            let b_end = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+            let (a_digits, b_digits) = (
+                a[..a_end].trim_start_matches('0'),
+                b[..b_end].trim_start_matches('0'),
+            );
+            let ordering = a_digits
+                .len()
+                .cmp(&b_digits.len())
+                .then_with(|| a_digits.cmp(b_digits));
+            if ordering.is_ne() {
+                return ordering;
+            }
+            a = &a[a_end..];
+            b = &b[b_end..];
+        } else {
+            if x != y {
+                return x.cmp(&y);
+            }
+            a = &a[x.len_utf8()..];
+            b = &b[y.len_utf8()..];
+        }
+    }
+}
+
+/// Formats a computed number for display: integers without a fraction,
+/// other values with up to 6 decimals, very small ones in exponent form.
+pub fn format_number(value: f64) -> String {
+    if value.is_finite() && value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else if value.is_finite() && value.abs() < 1e-4 {
+        format!("{:e}", value)
+    } else if value.is_finite() {
+        let formatted = format!("{:.6}", value);
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Column and direction a table is sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortOrder {
+    pub column: usize,
+    pub ascending: bool,
+}
+
+impl SortOrder {
+    /// Orders two rows by the sort column with [`compare_cells`]. Blanks stay
+    /// last in both directions.
+    pub fn compare(&self, a: &[String], b: &[String]) -> Ordering {
+        let a = a.get(self.column).map(String::as_str).unwrap_or_default();
+        let b = b.get(self.column).map(String::as_str).unwrap_or_default();
+        let ordering = compare_cells(a, b);
+        if self.ascending || a.trim().is_empty() || b.trim().is_empty() {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+
+    /// The order after clicking `column`: ascending first, then descending.
+    pub fn toggled(current: Option<SortOrder>, column: usize) -> SortOrder {
+        let ascending = current.is_none_or(|order| order.column != column || !order.ascending);
+        SortOrder { column, ascending }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(parse_number(" 1.5 "), Some(1.5));
+        assert_eq!(parse_number("-2"), Some(-2.0));
+        assert_eq!(parse_number("1e3"), Some(1000.0));
+        assert_eq!(parse_number(".5"), Some(0.5));
+        for text in [
+            "", "  ", "abc", "inf", "-inf", "NaN", "1,000", "12 pcs", "1e",
+        ] {
+            assert_eq!(parse_number(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn orders_cells() {
+        let mut cells = vec![
+            "W10", "", "banana", "10", "W2", "Apple", "9.5", " ", "apple", "-1",
+        ];
+        cells.sort_by(|a, b| compare_cells(a, b));
+        assert_eq!(
+            cells,
+            ["-1", "9.5", "10", "Apple", "apple", "banana", "W2", "W10", "", " "]
+        );
+        assert_eq!(compare_cells("1.0", "1"), Ordering::Equal);
+        assert_eq!(compare_cells("W02", "W2"), Ordering::Less);
+        assert_eq!(compare_cells("lot 7b", "LOT 7A"), Ordering::Greater);
+    }
+
+    #[test]
+    fn sort_order_keeps_blanks_last() {
+        let rows: Vec<Vec<String>> = ["2", "", "10"]
+            .iter()
+            .map(|c| vec![c.to_string()])
+            .collect();
+        let descending = SortOrder {
+            column: 0,
+            ascending: false,
+        };
+        let mut sorted = rows.clone();
+        sorted.sort_by(|a, b| descending.compare(a, b));
+        assert_eq!(sorted, [["10"], ["2"], [""]]);
+        assert!(!SortOrder::toggled(Some(SortOrder::toggled(None, 0)), 0).ascending);
+        assert!(SortOrder::toggled(Some(descending), 1).ascending);
+    }
+
+    #[test]
+    fn formats_numbers() {
+        assert_eq!(format_number(3.0), "3");
+        assert_eq!(format_number(-0.5), "-0.5");
+        assert_eq!(format_number(1.0 / 3.0), "0.333333");
+        assert_eq!(format_number(2.1000004), "2.1");
+        assert_eq!(format_number(0.00001), "1e-5");
+        assert_eq!(format_number(0.0), "0");
+        assert_eq!(format_number(1e15), "1000000000000000");
+        assert_eq!(format_number(f64::NAN), "NaN");
+        assert_eq!(format_number(f64::NEG_INFINITY), "-inf");
+    }
+}
@@ -0,0 +1,421 @@
+# Messages of the interface in English. Every other catalog falls back to
+# this one for messages it lacks.
+
+## Toolbar
+
+open-file = 📁 Open File
+open-folder = 📂 Open Folder
+fixed-width-columns = 📐 Columns
+toggle-sql = 🗄 SQL
+toggle-compare = ⇄ Compare
+toggle-merge = 🔗 Merge
+toggle-pivot = 🧮 Pivot
+toggle-format = 🎨 Format
+toggle-inspector = 🔎 Inspector
+toggle-issues = ⚠ Issues ({ $count })
+toggle-quality = ✅ Quality
+toggle-quality-findings = ✅ Quality ({ $count })
+toggle-computed-columns = ƒx Columns
+sheet-label = 📊 Sheet:
+watch-label = 🔄 On change:
+watch-off = Off
+watch-reload = Reload
+watch-tail = Tail -f
+
+## File dialog filters
+
+filter-spreadsheet = Spreadsheet
+filter-columnar = Columnar
+filter-fixed-width = Fixed-width text
+filter-compressed = Compressed CSV
+filter-archive = Archive
+filter-font = Font
+
+## Preferences
+
+prefs-language = Language
+language-system = System language
+prefs-font = Font
+prefs-font-default = System default
+prefs-font-choose = Choose…
+prefs-font-size = Size
+prefs-cjk-font = CJK font: { $font }
+prefs-no-cjk-font = No CJK font found; Chinese text may not display
+prefs-theme = Theme
+theme-dark = Dark
+theme-light = Light
+theme-system = System
+theme-reset = Theme color
+prefs-high-contrast = High contrast
+prefs-row-stripes = Row stripes
+prefs-column-headers = Column headers
+prefs-accent = Accent
+
+## Archives
+
+archive-choose = Choose a file to open:
+archive-empty = The archive contains no supported files.
+
+## Welcome screen
+
+welcome-title = Excel & CSV Reader
+welcome-hint = Click 'Open File' to load your spreadsheet
+welcome-supports = Supports:
+
+## Tabs and export
+
+tab-file = 📄 File
+export-csv = 💾 Export CSV
+export-view-hint = Writes the whole sheet with computed columns, filter and sort applied
+saved = Saved
+saved-file = Saved { $path }
+saved-rows = Saved { $count ->
+    [one] 1 row
+   *[other] { $count } rows
+} to { $path }
+row-count = { $count ->
+    [one] 1 row
+   *[other] { $count } rows
+}
+
+## View bar
+
+filter-label = 🔍 Filter:
+filter-hint = e.g. bin != 1 and fwhm > 20
+filter-matches = { $shown } of { $loaded } loaded rows
+undo-hint = Undo (Ctrl+Z)
+redo-hint = Redo (Ctrl+Y)
+save = 💾 Save
+save-hint = Writes the edits back to the file
+save-edit-hint = Double-click a cell to edit it; right-click a row number to insert or delete rows
+save-unsupported-hint = Only CSV and XLSX files can be saved; use Export CSV to keep the edits
+save-tolerant-hint = Files read with skipped or repaired records cannot be saved; use Export CSV to keep the edits
+row-lock-references = Rows cannot be inserted or deleted in a sheet with { $parts }; their row numbers would no longer match
+row-lock-unknown = Rows cannot be inserted or deleted: { $detail }
+row-reference-formulas = formulas
+row-reference-merged-cells = merged cells
+row-reference-conditional-formats = conditional formats
+row-reference-data-validations = data validations
+row-reference-hyperlinks = hyperlinks
+row-reference-tables = tables
+row-reference-defined-names = defined names
+unsaved-edits = ● unsaved edits
+
+## Status
+
+loading-more = Loading more data...
+all-loaded = All data loaded
+footer-sheet = Sheet: { $sheet }
+footer-sheet-count = { $count } sheets total
+footer-rows-complete = { $count ->
+    [one] 1 row
+   *[other] { $count } rows
+} (complete)
+footer-rows-loading = { $count ->
+    [one] 1 row
+   *[other] { $count } rows
+} (loading...)
+footer-rows-of = { $loaded } of { $total ->
+    [one] 1 row
+   *[other] { $total } rows
+} loaded
+progress-counting = Counting rows
+progress-loading = Loading
+progress-exporting = Exporting
+progress-rows = { $label }: { $rows } rows read
+status-complete = ✅ Complete
+file-changed = ⚠ Changed on disk
+reload = Reload
+
+## Go to
+
+goto-button = ↧ Go to…
+goto-button-hint = Go to a row, a cell or a key value (Ctrl+G)
+goto-title = ↧ Go to
+goto-hint = 1234, C1234 or a key value
+goto-key-column = Key column:
+goto-always-find = Search the key column for numbers too
+goto-preview-row = Row { $row }
+goto-preview-cell = Row { $row }, column { $column }
+goto-preview-find = First row whose { $column } is this value
+goto-go = Go
+goto-searching = Searching the sheet...
+goto-not-found = No row found
+goto-filtered = Row { $row } is hidden by the filter
+goto-deleted = Row { $row } has been deleted
+goto-past-end = The sheet has no row { $row }
+window-notice = Showing rows from { $row } on; earlier rows are not loaded
+window-load-start = ⤒ Load from the first row
+
+## Row inspector
+
+inspector-title = 🔎 Row inspector
+inspector-no-row = Click a cell to inspect its row.
+inspector-search = Search columns and values…
+inspector-row = Row { $row }
+inspector-new-row = New row
+inspector-new = new
+inspector-pin = 📌 Pin
+inspector-pin-hint = Keep this row to compare it with the rows selected next
+inspector-unpin = Unpin
+inspector-copy-json = 📋 Copy as JSON
+inspector-copied = Copied
+inspector-column = Column
+inspector-matches = { $shown } of { $total } columns
+inspector-limits = Spec limits
+inspector-limits-hint = Set the spec limits of this column
+inspector-low = Lower
+inspector-high = Upper
+inspector-apply = Apply
+inspector-clear = Clear
+inspector-cancel = Cancel
+inspector-invalid-limit = Limits must be numbers; leave a side empty to keep it open
+inspector-limit-summary = Spec { $low } to { $high }
+inspector-below = Below the lower spec limit
+inspector-above = Above the upper spec limit
+
+## Cell viewer
+
+grid-view-cell = 📄 View cell
+grid-wrap-text = Wrap text
+grid-truncate-after = Cut short after
+grid-characters = characters
+viewer-title = 📄 { $column }, row { $row }
+viewer-title-new = 📄 { $column }, new row
+viewer-pretty = Format as { $format }
+viewer-size = { $chars } characters, { $lines ->
+    [one] 1 line
+   *[other] { $lines } lines
+}
+viewer-copy = 📋 Copy
+viewer-copied = Copied
+viewer-edit = ✏ Edit
+
+## Parse issues
+
+issues-title = ⚠ Parse issues
+issues-tolerance = Malformed records:
+issues-tolerance-locked = Save or undo the edits first; the row numbers change with this setting
+tolerance-strict = Stop at invalid text, keep ragged rows
+tolerance-skip = Skip them
+tolerance-repair = Repair them
+issues-mark-rows = Mark rows with issues in the table
+issues-scan = 🔍 Scan whole file
+issues-scanning = Scanning... { $rows } rows read
+issues-file-summary = { $count ->
+    [one] 1 issue in the file
+   *[other] { $count } issues in the file
+}, { $shown } listed
+issues-loaded-summary = { $count ->
+    [one] 1 issue in the loaded rows
+   *[other] { $count } issues in the loaded rows
+}
+issues-line = Line { $line }
+issues-go-to = Go to row { $row } (byte { $byte })
+issues-skipped = { $issue } (skipped)
+issue-unterminated-quote = Unterminated quote swallowing { $lines } lines
+issue-invalid-utf8 = Invalid UTF-8 text
+issue-field-count = { $found } fields, the header has { $expected }
+
+## Quality report
+
+quality-title = ✅ Quality report
+quality-keys = Key columns:
+quality-no-keys = None
+quality-ranges = { $count ->
+    [one] 1 column has spec limits, set in the row inspector
+   *[other] { $count } columns have spec limits, set in the row inspector
+}
+quality-run = ▶ Scan sheet
+quality-run-hint = Reads every row of the file as saved, without edits
+quality-scanning = Scanning... { $rows } rows read
+quality-summary = { $rows } rows, { $findings ->
+    [one] 1 finding
+   *[other] { $findings } findings
+}
+quality-export-json = Export JSON
+quality-export-html = Export HTML
+quality-clean-columns = { $count ->
+    [one] 1 column without findings
+   *[other] { $count } columns without findings
+}
+quality-filled = { $filled } filled, { $empty } empty
+quality-go-to = Go to the first example row
+quality-row = Row { $row }
+finding-empty = Empty column
+finding-constant = Constant value “{ $value }”
+finding-duplicate-keys = { $count } rows repeat a key ({ $columns }); { $values } keys occur more than once
+finding-mixed-types = Mixed types: { $numbers } numbers, { $text } text values
+finding-out-of-range = { $count } values outside [{ $low }, { $high }]
+finding-trailing-whitespace = { $count } values with trailing whitespace
+
+## Table grid
+
+grid-resize-hint = Drag to resize, double-click to fit
+grid-hide-column = Hide column
+grid-freeze = Freeze up to this column
+grid-unfreeze = Unfreeze columns
+grid-fit = Fit width to content
+grid-show-all-columns = Show all columns
+grid-insert-above = Insert row above
+grid-insert-below = Insert row below
+grid-delete-row = Delete row
+columns-menu = ▥ Columns
+columns-menu-hidden = ▥ Columns ({ $count } hidden)
+columns-show-all = Show all
+columns-hide-all = Hide all
+columns-unfreeze = Unfreeze
+columns-reset = Reset
+columns-reset-hint = Shows every column in file order at its initial width
+columns-find = Find column
+columns-help = Drag headers to reorder, drag their edge to resize. Right-click a header to freeze columns.
+column-add = ➕ Add column…
+
+## Formatting
+
+format-title = 🎨 Formatting
+format-scanning = Computing column ranges…
+format-no-rules = No rules yet. Rules are kept for every file with these columns.
+format-add-rule = Add rule
+format-add = ➕ Add
+rule-color-scale = Color scale
+rule-threshold = Threshold
+rule-text = Text match
+rule-data-bar = Data bar
+rule-min = min
+rule-max = → max
+rule-bar = bar
+text-match-contains = contains
+text-match-equals = equals
+text-match-starts-with = starts with
+text-match-regex = matches regex
+
+## Pivot table
+
+pivot-title = 🧮 Pivot table
+pivot-whole-sheet = Computed over the whole sheet, not only the rows loaded so far.
+pivot-rows = Rows
+pivot-columns = Columns
+pivot-values = Values
+pivot-add = ➕ Add
+pivot-compute = ▶ Compute
+pivot-clear = Clear
+pivot-tab = Pivot { $number }
+pivot-description = { $values } by { $groups }
+pivot-description-rows = rows by { $groups }
+
+## Compare
+
+diff-title = ⇄ Compare
+diff-before = Before:
+diff-after = After:
+diff-not-chosen = not chosen
+diff-align = Align rows:
+diff-by-position = By position
+diff-by-key = By key columns
+diff-key = Key
+diff-tolerance = Numeric tolerance:
+diff-trim = Ignore spaces around values
+diff-trim-hint = Leading and trailing spaces do not count as a change or tell keys apart
+diff-compare = ▶ Compare
+diff-tab = Diff { $number }
+diff-summary = { $changed } changed, { $added } added, { $removed } removed, { $unchanged } unchanged ({ $cells } changed cells{ $within ->
+    [0] {""}
+   *[other] , { $within } within tolerance
+})
+diff-truncated = ; only the first { $count } differing rows are listed
+diff-only-before = ; only before: { $columns }
+diff-only-after = ; only after: { $columns }
+
+## Merge
+
+merge-title = 🔗 Merge
+merge-add = Add:
+merge-join = Join:
+merge-key = Key
+merge-run = ▶ Merge
+join-inner = inner
+join-left = left
+join-outer = outer
+
+## Sheet picker
+
+sheet-current = Current sheet
+sheet-choose-file = Choose file…
+
+## SQL console
+
+sql-title = 🗄 SQL console
+sql-run = ▶ Run
+sql-add-file = ➕ Add file…
+sql-tables = Tables
+sql-no-tables = The open file is added when you run a query.
+sql-status = { $rows ->
+    [one] 1 row
+   *[other] { $rows } rows
+} in { $ms } ms
+sql-tab = Query { $number }
+
+## Fixed-width import
+
+fixed-width-title = 📐 Fixed-width import
+fixed-width-help = Drag a boundary to move it, click to add one, double-click or right-click to remove it.
+fixed-width-guess = Guess again
+fixed-width-clear = Clear
+fixed-width-save-profile = Save as profile
+fixed-width-import = Import
+fixed-width-cancel = Cancel
+
+## Open folder
+
+folder-title = 📂 Open folder
+folder-files = Files:
+folder-scan = 🔄 Scan
+folder-summary = { $count ->
+    [one] 1 file
+   *[other] { $count } files
+}, { $mismatches } with columns that do not line up
+folder-help = Columns are lined up by name. A source_file column and the preamble fields are added in front.
+folder-combine = ▶ Combine { $count ->
+    [one] 1 file
+   *[other] { $count } files
+}
+
+## Computed columns
+
+computed-title = ƒx Computed columns
+computed-name = Name:
+computed-expression = Expression:
+computed-first-row = First row: { $value }
+computed-add = ➕ Add column
+computed-reference = Columns and functions
+computed-columns-hint = Refer to columns by header name; use [Die X] or `Die X` for names with spaces.
+computed-operators-hint = Operators: + - * / % ^, = != < <= > >=, and or not, & joins text.
+
+## Load errors
+
+error-task = Task execution error: { $detail }
+error-io = Cannot read the file: { $detail }
+error-empty-file = The file is empty
+error-parse = Cannot parse the file: { $detail }
+error-open-workbook = Failed to open Excel file: { $detail }
+error-no-worksheets = No worksheets found in the file
+error-read-worksheet = Failed to read worksheet '{ $sheet }': { $detail }
+error-archive-entry = Archives must be opened through one of their entries.
+error-unsupported-format = Unsupported file format. Please select a CSV, Excel, ODS, Parquet, Arrow, JSON, SQLite, or fixed-width text file.
+error-open-archive = Failed to open archive: { $detail }
+error-extract-entry = Failed to extract '{ $entry }': { $detail }
+error-invalid-entry-name = Invalid file name in archive: '{ $entry }'
+error-invalid-json = Invalid JSON: { $detail }
+error-invalid-json-line = Invalid JSON on line { $line }: { $detail }
+error-open-database = Failed to open SQLite database: { $detail }
+error-no-tables = No tables or views found in the database
+error-read-table = Failed to read '{ $table }': { $detail }
+error-create-file = Failed to create { $path }: { $detail }
+error-no-common-columns = The two sheets have no column names in common.
+error-no-key-columns = Pick at least one key column.
+error-missing-key-column = Key column '{ $column }' is not in { $file }
+error-key-count-mismatch = { $file } has { $count } key columns, but { $first_file } has { $first_count }
+error-too-few-inputs = Pick at least two files to merge.
+error-query-engine = Failed to start the query engine: { $detail }
+error-attach-database = Failed to attach '{ $path }': { $detail }
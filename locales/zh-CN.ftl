@@ -0,0 +1,378 @@
+# 界面消息（简体中文）。缺少的消息会使用英文版本。
+
+## Toolbar
+
+open-file = 📁 打开文件
+open-folder = 📂 打开文件夹
+fixed-width-columns = 📐 列
+toggle-sql = 🗄 SQL
+toggle-compare = ⇄ 比较
+toggle-merge = 🔗 合并
+toggle-pivot = 🧮 数据透视
+toggle-format = 🎨 格式
+toggle-inspector = 🔎 查看行
+toggle-issues = ⚠ 解析问题 ({ $count })
+toggle-quality = ✅ 质量
+toggle-quality-findings = ✅ 质量 ({ $count })
+toggle-computed-columns = ƒx 计算列
+sheet-label = 📊 工作表：
+watch-label = 🔄 文件变更时：
+watch-off = 不处理
+watch-reload = 重新加载
+watch-tail = 跟踪末尾
+
+## File dialog filters
+
+filter-spreadsheet = 电子表格
+filter-columnar = 列式存储
+filter-fixed-width = 固定宽度文本
+filter-compressed = 压缩的 CSV
+filter-archive = 压缩包
+filter-font = 字体
+
+## Preferences
+
+prefs-language = 语言
+language-system = 系统语言
+prefs-font = 字体
+prefs-font-default = 系统默认
+prefs-font-choose = 选择…
+prefs-font-size = 大小
+prefs-cjk-font = 中文字体：{ $font }
+prefs-no-cjk-font = 找不到中文字体，中文可能无法正常显示
+prefs-theme = 主题
+theme-dark = 深色
+theme-light = 浅色
+theme-system = 跟随系统
+theme-reset = 主题颜色
+prefs-high-contrast = 高对比度
+prefs-row-stripes = 行条纹
+prefs-column-headers = 列标题
+prefs-accent = 强调色
+
+## Archives
+
+archive-choose = 选择要打开的文件：
+archive-empty = 压缩包中没有支持的文件。
+
+## Welcome screen
+
+welcome-title = Excel 与 CSV 查看器
+welcome-hint = 点击“打开文件”加载电子表格
+welcome-supports = 支持格式：
+
+## Tabs and export
+
+tab-file = 📄 文件
+export-csv = 💾 导出 CSV
+export-view-hint = 应用计算列、筛选和排序后导出整个工作表
+saved = 已保存
+saved-file = 已保存 { $path }
+saved-rows = 已将 { $count } 行保存到 { $path }
+row-count = { $count } 行
+
+## View bar
+
+filter-label = 🔍 筛选：
+filter-hint = 例如 bin != 1 and fwhm > 20
+filter-matches = 已加载 { $loaded } 行中的 { $shown } 行
+undo-hint = 撤销 (Ctrl+Z)
+redo-hint = 重做 (Ctrl+Y)
+save = 💾 保存
+save-hint = 将编辑写回文件
+save-edit-hint = 双击单元格即可编辑；在行号上右键可插入或删除行
+save-unsupported-hint = 只有 CSV 和 XLSX 文件可以保存；请用“导出 CSV”保留编辑
+save-tolerant-hint = 跳过或修复记录后读取的文件无法保存；请用“导出 CSV”保留编辑
+row-lock-references = 含有{ $parts }的工作表无法插入或删除行，否则其行号将不再相符
+row-lock-unknown = 无法插入或删除行：{ $detail }
+row-reference-formulas = 公式
+row-reference-merged-cells = 合并单元格
+row-reference-conditional-formats = 条件格式
+row-reference-data-validations = 数据验证
+row-reference-hyperlinks = 超链接
+row-reference-tables = 表格
+row-reference-defined-names = 已定义名称
+unsaved-edits = ● 有未保存的编辑
+
+## Status
+
+loading-more = 正在加载更多数据...
+all-loaded = 已加载全部数据
+footer-sheet = 工作表：{ $sheet }
+footer-sheet-count = 共 { $count } 个工作表
+footer-rows-complete = { $count } 行（完成）
+footer-rows-loading = { $count } 行（加载中...）
+footer-rows-of = 已加载 { $loaded } / { $total } 行
+progress-counting = 正在统计行数
+progress-loading = 正在加载
+progress-exporting = 正在导出
+progress-rows = { $label }：已读取 { $rows } 行
+status-complete = ✅ 完成
+file-changed = ⚠ 文件已变更
+reload = 重新加载
+
+## Go to
+
+goto-button = ↧ 转到…
+goto-button-hint = 转到某行、单元格或键值 (Ctrl+G)
+goto-title = ↧ 转到
+goto-hint = 1234、C1234 或键值
+goto-key-column = 键列：
+goto-always-find = 数字也在键列中搜索
+goto-preview-row = 第 { $row } 行
+goto-preview-cell = 第 { $row } 行，列 { $column }
+goto-preview-find = { $column } 等于此值的第一行
+goto-go = 转到
+goto-searching = 正在搜索工作表...
+goto-not-found = 找不到匹配的行
+goto-filtered = 第 { $row } 行被筛选隐藏
+goto-deleted = 第 { $row } 行已删除
+goto-past-end = 工作表没有第 { $row } 行
+window-notice = 当前从第 { $row } 行开始显示，之前的行尚未加载
+window-load-start = ⤒ 从第一行加载
+
+## Row inspector
+
+inspector-title = 🔎 行查看器
+inspector-no-row = 点击单元格以查看该行。
+inspector-search = 搜索列与数值…
+inspector-row = 第 { $row } 行
+inspector-new-row = 新增的行
+inspector-new = 新增
+inspector-pin = 📌 固定
+inspector-pin-hint = 保留此行，与接下来选择的行并排比较
+inspector-unpin = 取消固定
+inspector-copy-json = 📋 复制为 JSON
+inspector-copied = 已复制
+inspector-column = 列
+inspector-matches = { $total } 列中的 { $shown } 列
+inspector-limits = 规格界限
+inspector-limits-hint = 设置此列的规格界限
+inspector-low = 下限
+inspector-high = 上限
+inspector-apply = 应用
+inspector-clear = 清除
+inspector-cancel = 取消
+inspector-invalid-limit = 界限必须是数字；留空表示该侧不设限
+inspector-limit-summary = 规格 { $low } 至 { $high }
+inspector-below = 低于规格下限
+inspector-above = 高于规格上限
+
+## Cell viewer
+
+grid-view-cell = 📄 查看单元格
+grid-wrap-text = 自动换行
+grid-truncate-after = 截断长度
+grid-characters = 个字符
+viewer-title = 📄 { $column }，第 { $row } 行
+viewer-title-new = 📄 { $column }，新增的行
+viewer-pretty = 以 { $format } 格式显示
+viewer-size = { $chars } 个字符，{ $lines } 行
+viewer-copy = 📋 复制
+viewer-copied = 已复制
+viewer-edit = ✏ 编辑
+
+## Parse issues
+
+issues-title = ⚠ 解析问题
+issues-tolerance = 格式错误的记录：
+issues-tolerance-locked = 请先保存或撤销编辑；此设置会改变行号
+tolerance-strict = 遇到无效文本即停止，保留字段数不符的行
+tolerance-skip = 跳过
+tolerance-repair = 修复
+issues-mark-rows = 在表格中标记有问题的行
+issues-scan = 🔍 扫描整个文件
+issues-scanning = 扫描中...已读取 { $rows } 行
+issues-file-summary = 文件中有 { $count } 个问题，列出 { $shown } 个
+issues-loaded-summary = 已加载的行中有 { $count } 个问题
+issues-line = 第 { $line } 行
+issues-go-to = 跳至第 { $row } 行（字节 { $byte }）
+issues-skipped = { $issue }（已跳过）
+issue-unterminated-quote = 未结束的引号吞掉了 { $lines } 行
+issue-invalid-utf8 = 无效的 UTF-8 文本
+issue-field-count = { $found } 个字段，标题行有 { $expected } 个
+
+## Quality report
+
+quality-title = ✅ 质量报告
+quality-keys = 键列：
+quality-no-keys = 无
+quality-ranges = { $count } 列设有规格界限（在行查看器中设置）
+quality-run = ▶ 扫描工作表
+quality-run-hint = 读取已保存文件的每一行，不含编辑
+quality-scanning = 扫描中... 已读取 { $rows } 行
+quality-summary = { $rows } 行，{ $findings } 项发现
+quality-export-json = 导出 JSON
+quality-export-html = 导出 HTML
+quality-clean-columns = { $count } 列没有发现
+quality-filled = { $filled } 个有值，{ $empty } 个为空
+quality-go-to = 跳至第一个示例行
+quality-row = 第 { $row } 行
+finding-empty = 空列
+finding-constant = 固定值“{ $value }”
+finding-duplicate-keys = { $count } 行的键重复（{ $columns }）；{ $values } 个键出现不止一次
+finding-mixed-types = 类型混杂：{ $numbers } 个数字，{ $text } 个文本
+finding-out-of-range = { $count } 个值超出 [{ $low }, { $high }]
+finding-trailing-whitespace = { $count } 个值末尾有空白
+
+## Table grid
+
+grid-resize-hint = 拖动以调整宽度，双击以适应内容
+grid-hide-column = 隐藏列
+grid-freeze = 冻结至此列
+grid-unfreeze = 取消冻结列
+grid-fit = 根据内容调整宽度
+grid-show-all-columns = 显示所有列
+grid-insert-above = 在上方插入行
+grid-insert-below = 在下方插入行
+grid-delete-row = 删除行
+columns-menu = ▥ 列
+columns-menu-hidden = ▥ 列（隐藏 { $count } 个）
+columns-show-all = 全部显示
+columns-hide-all = 全部隐藏
+columns-unfreeze = 取消冻结
+columns-reset = 重置
+columns-reset-hint = 按文件顺序以初始宽度显示所有列
+columns-find = 查找列
+columns-help = 拖动标题可调整顺序，拖动边缘可调整宽度。在标题上右键单击可冻结列。
+column-add = ➕ 添加列…
+
+## Formatting
+
+format-title = 🎨 格式设置
+format-scanning = 正在计算列范围…
+format-no-rules = 尚无规则。规则会应用到所有具有相同列的文件。
+format-add-rule = 添加规则
+format-add = ➕ 添加
+rule-color-scale = 色阶
+rule-threshold = 阈值
+rule-text = 文本匹配
+rule-data-bar = 数据条
+rule-min = 最小值
+rule-max = → 最大值
+rule-bar = 数据条
+text-match-contains = 包含
+text-match-equals = 等于
+text-match-starts-with = 开头为
+text-match-regex = 匹配正则表达式
+
+## Pivot table
+
+pivot-title = 🧮 数据透视表
+pivot-whole-sheet = 基于整个工作表计算，而非仅限已加载的行。
+pivot-rows = 行
+pivot-columns = 列
+pivot-values = 值
+pivot-add = ➕ 添加
+pivot-compute = ▶ 计算
+pivot-clear = 清除
+pivot-tab = 透视 { $number }
+pivot-description = 按 { $groups } 的 { $values }
+pivot-description-rows = 按 { $groups } 分组的行
+
+## Compare
+
+diff-title = ⇄ 比较
+diff-before = 之前：
+diff-after = 之后：
+diff-not-chosen = 尚未选择
+diff-align = 对齐行：
+diff-by-position = 按位置
+diff-by-key = 按键列
+diff-key = 键
+diff-tolerance = 数值容差：
+diff-trim = 忽略值前后的空格
+diff-trim-hint = 前后的空格不算更改，也不用来区分键值
+diff-compare = ▶ 比较
+diff-tab = 比较 { $number }
+diff-summary = { $changed } 行更改，{ $added } 行新增，{ $removed } 行删除，{ $unchanged } 行未更改（{ $cells } 个单元格更改{ $within ->
+    [0] {""}
+   *[other] ，{ $within } 个在容差内
+}）
+diff-truncated = ；仅列出前 { $count } 个不同的行
+diff-only-before = ；仅在之前：{ $columns }
+diff-only-after = ；仅在之后：{ $columns }
+
+## Merge
+
+merge-title = 🔗 合并
+merge-add = 添加：
+merge-join = 连接：
+merge-key = 键
+merge-run = ▶ 合并
+join-inner = 内连接
+join-left = 左连接
+join-outer = 全外连接
+
+## Sheet picker
+
+sheet-current = 当前工作表
+sheet-choose-file = 选择文件…
+
+## SQL console
+
+sql-title = 🗄 SQL 控制台
+sql-run = ▶ 运行
+sql-add-file = ➕ 添加文件…
+sql-tables = 表
+sql-no-tables = 运行查询时会添加当前打开的文件。
+sql-status = { $rows } 行，耗时 { $ms } 毫秒
+sql-tab = 查询 { $number }
+
+## Fixed-width import
+
+fixed-width-title = 📐 固定宽度导入
+fixed-width-help = 拖动分隔线可移动，单击可添加，双击或右键单击可删除。
+fixed-width-guess = 重新推测
+fixed-width-clear = 清除
+fixed-width-save-profile = 保存为配置
+fixed-width-import = 导入
+fixed-width-cancel = 取消
+
+## Open folder
+
+folder-title = 📂 打开文件夹
+folder-files = 文件：
+folder-scan = 🔄 扫描
+folder-summary = { $count } 个文件，其中 { $mismatches } 个的列无法对齐
+folder-help = 列按名称对齐。前面会添加 source_file 列与前置信息列。
+folder-combine = ▶ 合并 { $count } 个文件
+
+## Computed columns
+
+computed-title = ƒx 计算列
+computed-name = 名称：
+computed-expression = 表达式：
+computed-first-row = 第一行：{ $value }
+computed-add = ➕ 添加列
+computed-reference = 列与函数
+computed-columns-hint = 按标题名称引用列；名称含空格时请使用 [Die X] 或 `Die X`。
+computed-operators-hint = 运算符：+ - * / % ^、= != < <= > >=、and or not，& 连接文本。
+
+## Load errors
+
+error-task = 任务执行错误：{ $detail }
+error-io = 无法读取文件：{ $detail }
+error-empty-file = 文件为空
+error-parse = 无法解析文件：{ $detail }
+error-open-workbook = 无法打开 Excel 文件：{ $detail }
+error-no-worksheets = 文件中没有工作表
+error-read-worksheet = 无法读取工作表“{ $sheet }”：{ $detail }
+error-archive-entry = 压缩包必须通过其中的文件打开。
+error-unsupported-format = 不支持的文件格式。请选择 CSV、Excel、ODS、Parquet、Arrow、JSON、SQLite 或固定宽度文本文件。
+error-open-archive = 无法打开压缩包：{ $detail }
+error-extract-entry = 无法解压“{ $entry }”：{ $detail }
+error-invalid-entry-name = 压缩包中的文件名无效：“{ $entry }”
+error-invalid-json = 无效的 JSON：{ $detail }
+error-invalid-json-line = 第 { $line } 行的 JSON 无效：{ $detail }
+error-open-database = 无法打开 SQLite 数据库：{ $detail }
+error-no-tables = 数据库中没有表或视图
+error-read-table = 无法读取“{ $table }”：{ $detail }
+error-create-file = 无法创建 { $path }：{ $detail }
+error-no-common-columns = 两个工作表没有名称相同的列。
+error-no-key-columns = 请至少选择一个键列。
+error-missing-key-column = { $file } 中没有键列“{ $column }”
+error-key-count-mismatch = { $file } 有 { $count } 个键列，但 { $first_file } 有 { $first_count } 个
+error-too-few-inputs = 请至少选择两个要合并的文件。
+error-query-engine = 无法启动查询引擎：{ $detail }
+error-attach-database = 无法附加“{ $path }”：{ $detail }
@@ -0,0 +1,378 @@
+# 介面訊息（繁體中文）。缺少的訊息會使用英文版本。
+
+## Toolbar
+
+open-file = 📁 開啟檔案
+open-folder = 📂 開啟資料夾
+fixed-width-columns = 📐 欄位
+toggle-sql = 🗄 SQL
+toggle-compare = ⇄ 比較
+toggle-merge = 🔗 合併
+toggle-pivot = 🧮 樞紐分析
+toggle-format = 🎨 格式
+toggle-inspector = 🔎 檢視列
+toggle-issues = ⚠ 解析問題 ({ $count })
+toggle-quality = ✅ 品質
+toggle-quality-findings = ✅ 品質 ({ $count })
+toggle-computed-columns = ƒx 計算欄位
+sheet-label = 📊 工作表：
+watch-label = 🔄 檔案變更時：
+watch-off = 不處理
+watch-reload = 重新載入
+watch-tail = 追蹤尾端
+
+## File dialog filters
+
+filter-spreadsheet = 試算表
+filter-columnar = 欄式儲存
+filter-fixed-width = 固定寬度文字
+filter-compressed = 壓縮的 CSV
+filter-archive = 壓縮檔
+filter-font = 字型
+
+## Preferences
+
+prefs-language = 語言
+language-system = 系統語言
+prefs-font = 字型
+prefs-font-default = 系統預設
+prefs-font-choose = 選擇…
+prefs-font-size = 大小
+prefs-cjk-font = 中文字型：{ $font }
+prefs-no-cjk-font = 找不到中文字型，中文可能無法正常顯示
+prefs-theme = 主題
+theme-dark = 深色
+theme-light = 淺色
+theme-system = 跟隨系統
+theme-reset = 主題色彩
+prefs-high-contrast = 高對比
+prefs-row-stripes = 列條紋
+prefs-column-headers = 欄位標題
+prefs-accent = 強調色
+
+## Archives
+
+archive-choose = 選擇要開啟的檔案：
+archive-empty = 壓縮檔中沒有支援的檔案。
+
+## Welcome screen
+
+welcome-title = Excel 與 CSV 檢視器
+welcome-hint = 按下「開啟檔案」載入試算表
+welcome-supports = 支援格式：
+
+## Tabs and export
+
+tab-file = 📄 檔案
+export-csv = 💾 匯出 CSV
+export-view-hint = 套用計算欄位、篩選與排序後匯出整個工作表
+saved = 已儲存
+saved-file = 已儲存 { $path }
+saved-rows = 已將 { $count } 列儲存至 { $path }
+row-count = { $count } 列
+
+## View bar
+
+filter-label = 🔍 篩選：
+filter-hint = 例如 bin != 1 and fwhm > 20
+filter-matches = 已載入 { $loaded } 列中的 { $shown } 列
+undo-hint = 復原 (Ctrl+Z)
+redo-hint = 重做 (Ctrl+Y)
+save = 💾 儲存
+save-hint = 將編輯寫回檔案
+save-edit-hint = 按兩下儲存格即可編輯；在列號上按右鍵可插入或刪除列
+save-unsupported-hint = 只有 CSV 與 XLSX 檔案可以儲存；請用「匯出 CSV」保留編輯
+save-tolerant-hint = 略過或修復記錄後讀取的檔案無法儲存；請用「匯出 CSV」保留編輯
+row-lock-references = 含有{ $parts }的工作表無法插入或刪除列，否則其列號將不再相符
+row-lock-unknown = 無法插入或刪除列：{ $detail }
+row-reference-formulas = 公式
+row-reference-merged-cells = 合併儲存格
+row-reference-conditional-formats = 條件式格式
+row-reference-data-validations = 資料驗證
+row-reference-hyperlinks = 超連結
+row-reference-tables = 表格
+row-reference-defined-names = 已定義名稱
+unsaved-edits = ● 有未儲存的編輯
+
+## Status
+
+loading-more = 正在載入更多資料...
+all-loaded = 已載入全部資料
+footer-sheet = 工作表：{ $sheet }
+footer-sheet-count = 共 { $count } 個工作表
+footer-rows-complete = { $count } 列（完成）
+footer-rows-loading = { $count } 列（載入中...）
+footer-rows-of = 已載入 { $loaded } / { $total } 列
+progress-counting = 正在計算列數
+progress-loading = 正在載入
+progress-exporting = 正在匯出
+progress-rows = { $label }：已讀取 { $rows } 列
+status-complete = ✅ 完成
+file-changed = ⚠ 檔案已變更
+reload = 重新載入
+
+## Go to
+
+goto-button = ↧ 跳至…
+goto-button-hint = 跳至某列、儲存格或鍵值 (Ctrl+G)
+goto-title = ↧ 跳至
+goto-hint = 1234、C1234 或鍵值
+goto-key-column = 鍵欄位：
+goto-always-find = 數字也在鍵欄位中搜尋
+goto-preview-row = 第 { $row } 列
+goto-preview-cell = 第 { $row } 列，欄位 { $column }
+goto-preview-find = { $column } 等於此值的第一列
+goto-go = 前往
+goto-searching = 正在搜尋工作表...
+goto-not-found = 找不到符合的列
+goto-filtered = 第 { $row } 列被篩選隱藏
+goto-deleted = 第 { $row } 列已刪除
+goto-past-end = 工作表沒有第 { $row } 列
+window-notice = 目前從第 { $row } 列開始顯示，之前的列尚未載入
+window-load-start = ⤒ 從第一列載入
+
+## Row inspector
+
+inspector-title = 🔎 列檢視器
+inspector-no-row = 點選儲存格以檢視該列。
+inspector-search = 搜尋欄位與數值…
+inspector-row = 第 { $row } 列
+inspector-new-row = 新增的列
+inspector-new = 新增
+inspector-pin = 📌 釘選
+inspector-pin-hint = 保留此列，與接下來選取的列並排比較
+inspector-unpin = 取消釘選
+inspector-copy-json = 📋 複製為 JSON
+inspector-copied = 已複製
+inspector-column = 欄位
+inspector-matches = { $total } 個欄位中的 { $shown } 個
+inspector-limits = 規格界限
+inspector-limits-hint = 設定此欄位的規格界限
+inspector-low = 下限
+inspector-high = 上限
+inspector-apply = 套用
+inspector-clear = 清除
+inspector-cancel = 取消
+inspector-invalid-limit = 界限必須是數字；留空表示該側不設限
+inspector-limit-summary = 規格 { $low } 至 { $high }
+inspector-below = 低於規格下限
+inspector-above = 高於規格上限
+
+## Cell viewer
+
+grid-view-cell = 📄 檢視儲存格
+grid-wrap-text = 自動換行
+grid-truncate-after = 截斷長度
+grid-characters = 個字元
+viewer-title = 📄 { $column }，第 { $row } 列
+viewer-title-new = 📄 { $column }，新增的列
+viewer-pretty = 以 { $format } 格式顯示
+viewer-size = { $chars } 個字元，{ $lines } 行
+viewer-copy = 📋 複製
+viewer-copied = 已複製
+viewer-edit = ✏ 編輯
+
+## Parse issues
+
+issues-title = ⚠ 解析問題
+issues-tolerance = 格式錯誤的記錄：
+issues-tolerance-locked = 請先儲存或復原編輯；此設定會改變列號
+tolerance-strict = 遇到無效文字即停止，保留欄數不符的列
+tolerance-skip = 略過
+tolerance-repair = 修復
+issues-mark-rows = 在表格中標示有問題的列
+issues-scan = 🔍 掃描整個檔案
+issues-scanning = 掃描中...已讀取 { $rows } 列
+issues-file-summary = 檔案中有 { $count } 個問題，列出 { $shown } 個
+issues-loaded-summary = 已載入的列中有 { $count } 個問題
+issues-line = 第 { $line } 行
+issues-go-to = 跳至第 { $row } 列（位元組 { $byte }）
+issues-skipped = { $issue }（已略過）
+issue-unterminated-quote = 未結束的引號吞掉了 { $lines } 行
+issue-invalid-utf8 = 無效的 UTF-8 文字
+issue-field-count = { $found } 個欄位，標題列有 { $expected } 個
+
+## Quality report
+
+quality-title = ✅ 品質報告
+quality-keys = 鍵欄位：
+quality-no-keys = 無
+quality-ranges = { $count } 個欄位設有規格界限（於列檢視器設定）
+quality-run = ▶ 掃描工作表
+quality-run-hint = 讀取已儲存檔案的每一列，不含編輯
+quality-scanning = 掃描中... 已讀取 { $rows } 列
+quality-summary = { $rows } 列，{ $findings } 項發現
+quality-export-json = 匯出 JSON
+quality-export-html = 匯出 HTML
+quality-clean-columns = { $count } 個欄位沒有發現
+quality-filled = { $filled } 個有值，{ $empty } 個空白
+quality-go-to = 跳至第一個範例列
+quality-row = 第 { $row } 列
+finding-empty = 空白欄位
+finding-constant = 固定值「{ $value }」
+finding-duplicate-keys = { $count } 列的鍵重複（{ $columns }）；{ $values } 個鍵出現不只一次
+finding-mixed-types = 類型混雜：{ $numbers } 個數字，{ $text } 個文字
+finding-out-of-range = { $count } 個值超出 [{ $low }, { $high }]
+finding-trailing-whitespace = { $count } 個值結尾有空白
+
+## Table grid
+
+grid-resize-hint = 拖曳以調整寬度，按兩下以符合內容
+grid-hide-column = 隱藏欄位
+grid-freeze = 凍結至此欄位
+grid-unfreeze = 取消凍結欄位
+grid-fit = 依內容調整寬度
+grid-show-all-columns = 顯示所有欄位
+grid-insert-above = 在上方插入列
+grid-insert-below = 在下方插入列
+grid-delete-row = 刪除列
+columns-menu = ▥ 欄位
+columns-menu-hidden = ▥ 欄位（隱藏 { $count } 個）
+columns-show-all = 全部顯示
+columns-hide-all = 全部隱藏
+columns-unfreeze = 取消凍結
+columns-reset = 重設
+columns-reset-hint = 依檔案順序以初始寬度顯示所有欄位
+columns-find = 尋找欄位
+columns-help = 拖曳標題可調整順序，拖曳邊緣可調整寬度。在標題上按右鍵可凍結欄位。
+column-add = ➕ 新增欄位…
+
+## Formatting
+
+format-title = 🎨 格式設定
+format-scanning = 正在計算欄位範圍…
+format-no-rules = 尚無規則。規則會套用到所有具有相同欄位的檔案。
+format-add-rule = 新增規則
+format-add = ➕ 新增
+rule-color-scale = 色階
+rule-threshold = 門檻值
+rule-text = 文字比對
+rule-data-bar = 資料橫條
+rule-min = 最小值
+rule-max = → 最大值
+rule-bar = 橫條
+text-match-contains = 包含
+text-match-equals = 等於
+text-match-starts-with = 開頭為
+text-match-regex = 符合規則運算式
+
+## Pivot table
+
+pivot-title = 🧮 樞紐分析表
+pivot-whole-sheet = 以整個工作表計算，而非僅限已載入的列。
+pivot-rows = 列
+pivot-columns = 欄
+pivot-values = 值
+pivot-add = ➕ 新增
+pivot-compute = ▶ 計算
+pivot-clear = 清除
+pivot-tab = 樞紐分析 { $number }
+pivot-description = 依 { $groups } 的 { $values }
+pivot-description-rows = 依 { $groups } 分組的列
+
+## Compare
+
+diff-title = ⇄ 比較
+diff-before = 之前：
+diff-after = 之後：
+diff-not-chosen = 尚未選擇
+diff-align = 對齊列：
+diff-by-position = 依位置
+diff-by-key = 依鍵欄位
+diff-key = 鍵
+diff-tolerance = 數值容差：
+diff-trim = 忽略值前後的空白
+diff-trim-hint = 前後的空白不算變更，也不用來區分鍵值
+diff-compare = ▶ 比較
+diff-tab = 比較 { $number }
+diff-summary = { $changed } 列變更，{ $added } 列新增，{ $removed } 列移除，{ $unchanged } 列未變更（{ $cells } 個儲存格變更{ $within ->
+    [0] {""}
+   *[other] ，{ $within } 個在容差內
+}）
+diff-truncated = ；僅列出前 { $count } 個不同的列
+diff-only-before = ；僅在之前：{ $columns }
+diff-only-after = ；僅在之後：{ $columns }
+
+## Merge
+
+merge-title = 🔗 合併
+merge-add = 新增：
+merge-join = 聯結：
+merge-key = 鍵
+merge-run = ▶ 合併
+join-inner = 內部
+join-left = 左方
+join-outer = 完整外部
+
+## Sheet picker
+
+sheet-current = 目前工作表
+sheet-choose-file = 選擇檔案…
+
+## SQL console
+
+sql-title = 🗄 SQL 主控台
+sql-run = ▶ 執行
+sql-add-file = ➕ 加入檔案…
+sql-tables = 資料表
+sql-no-tables = 執行查詢時會加入開啟中的檔案。
+sql-status = { $rows } 列，耗時 { $ms } 毫秒
+sql-tab = 查詢 { $number }
+
+## Fixed-width import
+
+fixed-width-title = 📐 固定寬度匯入
+fixed-width-help = 拖曳分隔線可移動，按一下可新增，按兩下或按右鍵可移除。
+fixed-width-guess = 重新推測
+fixed-width-clear = 清除
+fixed-width-save-profile = 儲存為設定檔
+fixed-width-import = 匯入
+fixed-width-cancel = 取消
+
+## Open folder
+
+folder-title = 📂 開啟資料夾
+folder-files = 檔案：
+folder-scan = 🔄 掃描
+folder-summary = { $count } 個檔案，其中 { $mismatches } 個的欄位無法對齊
+folder-help = 欄位依名稱對齊。前面會加上 source_file 欄位與前置資訊欄位。
+folder-combine = ▶ 合併 { $count } 個檔案
+
+## Computed columns
+
+computed-title = ƒx 計算欄位
+computed-name = 名稱：
+computed-expression = 運算式：
+computed-first-row = 第一列：{ $value }
+computed-add = ➕ 新增欄位
+computed-reference = 欄位與函式
+computed-columns-hint = 以標題名稱參照欄位；名稱含空格時請用 [Die X] 或 `Die X`。
+computed-operators-hint = 運算子：+ - * / % ^、= != < <= > >=、and or not，& 串接文字。
+
+## Load errors
+
+error-task = 任務執行錯誤：{ $detail }
+error-io = 無法讀取檔案：{ $detail }
+error-empty-file = 檔案為空
+error-parse = 無法解析檔案：{ $detail }
+error-open-workbook = 無法開啟 Excel 檔案：{ $detail }
+error-no-worksheets = 檔案中沒有工作表
+error-read-worksheet = 無法讀取工作表「{ $sheet }」：{ $detail }
+error-archive-entry = 壓縮檔必須透過其中的檔案開啟。
+error-unsupported-format = 不支援的檔案格式。請選擇 CSV、Excel、ODS、Parquet、Arrow、JSON、SQLite 或固定寬度文字檔。
+error-open-archive = 無法開啟壓縮檔：{ $detail }
+error-extract-entry = 無法解壓縮「{ $entry }」：{ $detail }
+error-invalid-entry-name = 壓縮檔中的檔名無效：「{ $entry }」
+error-invalid-json = 無效的 JSON：{ $detail }
+error-invalid-json-line = 第 { $line } 行的 JSON 無效：{ $detail }
+error-open-database = 無法開啟 SQLite 資料庫：{ $detail }
+error-no-tables = 資料庫中沒有資料表或檢視表
+error-read-table = 無法讀取「{ $table }」：{ $detail }
+error-create-file = 無法建立 { $path }：{ $detail }
+error-no-common-columns = 兩個工作表沒有相同名稱的欄位。
+error-no-key-columns = 請至少選擇一個鍵欄位。
+error-missing-key-column = { $file } 中沒有鍵欄位「{ $column }」
+error-key-count-mismatch = { $file } 有 { $count } 個鍵欄位，但 { $first_file } 有 { $first_count } 個
+error-too-few-inputs = 請至少選擇兩個要合併的檔案。
+error-query-engine = 無法啟動查詢引擎：{ $detail }
+error-attach-database = 無法附加「{ $path }」：{ $detail }
@@ -1,599 +1,2571 @@
-use eframe::{egui, App, CreationContext, Frame};
-use egui::{
-    CentralPanel, Color32, Context, Margin, Rounding, ScrollArea, Stroke, TopBottomPanel,
-    Vec2,
-};
-use std::path::PathBuf;
-use std::sync::mpsc;
-
-use crate::data_loader::{DataLoadResult, DataLoader};
-use crate::font_setup::setup_custom_fonts;
-
-pub struct MyApp {
-    table: Vec<Vec<String>>,
-    headers: Vec<String>,
-    error: Option<String>,
-    file_path: Option<PathBuf>,
-    tx: mpsc::Sender<DataLoadResult>,
-    rx: mpsc::Receiver<DataLoadResult>,
-    egui_ctx: Context,
-    loading: bool,
-    rows_to_show: usize,
-    headers_loaded: bool,
-    sheet_names: Vec<String>,
-    current_sheet: usize,
-    is_excel_file: bool,
-    end_of_file: bool,
-    data_loader: DataLoader,
-}
-
-impl MyApp {
-    pub fn new(cc: &CreationContext) -> Self {
-        setup_custom_fonts(&cc.egui_ctx);
-
-        let (tx, rx) = mpsc::channel();
-        Self {
-            table: Vec::new(),
-            headers: Vec::new(),
-            error: None,
-            file_path: None,
-            tx,
-            rx,
-            egui_ctx: cc.egui_ctx.clone(),
-            loading: false,
-            rows_to_show: 100,
-            headers_loaded: false,
-            sheet_names: Vec::new(),
-            current_sheet: 0,
-            is_excel_file: false,
-            end_of_file: false,
-            data_loader: DataLoader::new(),
-        }
-    }
-
-    fn open_file(&mut self) {
-        let file = rfd::FileDialog::new()
-            .add_filter("Spreadsheet", &["csv", "xlsx", "xls", "ods"])
-            .pick_file();
-
-        if let Some(path) = file {
-            self.reset_state();
-            self.file_path = Some(path);
-            self.load_more_data();
-        }
-    }
-
-    fn reset_state(&mut self) {
-        self.table.clear();
-        self.headers.clear();
-        self.headers_loaded = false;
-        self.sheet_names.clear();
-        self.current_sheet = 0;
-        self.is_excel_file = false;
-        self.error = None;
-        self.end_of_file = false;
-        self.rows_to_show = 100;
-    }
-
-    fn load_more_data(&mut self) {
-        if self.loading || self.file_path.is_none() || self.end_of_file {
-            return;
-        }
-
-        self.loading = true;
-        let path = self.file_path.as_ref().unwrap().clone();
-        let start_row = self.table.len();
-        let num_rows = self.rows_to_show - self.table.len();
-        let sheet_index = self.current_sheet;
-
-        self.data_loader.load_data_async(
-            path,
-            start_row,
-            num_rows,
-            sheet_index,
-            self.tx.clone(),
-            self.egui_ctx.clone(),
-        );
-    }
-
-    fn switch_sheet(&mut self, sheet_index: usize) {
-        if sheet_index != self.current_sheet && sheet_index < self.sheet_names.len() {
-            self.current_sheet = sheet_index;
-            self.table.clear();
-            self.headers.clear();
-            self.headers_loaded = false;
-            self.end_of_file = false;
-            self.rows_to_show = 100;
-            self.load_more_data();
-        }
-    }
-
-    fn handle_data_response(&mut self) {
-        if let Ok(result) = self.rx.try_recv() {
-            self.loading = false;
-            match result {
-                Ok((headers, data, sheet_names, end_of_file)) => {
-                    self.update_sheet_info(sheet_names);
-                    self.update_headers(headers);
-                    self.table.extend(data);
-                    self.end_of_file = end_of_file;
-                }
-                Err(e) => {
-                    self.error = Some(e);
-                }
-            }
-        }
-    }
-
-    fn update_sheet_info(&mut self, sheet_names: Vec<String>) {
-        if self.sheet_names.is_empty() && !sheet_names.is_empty() {
-            self.sheet_names = sheet_names;
-            self.is_excel_file = self.sheet_names.len() > 1 || self.sheet_names[0] != "CSV";
-        }
-    }
-
-    fn update_headers(&mut self, headers: Vec<String>) {
-        if !self.headers_loaded && !headers.is_empty() {
-            self.headers = headers;
-            self.headers_loaded = true;
-        }
-    }
-}
-
-impl App for MyApp {
-    fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
-        // Set up modern dark theme
-        self.setup_theme(ctx);
-        self.handle_data_response();
-
-        self.render_top_panel(ctx);
-        self.render_main_content(ctx);
-        self.render_footer(ctx);
-    }
-}
-
-impl MyApp {
-    fn setup_theme(&self, ctx: &Context) {
-        let mut style = (*ctx.style()).clone();
-
-        // Modern dark theme colors
-        style.visuals.dark_mode = true;
-        style.visuals.override_text_color = Some(Color32::from_rgb(220, 220, 220));
-        style.visuals.panel_fill = Color32::from_rgb(32, 33, 36);
-        style.visuals.window_fill = Color32::from_rgb(40, 42, 46);
-        style.visuals.extreme_bg_color = Color32::from_rgb(24, 25, 28);
-        style.visuals.code_bg_color = Color32::from_rgb(48, 50, 54);
-
-        // Button styling
-        style.visuals.widgets.inactive.bg_fill = Color32::from_rgb(66, 133, 244);
-        style.visuals.widgets.hovered.bg_fill = Color32::from_rgb(85, 145, 255);
-        style.visuals.widgets.active.bg_fill = Color32::from_rgb(51, 103, 214);
-
-        // Rounded corners
-        style.visuals.widgets.inactive.rounding = Rounding::same(8.0);
-        style.visuals.widgets.hovered.rounding = Rounding::same(8.0);
-        style.visuals.widgets.active.rounding = Rounding::same(8.0);
-        style.visuals.window_rounding = Rounding::same(12.0);
-
-        // Spacing
-        style.spacing.item_spacing = Vec2::new(8.0, 6.0);
-        style.spacing.button_padding = Vec2::new(16.0, 8.0);
-        style.spacing.menu_margin = Margin::same(8.0);
-
-        ctx.set_style(style);
-    }
-
-    fn render_top_panel(&mut self, ctx: &Context) {
-        TopBottomPanel::top("top_panel")
-            .exact_height(50.0)
-            .show(ctx, |ui| {
-                ui.add_space(10.0);
-                ui.horizontal(|ui| {
-                    // Smaller, more compact open file button
-                    let open_btn = egui::Button::new("📁 Open File")
-                        .fill(Color32::from_rgb(76, 175, 80))
-                        .stroke(Stroke::new(1.0, Color32::from_rgb(56, 142, 60)))
-                        .rounding(Rounding::same(8.0));
-
-                    if ui.add_sized([100.0, 32.0], open_btn).clicked() {
-                        self.open_file();
-                    }
-
-                    ui.add_space(20.0);
-                    
-                    // Sheet selector
-                    self.render_sheet_selector(ui);
-                });
-                ui.add_space(10.0);
-            });
-    }
-
-    fn render_sheet_selector(&mut self, ui: &mut egui::Ui) {
-        if self.is_excel_file && !self.sheet_names.is_empty() {
-            // Simple sheet selector without complex wrappers
-            ui.horizontal(|ui| {
-                ui.label(egui::RichText::new("📊 Sheet:").color(Color32::from_rgb(156, 163, 175)));
-                ui.add_space(8.0);
-
-                let mut selected_sheet = self.current_sheet;
-                let combo = egui::ComboBox::from_id_source("sheet_selector")
-                    .selected_text(format!("📄 {}", &self.sheet_names[self.current_sheet]))
-                    .width(180.0);
-
-                combo.show_ui(ui, |ui| {
-                    for (index, sheet_name) in self.sheet_names.iter().enumerate() {
-                        let is_selected = index == self.current_sheet;
-                        let text = if is_selected {
-                            egui::RichText::new(format!("📄 {}", sheet_name))
-                                .color(Color32::from_rgb(66, 133, 244))
-                        } else {
-                            egui::RichText::new(format!("📄 {}", sheet_name))
-                        };
-
-                        ui.selectable_value(&mut selected_sheet, index, text);
-                    }
-                });
-
-                if selected_sheet != self.current_sheet {
-                    self.switch_sheet(selected_sheet);
-                }
-            });
-        }
-    }
-
-
-
-    fn render_main_content(&mut self, ctx: &Context) {
-        CentralPanel::default().show(ctx, |ui| {
-            if let Some(error) = &self.error {
-                // Modern error display
-                ui.add_space(20.0);
-                ui.horizontal(|ui| {
-                    ui.add_space(20.0);
-                    egui::Frame::none()
-                        .fill(Color32::from_rgb(220, 53, 69))
-                        .rounding(Rounding::same(8.0))
-                        .inner_margin(Margin::same(16.0))
-                        .show(ui, |ui| {
-                            ui.horizontal(|ui| {
-                                ui.label(egui::RichText::new("⚠️").size(20.0));
-                                ui.label(
-                                    egui::RichText::new(error).color(Color32::WHITE).size(14.0),
-                                );
-                            });
-                        });
-                });
-            } else if self.file_path.is_none() {
-                self.render_welcome_screen(ui);
-            } else {
-                self.render_data_table(ui);
-            }
-        });
-    }
-
-    fn render_welcome_screen(&self, ui: &mut egui::Ui) {
-        ui.vertical_centered(|ui| {
-            ui.add_space(100.0);
-
-            // Welcome card
-            egui::Frame::none()
-                .fill(Color32::from_rgb(48, 50, 54))
-                .rounding(Rounding::same(16.0))
-                .inner_margin(Margin::same(40.0))
-
-                .show(ui, |ui| {
-                    ui.vertical_centered(|ui| {
-                        ui.label(egui::RichText::new("📊").size(64.0));
-
-                        ui.add_space(16.0);
-
-                        ui.label(
-                            egui::RichText::new("Excel & CSV Reader")
-                                .size(24.0)
-                                .color(Color32::from_rgb(66, 133, 244)),
-                        );
-
-                        ui.add_space(8.0);
-
-                        ui.label(
-                            egui::RichText::new("Click 'Open File' to load your spreadsheet")
-                                .size(14.0)
-                                .color(Color32::from_rgb(156, 163, 175)),
-                        );
-
-                        ui.add_space(16.0);
-
-                        ui.horizontal(|ui| {
-                            ui.label(
-                                egui::RichText::new("Supports:")
-                                    .size(12.0)
-                                    .color(Color32::from_rgb(120, 120, 120)),
-                            );
-                            ui.label(
-                                egui::RichText::new("CSV • XLSX • XLS • ODS")
-                                    .size(12.0)
-                                    .color(Color32::from_rgb(76, 175, 80)),
-                            );
-                        });
-                    });
-                });
-        });
-    }
-
-    fn render_data_table(&mut self, ui: &mut egui::Ui) {
-        // Add padding around the table
-        ui.add_space(16.0);
-
-        // Modern table container
-        egui::Frame::none()
-            .fill(Color32::from_rgb(40, 42, 46))
-            .rounding(Rounding::same(12.0))
-            .inner_margin(Margin::same(16.0))
-            .show(ui, |ui| {
-                ScrollArea::both()
-                    .auto_shrink([false, false])
-                    .show(ui, |ui| {
-                        let grid = self.render_table_grid(ui);
-                        self.handle_lazy_loading(ui, &grid);
-                    });
-            });
-
-        ui.add_space(16.0);
-        self.render_status_message(ui);
-    }
-
-    fn render_table_grid(&self, ui: &mut egui::Ui) -> egui::InnerResponse<()> {
-        egui::Grid::new("table_grid")
-            .striped(false) // We'll handle striping manually for better control
-            .min_col_width(80.0)
-            .max_col_width(250.0)
-            .spacing([1.0, 1.0])
-            .show(ui, |ui| {
-                self.render_table_headers(ui);
-                self.render_table_rows(ui);
-            })
-    }
-
-    fn render_table_headers(&self, ui: &mut egui::Ui) {
-        if !self.headers.is_empty() {
-            // Index header with special styling - more compact
-            egui::Frame::none()
-                .fill(Color32::from_rgb(66, 133, 244))
-                .rounding(Rounding::same(4.0))
-                .inner_margin(Margin::symmetric(8.0, 4.0))
-                .show(ui, |ui| {
-                    ui.label(
-                        egui::RichText::new("#")
-                            .color(Color32::WHITE)
-                            .strong()
-                            .size(12.0),
-                    );
-                });
-
-            // Column headers - more compact
-            for header in &self.headers {
-                egui::Frame::none()
-                    .fill(Color32::from_rgb(76, 175, 80))
-                    .rounding(Rounding::same(4.0))
-                    .inner_margin(Margin::symmetric(8.0, 4.0))
-                    .show(ui, |ui| {
-                        ui.label(
-                            egui::RichText::new(header)
-                                .color(Color32::WHITE)
-                                .strong()
-                                .size(12.0),
-                        );
-                    });
-            }
-            ui.end_row();
-        }
-    }
-
-    fn render_table_rows(&self, ui: &mut egui::Ui) {
-        for (row_index, row) in self.table.iter().enumerate() {
-            let is_even = row_index % 2 == 0;
-            let row_bg = if is_even {
-                Color32::from_rgb(48, 50, 54)
-            } else {
-                Color32::from_rgb(44, 46, 50)
-            };
-
-            // Row index cell - more compact
-            egui::Frame::none()
-                .fill(row_bg)
-                .rounding(Rounding::same(3.0))
-                .inner_margin(Margin::symmetric(6.0, 3.0))
-                .show(ui, |ui| {
-                    ui.label(
-                        egui::RichText::new((row_index + 1).to_string())
-                            .color(Color32::from_rgb(156, 163, 175))
-                            .size(11.0)
-                            .monospace(),
-                    );
-                });
-
-            // Data cells - more compact
-            for (col_index, cell) in row.iter().enumerate() {
-                let cell_bg = if col_index % 2 == 0 {
-                    row_bg
-                } else {
-                    Color32::from_rgb(
-                        row_bg.r().saturating_add(4),
-                        row_bg.g().saturating_add(4),
-                        row_bg.b().saturating_add(4),
-                    )
-                };
-
-                egui::Frame::none()
-                    .fill(cell_bg)
-                    .rounding(Rounding::same(3.0))
-                    .inner_margin(Margin::symmetric(6.0, 3.0))
-                    .show(ui, |ui| {
-                        // Truncate long text and add tooltip (Unicode-safe)
-                        let display_text = if cell.chars().count() > 50 {
-                            let truncated: String = cell.chars().take(47).collect();
-                            format!("{}...", truncated)
-                        } else {
-                            cell.clone()
-                        };
-
-                        let label = ui.label(
-                            egui::RichText::new(&display_text)
-                                .color(Color32::from_rgb(220, 220, 220))
-                                .size(12.0),
-                        );
-
-                        // Show full text on hover if truncated
-                        if cell.len() > 50 {
-                            label.on_hover_text(cell);
-                        }
-                    });
-            }
-            ui.end_row();
-        }
-    }
-
-    fn handle_lazy_loading(&mut self, ui: &mut egui::Ui, grid: &egui::InnerResponse<()>) {
-        if ui.clip_rect().bottom() >= grid.response.rect.bottom() - 10.0
-            && !self.loading
-            && !self.end_of_file
-        {
-            self.rows_to_show += 100;
-            self.load_more_data();
-        }
-    }
-
-    fn render_status_message(&self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.add_space(20.0);
-
-            if self.loading {
-                // Modern loading indicator
-                egui::Frame::none()
-                    .fill(Color32::from_rgb(66, 133, 244))
-                    .rounding(Rounding::same(20.0))
-                    .inner_margin(Margin::symmetric(16.0, 8.0))
-                    .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            ui.spinner();
-                            ui.label(
-                                egui::RichText::new("Loading more data...")
-                                    .color(Color32::WHITE)
-                                    .size(13.0),
-                            );
-                        });
-                    });
-            } else if self.end_of_file && !self.table.is_empty() {
-                // Modern end-of-file indicator
-                egui::Frame::none()
-                    .fill(Color32::from_rgb(76, 175, 80))
-                    .rounding(Rounding::same(20.0))
-                    .inner_margin(Margin::symmetric(16.0, 8.0))
-                    .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            ui.label(egui::RichText::new("✅").size(14.0));
-                            ui.label(
-                                egui::RichText::new("All data loaded")
-                                    .color(Color32::WHITE)
-                                    .size(13.0),
-                            );
-                        });
-                    });
-            }
-        });
-    }
-
-    fn render_footer(&self, ctx: &Context) {
-        if self.file_path.is_some() {
-            TopBottomPanel::bottom("footer_panel")
-                .exact_height(35.0)
-                .show(ctx, |ui| {
-                    ui.add_space(6.0);
-                    ui.horizontal(|ui| {
-                        ui.add_space(16.0);
-                        
-                        // File information in footer
-                        if let Some(path) = &self.file_path {
-                            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
-                            let extension = path
-                                .extension()
-                                .unwrap_or_default()
-                                .to_string_lossy()
-                                .to_uppercase();
-
-                            let file_icon = match extension.as_str() {
-                                "CSV" => "📊",
-                                "XLSX" | "XLS" => "📈",
-                                "ODS" => "📋",
-                                _ => "📄",
-                            };
-
-                            // File name and icon
-                            ui.label(
-                                egui::RichText::new(format!("{} {}", file_icon, file_name))
-                                    .color(Color32::from_rgb(200, 200, 200))
-                                    .size(12.0),
-                            );
-
-                            ui.separator();
-
-                            // Sheet information
-                            if self.is_excel_file && !self.sheet_names.is_empty() {
-                                ui.label(
-                                    egui::RichText::new(format!("Sheet: {}", &self.sheet_names[self.current_sheet]))
-                                        .color(Color32::from_rgb(156, 163, 175))
-                                        .size(12.0),
-                                );
-                                ui.separator();
-                                
-                                ui.label(
-                                    egui::RichText::new(format!("{} sheets total", self.sheet_names.len()))
-                                        .color(Color32::from_rgb(120, 120, 120))
-                                        .size(12.0),
-                                );
-                                ui.separator();
-                            }
-
-                            // Row count
-                            if !self.table.is_empty() {
-                                let status_text = if self.end_of_file {
-                                    format!("{} rows (complete)", self.table.len())
-                                } else {
-                                    format!("{} rows (loading...)", self.table.len())
-                                };
-                                
-                                ui.label(
-                                    egui::RichText::new(status_text)
-                                        .color(Color32::from_rgb(120, 120, 120))
-                                        .size(12.0),
-                                );
-                            }
-                        }
-
-                        // Push loading status to the right
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            ui.add_space(16.0);
-                            
-                            if self.loading {
-                                ui.horizontal(|ui| {
-                                    ui.spinner();
-                                    ui.label(
-                                        egui::RichText::new("Loading...")
-                                            .color(Color32::from_rgb(66, 133, 244))
-                                            .size(11.0),
-                                    );
-                                });
-                            } else if self.end_of_file && !self.table.is_empty() {
-                                ui.label(
-                                    egui::RichText::new("✅ Complete")
-                                        .color(Color32::from_rgb(76, 175, 80))
-                                        .size(11.0),
-                                );
-                            }
-                        });
-                    });
-                    ui.add_space(6.0);
-                });
-        }
-    }
-}
+use eframe::{egui, App, CreationContext, Frame};
+use egui::{
+    CentralPanel, Color32, Context, Margin, Rounding, ScrollArea, Stroke, TopBottomPanel,
+    Vec2,
+};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use rust_excel_reader_core::archive::{
+    extract_archive_entry, is_archive, list_archive_entries, ExtractedEntry,
+};
+use rust_excel_reader_core::csv_loader::Tolerance;
+use rust_excel_reader_core::data_loader::spawn_task;
+use rust_excel_reader_core::edits::{Edit, EditOverlay, RowId, END};
+use rust_excel_reader_core::fixed_width_loader::{find_profile, read_preview_lines};
+use rust_excel_reader_core::formatting::header_signature;
+use rust_excel_reader_core::pretty::is_structured;
+use rust_excel_reader_core::progress::{Progress, ProgressEvent};
+use rust_excel_reader_core::row_count::RowCount;
+use rust_excel_reader_core::save::{can_save, row_references, save_sheet, RowReference};
+use rust_excel_reader_core::sheet_view::SheetView;
+use rust_excel_reader_core::table_reader::SheetSource;
+use rust_excel_reader_core::values::SortOrder;
+use rust_excel_reader_core::{
+    is_csv, is_fixed_width, is_pseudo_sheet, ColumnType, DataLoadResult, DataLoader, FileWatcher,
+    LoadOptions,
+};
+use crate::cell_viewer::{CellViewer, CellViewerAction};
+use crate::computed_column_dialog::{ComputedColumnAction, ComputedColumnDialog};
+use crate::derived_table::{CellMark, DerivedTable};
+use crate::diff_panel::DiffPanel;
+use crate::fixed_width_dialog::{FixedWidthAction, FixedWidthDialog};
+use crate::folder_dialog::{FolderAction, FolderDialog};
+use crate::font_setup::{apply_text_size, setup_custom_fonts, FontReport};
+use crate::format_panel::FormatPanel;
+use crate::goto_dialog::{GotoAction, GotoDialog};
+use crate::i18n::{self, tr, Language};
+use crate::merge_panel::MergePanel;
+use crate::parse_issues_panel::{ParseIssuesAction, ParseIssuesPanel};
+use crate::quality_panel::{QualityAction, QualityPanel, QualitySource};
+use crate::pivot_panel::{PivotPanel, PivotSource};
+use crate::query_console::QueryConsole;
+use crate::row_inspector::{InspectedRow, RowInspector};
+use crate::settings::Settings;
+use crate::table_grid::{
+    self, CellEditor, CellStyle, ColumnLayout, EditorOutcome, GridEvents, GridOptions, RowAction,
+};
+use crate::theme::{self, Palette, ThemeMode, ThemeSettings};
+
+/// Lines of a fixed-width file shown in the import dialog.
+const FIXED_WIDTH_PREVIEW_LINES: usize = 30;
+
+/// A go-to target less than this many rows past the loaded ones is reached
+/// by loading the rows in between; farther ones replace the loaded rows
+/// with a page around the target.
+const GOTO_STREAM_ROWS: usize = 5_000;
+/// Rows loaded above a go-to target when the loaded rows are replaced.
+const GOTO_CONTEXT_ROWS: usize = 20;
+/// Rows loaded above the cursor when it moves up out of a go-to window.
+const PREVIOUS_PAGE_ROWS: usize = 80;
+/// How often progress bars are redrawn while a background task runs.
+const PROGRESS_REFRESH: Duration = Duration::from_millis(100);
+
+/// What to do when the open file changes on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WatchMode {
+    /// Only show the "changed on disk" indicator.
+    Off,
+    /// Reload the loaded rows, keeping the scroll position.
+    Reload,
+    /// Append new rows and keep the view at the bottom, like `tail -f`.
+    Tail,
+}
+
+impl WatchMode {
+    fn label(self) -> String {
+        match self {
+            WatchMode::Off => tr!("watch-off"),
+            WatchMode::Reload => tr!("watch-reload"),
+            WatchMode::Tail => tr!("watch-tail"),
+        }
+    }
+}
+
+pub struct MyApp {
+    table: Vec<Vec<String>>,
+    /// Which row of the sheet each row of `table` is.
+    row_ids: Vec<RowId>,
+    /// Rows read from the file so far; pages continue from here. Differs
+    /// from `table.len()` once rows are inserted or deleted.
+    source_rows: usize,
+    /// Source row of the first loaded row. Not 0 after a go-to jumped past
+    /// the loaded rows, which loads the rows around the target instead.
+    first_row: usize,
+    headers: Vec<String>,
+    column_types: Vec<ColumnType>,
+    error: Option<String>,
+    file_path: Option<PathBuf>,
+    tx: mpsc::Sender<DataLoadResult>,
+    rx: mpsc::Receiver<DataLoadResult>,
+    egui_ctx: Context,
+    loading: bool,
+    rows_to_show: usize,
+    headers_loaded: bool,
+    sheet_names: Vec<String>,
+    current_sheet: usize,
+    is_excel_file: bool,
+    end_of_file: bool,
+    /// Data rows of the sheet, from the background row count.
+    total_rows: Option<usize>,
+    counting: bool,
+    count_tx: mpsc::Sender<RowCount>,
+    count_rx: mpsc::Receiver<RowCount>,
+    data_loader: DataLoader,
+    watcher: Option<FileWatcher>,
+    change_tx: mpsc::Sender<()>,
+    change_rx: mpsc::Receiver<()>,
+    watch_mode: WatchMode,
+    file_changed: bool,
+    reloading: bool,
+    scroll_to_bottom: bool,
+    /// Set while the user picks an entry of a zip archive.
+    archive_picker: Option<(PathBuf, Vec<String>)>,
+    /// The archive the open file was extracted from, if any.
+    archive_path: Option<PathBuf>,
+    /// Temporary copy of the open archive entry, deleted with it.
+    extracted: Option<ExtractedEntry>,
+    /// An entry extracted for a file that is not open yet, e.g. while its
+    /// fixed-width layout is being chosen.
+    pending_extracted: Option<ExtractedEntry>,
+    fixed_width_dialog: Option<FixedWidthDialog>,
+    folder_dialog: Option<FolderDialog>,
+    settings: Settings,
+    /// Fonts in use, shown in the preferences menu.
+    font_report: FontReport,
+    /// Theme settings and system theme the style was last built for.
+    applied_theme: Option<(ThemeSettings, Option<bool>)>,
+    query_console: QueryConsole,
+    pivot_panel: PivotPanel,
+    diff_panel: DiffPanel,
+    merge_panel: MergePanel,
+    format_panel: FormatPanel,
+    row_inspector: RowInspector,
+    parse_issues: ParseIssuesPanel,
+    quality_panel: QualityPanel,
+    column_layout: ColumnLayout,
+    /// Query results and other computed tables, each shown in a tab.
+    derived_tables: Vec<DerivedTable>,
+    /// Index into `derived_tables`, or `None` when the file itself is shown.
+    active_tab: Option<usize>,
+    /// Outcome of the last export, shown in the tab bar.
+    export_status: Option<String>,
+    /// Computed columns, filter and sort of the file view.
+    view: SheetView,
+    /// Indices into `table` in display order when the view filters or
+    /// sorts; `None` shows every loaded row in file order.
+    view_rows: Option<Vec<usize>>,
+    filter_text: String,
+    view_error: Option<String>,
+    computed_column_dialog: Option<ComputedColumnDialog>,
+    view_export_tx: mpsc::Sender<Result<(PathBuf, usize), String>>,
+    view_export_rx: mpsc::Receiver<Result<(PathBuf, usize), String>>,
+    view_exporting: bool,
+    export_progress: Progress,
+    /// Unsaved edits of the open sheet.
+    edits: EditOverlay,
+    cell_editor: Option<CellEditor>,
+    cell_viewer: Option<CellViewer>,
+    /// The cell under the keyboard cursor, as (display index, column).
+    cursor: Option<(usize, usize)>,
+    scroll_to_cursor: bool,
+    /// Rows that fit in the table, the step of PageUp / PageDown.
+    page_rows: usize,
+    goto_dialog: Option<GotoDialog>,
+    /// Source row (and column) to put the cursor on once it is loaded.
+    goto_target: Option<(usize, Option<usize>)>,
+    /// Source rows found by a background key search or row count.
+    goto_tx: mpsc::Sender<Result<Option<usize>, String>>,
+    goto_rx: mpsc::Receiver<Result<Option<usize>, String>>,
+    /// Set while a search or count runs, with the column to put the cursor
+    /// on.
+    goto_search: Option<Option<usize>>,
+    /// Why the last go-to target could not be reached.
+    goto_message: Option<String>,
+    goto_progress: Progress,
+    save_tx: mpsc::Sender<Result<(), String>>,
+    save_rx: mpsc::Receiver<Result<(), String>>,
+    saving: bool,
+    /// Why rows cannot be inserted or deleted in the open sheet.
+    row_lock: Option<String>,
+    row_lock_tx: mpsc::Sender<RowReferenceCheck>,
+    row_lock_rx: mpsc::Receiver<RowReferenceCheck>,
+}
+
+/// Where a cursor key moves the cursor, in display rows.
+enum RowMove {
+    To(usize),
+    /// Up by a number of rows, possibly past the top of a go-to window.
+    Up(usize),
+    /// The first or last row of the sheet, which may not be loaded.
+    First,
+    Last,
+}
+
+/// What refers to the rows of a sheet by number, found in the background.
+type RowReferenceCheck = (PathBuf, usize, Result<Vec<RowReference>, String>);
+
+impl MyApp {
+    pub fn new(cc: &CreationContext) -> Self {
+        let settings = Settings::load(cc.storage);
+        i18n::set_language(settings.language);
+        let font_report = setup_custom_fonts(&cc.egui_ctx, &settings.font);
+
+        let (tx, rx) = mpsc::channel();
+        let (change_tx, change_rx) = mpsc::channel();
+        let (view_export_tx, view_export_rx) = mpsc::channel();
+        let (save_tx, save_rx) = mpsc::channel();
+        let (goto_tx, goto_rx) = mpsc::channel();
+        let (count_tx, count_rx) = mpsc::channel();
+        let (row_lock_tx, row_lock_rx) = mpsc::channel();
+        Self {
+            table: Vec::new(),
+            row_ids: Vec::new(),
+            source_rows: 0,
+            first_row: 0,
+            headers: Vec::new(),
+            column_types: Vec::new(),
+            error: None,
+            file_path: None,
+            tx,
+            rx,
+            egui_ctx: cc.egui_ctx.clone(),
+            loading: false,
+            rows_to_show: 100,
+            headers_loaded: false,
+            sheet_names: Vec::new(),
+            current_sheet: 0,
+            is_excel_file: false,
+            end_of_file: false,
+            total_rows: None,
+            counting: false,
+            count_tx,
+            count_rx,
+            data_loader: DataLoader::new(),
+            watcher: None,
+            change_tx,
+            change_rx,
+            watch_mode: WatchMode::Off,
+            file_changed: false,
+            reloading: false,
+            scroll_to_bottom: false,
+            archive_picker: None,
+            archive_path: None,
+            extracted: None,
+            pending_extracted: None,
+            fixed_width_dialog: None,
+            folder_dialog: None,
+            settings,
+            font_report,
+            applied_theme: None,
+            query_console: QueryConsole::new(),
+            pivot_panel: PivotPanel::new(),
+            diff_panel: DiffPanel::new(),
+            merge_panel: MergePanel::new(),
+            format_panel: FormatPanel::new(),
+            row_inspector: RowInspector::new(),
+            parse_issues: ParseIssuesPanel::new(),
+            quality_panel: QualityPanel::new(),
+            column_layout: ColumnLayout::default(),
+            derived_tables: Vec::new(),
+            active_tab: None,
+            export_status: None,
+            view: SheetView::default(),
+            view_rows: None,
+            filter_text: String::new(),
+            view_error: None,
+            computed_column_dialog: None,
+            view_export_tx,
+            view_export_rx,
+            view_exporting: false,
+            export_progress: Progress::new(),
+            edits: EditOverlay::default(),
+            cell_editor: None,
+            cell_viewer: None,
+            cursor: None,
+            scroll_to_cursor: false,
+            page_rows: 20,
+            goto_dialog: None,
+            goto_target: None,
+            goto_tx,
+            goto_rx,
+            goto_search: None,
+            goto_message: None,
+            goto_progress: Progress::new(),
+            save_tx,
+            save_rx,
+            saving: false,
+            row_lock: None,
+            row_lock_tx,
+            row_lock_rx,
+        }
+    }
+
+    fn open_file(&mut self) {
+        let file = rfd::FileDialog::new()
+            .add_filter(tr!("filter-spreadsheet"), &["csv", "xlsx", "xls", "ods"])
+            .add_filter(tr!("filter-columnar"), &["parquet", "arrow", "feather"])
+            .add_filter("JSON", &["json", "ndjson", "jsonl"])
+            .add_filter("SQLite", &["db", "sqlite", "sqlite3"])
+            .add_filter(tr!("filter-fixed-width"), &["txt", "dat", "prn", "fwf"])
+            .add_filter(tr!("filter-compressed"), &["gz", "zst", "bz2"])
+            .add_filter(tr!("filter-archive"), &["zip"])
+            .pick_file();
+
+        if let Some(path) = file {
+            if is_archive(&path) {
+                self.open_archive(path);
+            } else {
+                self.open_path(path, None);
+            }
+        }
+    }
+
+    fn open_folder(&mut self) {
+        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+            self.folder_dialog = Some(FolderDialog::new(dir));
+        }
+    }
+
+    /// Opens `path`, first asking for a column layout if it is a
+    /// fixed-width file without a matching saved profile.
+    fn open_path(&mut self, path: PathBuf, archive: Option<PathBuf>) {
+        let mut options = LoadOptions {
+            tolerance: self.settings.csv_tolerance,
+            ..LoadOptions::default()
+        };
+
+        if is_fixed_width(&path) {
+            let lines = match read_preview_lines(&path, FIXED_WIDTH_PREVIEW_LINES) {
+                Ok(lines) => lines,
+                Err(e) => {
+                    self.reset_state();
+                    self.error = Some(e);
+                    return;
+                }
+            };
+
+            let header_line = lines.first().map(String::as_str).unwrap_or_default();
+            match find_profile(&self.settings.fixed_width_profiles, header_line) {
+                Some(profile) => options.fixed_width = Some(profile.layout.clone()),
+                None => {
+                    self.fixed_width_dialog =
+                        Some(FixedWidthDialog::new(path, archive, lines, None));
+                    return;
+                }
+            }
+        }
+
+        self.start_loading(path, archive, options);
+    }
+
+    fn start_loading(&mut self, path: PathBuf, archive: Option<PathBuf>, options: LoadOptions) {
+        self.reset_state();
+        // Keep the extracted copy of the file being opened, which may be
+        // open already, and delete any other
+        let pending = self.pending_extracted.take().filter(|entry| entry.path() == path);
+        let current = self.extracted.take().filter(|entry| entry.path() == path);
+        self.extracted = pending.or(current);
+        self.data_loader.set_options(options);
+        self.start_watching(&path);
+        self.file_path = Some(path);
+        self.archive_path = archive;
+        self.load_more_data();
+        self.count_rows();
+    }
+
+    /// Reopens the import dialog for the open fixed-width file.
+    fn edit_fixed_width_layout(&mut self) {
+        let Some(path) = self.file_path.clone() else {
+            return;
+        };
+
+        match read_preview_lines(&path, FIXED_WIDTH_PREVIEW_LINES) {
+            Ok(lines) => {
+                let layout = self.data_loader.options().fixed_width.clone();
+                self.fixed_width_dialog = Some(FixedWidthDialog::new(
+                    path,
+                    self.archive_path.clone(),
+                    lines,
+                    layout,
+                ));
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn open_archive(&mut self, archive: PathBuf) {
+        match list_archive_entries(&archive) {
+            Ok(entries) if entries.is_empty() => {
+                self.reset_state();
+                self.error = Some(tr!("archive-empty"));
+            }
+            Ok(entries) if entries.len() == 1 => self.open_archive_entry(archive, &entries[0]),
+            Ok(entries) => self.archive_picker = Some((archive, entries)),
+            Err(e) => {
+                self.reset_state();
+                self.error = Some(i18n::load_error(&e));
+            }
+        }
+    }
+
+    fn open_archive_entry(&mut self, archive: PathBuf, entry: &str) {
+        match extract_archive_entry(&archive, entry) {
+            Ok(extracted) => {
+                let path = extracted.path().to_path_buf();
+                self.pending_extracted = Some(extracted);
+                self.open_path(path, Some(archive));
+            }
+            Err(e) => {
+                self.reset_state();
+                self.error = Some(i18n::load_error(&e));
+            }
+        }
+    }
+
+    fn start_watching(&mut self, path: &std::path::Path) {
+        let tx = self.change_tx.clone();
+        let egui_ctx = self.egui_ctx.clone();
+        // A file that cannot be watched is still readable, so only the
+        // auto-refresh feature is lost.
+        self.watcher = FileWatcher::watch(path, move || {
+            let _ = tx.send(());
+            egui_ctx.request_repaint();
+        })
+        .ok();
+    }
+
+    fn reset_state(&mut self) {
+        self.first_row = 0;
+        self.clear_rows();
+        self.edits = EditOverlay::default();
+        self.headers.clear();
+        self.column_types.clear();
+        self.headers_loaded = false;
+        self.sheet_names.clear();
+        self.current_sheet = 0;
+        self.is_excel_file = false;
+        self.error = None;
+        self.end_of_file = false;
+        self.total_rows = None;
+        self.counting = false;
+        self.rows_to_show = 100;
+        self.watcher = None;
+        self.file_changed = false;
+        self.reloading = false;
+        self.scroll_to_bottom = false;
+        self.archive_path = None;
+        self.format_panel.clear();
+        self.row_inspector.clear();
+        self.parse_issues.clear();
+        self.quality_panel.clear();
+        self.row_lock = None;
+        self.cursor = None;
+        self.goto_dialog = None;
+        self.goto_target = None;
+        self.goto_message = None;
+        self.reset_view();
+        while self.change_rx.try_recv().is_ok() {}
+    }
+
+    /// Drops the loaded rows, e.g. before a reload; loading starts again at
+    /// `first_row`.
+    fn clear_rows(&mut self) {
+        self.table.clear();
+        self.row_ids.clear();
+        self.source_rows = self.first_row;
+        self.cell_editor = None;
+        self.cell_viewer = None;
+        self.parse_issues.clear_loaded();
+    }
+
+    /// Replaces the loaded rows with the rows from source row `start` on.
+    fn load_window(&mut self, start: usize) {
+        self.first_row = start;
+        self.clear_rows();
+        self.cursor = None;
+        self.end_of_file = false;
+        self.rows_to_show = start + 100;
+        self.refresh_view_rows();
+        self.load_more_data();
+    }
+
+    /// Drops computed columns, filter and sort, e.g. for another sheet whose
+    /// columns they would not fit.
+    fn reset_view(&mut self) {
+        self.view = SheetView::default();
+        self.view_rows = None;
+        self.filter_text.clear();
+        self.view_error = None;
+        self.computed_column_dialog = None;
+        self.column_layout = ColumnLayout::default();
+    }
+
+    /// Recomputes the computed cells of every loaded row after the computed
+    /// columns changed. `had_computed` tells whether the rows carry cells of
+    /// the previous computed columns that must be dropped first.
+    fn reapply_view(&mut self, had_computed: bool) {
+        let base_width = self.headers.len();
+        for row in &mut self.table {
+            if had_computed {
+                row.truncate(base_width);
+            }
+            self.view.apply(row, base_width);
+        }
+        self.refresh_view_rows();
+    }
+
+    /// Recomputes which loaded rows pass the filter and in which order.
+    fn refresh_view_rows(&mut self) {
+        if self.view.filter_source().is_none() && self.view.sort.is_none() {
+            self.view_rows = None;
+            return;
+        }
+
+        let mut indices: Vec<usize> = (0..self.table.len())
+            .filter(|&index| self.view.matches(&self.table[index]))
+            .collect();
+        if let Some(order) = self.view.sort {
+            indices.sort_by(|&a, &b| order.compare(&self.table[a], &self.table[b]));
+        }
+        self.view_rows = Some(indices);
+    }
+
+    fn add_computed_column(&mut self, name: &str, source: &str) -> Result<(), String> {
+        let had_computed = !self.view.computed_columns().is_empty();
+        self.view.add_computed_column(&self.headers, name, source)?;
+        self.reapply_view(had_computed);
+        Ok(())
+    }
+
+    fn remove_computed_column(&mut self, index: usize) -> Result<(), String> {
+        self.view.remove_computed_column(&self.headers, index)?;
+        self.reapply_view(true);
+        Ok(())
+    }
+
+    fn apply_filter(&mut self) {
+        match self.view.set_filter(&self.headers, &self.filter_text) {
+            Ok(()) => self.view_error = None,
+            Err(e) => self.view_error = Some(e),
+        }
+        self.refresh_view_rows();
+    }
+
+    /// Writes the whole sheet with the view applied in the background.
+    fn export_view(&mut self) {
+        let Some(path) = self.file_path.clone() else {
+            return;
+        };
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let Some(target) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name(format!("{}_view.csv", stem))
+            .save_file()
+        else {
+            return;
+        };
+
+        self.view_exporting = true;
+        self.export_status = None;
+        self.export_progress = Progress::new();
+        if let Some(total) = self.total_rows {
+            self.export_progress.set_total_rows(total);
+        }
+        let progress = self.export_progress.clone();
+        let view = self.view.clone();
+        let sheet_index = self.current_sheet;
+        let options = self.data_loader.options().clone();
+        let edits = self.edits.clone();
+        let egui_ctx = self.egui_ctx.clone();
+        spawn_task(
+            move || {
+                view.export_sheet(&path, sheet_index, &options, &edits, &target, &progress)
+                    .map(|count| (target, count))
+            },
+            self.view_export_tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_view_export(&mut self) {
+        if let Ok(result) = self.view_export_rx.try_recv() {
+            self.view_exporting = false;
+            self.export_status = Some(match result {
+                Ok((path, count)) => tr!(
+                    "saved-rows",
+                    count = count,
+                    path = path.display().to_string()
+                ),
+                Err(e) => format!("⚠ {}", e),
+            });
+        }
+    }
+
+    fn load_more_data(&mut self) {
+        if self.loading || self.file_path.is_none() || self.end_of_file {
+            return;
+        }
+
+        self.loading = true;
+        let path = self.file_path.as_ref().unwrap().clone();
+        let start_row = self.source_rows;
+        let num_rows = self.rows_to_show.saturating_sub(self.source_rows).max(1);
+        let sheet_index = self.current_sheet;
+
+        let egui_ctx = self.egui_ctx.clone();
+        self.data_loader.load_data_async(
+            path,
+            start_row,
+            num_rows,
+            sheet_index,
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    /// Re-reads the rows that are currently loaded without clearing the
+    /// table first, so the scroll position survives the refresh.
+    fn reload_data(&mut self) {
+        if self.loading || self.file_path.is_none() {
+            return;
+        }
+
+        self.loading = true;
+        self.reloading = true;
+        self.file_changed = false;
+        let path = self.file_path.as_ref().unwrap().clone();
+        let num_rows = (self.source_rows - self.first_row).max(100);
+        self.rows_to_show = self.first_row + num_rows;
+
+        let egui_ctx = self.egui_ctx.clone();
+        self.data_loader.load_data_async(
+            path,
+            self.first_row,
+            num_rows,
+            self.current_sheet,
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+        self.count_rows();
+    }
+
+    /// Loads rows appended since the last page and scrolls to them.
+    fn follow_tail(&mut self) {
+        if self.loading {
+            return;
+        }
+
+        self.file_changed = false;
+        self.end_of_file = false;
+        self.rows_to_show = self.source_rows + 100;
+        self.load_more_data();
+        self.count_rows();
+    }
+
+    /// Counts the rows of the open sheet in the background, for the
+    /// footer and for going to the last row.
+    fn count_rows(&mut self) {
+        let Some(path) = self.file_path.clone() else {
+            return;
+        };
+        self.counting = true;
+        let egui_ctx = self.egui_ctx.clone();
+        self.data_loader.count_rows_async(
+            path,
+            self.current_sheet,
+            self.count_tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_row_count(&mut self) {
+        while let Ok(count) = self.count_rx.try_recv() {
+            // Counts of a file or sheet that is no longer shown are dropped
+            if self.file_path.as_ref() != Some(&count.path)
+                || count.sheet_index != self.current_sheet
+            {
+                continue;
+            }
+            self.counting = false;
+            self.total_rows = count.rows.ok();
+        }
+    }
+
+    /// Looks in the background for what refers to rows of the open XLSX
+    /// sheet by number, which locks inserting and deleting rows.
+    fn check_row_references(&mut self) {
+        let Some(path) = self.file_path.clone() else {
+            return;
+        };
+        let xlsx = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("xlsx"));
+        if !xlsx || !self.can_save_file() {
+            return;
+        }
+        let sheet_index = self.current_sheet;
+        let egui_ctx = self.egui_ctx.clone();
+        spawn_task(
+            move || {
+                let references = row_references(&path, sheet_index);
+                (path, sheet_index, references)
+            },
+            self.row_lock_tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_row_references(&mut self) {
+        while let Ok((path, sheet_index, references)) = self.row_lock_rx.try_recv() {
+            if self.file_path.as_ref() != Some(&path) || sheet_index != self.current_sheet {
+                continue;
+            }
+            self.row_lock = match references {
+                Ok(references) if references.is_empty() => None,
+                Ok(references) => {
+                    let parts: Vec<String> =
+                        references.into_iter().map(row_reference_label).collect();
+                    Some(tr!("row-lock-references", parts = parts.join(", ")))
+                }
+                Err(e) => Some(tr!("row-lock-unknown", detail = e)),
+            };
+        }
+    }
+
+    fn handle_file_changes(&mut self) {
+        while self.change_rx.try_recv().is_ok() {
+            self.file_changed = true;
+        }
+
+        if !self.file_changed || self.loading {
+            return;
+        }
+
+        match self.watch_mode {
+            WatchMode::Off => {}
+            WatchMode::Reload => self.reload_data(),
+            WatchMode::Tail => self.follow_tail(),
+        }
+    }
+
+    fn switch_sheet(&mut self, sheet_index: usize) {
+        if sheet_index != self.current_sheet && sheet_index < self.sheet_names.len() {
+            self.current_sheet = sheet_index;
+            self.first_row = 0;
+            self.clear_rows();
+            self.cursor = None;
+            self.goto_target = None;
+            self.edits = EditOverlay::default();
+            self.headers.clear();
+            self.column_types.clear();
+            self.headers_loaded = false;
+            self.end_of_file = false;
+            self.rows_to_show = 100;
+            self.total_rows = None;
+            self.quality_panel.clear();
+            self.row_lock = None;
+            self.reset_view();
+            self.load_more_data();
+            self.count_rows();
+        }
+    }
+
+    fn handle_data_response(&mut self) {
+        if let Ok(result) = self.rx.try_recv() {
+            self.loading = false;
+            match result {
+                Ok(page) => {
+                    if self.reloading {
+                        self.sheet_names.clear();
+                        self.headers_loaded = false;
+                        self.clear_rows();
+                    } else if self.watch_mode == WatchMode::Tail && !page.rows.is_empty() {
+                        self.scroll_to_bottom = true;
+                    }
+                    self.reloading = false;
+                    self.update_sheet_info(page.sheet_names);
+                    self.update_headers(page.headers, page.column_types);
+                    // Rows inserted past the loaded rows come again with the page
+                    let loaded = self.source_rows;
+                    while let Some(&id) = self.row_ids.last() {
+                        if self.edits.row_order(id).0 < loaded {
+                            break;
+                        }
+                        self.row_ids.pop();
+                        self.table.pop();
+                    }
+                    self.source_rows += page.rows.len();
+                    self.parse_issues.add_page(&page.parse_issues);
+                    // Computed cells are evaluated per page, as rows arrive
+                    let base_width = self.headers.len();
+                    for (id, mut row) in self.edits.page_rows(loaded, page.rows, page.end_of_file) {
+                        self.view.apply(&mut row, base_width);
+                        self.table.push(row);
+                        self.row_ids.push(id);
+                    }
+                    self.refresh_view_rows();
+                    self.end_of_file = page.end_of_file;
+                    self.seek_goto_target();
+                }
+                Err(e) => {
+                    self.reloading = false;
+                    self.goto_target = None;
+                    self.error = Some(i18n::load_error(&e));
+                }
+            }
+        }
+    }
+
+    fn handle_grid_events(&mut self, events: &GridEvents) {
+        if let Some(column) = events.sort_clicked {
+            self.view.sort = Some(SortOrder::toggled(self.view.sort, column));
+            self.refresh_view_rows();
+        }
+        match events.editor {
+            Some(EditorOutcome::Commit) => self.commit_cell_edit(),
+            Some(EditorOutcome::Cancel) => self.cell_editor = None,
+            None => {}
+        }
+        if let Some(cell) = events.cell_clicked {
+            self.cursor = Some(cell);
+        }
+        self.page_rows = events.page_rows;
+        if let Some((row, column)) = events.edit_requested {
+            self.start_cell_edit(row, column);
+        }
+        if let Some(cell) = events.view_requested {
+            self.open_cell_viewer(cell);
+        }
+        match events.row_action {
+            Some((row, RowAction::InsertAbove)) => self.insert_row(row),
+            Some((row, RowAction::InsertBelow)) => self.insert_row(row + 1),
+            Some((row, RowAction::Delete)) => self.delete_row(row),
+            None => {}
+        }
+    }
+
+    fn start_cell_edit(&mut self, row: usize, column: usize) {
+        self.commit_cell_edit();
+        self.cell_viewer = None;
+        let text = self.table[row].get(column).cloned().unwrap_or_default();
+        self.cell_editor = Some(CellEditor::new(row, column, text));
+    }
+
+    /// Shows a cell of the file view in full, as (row index, column); the
+    /// row index is into the loaded rows, not the sorted or filtered view.
+    fn open_cell_viewer(&mut self, (row, column): (usize, usize)) {
+        let Some(text) = self.table.get(row).and_then(|cells| cells.get(column)) else {
+            return;
+        };
+        let headers = self.view.headers(&self.headers);
+        let column_name = headers.get(column).cloned().unwrap_or_default();
+        // Numbered by the row in the file, as the row inspector does
+        let title = match self.row_ids[row] {
+            RowId::Source(source) => tr!("viewer-title", column = column_name, row = source + 1),
+            RowId::Inserted(_) => tr!("viewer-title-new", column = column_name),
+        };
+        let editable = column < self.headers.len() && !self.saving;
+        self.cell_viewer = Some(CellViewer::new((row, column), title, text.clone(), editable));
+    }
+
+    fn render_cell_viewer(&mut self, ctx: &Context) {
+        let Some(viewer) = &mut self.cell_viewer else {
+            return;
+        };
+        match viewer.show(ctx) {
+            CellViewerAction::None => {}
+            CellViewerAction::Close => self.cell_viewer = None,
+            CellViewerAction::Edit => {
+                let (row, column) = viewer.cell;
+                if self.active_tab.is_none() && row < self.table.len() {
+                    self.start_cell_edit(row, column);
+                } else {
+                    self.cell_viewer = None;
+                }
+            }
+        }
+    }
+
+    fn commit_cell_edit(&mut self) {
+        let Some(editor) = self.cell_editor.take() else {
+            return;
+        };
+        let old = self.table[editor.row]
+            .get(editor.column)
+            .cloned()
+            .unwrap_or_default();
+        if old != editor.text {
+            self.record_edit(Edit::SetCell {
+                row: self.row_ids[editor.row],
+                column: editor.column,
+                old,
+                new: editor.text,
+            });
+        }
+    }
+
+    /// Inserts an empty row so that it becomes row `index` of the table.
+    fn insert_row(&mut self, index: usize) {
+        // The row goes in front of the next row of the file, or at the end
+        let anchor = self.row_ids[index..]
+            .iter()
+            .find_map(|id| match id {
+                RowId::Source(source) => Some(*source),
+                RowId::Inserted(_) => None,
+            })
+            .unwrap_or(if self.end_of_file { END } else { self.source_rows });
+        let position = self.row_ids[..index]
+            .iter()
+            .rev()
+            .take_while(|&&id| self.edits.row_order(id) >= (anchor, 0, 0))
+            .count();
+        let row = self.edits.new_row_id();
+        self.record_edit(Edit::InsertRow {
+            row,
+            anchor,
+            position,
+            cells: vec![String::new(); self.headers.len()],
+        });
+    }
+
+    fn delete_row(&mut self, index: usize) {
+        let row = self.row_ids[index];
+        let (anchor, _, position) = self.edits.row_order(row);
+        let mut cells = self.table[index].clone();
+        cells.truncate(self.headers.len());
+        self.record_edit(Edit::DeleteRow {
+            row,
+            anchor,
+            position,
+            cells,
+        });
+    }
+
+    fn record_edit(&mut self, edit: Edit) {
+        self.edits.record(edit.clone());
+        self.show_edit(&edit);
+    }
+
+    fn undo(&mut self) {
+        self.cell_editor = None;
+        if let Some(edit) = self.edits.undo() {
+            self.show_edit(&edit);
+        }
+    }
+
+    fn redo(&mut self) {
+        self.cell_editor = None;
+        if let Some(edit) = self.edits.redo() {
+            self.show_edit(&edit);
+        }
+    }
+
+    /// Mirrors an edit already applied to `edits` in the loaded rows.
+    fn show_edit(&mut self, edit: &Edit) {
+        let base_width = self.headers.len();
+        match edit {
+            Edit::SetCell {
+                row, column, new, ..
+            } => {
+                let Some(index) = self.row_ids.iter().position(|id| id == row) else {
+                    return;
+                };
+                let cells = &mut self.table[index];
+                cells.truncate(base_width);
+                if cells.len() <= *column {
+                    cells.resize(column + 1, String::new());
+                }
+                cells[*column] = new.clone();
+                self.view.apply(cells, base_width);
+            }
+            Edit::InsertRow { row, cells, .. } => {
+                let key = self.edits.row_order(*row);
+                let index = self
+                    .row_ids
+                    .partition_point(|&id| self.edits.row_order(id) < key);
+                let mut cells = cells.clone();
+                self.view.apply(&mut cells, base_width);
+                self.table.insert(index, cells);
+                self.row_ids.insert(index, *row);
+                self.cell_editor = None;
+                self.cell_viewer = None;
+            }
+            Edit::DeleteRow { row, .. } => {
+                if let Some(index) = self.row_ids.iter().position(|id| id == row) {
+                    self.table.remove(index);
+                    self.row_ids.remove(index);
+                }
+                self.cell_editor = None;
+                self.cell_viewer = None;
+            }
+        }
+        self.refresh_view_rows();
+    }
+
+    /// Ctrl+Z / Ctrl+Y (or Ctrl+Shift+Z) outside of text fields, which
+    /// have their own undo.
+    fn handle_edit_shortcuts(&mut self, ctx: &Context) {
+        if self.active_tab.is_some() || self.saving || ctx.wants_keyboard_input() {
+            return;
+        }
+        let redo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+        let redo_shift = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::Z,
+        );
+        let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+        // The more specific shortcut first, as Ctrl+Z also matches Ctrl+Shift+Z
+        if ctx.input_mut(|i| i.consume_shortcut(&redo) || i.consume_shortcut(&redo_shift)) {
+            self.redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+            self.undo();
+        }
+    }
+
+    /// Arrow keys, PageUp / PageDown, Home / End and Ctrl+Home / Ctrl+End
+    /// move the cell cursor, Enter edits its cell (or shows it in the cell
+    /// viewer, as a double-click does), Shift+Enter always shows it and
+    /// Ctrl+G opens the go-to window.
+    fn handle_cursor_keys(&mut self, ctx: &Context) {
+        if self.active_tab.is_some()
+            || self.file_path.is_none()
+            || self.cell_editor.is_some()
+            || self.cell_viewer.is_some()
+            || ctx.wants_keyboard_input()
+        {
+            return;
+        }
+        let goto = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::G);
+        if ctx.input_mut(|i| i.consume_shortcut(&goto)) {
+            self.goto_dialog = Some(GotoDialog::new(&self.headers));
+            self.goto_message = None;
+        }
+
+        let columns = self.column_layout.visible_order();
+        let rows = self.view_rows.as_ref().map_or(self.table.len(), Vec::len);
+        if columns.is_empty() || rows == 0 {
+            return;
+        }
+        let (row, column) = self.cursor.unwrap_or((0, columns[0]));
+        let row = row.min(rows - 1);
+        let position = columns.iter().position(|&c| c == column).unwrap_or(0);
+        let page = self.page_rows.max(1);
+
+        let command = |key| egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, key);
+        let (first, last) = (command(egui::Key::Home), command(egui::Key::End));
+        let view = egui::KeyboardShortcut::new(egui::Modifiers::SHIFT, egui::Key::Enter);
+        let (moved, edit, view) = ctx.input_mut(|i| {
+            if i.consume_shortcut(&first) {
+                return (Some((RowMove::First, position)), false, false);
+            }
+            if i.consume_shortcut(&last) {
+                return (Some((RowMove::Last, position)), false, false);
+            }
+            if self.cursor.is_some() && i.consume_shortcut(&view) {
+                return (None, false, true);
+            }
+            let mut key = |key| i.consume_key(egui::Modifiers::NONE, key);
+            let moved = if key(egui::Key::ArrowUp) {
+                Some((RowMove::Up(1), position))
+            } else if key(egui::Key::ArrowDown) {
+                Some((RowMove::To(row + 1), position))
+            } else if key(egui::Key::ArrowLeft) {
+                Some((RowMove::To(row), position.saturating_sub(1)))
+            } else if key(egui::Key::ArrowRight) {
+                Some((RowMove::To(row), position + 1))
+            } else if key(egui::Key::PageUp) {
+                Some((RowMove::Up(page), position))
+            } else if key(egui::Key::PageDown) {
+                Some((RowMove::To(row + page), position))
+            } else if key(egui::Key::Home) {
+                Some((RowMove::To(row), 0))
+            } else if key(egui::Key::End) {
+                Some((RowMove::To(row), usize::MAX))
+            } else {
+                None
+            };
+            let edit = moved.is_none() && self.cursor.is_some() && key(egui::Key::Enter);
+            (moved, edit, false)
+        });
+        if edit || view {
+            let index = self.view_rows.as_ref().map_or(row, |order| order[row]);
+            let text = self.table[index].get(column).map_or("", String::as_str);
+            if edit && column < self.headers.len() && !self.saving && !is_structured(text) {
+                self.start_cell_edit(index, column);
+            } else {
+                self.open_cell_viewer((index, column));
+            }
+            return;
+        }
+        let Some((target, position)) = moved else {
+            return;
+        };
+        let column = columns[position.min(columns.len() - 1)];
+
+        // Rows above a go-to window and at the end of the sheet may not be
+        // loaded
+        let file_order = self.view_rows.is_none();
+        let target_row = match target {
+            RowMove::First if self.first_row > 0 && file_order => {
+                return self.go_to_row(0, Some(column));
+            }
+            RowMove::Up(count) if count > row && self.first_row > 0 && file_order => {
+                let source_row = self.first_row.saturating_sub(count - row);
+                return self.load_previous_page(source_row, column);
+            }
+            RowMove::Last if !self.end_of_file => {
+                return match self.total_rows {
+                    Some(total) => self.go_to_row(total.saturating_sub(1), Some(column)),
+                    None => self.start_goto_search(Some(column), |source, progress| {
+                        source.count_rows(progress).map(|count| count.checked_sub(1))
+                    }),
+                };
+            }
+            RowMove::First => 0,
+            RowMove::Up(count) => row.saturating_sub(count),
+            RowMove::Last => rows - 1,
+            RowMove::To(target_row) => target_row,
+        };
+        self.cursor = Some((target_row.min(rows - 1), column));
+        self.scroll_to_cursor = true;
+    }
+
+    /// Loads the rows above a go-to window, with the cursor on source row
+    /// `row` near the bottom of them.
+    fn load_previous_page(&mut self, row: usize, column: usize) {
+        if self.loading {
+            return;
+        }
+        self.goto_target = Some((row, Some(column)));
+        self.goto_message = None;
+        self.load_window(row.saturating_sub(PREVIOUS_PAGE_ROWS));
+    }
+
+    fn render_goto_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.goto_dialog else {
+            return;
+        };
+
+        let busy = self.goto_search.is_some() || self.goto_target.is_some();
+        match dialog.show(ctx, &self.headers, busy, self.goto_message.as_deref()) {
+            GotoAction::None => {}
+            GotoAction::Close => self.goto_dialog = None,
+            GotoAction::Row { row, column } => self.go_to_row(row, column),
+            GotoAction::Find { column, value } => {
+                self.start_goto_search(Some(column), move |source, progress| {
+                    source.find_row(column, &value, progress)
+                });
+            }
+        }
+    }
+
+    /// Looks for a source row over the whole sheet in the background, then
+    /// goes there.
+    fn start_goto_search<F>(&mut self, column: Option<usize>, search: F)
+    where
+        F: FnOnce(&SheetSource, &Progress) -> Result<Option<usize>, String> + Send + 'static,
+    {
+        let Some(path) = self.file_path.clone() else {
+            return;
+        };
+        if self.goto_search.is_some() {
+            return;
+        }
+        let source = SheetSource {
+            path,
+            sheet_index: self.current_sheet,
+            options: self.data_loader.options().clone(),
+        };
+        self.goto_search = Some(column);
+        self.goto_message = None;
+        self.goto_progress = Progress::new();
+        if let Some(total) = self.total_rows {
+            self.goto_progress.set_total_rows(total);
+        }
+        let progress = self.goto_progress.clone();
+        let egui_ctx = self.egui_ctx.clone();
+        spawn_task(
+            move || search(&source, &progress),
+            self.goto_tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_goto_search(&mut self) {
+        if let Ok(result) = self.goto_rx.try_recv() {
+            let column = self.goto_search.take().flatten();
+            match result {
+                Ok(Some(row)) => self.go_to_row(row, column),
+                Ok(None) => self.goto_message = Some(tr!("goto-not-found")),
+                Err(e) => self.goto_message = Some(e),
+            }
+        }
+    }
+
+    /// Puts the cursor on source row `row`, loading it first if needed.
+    fn go_to_row(&mut self, row: usize, column: Option<usize>) {
+        self.goto_target = Some((row, column));
+        self.goto_message = None;
+        // Otherwise sought once the page in flight has arrived
+        if !self.loading {
+            self.seek_goto_target();
+        }
+    }
+
+    /// Moves the cursor to the go-to target if it is loaded, or loads the
+    /// rows up to it, or a page around it when it is far off.
+    fn seek_goto_target(&mut self) {
+        let Some((row, column)) = self.goto_target else {
+            return;
+        };
+
+        if let Some(index) = self.row_ids.iter().position(|&id| id == RowId::Source(row)) {
+            self.goto_target = None;
+            let display = match &self.view_rows {
+                Some(order) => order.iter().position(|&i| i == index),
+                None => Some(index),
+            };
+            let Some(display) = display else {
+                self.goto_message = Some(tr!("goto-filtered", row = row + 1));
+                return;
+            };
+            let column = column
+                .or(self.cursor.map(|(_, column)| column))
+                .or(self.column_layout.visible_order().first().copied())
+                .unwrap_or(0);
+            self.cursor = Some((display, column));
+            self.scroll_to_cursor = true;
+            self.goto_dialog = None;
+            return;
+        }
+
+        if (self.first_row..self.source_rows).contains(&row) {
+            self.goto_target = None;
+            self.goto_message = Some(tr!("goto-deleted", row = row + 1));
+        } else if self.end_of_file && row >= self.source_rows {
+            self.goto_target = None;
+            self.goto_message = Some(tr!("goto-past-end", row = row + 1));
+        } else if row >= self.first_row && row < self.source_rows + GOTO_STREAM_ROWS {
+            self.rows_to_show = self.rows_to_show.max(row + 100);
+            self.load_more_data();
+        } else {
+            self.load_window(row.saturating_sub(GOTO_CONTEXT_ROWS));
+        }
+    }
+
+    /// Whether the open file is of a kind edits can be written back to;
+    /// files extracted from an archive are temporary copies.
+    fn can_save_file(&self) -> bool {
+        self.archive_path.is_none() && self.file_path.as_deref().is_some_and(can_save)
+    }
+
+    /// Why the edits cannot be saved, or `None` if they can. Saving copies
+    /// the records of the file as they are, so rows must not have been
+    /// skipped or repaired, and rows are only inserted or deleted where
+    /// nothing refers to them by number.
+    fn save_blocker(&self) -> Option<String> {
+        if !self.can_save_file() {
+            Some(tr!("save-unsupported-hint"))
+        } else if self.data_loader.options().tolerance != Tolerance::Strict {
+            Some(tr!("save-tolerant-hint"))
+        } else if self.edits.has_row_changes() {
+            self.row_lock.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Writes the edits back to the file in the background.
+    fn save_edits(&mut self) {
+        let Some(path) = self.file_path.clone() else {
+            return;
+        };
+        self.commit_cell_edit();
+        self.saving = true;
+        self.export_status = None;
+        let edits = self.edits.clone();
+        let sheet_index = self.current_sheet;
+        let egui_ctx = self.egui_ctx.clone();
+        spawn_task(
+            move || save_sheet(&path, sheet_index, &edits),
+            self.save_tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_save(&mut self) {
+        if let Ok(result) = self.save_rx.try_recv() {
+            self.saving = false;
+            match result {
+                Ok(()) => {
+                    // The file now holds the edits; read it again as it is
+                    self.edits = EditOverlay::default();
+                    self.export_status = Some(tr!("saved"));
+                    self.reload_data();
+                }
+                Err(e) => self.export_status = Some(format!("⚠ {}", e)),
+            }
+        }
+    }
+
+    fn update_sheet_info(&mut self, sheet_names: Vec<String>) {
+        if self.sheet_names.is_empty() && !sheet_names.is_empty() {
+            self.sheet_names = sheet_names;
+            self.is_excel_file =
+                self.sheet_names.len() > 1 || !is_pseudo_sheet(&self.sheet_names[0]);
+        }
+    }
+
+    fn update_headers(&mut self, headers: Vec<String>, column_types: Vec<ColumnType>) {
+        if !self.headers_loaded && !headers.is_empty() {
+            self.headers = headers;
+            self.column_types = column_types;
+            self.headers_loaded = true;
+
+            if let Some(path) = self.file_path.clone() {
+                let source = SheetSource {
+                    path,
+                    sheet_index: self.current_sheet,
+                    options: self.data_loader.options().clone(),
+                };
+                let rules = self
+                    .settings
+                    .format_rules
+                    .get(&header_signature(&self.headers))
+                    .cloned()
+                    .unwrap_or_default();
+                self.format_panel
+                    .set_sheet(&self.egui_ctx, source, self.headers.clone(), rules);
+                let limits = self
+                    .settings
+                    .spec_limits
+                    .get(&header_signature(&self.headers))
+                    .cloned()
+                    .unwrap_or_default();
+                self.row_inspector.set_sheet(limits);
+                self.check_row_references();
+            }
+        }
+    }
+}
+
+impl App for MyApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.settings.save(storage);
+    }
+
+    fn update(&mut self, ctx: &Context, frame: &mut Frame) {
+        self.setup_theme(ctx, frame.info().system_theme);
+        self.handle_data_response();
+        self.handle_row_count();
+        self.handle_row_references();
+        self.handle_file_changes();
+        self.handle_view_export();
+        self.handle_save();
+        self.handle_edit_shortcuts(ctx);
+        self.handle_goto_search();
+        self.handle_cursor_keys(ctx);
+
+        self.render_top_panel(ctx);
+        self.render_footer(ctx);
+        self.render_query_console(ctx);
+        self.render_format_panel(ctx);
+        self.render_row_inspector(ctx);
+        self.render_parse_issues(ctx);
+        self.render_quality_panel(ctx);
+        self.render_main_content(ctx);
+        self.render_archive_picker(ctx);
+        self.render_fixed_width_dialog(ctx);
+        self.render_folder_dialog(ctx);
+        self.render_pivot_panel(ctx);
+        self.render_diff_panel(ctx);
+        self.render_merge_panel(ctx);
+        self.render_computed_column_dialog(ctx);
+        self.render_goto_dialog(ctx);
+        self.render_cell_viewer(ctx);
+
+        // Progress bars move without input events
+        if self.loading || self.counting || self.goto_search.is_some() || self.view_exporting {
+            ctx.request_repaint_after(PROGRESS_REFRESH);
+        }
+    }
+}
+
+impl MyApp {
+    /// Applies the theme when the settings or the system theme changed;
+    /// eframe resets the style itself when the system theme changes.
+    fn setup_theme(&mut self, ctx: &Context, system_theme: Option<eframe::Theme>) {
+        let system_dark = system_theme.map(|theme| theme == eframe::Theme::Dark);
+        let key = (self.settings.theme.clone(), system_dark);
+        if self.applied_theme.as_ref() == Some(&key) {
+            return;
+        }
+        Palette::new(&self.settings.theme, system_dark).apply(ctx);
+        self.applied_theme = Some(key);
+    }
+
+    fn render_top_panel(&mut self, ctx: &Context) {
+        let palette = theme::palette(ctx);
+        TopBottomPanel::top("top_panel")
+            .exact_height(50.0)
+            .show(ctx, |ui| {
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    // Smaller, more compact open file button
+                    let open_btn = egui::Button::new(tr!("open-file"))
+                        .fill(palette.success)
+                        .stroke(Stroke::new(1.0, palette.success_stroke))
+                        .rounding(Rounding::same(8.0));
+
+                    if ui.add_sized([100.0, 32.0], open_btn).clicked() {
+                        self.open_file();
+                    }
+                    if ui.button(tr!("open-folder")).clicked() {
+                        self.open_folder();
+                    }
+
+                    ui.add_space(20.0);
+                    
+                    // Sheet selector
+                    self.render_sheet_selector(ui);
+
+                    if self.file_path.is_some() {
+                        ui.add_space(20.0);
+                        self.render_watch_selector(ui);
+                    }
+
+                    if self.file_path.as_deref().is_some_and(is_fixed_width) {
+                        ui.add_space(20.0);
+                        if ui.button(tr!("fixed-width-columns")).clicked() {
+                            self.edit_fixed_width_layout();
+                        }
+                    }
+
+                    ui.add_space(20.0);
+                    ui.toggle_value(&mut self.query_console.open, tr!("toggle-sql"));
+                    ui.toggle_value(&mut self.diff_panel.open, tr!("toggle-compare"));
+                    ui.toggle_value(&mut self.merge_panel.open, tr!("toggle-merge"));
+                    if !self.headers.is_empty() {
+                        ui.toggle_value(&mut self.pivot_panel.open, tr!("toggle-pivot"));
+                        ui.toggle_value(&mut self.format_panel.open, tr!("toggle-format"));
+                        ui.toggle_value(&mut self.row_inspector.open, tr!("toggle-inspector"));
+                        if self.file_path.as_deref().is_some_and(is_csv) {
+                            let count = self.parse_issues.issue_count();
+                            ui.toggle_value(
+                                &mut self.parse_issues.open,
+                                tr!("toggle-issues", count = count),
+                            );
+                        }
+                        let quality = match self.quality_panel.finding_count() {
+                            Some(count) => tr!("toggle-quality-findings", count = count),
+                            None => tr!("toggle-quality"),
+                        };
+                        ui.toggle_value(&mut self.quality_panel.open, quality);
+                        let mut dialog_open = self.computed_column_dialog.is_some();
+                        if ui.toggle_value(&mut dialog_open, tr!("toggle-computed-columns")).changed() {
+                            self.computed_column_dialog =
+                                dialog_open.then(ComputedColumnDialog::default);
+                            self.view_error = None;
+                        }
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        self.render_preferences_menu(ui);
+                    });
+                });
+                ui.add_space(10.0);
+            });
+    }
+
+    fn render_preferences_menu(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        ui.menu_button("⚙", |ui| {
+            ui.set_min_width(260.0);
+            ui.label(egui::RichText::new(tr!("prefs-language")).strong());
+            ui.horizontal_wrapped(|ui| {
+                for language in Language::ALL {
+                    let selected = self.settings.language == language;
+                    if ui.selectable_label(selected, language.label()).clicked() {
+                        self.settings.language = language;
+                        i18n::set_language(language);
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.label(egui::RichText::new(tr!("prefs-font")).strong());
+            ui.horizontal(|ui| {
+                let name = match &self.settings.font.custom_font {
+                    Some(path) => path.file_name().unwrap_or_default().to_string_lossy(),
+                    None => tr!("prefs-font-default").into(),
+                };
+                ui.label(name);
+                if ui.button(tr!("prefs-font-choose")).clicked() {
+                    let file = rfd::FileDialog::new()
+                        .add_filter(tr!("filter-font"), &["ttf", "otf", "ttc"])
+                        .pick_file();
+                    if let Some(path) = file {
+                        self.settings.font.custom_font = Some(path);
+                        self.font_report = setup_custom_fonts(ui.ctx(), &self.settings.font);
+                    }
+                }
+                if self.settings.font.custom_font.is_some() && ui.small_button("✖").clicked() {
+                    self.settings.font.custom_font = None;
+                    self.font_report = setup_custom_fonts(ui.ctx(), &self.settings.font);
+                }
+            });
+            // Applied on release, as the menu itself is scaled
+            let size = ui.add(
+                egui::Slider::new(&mut self.settings.font.size, 9.0..=24.0)
+                    .step_by(0.5)
+                    .suffix(" pt")
+                    .text(tr!("prefs-font-size")),
+            );
+            if size.drag_stopped() || (size.changed() && !size.dragged()) {
+                apply_text_size(ui.ctx(), self.settings.font.size);
+            }
+            let cjk = match &self.font_report.cjk_font {
+                Some(font) => tr!("prefs-cjk-font", font = font.as_str()),
+                None => tr!("prefs-no-cjk-font"),
+            };
+            ui.label(
+                egui::RichText::new(cjk)
+                    .color(palette.muted)
+                    .size(11.0),
+            );
+            if let Some(error) = &self.font_report.error {
+                ui.label(
+                    egui::RichText::new(format!("⚠ {}", error))
+                        .color(palette.error)
+                        .size(11.0),
+                );
+            }
+
+            ui.separator();
+            ui.label(egui::RichText::new(tr!("prefs-theme")).strong());
+            let theme = &mut self.settings.theme;
+            ui.horizontal(|ui| {
+                for mode in ThemeMode::ALL {
+                    ui.selectable_value(&mut theme.mode, mode, mode.label());
+                }
+            });
+            ui.checkbox(&mut theme.high_contrast, tr!("prefs-high-contrast"));
+            let stripe = tr!("prefs-row-stripes");
+            theme::color_setting(ui, &stripe, &mut theme.stripe, palette.stripe_even);
+            let header = tr!("prefs-column-headers");
+            theme::color_setting(ui, &header, &mut theme.header, palette.header);
+            theme::color_setting(ui, &tr!("prefs-accent"), &mut theme.accent, palette.accent);
+        });
+    }
+
+    fn render_computed_column_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.computed_column_dialog else {
+            return;
+        };
+
+        let headers = self.view.headers(&self.headers);
+        let action = dialog.show(
+            ctx,
+            &headers,
+            self.view.computed_columns(),
+            self.table.first().map(Vec::as_slice),
+            self.view_error.as_deref(),
+        );
+        let result = match action {
+            ComputedColumnAction::None => return,
+            ComputedColumnAction::Close => {
+                self.computed_column_dialog = None;
+                Ok(())
+            }
+            ComputedColumnAction::Add { name, source } => {
+                self.add_computed_column(&name, &source).map(|()| {
+                    if let Some(dialog) = &mut self.computed_column_dialog {
+                        dialog.clear();
+                    }
+                })
+            }
+            ComputedColumnAction::Remove(index) => self.remove_computed_column(index),
+        };
+        self.view_error = result.err();
+    }
+
+    fn render_folder_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.folder_dialog else {
+            return;
+        };
+
+        match dialog.show(ctx) {
+            FolderAction::None => {}
+            FolderAction::Cancel => self.folder_dialog = None,
+            FolderAction::Combined(path) => {
+                self.folder_dialog = None;
+                self.active_tab = None;
+                self.open_path(path, None);
+            }
+        }
+    }
+
+    fn render_fixed_width_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.fixed_width_dialog else {
+            return;
+        };
+
+        match dialog.show(ctx) {
+            FixedWidthAction::None => {}
+            FixedWidthAction::Cancel => self.fixed_width_dialog = None,
+            FixedWidthAction::Import { layout, profile } => {
+                let dialog = self.fixed_width_dialog.take().unwrap();
+                if let Some(profile) = profile {
+                    self.settings.save_fixed_width_profile(profile);
+                }
+                let options = LoadOptions {
+                    fixed_width: Some(layout),
+                    tolerance: self.settings.csv_tolerance,
+                };
+                self.start_loading(dialog.path, dialog.archive, options);
+            }
+        }
+    }
+
+    fn render_archive_picker(&mut self, ctx: &Context) {
+        let palette = theme::palette(ctx);
+        let Some((archive, entries)) = &self.archive_picker else {
+            return;
+        };
+
+        let mut open = true;
+        let mut picked = None;
+        let title = format!(
+            "🗜 {}",
+            archive.file_name().unwrap_or_default().to_string_lossy()
+        );
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(360.0)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(tr!("archive-choose"))
+                        .color(palette.muted),
+                );
+                ui.add_space(4.0);
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for entry in entries {
+                        if ui.selectable_label(false, format!("📄 {}", entry)).clicked() {
+                            picked = Some(entry.clone());
+                        }
+                    }
+                });
+            });
+
+        if let Some(entry) = picked {
+            let (archive, _) = self.archive_picker.take().unwrap();
+            self.open_archive_entry(archive, &entry);
+        } else if !open {
+            self.archive_picker = None;
+        }
+    }
+
+    fn render_watch_selector(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        ui.label(egui::RichText::new(tr!("watch-label")).color(palette.muted));
+
+        let mut mode = self.watch_mode;
+        egui::ComboBox::from_id_source("watch_mode_selector")
+            .selected_text(mode.label())
+            .width(90.0)
+            .show_ui(ui, |ui| {
+                for option in [WatchMode::Off, WatchMode::Reload, WatchMode::Tail] {
+                    ui.selectable_value(&mut mode, option, option.label());
+                }
+            });
+
+        if mode != self.watch_mode {
+            self.watch_mode = mode;
+            // Apply a change that happened while watching was off.
+            self.handle_file_changes();
+        }
+    }
+
+    fn render_sheet_selector(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        if self.is_excel_file && !self.sheet_names.is_empty() {
+            // Simple sheet selector without complex wrappers
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(tr!("sheet-label")).color(palette.muted));
+                ui.add_space(8.0);
+
+                let mut selected_sheet = self.current_sheet;
+                let combo = egui::ComboBox::from_id_source("sheet_selector")
+                    .selected_text(format!("📄 {}", &self.sheet_names[self.current_sheet]))
+                    .width(180.0);
+
+                combo.show_ui(ui, |ui| {
+                    for (index, sheet_name) in self.sheet_names.iter().enumerate() {
+                        let is_selected = index == self.current_sheet;
+                        let text = if is_selected {
+                            egui::RichText::new(format!("📄 {}", sheet_name))
+                                .color(palette.accent)
+                        } else {
+                            egui::RichText::new(format!("📄 {}", sheet_name))
+                        };
+
+                        ui.selectable_value(&mut selected_sheet, index, text);
+                    }
+                });
+
+                if selected_sheet != self.current_sheet {
+                    self.switch_sheet(selected_sheet);
+                }
+            });
+        }
+    }
+
+
+
+    fn render_main_content(&mut self, ctx: &Context) {
+        let palette = theme::palette(ctx);
+        CentralPanel::default().show(ctx, |ui| {
+            if let Some(error) = &self.error {
+                // Modern error display
+                ui.add_space(20.0);
+                ui.horizontal(|ui| {
+                    ui.add_space(20.0);
+                    egui::Frame::none()
+                        .fill(palette.error_bg)
+                        .rounding(Rounding::same(8.0))
+                        .inner_margin(Margin::same(16.0))
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new("⚠️").size(20.0));
+                                ui.label(
+                                    egui::RichText::new(error)
+                                        .color(palette.on_error)
+                                        .size(14.0),
+                                );
+                            });
+                        });
+                });
+            } else {
+                if !self.derived_tables.is_empty() {
+                    self.render_tab_bar(ui);
+                }
+                match self.active_tab {
+                    Some(index) => self.render_derived_table(ui, index),
+                    None if self.file_path.is_none() => self.render_welcome_screen(ui),
+                    None => self.render_data_table(ui),
+                }
+            }
+        });
+    }
+
+    fn render_query_console(&mut self, ctx: &Context) {
+        let current_file = self
+            .file_path
+            .as_deref()
+            .map(|path| (path, self.data_loader.options()));
+        if let Some(table) = self.query_console.show(ctx, current_file) {
+            self.add_derived_table(table);
+        }
+    }
+
+    fn render_format_panel(&mut self, ctx: &Context) {
+        if self.format_panel.show(ctx) {
+            let signature = header_signature(&self.headers);
+            let rules = self.format_panel.rules().to_vec();
+            if rules.is_empty() {
+                self.settings.format_rules.remove(&signature);
+            } else {
+                self.settings.format_rules.insert(signature, rules);
+            }
+        }
+    }
+
+    /// Inspects the row under the cursor of the file view.
+    fn render_row_inspector(&mut self, ctx: &Context) {
+        let headers = self.view.headers(&self.headers);
+        let row = self.cursor.and_then(|(row, _)| {
+            let index = match &self.view_rows {
+                Some(order) => *order.get(row)?,
+                None => row,
+            };
+            Some(InspectedRow {
+                row: *self.row_ids.get(index)?,
+                values: self.table.get(index)?,
+            })
+        });
+        if self.row_inspector.show(ctx, &headers, row) {
+            let signature = header_signature(&self.headers);
+            let limits = self.row_inspector.limits().clone();
+            if limits.is_empty() {
+                self.settings.spec_limits.remove(&signature);
+            } else {
+                self.settings.spec_limits.insert(signature, limits);
+            }
+        }
+    }
+
+    /// Lists the malformed records of the open CSV file.
+    fn render_parse_issues(&mut self, ctx: &Context) {
+        let source = self
+            .file_path
+            .clone()
+            .filter(|path| is_csv(path))
+            .map(|path| SheetSource {
+                path,
+                sheet_index: self.current_sheet,
+                options: self.data_loader.options().clone(),
+            });
+        // Row numbers change with the tolerance, which edits refer to
+        let locked = !self.edits.is_empty() || self.saving || self.loading;
+        match self.parse_issues.show(ctx, source, locked) {
+            ParseIssuesAction::None => {}
+            ParseIssuesAction::GoTo(row) => self.go_to_row(row, None),
+            ParseIssuesAction::SetTolerance(tolerance) => self.set_tolerance(tolerance),
+        }
+    }
+
+    /// Reports the quality checks of the whole open sheet.
+    fn render_quality_panel(&mut self, ctx: &Context) {
+        let sheet = self.file_path.clone().map(|path| QualitySource {
+            source: SheetSource {
+                path,
+                sheet_index: self.current_sheet,
+                options: self.data_loader.options().clone(),
+            },
+            headers: &self.headers,
+            limits: self.row_inspector.limits(),
+            total_rows: self.total_rows,
+        });
+        match self.quality_panel.show(ctx, sheet) {
+            QualityAction::None => {}
+            QualityAction::GoTo(row, column) => self.go_to_row(row, Some(column)),
+        }
+    }
+
+    /// Reads the open file again from the first row with another tolerance.
+    fn set_tolerance(&mut self, tolerance: Tolerance) {
+        self.settings.csv_tolerance = tolerance;
+        let mut options = self.data_loader.options().clone();
+        options.tolerance = tolerance;
+        self.data_loader.set_options(options);
+        self.first_row = 0;
+        self.clear_rows();
+        self.parse_issues.clear();
+        self.quality_panel.clear();
+        self.cursor = None;
+        self.goto_target = None;
+        self.end_of_file = false;
+        self.rows_to_show = 100;
+        self.load_more_data();
+        self.count_rows();
+    }
+
+    fn render_pivot_panel(&mut self, ctx: &Context) {
+        let source = self.file_path.as_deref().map(|path| PivotSource {
+            path,
+            sheet_index: self.current_sheet,
+            options: self.data_loader.options(),
+            headers: &self.headers,
+        });
+        if let Some(table) = self.pivot_panel.show(ctx, source) {
+            self.add_derived_table(table);
+        }
+    }
+
+    fn render_diff_panel(&mut self, ctx: &Context) {
+        let current = self
+            .file_path
+            .as_deref()
+            .map(|path| (path, self.current_sheet, self.data_loader.options()));
+        if let Some(table) = self.diff_panel.show(ctx, current) {
+            self.add_derived_table(table);
+        }
+    }
+
+    /// Opens the result of a merge in the file view.
+    fn render_merge_panel(&mut self, ctx: &Context) {
+        let current = self
+            .file_path
+            .as_deref()
+            .map(|path| (path, self.current_sheet, self.data_loader.options()));
+        if let Some(merged) = self.merge_panel.show(ctx, current) {
+            self.active_tab = None;
+            self.open_path(merged, None);
+        }
+    }
+
+    fn add_derived_table(&mut self, table: DerivedTable) {
+        self.derived_tables.push(table);
+        self.active_tab = Some(self.derived_tables.len() - 1);
+        self.export_status = None;
+    }
+
+    /// Tabs for the open file and each derived table, with export and close
+    /// buttons for the active derived table.
+    fn render_tab_bar(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        let mut closed = None;
+        let mut export = false;
+
+        ui.horizontal_wrapped(|ui| {
+            let file_title = self
+                .file_path
+                .as_ref()
+                .map(|path| format!("📄 {}", path.file_name().unwrap_or_default().to_string_lossy()))
+                .unwrap_or_else(|| tr!("tab-file"));
+            if ui.selectable_label(self.active_tab.is_none(), file_title).clicked() {
+                self.active_tab = None;
+                self.export_status = None;
+            }
+
+            for (index, table) in self.derived_tables.iter().enumerate() {
+                let selected = self.active_tab == Some(index);
+                let response = ui
+                    .selectable_label(selected, format!("🔎 {}", table.title))
+                    .on_hover_text(&table.description);
+                if response.clicked() {
+                    self.active_tab = Some(index);
+                    self.export_status = None;
+                }
+                if response.middle_clicked() || (selected && ui.small_button("✖").clicked()) {
+                    closed = Some(index);
+                }
+            }
+
+            if self.active_tab.is_some() {
+                ui.separator();
+                export = ui.button(tr!("export-csv")).clicked();
+                if let Some(status) = &self.export_status {
+                    ui.label(
+                        egui::RichText::new(status)
+                            .color(palette.muted)
+                            .size(12.0),
+                    );
+                }
+            }
+        });
+
+        if export {
+            if let Some(table) = self.active_tab.and_then(|i| self.derived_tables.get(i)) {
+                self.export_status = match table.export_csv() {
+                    Ok(Some(path)) => Some(tr!("saved-file", path = path.display().to_string())),
+                    Ok(None) => None,
+                    Err(e) => Some(format!("⚠ {}", e)),
+                };
+            }
+        }
+
+        if let Some(index) = closed {
+            self.derived_tables.remove(index);
+            self.export_status = None;
+            self.active_tab = match self.active_tab {
+                Some(active) if active > index => Some(active - 1),
+                Some(active) if active == index => {
+                    index.checked_sub(1).or((!self.derived_tables.is_empty()).then_some(0))
+                }
+                other => other,
+            };
+        }
+    }
+
+    fn render_derived_table(&mut self, ui: &mut egui::Ui, index: usize) {
+        let palette = theme::palette(ui.ctx());
+        let Some(table) = self.derived_tables.get_mut(index) else {
+            self.active_tab = None;
+            return;
+        };
+
+        let mut viewer = None;
+        ui.add_space(16.0);
+        egui::Frame::none()
+            .fill(palette.window)
+            .rounding(Rounding::same(12.0))
+            .inner_margin(Margin::same(16.0))
+            .show(ui, |ui| {
+                ScrollArea::both()
+                    .id_source(("derived_table", index))
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        // Taken out for the frame, as the closure borrows the table
+                        let mut layout = std::mem::take(&mut table.layout);
+                        let cell_style =
+                            |row, column| CellStyle::fill(mark_fill(&palette, table.mark(row, column)));
+                        let grid = table_grid::show(
+                            ui,
+                            &table.headers,
+                            &table.column_types,
+                            table.visible_rows(),
+                            GridOptions {
+                                sort: Some(table.sort),
+                                ..GridOptions::new(&mut layout, &cell_style)
+                            },
+                        );
+                        table.layout = layout;
+                        if let Some(column) = grid.inner.sort_clicked {
+                            table.toggle_sort(column);
+                        }
+                        if let Some((row, column)) = grid.inner.view_requested {
+                            let text = table.visible_rows()[row].get(column);
+                            let title = tr!(
+                                "viewer-title",
+                                column = table.headers.get(column).cloned().unwrap_or_default(),
+                                row = row + 1
+                            );
+                            viewer = text.map(|text| {
+                                CellViewer::new((row, column), title, text.clone(), false)
+                            });
+                        }
+                        if ui.clip_rect().bottom() >= grid.response.rect.bottom() - 10.0
+                            && !table.is_fully_shown()
+                        {
+                            table.show_more();
+                        }
+                    });
+            });
+
+        ui.add_space(16.0);
+        ui.horizontal(|ui| {
+            ui.add_space(20.0);
+            table_grid::column_menu(ui, &table.headers, &mut table.layout);
+            ui.separator();
+            ui.label(
+                egui::RichText::new(tr!("row-count", count = table.rows.len()))
+                    .color(palette.muted)
+                    .size(13.0),
+            );
+            if let Some(summary) = &table.summary {
+                ui.separator();
+                ui.label(
+                    egui::RichText::new(summary)
+                        .color(palette.muted)
+                        .size(13.0),
+                );
+            }
+        });
+        if viewer.is_some() {
+            self.cell_viewer = viewer;
+        }
+    }
+
+    fn render_welcome_screen(&self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        ui.vertical_centered(|ui| {
+            ui.add_space(100.0);
+
+            // Welcome card
+            egui::Frame::none()
+                .fill(palette.card)
+                .rounding(Rounding::same(16.0))
+                .inner_margin(Margin::same(40.0))
+
+                .show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(egui::RichText::new("📊").size(64.0));
+
+                        ui.add_space(16.0);
+
+                        ui.label(
+                            egui::RichText::new(tr!("welcome-title"))
+                                .size(24.0)
+                                .color(palette.accent),
+                        );
+
+                        ui.add_space(8.0);
+
+                        ui.label(
+                            egui::RichText::new(tr!("welcome-hint"))
+                                .size(14.0)
+                                .color(palette.muted),
+                        );
+
+                        ui.add_space(16.0);
+
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(tr!("welcome-supports"))
+                                    .size(12.0)
+                                    .color(palette.faint),
+                            );
+                            ui.label(
+                                egui::RichText::new("CSV • XLSX • XLS • ODS • Parquet • Arrow • JSON • SQLite • TXT • GZ • ZST • BZ2 • ZIP")
+                                    .size(12.0)
+                                    .color(palette.success),
+                            );
+                        });
+                    });
+                });
+        });
+    }
+
+    fn render_data_table(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        // Add padding around the table
+        ui.add_space(16.0);
+        self.render_view_bar(ui);
+        ui.add_space(8.0);
+
+        // Modern table container
+        egui::Frame::none()
+            .fill(palette.window)
+            .rounding(Rounding::same(12.0))
+            .inner_margin(Margin::same(16.0))
+            .show(ui, |ui| {
+                ScrollArea::both()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        let headers = self.view.headers(&self.headers);
+                        // Inserted rows and edited cells are marked until saved,
+                        // over any conditional formatting
+                        let (edits, row_ids, table) = (&self.edits, &self.row_ids, &self.table);
+                        let format = self.format_panel.format();
+                        let flagged = self.parse_issues.flagged_rows();
+                        let base_width = self.headers.len();
+                        let cell_style = |row: usize, column: usize| {
+                            let mark = match row_ids[row] {
+                                _ if column >= base_width => None,
+                                RowId::Inserted(_) => mark_fill(&palette, CellMark::Added),
+                                id if edits.is_edited(id, column) => {
+                                    mark_fill(&palette, CellMark::Changed)
+                                }
+                                RowId::Source(source)
+                                    if flagged.is_some_and(|rows| rows.contains(&source)) =>
+                                {
+                                    mark_fill(&palette, CellMark::Removed)
+                                }
+                                _ => None,
+                            };
+                            if format.is_empty() {
+                                return CellStyle::fill(mark);
+                            }
+                            let rule = format.format(&table[row], column);
+                            let rgb = |[r, g, b]: [u8; 3]| Color32::from_rgb(r, g, b);
+                            CellStyle {
+                                fill: mark.or(rule.fill.map(rgb)),
+                                bar: rule.bar.map(|(fraction, color)| (fraction, rgb(color))),
+                            }
+                        };
+                        let grid = table_grid::show(
+                            ui,
+                            &headers,
+                            &self.column_types,
+                            &self.table,
+                            GridOptions {
+                                order: self.view_rows.as_deref(),
+                                sort: Some(self.view.sort),
+                                cell_style: &cell_style,
+                                editable_columns: if self.saving { 0 } else { self.headers.len() },
+                                row_lock: self.row_lock.as_deref(),
+                                editor: self.cell_editor.as_mut(),
+                                layout: &mut self.column_layout,
+                                first_row: self.first_row,
+                                cursor: self.cursor,
+                                scroll_to_cursor: self.scroll_to_cursor,
+                            },
+                        );
+                        self.scroll_to_cursor = false;
+                        self.handle_grid_events(&grid.inner);
+                        if self.scroll_to_bottom {
+                            let rect = grid.response.rect;
+                            ui.scroll_to_rect(
+                                egui::Rect::from_min_max(
+                                    egui::pos2(rect.left(), rect.bottom() - 1.0),
+                                    egui::pos2(rect.left() + 1.0, rect.bottom()),
+                                ),
+                                Some(egui::Align::BOTTOM),
+                            );
+                            self.scroll_to_bottom = false;
+                        }
+                        self.handle_lazy_loading(ui, &grid);
+                    });
+            });
+
+        ui.add_space(16.0);
+        self.render_status_message(ui);
+    }
+
+    /// Filter expression, view summary and full-sheet export above the grid.
+    fn render_view_bar(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        ui.horizontal(|ui| {
+            ui.add_space(4.0);
+            ui.label(tr!("filter-label"));
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.filter_text)
+                    .desired_width(320.0)
+                    .hint_text(tr!("filter-hint")),
+            );
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.apply_filter();
+            }
+            if self.view.filter_source().is_some() && ui.small_button("✖").clicked() {
+                self.filter_text.clear();
+                self.apply_filter();
+            }
+
+            if let Some(rows) = &self.view_rows {
+                ui.label(
+                    egui::RichText::new(tr!(
+                        "filter-matches",
+                        shown = rows.len(),
+                        loaded = self.table.len()
+                    ))
+                        .color(palette.muted)
+                        .size(12.0),
+                );
+            }
+
+            ui.separator();
+            if ui
+                .button(tr!("goto-button"))
+                .on_hover_text(tr!("goto-button-hint"))
+                .clicked()
+            {
+                self.goto_dialog = Some(GotoDialog::new(&self.headers));
+                self.goto_message = None;
+            }
+
+            ui.separator();
+            if ui
+                .add_enabled(self.edits.can_undo() && !self.saving, egui::Button::new("↶"))
+                .on_hover_text(tr!("undo-hint"))
+                .clicked()
+            {
+                self.undo();
+            }
+            if ui
+                .add_enabled(self.edits.can_redo() && !self.saving, egui::Button::new("↷"))
+                .on_hover_text(tr!("redo-hint"))
+                .clicked()
+            {
+                self.redo();
+            }
+            let blocker = self.save_blocker();
+            let save = ui
+                .add_enabled(
+                    !self.edits.is_empty() && !self.saving && blocker.is_none(),
+                    egui::Button::new(tr!("save")),
+                )
+                .on_hover_text(tr!("save-hint"))
+                .on_disabled_hover_text(blocker.unwrap_or_else(|| tr!("save-edit-hint")));
+            if save.clicked() {
+                self.save_edits();
+            }
+            if self.saving {
+                ui.spinner();
+            } else if !self.edits.is_empty() {
+                ui.label(
+                    egui::RichText::new(tr!("unsaved-edits"))
+                        .color(palette.warning)
+                        .size(12.0),
+                );
+            }
+
+            ui.separator();
+            let headers = self.view.headers(&self.headers);
+            table_grid::column_menu(ui, &headers, &mut self.column_layout);
+
+            ui.separator();
+            let export = ui
+                .add_enabled(!self.view_exporting, egui::Button::new(tr!("export-csv")))
+                .on_hover_text(tr!("export-view-hint"));
+            if export.clicked() {
+                self.export_view();
+            }
+            if self.view_exporting {
+                progress_bar(ui, self.export_progress.snapshot(), tr!("progress-exporting"));
+            } else if let Some(status) = &self.export_status {
+                ui.label(
+                    egui::RichText::new(status)
+                        .color(palette.muted)
+                        .size(12.0),
+                );
+            }
+        });
+
+        if let Some(error) = self.view_error.as_ref().filter(|_| self.computed_column_dialog.is_none()) {
+            ui.label(
+                egui::RichText::new(format!("⚠ {}", error))
+                    .color(palette.warning)
+                    .size(12.0),
+            );
+        }
+
+        // Go-to feedback, shown in its window while that is open
+        if self.goto_dialog.is_none() {
+            if self.goto_search.is_some() {
+                ui.horizontal(|ui| {
+                    progress_bar(ui, self.goto_progress.snapshot(), tr!("goto-searching"));
+                });
+            } else if let Some(message) = &self.goto_message {
+                ui.label(
+                    egui::RichText::new(format!("⚠ {}", message))
+                        .color(palette.warning)
+                        .size(12.0),
+                );
+            }
+        }
+
+        if self.first_row > 0 {
+            ui.horizontal(|ui| {
+                ui.add_space(4.0);
+                ui.label(
+                    egui::RichText::new(tr!("window-notice", row = self.first_row + 1))
+                        .color(palette.muted)
+                        .size(12.0),
+                );
+                if ui.small_button(tr!("window-load-start")).clicked() && !self.loading {
+                    self.go_to_row(0, None);
+                }
+            });
+        }
+    }
+
+    fn handle_lazy_loading(&mut self, ui: &mut egui::Ui, grid: &egui::InnerResponse<GridEvents>) {
+        if ui.clip_rect().bottom() >= grid.response.rect.bottom() - 10.0
+            && !self.loading
+            && !self.end_of_file
+        {
+            self.rows_to_show += 100;
+            self.load_more_data();
+        }
+    }
+
+    fn render_status_message(&self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        ui.horizontal(|ui| {
+            ui.add_space(20.0);
+
+            if self.loading {
+                // Modern loading indicator
+                egui::Frame::none()
+                    .fill(palette.accent)
+                    .rounding(Rounding::same(20.0))
+                    .inner_margin(Margin::symmetric(16.0, 8.0))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(
+                                egui::RichText::new(tr!("loading-more"))
+                                    .color(palette.on_accent)
+                                    .size(13.0),
+                            );
+                        });
+                    });
+            } else if self.end_of_file && !self.table.is_empty() {
+                // Modern end-of-file indicator
+                egui::Frame::none()
+                    .fill(palette.success)
+                    .rounding(Rounding::same(20.0))
+                    .inner_margin(Margin::symmetric(16.0, 8.0))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("✅").size(14.0));
+                            ui.label(
+                                egui::RichText::new(tr!("all-loaded"))
+                                    .color(palette.on_accent)
+                                    .size(13.0),
+                            );
+                        });
+                    });
+            }
+        });
+    }
+
+    fn render_footer(&mut self, ctx: &Context) {
+        let palette = theme::palette(ctx);
+        if self.file_path.is_some() {
+            TopBottomPanel::bottom("footer_panel")
+                .exact_height(35.0)
+                .show(ctx, |ui| {
+                    ui.add_space(6.0);
+                    ui.horizontal(|ui| {
+                        ui.add_space(16.0);
+                        
+                        // File information in footer
+                        if let Some(path) = &self.file_path {
+                            let mut file_name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                            if let Some(archive) = &self.archive_path {
+                                file_name = format!(
+                                    "{} › {}",
+                                    archive.file_name().unwrap_or_default().to_string_lossy(),
+                                    file_name
+                                );
+                            }
+                            let extension = path
+                                .extension()
+                                .unwrap_or_default()
+                                .to_string_lossy()
+                                .to_uppercase();
+
+                            let file_icon = match extension.as_str() {
+                                "CSV" => "📊",
+                                "XLSX" | "XLS" => "📈",
+                                "ODS" => "📋",
+                                "GZ" | "ZST" | "BZ2" => "🗜",
+                                "PARQUET" | "ARROW" | "FEATHER" => "🧱",
+                                "JSON" | "NDJSON" | "JSONL" => "🧾",
+                                "DB" | "SQLITE" | "SQLITE3" => "🗄",
+                                "TXT" | "DAT" | "PRN" | "FWF" => "📃",
+                                _ => "📄",
+                            };
+
+                            // File name and icon
+                            ui.label(
+                                egui::RichText::new(format!("{} {}", file_icon, file_name))
+                                    .color(palette.text)
+                                    .size(12.0),
+                            );
+
+                            ui.separator();
+
+                            // Sheet information
+                            if self.is_excel_file && !self.sheet_names.is_empty() {
+                                ui.label(
+                                    egui::RichText::new(tr!(
+                                        "footer-sheet",
+                                        sheet = self.sheet_names[self.current_sheet].as_str()
+                                    ))
+                                        .color(palette.muted)
+                                        .size(12.0),
+                                );
+                                ui.separator();
+                                
+                                ui.label(
+                                    egui::RichText::new(tr!("footer-sheet-count", count = self.sheet_names.len()))
+                                        .color(palette.faint)
+                                        .size(12.0),
+                                );
+                                ui.separator();
+                            }
+
+                            // Row count
+                            if !self.table.is_empty() {
+                                let status_text = match self.total_rows {
+                                    _ if self.end_of_file && self.first_row == 0 => {
+                                        tr!("footer-rows-complete", count = self.table.len())
+                                    }
+                                    Some(total) => tr!(
+                                        "footer-rows-of",
+                                        loaded = self.table.len(),
+                                        total = total
+                                    ),
+                                    None => {
+                                        tr!("footer-rows-loading", count = self.table.len())
+                                    }
+                                };
+                                
+                                ui.label(
+                                    egui::RichText::new(status_text)
+                                        .color(palette.faint)
+                                        .size(12.0),
+                                );
+                            }
+                        }
+
+                        // Push loading status to the right
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.add_space(16.0);
+
+                            self.render_watch_status(ui);
+
+                            if self.counting {
+                                let progress = self.data_loader.count_progress().snapshot();
+                                progress_bar(ui, progress, tr!("progress-counting"));
+                                ui.separator();
+                            }
+                            if self.loading {
+                                // CSV pages far into the file report the rows
+                                // skipped to reach them
+                                let progress = self.data_loader.load_progress().snapshot();
+                                ui.horizontal(|ui| {
+                                    progress_bar(ui, progress, tr!("progress-loading"));
+                                });
+                            } else if self.end_of_file && !self.table.is_empty() {
+                                ui.label(
+                                    egui::RichText::new(tr!("status-complete"))
+                                        .color(palette.success)
+                                        .size(11.0),
+                                );
+                            }
+                        });
+                    });
+                    ui.add_space(6.0);
+                });
+        }
+    }
+
+    fn render_watch_status(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        if self.file_changed && self.watch_mode == WatchMode::Off {
+            if ui.small_button(tr!("reload")).clicked() {
+                self.reload_data();
+            }
+            ui.label(
+                egui::RichText::new(tr!("file-changed"))
+                    .color(palette.warning)
+                    .size(11.0),
+            );
+            ui.separator();
+        } else if self.watcher.is_some() && self.watch_mode != WatchMode::Off {
+            ui.label(
+                egui::RichText::new(format!("👁 {}", self.watch_mode.label()))
+                    .color(palette.accent)
+                    .size(11.0),
+            );
+            ui.separator();
+        }
+    }
+}
+
+/// A bar filling up as a background task reads the sheet, or a spinner
+/// with the rows read so far when the total is not known.
+fn progress_bar(ui: &mut egui::Ui, progress: ProgressEvent, label: String) {
+    let palette = theme::palette(ui.ctx());
+    match progress.fraction() {
+        Some(fraction) => {
+            let text = format!("{} {:.0}%", label, fraction * 100.0);
+            ui.add(
+                egui::ProgressBar::new(fraction)
+                    .desired_width(180.0)
+                    .text(egui::RichText::new(text).size(11.0)),
+            );
+        }
+        None => {
+            ui.spinner();
+            let text = if progress.rows > 0 {
+                tr!("progress-rows", label = label, rows = progress.rows)
+            } else {
+                label
+            };
+            ui.label(egui::RichText::new(text).color(palette.muted).size(11.0));
+        }
+    }
+}
+
+fn row_reference_label(reference: RowReference) -> String {
+    match reference {
+        RowReference::Formulas => tr!("row-reference-formulas"),
+        RowReference::MergedCells => tr!("row-reference-merged-cells"),
+        RowReference::ConditionalFormats => tr!("row-reference-conditional-formats"),
+        RowReference::DataValidations => tr!("row-reference-data-validations"),
+        RowReference::Hyperlinks => tr!("row-reference-hyperlinks"),
+        RowReference::Tables => tr!("row-reference-tables"),
+        RowReference::DefinedNames => tr!("row-reference-defined-names"),
+    }
+}
+
+/// Background of a highlighted cell in a derived table.
+fn mark_fill(palette: &Palette, mark: CellMark) -> Option<Color32> {
+    let (dark, light) = match mark {
+        CellMark::Plain => return None,
+        CellMark::Added => ((38, 84, 52), (200, 235, 205)),
+        CellMark::Removed => ((96, 42, 46), (245, 205, 207)),
+        CellMark::Changed => ((112, 84, 24), (250, 230, 180)),
+        CellMark::Minor => ((64, 64, 44), (235, 235, 210)),
+    };
+    let (r, g, b) = if palette.dark { dark } else { light };
+    Some(Color32::from_rgb(r, g, b))
+}
@@ -0,0 +1,144 @@
+use egui::Context;
+use rust_excel_reader_core::pretty::{pretty_print, Structure};
+
+use crate::i18n::tr;
+use crate::theme;
+
+/// What the user did in the cell viewer.
+pub enum CellViewerAction {
+    None,
+    Close,
+    /// Edit the cell in place instead.
+    Edit,
+}
+
+/// Window showing the full text of one cell, with JSON and XML values
+/// pretty-printed and line numbers for text of several lines.
+pub struct CellViewer {
+    /// The cell it shows, as (row index, column) in the table it was opened
+    /// from.
+    pub cell: (usize, usize),
+    title: String,
+    text: String,
+    /// The text reformatted, when it is JSON or XML.
+    pretty: Option<(Structure, String)>,
+    show_pretty: bool,
+    editable: bool,
+    copied: bool,
+}
+
+impl CellViewer {
+    pub fn new(cell: (usize, usize), title: String, text: String, editable: bool) -> Self {
+        let pretty = pretty_print(&text);
+        Self {
+            cell,
+            title,
+            text,
+            show_pretty: pretty.is_some(),
+            pretty,
+            editable,
+            copied: false,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) -> CellViewerAction {
+        let palette = theme::palette(ctx);
+        let mut action = CellViewerAction::None;
+        let mut open = true;
+
+        egui::Window::new(&self.title)
+            .id(egui::Id::new("cell_viewer"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_size([560.0, 420.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if let Some((structure, _)) = &self.pretty {
+                        ui.checkbox(
+                            &mut self.show_pretty,
+                            tr!("viewer-pretty", format = structure.label()),
+                        );
+                    }
+                    let lines = shown_text(&self.text, &self.pretty, self.show_pretty)
+                        .split('\n')
+                        .count();
+                    ui.label(
+                        egui::RichText::new(tr!(
+                            "viewer-size",
+                            chars = self.text.chars().count(),
+                            lines = lines
+                        ))
+                        .color(palette.muted)
+                        .size(12.0),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if self.editable && ui.button(tr!("viewer-edit")).clicked() {
+                            action = CellViewerAction::Edit;
+                        }
+                        if ui.button(tr!("viewer-copy")).clicked() {
+                            ui.ctx().copy_text(
+                                shown_text(&self.text, &self.pretty, self.show_pretty).to_owned(),
+                            );
+                            self.copied = true;
+                        }
+                        if self.copied {
+                            ui.label(
+                                egui::RichText::new(tr!("viewer-copied"))
+                                    .color(palette.success)
+                                    .size(12.0),
+                            );
+                        }
+                    });
+                });
+                ui.separator();
+
+                let mut text = shown_text(&self.text, &self.pretty, self.show_pretty);
+                let lines = text.split('\n').count();
+                egui::ScrollArea::both()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        ui.horizontal_top(|ui| {
+                            if lines > 1 {
+                                let numbers: Vec<String> =
+                                    (1..=lines).map(|line| line.to_string()).collect();
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(numbers.join("\n"))
+                                            .monospace()
+                                            .color(palette.faint),
+                                    )
+                                    .selectable(false),
+                                );
+                            }
+                            // Read-only: a `&str` cannot be edited
+                            ui.add(
+                                egui::TextEdit::multiline(&mut text)
+                                    .code_editor()
+                                    .frame(false)
+                                    .margin(egui::vec2(0.0, 0.0))
+                                    .desired_width(f32::INFINITY),
+                            );
+                        });
+                    });
+            });
+
+        if !open || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            action = CellViewerAction::Close;
+        }
+        action
+    }
+}
+
+/// The pretty-printed text when there is one and it is shown, else the text
+/// as it is.
+fn shown_text<'a>(
+    text: &'a str,
+    pretty: &'a Option<(Structure, String)>,
+    show_pretty: bool,
+) -> &'a str {
+    match pretty {
+        Some((_, pretty)) if show_pretty => pretty,
+        _ => text,
+    }
+}
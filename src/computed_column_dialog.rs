@@ -0,0 +1,146 @@
+use egui::Context;
+use rust_excel_reader_core::expression::{Expression, FUNCTIONS};
+use rust_excel_reader_core::sheet_view::ComputedColumn;
+
+use crate::i18n::tr;
+use crate::theme;
+
+/// What the user did in the computed-column window.
+pub enum ComputedColumnAction {
+    None,
+    Close,
+    Add { name: String, source: String },
+    Remove(usize),
+}
+
+/// Window for adding and removing computed columns of the file view.
+#[derive(Default)]
+pub struct ComputedColumnDialog {
+    name: String,
+    source: String,
+}
+
+impl ComputedColumnDialog {
+    /// `headers` are the columns an expression may use, `sample` a loaded
+    /// row (with its computed cells) used to preview the result.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        headers: &[String],
+        columns: &[ComputedColumn],
+        sample: Option<&[String]>,
+        error: Option<&str>,
+    ) -> ComputedColumnAction {
+        let palette = theme::palette(ctx);
+        let mut action = ComputedColumnAction::None;
+        let mut open = true;
+
+        egui::Window::new(tr!("computed-title"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                for (index, column) in columns.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✖").clicked() {
+                            action = ComputedColumnAction::Remove(index);
+                        }
+                        ui.label(egui::RichText::new(&column.name).strong());
+                        ui.label(
+                            egui::RichText::new(format!("= {}", column.source))
+                                .monospace()
+                                .color(palette.muted),
+                        );
+                    });
+                }
+                if !columns.is_empty() {
+                    ui.separator();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr!("computed-name"));
+                    ui.add(egui::TextEdit::singleline(&mut self.name).desired_width(160.0));
+                });
+                ui.label(tr!("computed-expression"));
+                let editor = ui.add(
+                    egui::TextEdit::multiline(&mut self.source)
+                        .code_editor()
+                        .desired_rows(2)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("if(fwhm > 20, \"wide\", \"ok\")"),
+                );
+
+                // Validate while typing and preview the value for the first row
+                let compiled = Expression::compile(&self.source, headers);
+                match (&compiled, self.source.trim().is_empty()) {
+                    (_, true) => {}
+                    (Ok(expression), false) => {
+                        if let Some(row) = sample {
+                            ui.label(
+                                egui::RichText::new(tr!(
+                                    "computed-first-row",
+                                    value = expression.evaluate_cell(row)
+                                ))
+                                .color(palette.success)
+                                .size(12.0),
+                            );
+                        }
+                    }
+                    (Err(e), false) => {
+                        ui.label(
+                            egui::RichText::new(e)
+                                .color(palette.warning)
+                                .size(12.0),
+                        );
+                    }
+                }
+                if let Some(error) = error {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .color(palette.error)
+                            .size(12.0),
+                    );
+                }
+
+                let submitted = editor.has_focus()
+                    && ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Enter));
+                ui.horizontal(|ui| {
+                    let can_add = compiled.is_ok() && !self.name.trim().is_empty();
+                    if (ui.add_enabled(can_add, egui::Button::new(tr!("computed-add"))).clicked()
+                        || (submitted && can_add))
+                        && compiled.is_ok()
+                    {
+                        action = ComputedColumnAction::Add {
+                            name: self.name.trim().to_string(),
+                            source: self.source.clone(),
+                        };
+                    }
+                });
+
+                ui.collapsing(tr!("computed-reference"), |ui| {
+                    ui.label(
+                        egui::RichText::new(tr!("computed-columns-hint")).size(12.0),
+                    );
+                    ui.label(
+                        egui::RichText::new(tr!("computed-operators-hint")).size(12.0),
+                    );
+                    egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                        for (_, signature) in FUNCTIONS {
+                            ui.label(egui::RichText::new(*signature).monospace().size(11.0));
+                        }
+                    });
+                });
+            });
+
+        if !open {
+            action = ComputedColumnAction::Close;
+        }
+        action
+    }
+
+    /// Clears the form after a column was added.
+    pub fn clear(&mut self) {
+        self.name.clear();
+        self.source.clear();
+    }
+}
@@ -0,0 +1,131 @@
+use rust_excel_reader_core::export::write_csv;
+use rust_excel_reader_core::values::SortOrder;
+use rust_excel_reader_core::ColumnType;
+
+use crate::table_grid::ColumnLayout;
+
+/// Rows added to the view each time the user scrolls to the bottom.
+const DISPLAY_INCREMENT: usize = 100;
+
+/// Highlight of a single cell, e.g. a changed value in a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellMark {
+    Plain,
+    Added,
+    Removed,
+    Changed,
+    /// A difference too small to count, e.g. within a numeric tolerance.
+    Minor,
+}
+
+/// A table computed from the open files, such as a query result, shown in
+/// its own tab next to the file view.
+pub struct DerivedTable {
+    pub title: String,
+    /// Shown when hovering the tab, e.g. the SQL that produced the table.
+    pub description: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub column_types: Vec<ColumnType>,
+    /// Rows rendered so far. The rows are all in memory already, but the
+    /// grid is only grown as the user scrolls, like the file view.
+    pub rows_to_show: usize,
+    pub sort: Option<SortOrder>,
+    /// Per-cell highlights, one entry per row, or empty for a plain table.
+    pub marks: Vec<Vec<CellMark>>,
+    /// Shown below the table, e.g. the counts of a diff.
+    pub summary: Option<String>,
+    pub layout: ColumnLayout,
+}
+
+impl DerivedTable {
+    pub fn new(
+        title: String,
+        description: String,
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+        column_types: Vec<ColumnType>,
+    ) -> Self {
+        Self {
+            title,
+            description,
+            headers,
+            rows,
+            column_types,
+            rows_to_show: DISPLAY_INCREMENT,
+            sort: None,
+            marks: Vec::new(),
+            summary: None,
+            layout: ColumnLayout::default(),
+        }
+    }
+
+    pub fn with_marks(mut self, marks: Vec<Vec<CellMark>>) -> Self {
+        self.marks = marks;
+        self
+    }
+
+    pub fn with_summary(mut self, summary: String) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
+    pub fn mark(&self, row: usize, column: usize) -> CellMark {
+        self.marks
+            .get(row)
+            .and_then(|marks| marks.get(column))
+            .copied()
+            .unwrap_or(CellMark::Plain)
+    }
+
+    pub fn visible_rows(&self) -> &[Vec<String>] {
+        &self.rows[..self.rows_to_show.min(self.rows.len())]
+    }
+
+    pub fn is_fully_shown(&self) -> bool {
+        self.rows_to_show >= self.rows.len()
+    }
+
+    pub fn show_more(&mut self) {
+        self.rows_to_show += DISPLAY_INCREMENT;
+    }
+
+    /// Sorts by `column`, ascending first and descending on the next click.
+    /// Numbers sort by value and blanks sort last in both directions.
+    pub fn toggle_sort(&mut self, column: usize) {
+        let order = SortOrder::toggled(self.sort, column);
+        self.sort = Some(order);
+        if self.marks.is_empty() {
+            self.rows.sort_by(|a, b| order.compare(a, b));
+            return;
+        }
+
+        // Keep each row's highlights with it
+        let mut rows: Vec<_> = std::mem::take(&mut self.rows)
+            .into_iter()
+            .zip(std::mem::take(&mut self.marks))
+            .collect();
+        rows.sort_by(|a, b| order.compare(&a.0, &b.0));
+        (self.rows, self.marks) = rows.into_iter().unzip();
+    }
+
+    /// Asks for a target file and writes the whole table to it as CSV.
+    /// Returns the path written, or `None` if the user cancelled.
+    pub fn export_csv(&self) -> Result<Option<std::path::PathBuf>, String> {
+        let file_name: String = self
+            .title
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name(format!("{}.csv", file_name.trim_matches('_')))
+            .save_file()
+        else {
+            return Ok(None);
+        };
+
+        write_csv(&path, &self.headers, &self.rows)?;
+        Ok(Some(path))
+    }
+}
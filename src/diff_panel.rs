@@ -0,0 +1,278 @@
+use egui::Context;
+use rust_excel_reader_core::data_loader::spawn_task;
+use rust_excel_reader_core::diff::{
+    diff_sheets, CellChange, DiffAlignment, DiffResult, DiffSpec, DiffStatus,
+};
+use rust_excel_reader_core::table_reader::SheetSource;
+use rust_excel_reader_core::LoadError;
+use std::sync::mpsc;
+
+use crate::derived_table::{CellMark, DerivedTable};
+use crate::i18n::{self, tr};
+use crate::pivot_panel::column_list;
+use crate::sheet_picker::{current_source, sheet_buttons, CurrentSheet, PickedSheet};
+use crate::theme;
+
+type DiffOutcome = (String, Result<DiffResult, LoadError>);
+
+/// Window for comparing two files or sheets, e.g. results before and after
+/// a tester was recalibrated. The diff streams both sheets in the
+/// background and opens as a tab with the differing cells highlighted.
+pub struct DiffPanel {
+    pub open: bool,
+    /// "Before" and "after".
+    sides: [Option<PickedSheet>; 2],
+    by_key: bool,
+    /// Key columns, as indexes into the headers of the first sheet.
+    key_columns: Vec<usize>,
+    tolerance: f64,
+    /// Ignore spaces around cells and keys.
+    trim: bool,
+    tx: mpsc::Sender<DiffOutcome>,
+    rx: mpsc::Receiver<DiffOutcome>,
+    running: bool,
+    error: Option<String>,
+    diff_count: usize,
+}
+
+impl DiffPanel {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            open: false,
+            sides: [None, None],
+            by_key: false,
+            key_columns: Vec::new(),
+            tolerance: 0.0,
+            trim: true,
+            tx,
+            rx,
+            running: false,
+            error: None,
+            diff_count: 0,
+        }
+    }
+
+    /// Shows the window if it is open and returns a diff that finished since
+    /// the last frame. `current` is the sheet open in the file view.
+    pub fn show(&mut self, ctx: &Context, current: Option<CurrentSheet>) -> Option<DerivedTable> {
+        let palette = theme::palette(ctx);
+        let finished = self.handle_outcome();
+        if !self.open {
+            return finished;
+        }
+        if self.sides[0].is_none() {
+            if let Some(current) = current {
+                self.set_side(0, current_source(current));
+            }
+        }
+
+        let mut open = true;
+        let mut compare = false;
+
+        egui::Window::new(tr!("diff-title"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                for (side, label) in [(0, tr!("diff-before")), (1, tr!("diff-after"))] {
+                    self.side_picker(ui, side, &label, current);
+                    ui.add_space(4.0);
+                }
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label(tr!("diff-align"));
+                    ui.radio_value(&mut self.by_key, false, tr!("diff-by-position"));
+                    ui.radio_value(&mut self.by_key, true, tr!("diff-by-key"));
+                });
+                if self.by_key {
+                    let headers = self.sides[0]
+                        .as_ref()
+                        .map(|side| side.headers.as_slice())
+                        .unwrap_or_default();
+                    column_list(ui, &tr!("diff-key"), "diff_keys", headers, &mut self.key_columns);
+                }
+                ui.horizontal(|ui| {
+                    ui.label(tr!("diff-tolerance"));
+                    ui.add(
+                        egui::DragValue::new(&mut self.tolerance)
+                            .speed(0.001)
+                            .range(0.0..=f64::MAX),
+                    );
+                });
+                ui.checkbox(&mut self.trim, tr!("diff-trim"))
+                    .on_hover_text(tr!("diff-trim-hint"));
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let ready = self.sides.iter().all(Option::is_some)
+                        && (!self.by_key || !self.key_columns.is_empty());
+                    compare = ui
+                        .add_enabled(ready && !self.running, egui::Button::new(tr!("diff-compare")))
+                        .clicked();
+                    if self.running {
+                        ui.spinner();
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .color(palette.error)
+                            .size(12.0),
+                    );
+                }
+            });
+
+        if !open {
+            self.open = false;
+        }
+        if compare {
+            self.start(ctx);
+        }
+
+        finished
+    }
+
+    fn side_picker(
+        &mut self,
+        ui: &mut egui::Ui,
+        side: usize,
+        label: &str,
+        current: Option<CurrentSheet>,
+    ) {
+        let palette = theme::palette(ui.ctx());
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(label).strong());
+            match &self.sides[side] {
+                Some(chosen) => ui.label(chosen.name()),
+                None => ui.label(
+                    egui::RichText::new(tr!("diff-not-chosen")).color(palette.muted),
+                ),
+            };
+        });
+
+        let picked = sheet_buttons(ui, ("diff_sheet", side), self.sides[side].as_ref(), current);
+        if let Some(source) = picked {
+            self.set_side(side, source);
+        }
+    }
+
+    fn set_side(&mut self, side: usize, source: SheetSource) {
+        match PickedSheet::probe(source) {
+            Ok(probed) => {
+                if side == 0 && self.sides[0].as_ref().map(|s| &s.headers) != Some(&probed.headers)
+                {
+                    self.key_columns.clear();
+                }
+                self.sides[side] = Some(probed);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn start(&mut self, ctx: &Context) {
+        let [Some(before), Some(after)] = &self.sides else {
+            return;
+        };
+        let alignment = if self.by_key {
+            DiffAlignment::Key(
+                self.key_columns
+                    .iter()
+                    .filter_map(|&column| before.headers.get(column).cloned())
+                    .collect(),
+            )
+        } else {
+            DiffAlignment::Position
+        };
+        let spec = DiffSpec {
+            alignment,
+            tolerance: self.tolerance,
+            trim: self.trim,
+        };
+
+        self.running = true;
+        self.error = None;
+        let title = format!("{} ⇄ {}", before.name(), after.name());
+        let left = before.source.clone();
+        let right = after.source.clone();
+        let egui_ctx = ctx.clone();
+        spawn_task(
+            move || (title, diff_sheets(&left, &right, &spec)),
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_outcome(&mut self) -> Option<DerivedTable> {
+        let (title, result) = self.rx.try_recv().ok()?;
+        self.running = false;
+        match result {
+            Ok(diff) => {
+                self.diff_count += 1;
+                Some(diff_table(tr!("diff-tab", number = self.diff_count), title, diff))
+            }
+            Err(e) => {
+                self.error = Some(i18n::load_error(&e));
+                None
+            }
+        }
+    }
+}
+
+/// Lays a diff out as a table: status and key first, then the compared
+/// columns, with added, removed and changed cells highlighted.
+fn diff_table(title: String, description: String, diff: DiffResult) -> DerivedTable {
+    let mut headers = vec!["status".to_string(), diff.key_header];
+    headers.extend(diff.headers);
+
+    let mut rows = Vec::with_capacity(diff.rows.len());
+    let mut marks = Vec::with_capacity(diff.rows.len());
+    for row in diff.rows {
+        let status_mark = match row.status {
+            DiffStatus::Added => CellMark::Added,
+            DiffStatus::Removed => CellMark::Removed,
+            DiffStatus::Changed => CellMark::Changed,
+        };
+        let mut row_marks = vec![status_mark, CellMark::Plain];
+        row_marks.extend(row.changes.iter().map(|change| match (row.status, change) {
+            (DiffStatus::Changed, CellChange::Changed) => CellMark::Changed,
+            (DiffStatus::Changed, CellChange::WithinTolerance) => CellMark::Minor,
+            (DiffStatus::Changed, CellChange::Same) => CellMark::Plain,
+            _ => status_mark,
+        }));
+        marks.push(row_marks);
+
+        let mut cells = vec![row.status.label().to_string(), row.key.join(" · ")];
+        cells.extend(row.cells);
+        rows.push(cells);
+    }
+
+    let counts = diff.summary;
+    let mut summary = tr!(
+        "diff-summary",
+        changed = counts.changed,
+        added = counts.added,
+        removed = counts.removed,
+        unchanged = counts.unchanged,
+        cells = counts.changed_cells,
+        within = counts.cells_within_tolerance
+    );
+    if diff.truncated {
+        summary.push_str(&tr!("diff-truncated", count = rows.len()));
+    }
+    if !diff.left_only_columns.is_empty() {
+        let columns = diff.left_only_columns.join(", ");
+        summary.push_str(&tr!("diff-only-before", columns = columns));
+    }
+    if !diff.right_only_columns.is_empty() {
+        let columns = diff.right_only_columns.join(", ");
+        summary.push_str(&tr!("diff-only-after", columns = columns));
+    }
+
+    DerivedTable::new(title, description, headers, rows, Vec::new())
+        .with_marks(marks)
+        .with_summary(summary)
+}
@@ -0,0 +1,255 @@
+use egui::{Align2, Context, CursorIcon, FontId, Rect, Sense, Stroke, Vec2};
+use rust_excel_reader_core::fixed_width_loader::{
+    guess_layout, FixedWidthLayout, FixedWidthProfile,
+};
+use std::path::PathBuf;
+
+use crate::i18n::tr;
+use crate::theme;
+
+/// Number of split rows shown under the boundary editor.
+const PREVIEW_ROWS: usize = 8;
+
+/// What the user chose in the import dialog.
+pub enum FixedWidthAction {
+    None,
+    Cancel,
+    Import {
+        layout: FixedWidthLayout,
+        /// Set when the layout should be saved as a named profile.
+        profile: Option<FixedWidthProfile>,
+    },
+}
+
+/// Import dialog for fixed-width files: shows the first lines of the file and
+/// lets the user drag, add and remove column boundaries over them.
+pub struct FixedWidthDialog {
+    pub path: PathBuf,
+    /// The archive `path` was extracted from, if any.
+    pub archive: Option<PathBuf>,
+    lines: Vec<String>,
+    layout: FixedWidthLayout,
+    profile_name: String,
+    save_profile: bool,
+}
+
+impl FixedWidthDialog {
+    pub fn new(
+        path: PathBuf,
+        archive: Option<PathBuf>,
+        lines: Vec<String>,
+        layout: Option<FixedWidthLayout>,
+    ) -> Self {
+        let layout = layout.unwrap_or_else(|| guess_layout(&lines));
+        let profile_name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+
+        Self {
+            path,
+            archive,
+            lines,
+            layout,
+            profile_name,
+            save_profile: true,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) -> FixedWidthAction {
+        let palette = theme::palette(ctx);
+        let mut action = FixedWidthAction::None;
+        let mut open = true;
+
+        egui::Window::new(tr!("fixed-width-title"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_size([720.0, 480.0])
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(tr!("fixed-width-help"))
+                        .color(palette.muted)
+                        .size(12.0),
+                );
+                ui.add_space(6.0);
+
+                egui::ScrollArea::both()
+                    .id_source("fixed_width_editor")
+                    .max_height(260.0)
+                    .show(ui, |ui| self.boundary_editor(ui));
+
+                ui.add_space(8.0);
+                self.preview_table(ui);
+                ui.add_space(8.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button(tr!("fixed-width-guess")).clicked() {
+                        self.layout = guess_layout(&self.lines);
+                    }
+                    if ui.button(tr!("fixed-width-clear")).clicked() {
+                        self.layout.boundaries.clear();
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.save_profile, tr!("fixed-width-save-profile"));
+                    ui.add_enabled(
+                        self.save_profile,
+                        egui::TextEdit::singleline(&mut self.profile_name).desired_width(160.0),
+                    );
+                });
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button(tr!("fixed-width-import")).clicked() {
+                        action = FixedWidthAction::Import {
+                            layout: self.layout.clone(),
+                            profile: self.profile(),
+                        };
+                    }
+                    if ui.button(tr!("fixed-width-cancel")).clicked() {
+                        action = FixedWidthAction::Cancel;
+                    }
+                });
+            });
+
+        if !open {
+            action = FixedWidthAction::Cancel;
+        }
+        action
+    }
+
+    fn profile(&self) -> Option<FixedWidthProfile> {
+        let header_line = self.lines.first()?;
+        let name = self.profile_name.trim();
+        if !self.save_profile || name.is_empty() {
+            return None;
+        }
+
+        Some(FixedWidthProfile {
+            name: name.to_string(),
+            header_line: header_line.clone(),
+            layout: self.layout.clone(),
+        })
+    }
+
+    fn boundary_editor(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        let font = FontId::monospace(12.0);
+        let char_width = ui.fonts(|f| f.glyph_width(&font, 'M'));
+        let row_height = ui.fonts(|f| f.row_height(&font));
+        let width_chars = self
+            .lines
+            .iter()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0)
+            + 10;
+
+        let size = Vec2::new(
+            char_width * width_chars as f32,
+            row_height * (self.lines.len() + 1) as f32,
+        );
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 4.0, palette.extreme_bg);
+
+        // Ruler with a tick every 10 characters
+        for position in (0..width_chars).step_by(10) {
+            let x = rect.left() + position as f32 * char_width;
+            painter.text(
+                egui::pos2(x + 2.0, rect.top()),
+                Align2::LEFT_TOP,
+                position.to_string(),
+                FontId::monospace(10.0),
+                palette.faint,
+            );
+        }
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let color = if index == 0 {
+                palette.success
+            } else {
+                palette.text
+            };
+            painter.text(
+                egui::pos2(rect.left(), rect.top() + row_height * (index + 1) as f32),
+                Align2::LEFT_TOP,
+                line,
+                font.clone(),
+                color,
+            );
+        }
+
+        let to_position = |x: f32| ((x - rect.left()) / char_width).round().max(1.0) as usize;
+        let mut moved = None;
+        let mut removed = None;
+        let mut on_handle = false;
+
+        for (index, &boundary) in self.layout.boundaries.iter().enumerate() {
+            let x = rect.left() + boundary as f32 * char_width;
+            let handle = Rect::from_min_max(
+                egui::pos2(x - 4.0, rect.top()),
+                egui::pos2(x + 4.0, rect.bottom()),
+            );
+            let handle_response = ui
+                .interact(handle, ui.id().with(("fixed_width_boundary", index)), Sense::click_and_drag())
+                .on_hover_cursor(CursorIcon::ResizeHorizontal);
+
+            if handle_response.dragged() {
+                if let Some(pointer) = handle_response.interact_pointer_pos() {
+                    moved = Some((index, to_position(pointer.x)));
+                }
+            }
+            if handle_response.double_clicked() || handle_response.secondary_clicked() {
+                removed = Some(index);
+            }
+            on_handle |= handle_response.hovered() || handle_response.dragged();
+
+            let color = if handle_response.hovered() || handle_response.dragged() {
+                palette.warning
+            } else {
+                palette.accent
+            };
+            painter.line_segment(
+                [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                Stroke::new(2.0, color),
+            );
+        }
+
+        if let Some((index, position)) = moved {
+            // Keep boundaries from crossing so handle ids stay stable mid-drag
+            let boundaries = &mut self.layout.boundaries;
+            let min = if index > 0 { boundaries[index - 1] + 1 } else { 1 };
+            let max = boundaries.get(index + 1).map_or(usize::MAX, |next| next - 1);
+            boundaries[index] = position.clamp(min, max.max(min));
+        }
+        if let Some(index) = removed {
+            self.layout.boundaries.remove(index);
+        } else if response.clicked() && !on_handle {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                self.layout.insert_boundary(to_position(pointer.x));
+            }
+        }
+    }
+
+    fn preview_table(&self, ui: &mut egui::Ui) {
+        egui::ScrollArea::horizontal()
+            .id_source("fixed_width_preview")
+            .show(ui, |ui| {
+                egui::Grid::new("fixed_width_preview_grid")
+                    .striped(true)
+                    .spacing([12.0, 2.0])
+                    .show(ui, |ui| {
+                        for (index, line) in self.lines.iter().take(PREVIEW_ROWS + 1).enumerate() {
+                            for field in self.layout.split_line(line) {
+                                let text = egui::RichText::new(field).size(12.0);
+                                ui.label(if index == 0 { text.strong() } else { text });
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
@@ -0,0 +1,227 @@
+use egui::Context;
+use rust_excel_reader_core::data_loader::spawn_task;
+use rust_excel_reader_core::folder::{concat_files, reference_headers, scan_folder, FolderFile};
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use crate::i18n::tr;
+use crate::theme;
+
+/// File name pattern offered when a folder is opened.
+const DEFAULT_PATTERN: &str = "*.csv";
+
+/// What happened in the folder dialog.
+pub enum FolderAction {
+    None,
+    Cancel,
+    /// The files were combined into this file.
+    Combined(PathBuf),
+}
+
+/// Dialog for "Open folder": lists the files matching a pattern, flags
+/// those whose headers do not line up with the rest, and combines the
+/// chosen files into one table in the background.
+pub struct FolderDialog {
+    dir: PathBuf,
+    pattern: String,
+    files: Vec<FolderFile>,
+    /// Whether each of `files` goes into the table.
+    included: Vec<bool>,
+    reference: Vec<String>,
+    tx: mpsc::Sender<Result<PathBuf, String>>,
+    rx: mpsc::Receiver<Result<PathBuf, String>>,
+    running: bool,
+    error: Option<String>,
+}
+
+impl FolderDialog {
+    pub fn new(dir: PathBuf) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let mut dialog = Self {
+            dir,
+            pattern: DEFAULT_PATTERN.to_string(),
+            files: Vec::new(),
+            included: Vec::new(),
+            reference: Vec::new(),
+            tx,
+            rx,
+            running: false,
+            error: None,
+        };
+        dialog.scan();
+        dialog
+    }
+
+    /// Lists the matching files and reads their headers and preambles.
+    fn scan(&mut self) {
+        match scan_folder(&self.dir, &self.pattern) {
+            Ok(files) => {
+                self.reference = reference_headers(&files);
+                self.included = files.iter().map(|f| f.error.is_none()).collect();
+                self.files = files;
+                self.error = None;
+            }
+            Err(e) => {
+                self.files.clear();
+                self.included.clear();
+                self.error = Some(e);
+            }
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) -> FolderAction {
+        let palette = theme::palette(ctx);
+        if let Ok(result) = self.rx.try_recv() {
+            self.running = false;
+            match result {
+                Ok(path) => return FolderAction::Combined(path),
+                Err(e) => self.error = Some(e),
+            }
+        }
+
+        let mut action = FolderAction::None;
+        let mut open = true;
+        let mut combine = false;
+
+        egui::Window::new(tr!("folder-title"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(self.dir.display().to_string())
+                        .color(palette.muted)
+                        .size(12.0),
+                );
+                ui.horizontal(|ui| {
+                    ui.label(tr!("folder-files"));
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.pattern)
+                            .desired_width(200.0)
+                            .hint_text("*.csv;*.csv.gz"),
+                    );
+                    let rescan = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button(tr!("folder-scan")).clicked() || rescan {
+                        self.scan();
+                    }
+                });
+                ui.add_space(4.0);
+
+                let mismatches = self
+                    .files
+                    .iter()
+                    .filter(|f| f.header_mismatch(&self.reference).is_some())
+                    .count();
+                ui.label(tr!(
+                    "folder-summary",
+                    count = self.files.len(),
+                    mismatches = mismatches
+                ));
+
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    for (file, included) in self.files.iter().zip(&mut self.included) {
+                        ui.horizontal(|ui| {
+                            ui.add_enabled(
+                                file.error.is_none(),
+                                egui::Checkbox::new(included, file.file_name()),
+                            );
+                            let preamble: Vec<String> = file
+                                .preamble
+                                .iter()
+                                .map(|(k, v)| format!("{} = {}", k, v))
+                                .collect();
+                            if !preamble.is_empty() {
+                                ui.label(
+                                    egui::RichText::new(preamble.join(" · "))
+                                        .color(palette.muted)
+                                        .size(11.0),
+                                );
+                            }
+                        });
+                        let problem = file
+                            .error
+                            .as_ref()
+                            .map(|e| format!("✖ {}", e))
+                            .or_else(|| {
+                                file.header_mismatch(&self.reference)
+                                    .map(|m| format!("⚠ {}", m))
+                            });
+                        if let Some(problem) = problem {
+                            ui.label(
+                                egui::RichText::new(problem)
+                                    .color(palette.warning)
+                                    .size(11.0),
+                            );
+                        }
+                    }
+                });
+
+                ui.add_space(6.0);
+                ui.label(
+                    egui::RichText::new(tr!("folder-help"))
+                        .color(palette.muted)
+                        .size(12.0),
+                );
+                ui.horizontal(|ui| {
+                    let count = self.included.iter().filter(|&&i| i).count();
+                    combine = ui
+                        .add_enabled(
+                            count > 0 && !self.running,
+                            egui::Button::new(tr!("folder-combine", count = count)),
+                        )
+                        .clicked();
+                    if self.running {
+                        ui.spinner();
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .color(palette.error)
+                            .size(12.0),
+                    );
+                }
+            });
+
+        if combine {
+            self.start(ctx);
+        }
+        if !open {
+            action = FolderAction::Cancel;
+        }
+        action
+    }
+
+    fn start(&mut self, ctx: &Context) {
+        let files: Vec<FolderFile> = self
+            .files
+            .iter()
+            .zip(&self.included)
+            .filter(|(_, &included)| included)
+            .map(|(file, _)| file.clone())
+            .collect();
+        let name = self
+            .dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let target_dir = std::env::temp_dir()
+            .join("rust_excel_reader")
+            .join("folders");
+        let target = target_dir.join(format!("{}.csv", name));
+
+        self.running = true;
+        self.error = None;
+        let egui_ctx = ctx.clone();
+        spawn_task(
+            move || {
+                std::fs::create_dir_all(&target_dir).map_err(|e| e.to_string())?;
+                concat_files(&files, &target).map(|_| target)
+            },
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+}
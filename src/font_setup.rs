@@ -1,48 +1,223 @@
-use egui::{Context, FontDefinitions, FontFamily};
-
-pub fn setup_custom_fonts(ctx: &Context) {
-    let mut fonts = FontDefinitions::default();
-
-    // Try to load system fonts that support Chinese characters
-    #[cfg(target_os = "windows")]
-    {
-        try_load_windows_fonts(&mut fonts);
-    }
-
-    // Always set fonts (even if no Chinese font was loaded, this ensures proper Unicode handling)
-    ctx.set_fonts(fonts);
-}
-
-#[cfg(target_os = "windows")]
-fn try_load_windows_fonts(fonts: &mut FontDefinitions) -> bool {
-    let font_paths = [
-        "C:/Windows/Fonts/msyh.ttc",   // Microsoft YaHei
-        "C:/Windows/Fonts/simsun.ttc", // SimSun
-        "C:/Windows/Fonts/msyhl.ttc",  // Microsoft YaHei Light
-    ];
-
-    for font_path in &font_paths {
-        if let Ok(font_data) = std::fs::read(font_path) {
-            fonts.font_data.insert(
-                "chinese_font".to_owned(),
-                egui::FontData::from_owned(font_data),
-            );
-
-            // Add the font to the font families
-            fonts
-                .families
-                .entry(FontFamily::Proportional)
-                .or_default()
-                .insert(0, "chinese_font".to_owned());
-            fonts
-                .families
-                .entry(FontFamily::Monospace)
-                .or_default()
-                .insert(0, "chinese_font".to_owned());
-
-            return true;
-        }
-    }
-
-    false
-}
+use egui::{Context, FontDefinitions, FontFamily};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Size of the table text, at which the interface is drawn unscaled.
+pub const BASE_TEXT_SIZE: f32 = 12.0;
+
+/// Font files with CJK coverage by file name, most preferred first. Names
+/// are compared in lowercase with spaces, dashes and underscores removed,
+/// so `NotoSansCJKtc-Regular.otf` matches `notosanscjk`.
+const CJK_FONT_NAMES: &[&str] = &[
+    "msyh",
+    "msjh",
+    "notosanscjk",
+    "notosanstc",
+    "notosanssc",
+    "sourcehansans",
+    "pingfang",
+    "hiraginosansgb",
+    "stheiti",
+    "wqymicrohei",
+    "wqyzenhei",
+    "droidsansfallback",
+    "notoserifcjk",
+    "sourcehanserif",
+    "simsun",
+    "uming",
+    "ukai",
+    "arialunicode",
+];
+
+/// Directories searched for fonts, recursively.
+#[cfg(target_os = "windows")]
+fn font_dirs() -> Vec<PathBuf> {
+    vec![PathBuf::from("C:/Windows/Fonts")]
+}
+
+#[cfg(target_os = "macos")]
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/System/Library/Fonts"),
+        PathBuf::from("/Library/Fonts"),
+    ];
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(Path::new(&home).join("Library/Fonts"));
+    }
+    dirs
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/usr/share/fonts"),
+        PathBuf::from("/usr/local/share/fonts"),
+    ];
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    match std::env::var_os("XDG_DATA_HOME") {
+        Some(data_home) => dirs.push(Path::new(&data_home).join("fonts")),
+        None => dirs.extend(home.iter().map(|home| home.join(".local/share/fonts"))),
+    }
+    dirs.extend(home.iter().map(|home| home.join(".fonts")));
+    dirs
+}
+
+/// Levels of subdirectories searched below each font directory.
+const MAX_SCAN_DEPTH: usize = 4;
+
+/// A CJK font compiled into the binary, used when the system has none.
+#[cfg(all(feature = "bundled-font", not(bundled_font_missing)))]
+const BUNDLED_FONT: &[u8] = include_bytes!("../assets/fonts/NotoSansTC-Regular.otf");
+
+// The font is not in the repository; build.rs checks that it was added
+#[cfg(bundled_font_missing)]
+compile_error!(
+    "the bundled-font feature needs assets/fonts/NotoSansTC-Regular.otf: download Noto Sans TC \
+     (SIL Open Font License) from https://fonts.google.com/noto/specimen/Noto+Sans+TC and \
+     put the file there, or build without the feature"
+);
+
+/// Font choices of the user, persisted in the settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontSettings {
+    /// Font file used before the CJK and built-in fonts.
+    pub custom_font: Option<PathBuf>,
+    /// Size of the table text in points. The whole interface is scaled to
+    /// match, so layouts stay aligned.
+    pub size: f32,
+}
+
+impl Default for FontSettings {
+    fn default() -> Self {
+        Self {
+            custom_font: None,
+            size: BASE_TEXT_SIZE,
+        }
+    }
+}
+
+/// Which fonts were installed, shown in the preferences menu.
+#[derive(Debug, Clone, Default)]
+pub struct FontReport {
+    /// The CJK font found, as a path or "bundled".
+    pub cjk_font: Option<String>,
+    /// Why the custom font could not be used.
+    pub error: Option<String>,
+}
+
+/// Installs the custom font, a CJK font found on the system (or the
+/// bundled one) and egui's own fonts, in that order, and applies the text
+/// size.
+pub fn setup_custom_fonts(ctx: &Context, settings: &FontSettings) -> FontReport {
+    let mut fonts = FontDefinitions::default();
+    let mut report = FontReport::default();
+
+    // Try to load system fonts that support Chinese characters
+    if let Some((path, data)) = find_cjk_font() {
+        install_font(&mut fonts, "chinese_font", data);
+        report.cjk_font = Some(path.display().to_string());
+    } else {
+        #[cfg(all(feature = "bundled-font", not(bundled_font_missing)))]
+        {
+            install_font(&mut fonts, "chinese_font", BUNDLED_FONT.to_vec());
+            report.cjk_font = Some("bundled".to_owned());
+        }
+    }
+
+    if let Some(path) = &settings.custom_font {
+        match read_font(path) {
+            Ok(data) => install_font(&mut fonts, "custom_font", data),
+            Err(e) => report.error = Some(e),
+        }
+    }
+
+    // Always set fonts (even if no Chinese font was loaded, this ensures proper Unicode handling)
+    ctx.set_fonts(fonts);
+    apply_text_size(ctx, settings.size);
+    report
+}
+
+pub fn apply_text_size(ctx: &Context, size: f32) {
+    ctx.set_zoom_factor(size / BASE_TEXT_SIZE);
+}
+
+/// Puts a font first in both families, so it is used wherever it has
+/// glyphs.
+fn install_font(fonts: &mut FontDefinitions, name: &str, data: Vec<u8>) {
+    fonts
+        .font_data
+        .insert(name.to_owned(), egui::FontData::from_owned(data));
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .insert(0, name.to_owned());
+    }
+}
+
+/// Reads a font file, checking that it parses: egui panics on a broken
+/// font.
+fn read_font(path: &Path) -> Result<Vec<u8>, String> {
+    let data =
+        std::fs::read(path).map_err(|e| format!("Cannot read font {}: {}", path.display(), e))?;
+    ab_glyph::FontRef::try_from_slice(&data)
+        .map_err(|_| format!("{} is not a usable font file", path.display()))?;
+    Ok(data)
+}
+
+/// The most preferred CJK font in the font directories that can be read.
+fn find_cjk_font() -> Option<(PathBuf, Vec<u8>)> {
+    let mut candidates = Vec::new();
+    for dir in font_dirs() {
+        scan_fonts(&dir, MAX_SCAN_DEPTH, &mut candidates);
+    }
+    candidates.sort_by_key(|(rank, path)| {
+        let name = normalized_name(path);
+        // The regular weight of a family, then the shortest name
+        (*rank, !name.contains("regular"), name.len())
+    });
+    candidates
+        .into_iter()
+        .find_map(|(_, path)| read_font(&path).ok().map(|data| (path, data)))
+}
+
+/// Collects font files below `dir` whose name is in [`CJK_FONT_NAMES`],
+/// with the position of that name.
+fn scan_fonts(dir: &Path, depth: usize, found: &mut Vec<(usize, PathBuf)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth > 0 {
+                scan_fonts(&path, depth - 1, found);
+            }
+            continue;
+        }
+        let is_font = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e.to_lowercase().as_str(), "ttf" | "ttc" | "otf"));
+        if !is_font {
+            continue;
+        }
+        let name = normalized_name(&path);
+        if let Some(rank) = CJK_FONT_NAMES
+            .iter()
+            .position(|wanted| name.contains(wanted))
+        {
+            found.push((rank, path));
+        }
+    }
+}
+
+fn normalized_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase()
+        .replace([' ', '-', '_'], "")
+}
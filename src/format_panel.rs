@@ -0,0 +1,330 @@
+use egui::Context;
+use rust_excel_reader_core::data_loader::spawn_task;
+use rust_excel_reader_core::formatting::{
+    Comparison, ConditionalFormat, FormatRule, RuleKind, TextMatch,
+};
+use rust_excel_reader_core::stats::{numeric_ranges, NumericRange};
+use rust_excel_reader_core::table_reader::SheetSource;
+use std::collections::HashMap;
+use std::sync::mpsc;
+
+use crate::i18n::tr;
+use crate::pivot_panel::column_combo;
+use crate::theme;
+
+/// Column ranges scanned for one sheet, tagged with the sheet they belong to.
+type RangeOutcome = (u64, Result<Vec<(usize, Option<NumericRange>)>, String>);
+
+/// Side panel for conditional formatting of the file view: color scales,
+/// thresholds, text matches and data bars.
+///
+/// Rules are saved per header signature by the app, so they come back for
+/// every file with the same columns. Color scales and data bars need the
+/// range of their column over the whole file, which is scanned once in the
+/// background and kept while the rules are edited.
+pub struct FormatPanel {
+    pub open: bool,
+    rules: Vec<FormatRule>,
+    format: ConditionalFormat,
+    headers: Vec<String>,
+    source: Option<SheetSource>,
+    /// Ranges found so far; `None` for columns without numbers.
+    ranges: HashMap<usize, Option<NumericRange>>,
+    /// Bumped for every sheet, so ranges of a previous sheet are dropped.
+    generation: u64,
+    tx: mpsc::Sender<RangeOutcome>,
+    rx: mpsc::Receiver<RangeOutcome>,
+    scanning: bool,
+    error: Option<String>,
+    new_column: usize,
+    new_kind: usize,
+}
+
+impl FormatPanel {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            open: false,
+            rules: Vec::new(),
+            format: ConditionalFormat::default(),
+            headers: Vec::new(),
+            source: None,
+            ranges: HashMap::new(),
+            generation: 0,
+            tx,
+            rx,
+            scanning: false,
+            error: None,
+            new_column: 0,
+            new_kind: 0,
+        }
+    }
+
+    pub fn format(&self) -> &ConditionalFormat {
+        &self.format
+    }
+
+    pub fn rules(&self) -> &[FormatRule] {
+        &self.rules
+    }
+
+    /// Switches to a newly loaded sheet and its saved rules.
+    pub fn set_sheet(
+        &mut self,
+        ctx: &Context,
+        source: SheetSource,
+        headers: Vec<String>,
+        rules: Vec<FormatRule>,
+    ) {
+        self.generation += 1;
+        self.scanning = false;
+        self.ranges.clear();
+        self.source = Some(source);
+        self.headers = headers;
+        self.rules = rules;
+        self.error = None;
+        self.recompile(ctx);
+    }
+
+    pub fn clear(&mut self) {
+        self.generation += 1;
+        self.scanning = false;
+        self.ranges.clear();
+        self.source = None;
+        self.headers.clear();
+        self.rules.clear();
+        self.format = ConditionalFormat::default();
+    }
+
+    /// Compiles the rules and scans the ranges that are still missing.
+    fn recompile(&mut self, ctx: &Context) {
+        self.format = ConditionalFormat::compile(&self.rules, &self.headers);
+        for (&column, range) in &self.ranges {
+            if let Some(range) = range {
+                self.format.set_range(column, *range);
+            }
+        }
+
+        let missing: Vec<usize> = self
+            .format
+            .columns_needing_range()
+            .into_iter()
+            .filter(|column| !self.ranges.contains_key(column))
+            .collect();
+        let Some(source) = self
+            .source
+            .clone()
+            .filter(|_| !missing.is_empty() && !self.scanning)
+        else {
+            return;
+        };
+
+        self.scanning = true;
+        let generation = self.generation;
+        let egui_ctx = ctx.clone();
+        spawn_task(
+            move || {
+                let ranges = numeric_ranges(&source, &missing)
+                    .map(|ranges| missing.into_iter().zip(ranges).collect());
+                (generation, ranges)
+            },
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_ranges(&mut self, ctx: &Context) {
+        while let Ok((generation, result)) = self.rx.try_recv() {
+            if generation != self.generation {
+                continue;
+            }
+            self.scanning = false;
+            match result {
+                Ok(ranges) => {
+                    self.ranges.extend(ranges);
+                    // Rules added during the scan may need more columns
+                    self.recompile(ctx);
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+    }
+
+    /// Shows the panel if it is open. Returns `true` when the rules changed
+    /// and should be saved.
+    pub fn show(&mut self, ctx: &Context) -> bool {
+        let palette = theme::palette(ctx);
+        self.handle_ranges(ctx);
+        if !self.open {
+            return false;
+        }
+
+        let before = self.rules.clone();
+        egui::SidePanel::right("format_panel")
+            .resizable(true)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(tr!("format-title"));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").clicked() {
+                            self.open = false;
+                        }
+                    });
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.rule_list(ui);
+                    self.add_rule(ui);
+                });
+
+                if self.scanning {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(tr!("format-scanning"));
+                    });
+                }
+                if let Some(error) = &self.error {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .color(palette.error)
+                            .size(12.0),
+                    );
+                }
+            });
+
+        let changed = self.rules != before;
+        if changed {
+            self.recompile(ctx);
+        }
+        changed
+    }
+
+    fn rule_list(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        if self.rules.is_empty() {
+            ui.label(
+                egui::RichText::new(tr!("format-no-rules"))
+                    .color(palette.muted)
+                .size(12.0),
+            );
+        }
+
+        let mut removed = None;
+        for (index, rule) in self.rules.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").clicked() {
+                    removed = Some(index);
+                }
+                ui.label(egui::RichText::new(&rule.column).strong());
+                ui.label(rule_kind_label(&rule.kind));
+            });
+            ui.horizontal(|ui| rule_settings(ui, index, &mut rule.kind));
+            if let Some((_, error)) = self.format.errors().iter().find(|(i, _)| *i == index) {
+                ui.label(
+                    egui::RichText::new(format!("⚠ {}", error))
+                        .color(palette.warning)
+                        .size(11.0),
+                );
+            }
+            ui.separator();
+        }
+        if let Some(index) = removed {
+            self.rules.remove(index);
+        }
+    }
+
+    fn add_rule(&mut self, ui: &mut egui::Ui) {
+        let kinds = RuleKind::defaults();
+        ui.label(egui::RichText::new(tr!("format-add-rule")).strong());
+        column_combo(ui, "format_new_column", &self.headers, &mut self.new_column);
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("format_new_kind")
+                .selected_text(rule_kind_label(&kinds[self.new_kind]))
+                .show_ui(ui, |ui| {
+                    for (index, kind) in kinds.iter().enumerate() {
+                        ui.selectable_value(&mut self.new_kind, index, rule_kind_label(kind));
+                    }
+                });
+            if ui
+                .add_enabled(
+                    self.new_column < self.headers.len(),
+                    egui::Button::new(tr!("format-add")),
+                )
+                .clicked()
+            {
+                self.rules.push(FormatRule {
+                    column: self.headers[self.new_column].clone(),
+                    kind: kinds[self.new_kind].clone(),
+                });
+            }
+        });
+    }
+}
+
+/// Editors for the settings of one rule.
+fn rule_settings(ui: &mut egui::Ui, index: usize, kind: &mut RuleKind) {
+    match kind {
+        RuleKind::ColorScale { low, high } => {
+            ui.label(tr!("rule-min"));
+            ui.color_edit_button_srgb(low);
+            ui.label(tr!("rule-max"));
+            ui.color_edit_button_srgb(high);
+        }
+        RuleKind::Threshold {
+            comparison,
+            value,
+            color,
+        } => {
+            egui::ComboBox::from_id_source(("format_comparison", index))
+                .selected_text(comparison.symbol())
+                .width(50.0)
+                .show_ui(ui, |ui| {
+                    for option in Comparison::ALL {
+                        ui.selectable_value(comparison, option, option.symbol());
+                    }
+                });
+            ui.add(egui::DragValue::new(value).speed(0.1));
+            ui.color_edit_button_srgb(color);
+        }
+        RuleKind::Text {
+            mode,
+            pattern,
+            color,
+        } => {
+            egui::ComboBox::from_id_source(("format_text_mode", index))
+                .selected_text(text_match_label(*mode))
+                .width(110.0)
+                .show_ui(ui, |ui| {
+                    for option in TextMatch::ALL {
+                        ui.selectable_value(mode, option, text_match_label(option));
+                    }
+                });
+            ui.add(egui::TextEdit::singleline(pattern).desired_width(100.0));
+            ui.color_edit_button_srgb(color);
+        }
+        RuleKind::DataBar { color } => {
+            ui.label(tr!("rule-bar"));
+            ui.color_edit_button_srgb(color);
+        }
+    }
+}
+
+fn rule_kind_label(kind: &RuleKind) -> String {
+    match kind {
+        RuleKind::ColorScale { .. } => tr!("rule-color-scale"),
+        RuleKind::Threshold { .. } => tr!("rule-threshold"),
+        RuleKind::Text { .. } => tr!("rule-text"),
+        RuleKind::DataBar { .. } => tr!("rule-data-bar"),
+    }
+}
+
+fn text_match_label(mode: TextMatch) -> String {
+    match mode {
+        TextMatch::Contains => tr!("text-match-contains"),
+        TextMatch::Equals => tr!("text-match-equals"),
+        TextMatch::StartsWith => tr!("text-match-starts-with"),
+        TextMatch::Regex => tr!("text-match-regex"),
+    }
+}
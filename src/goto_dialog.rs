@@ -0,0 +1,167 @@
+use egui::Context;
+
+use crate::i18n::tr;
+use crate::theme;
+
+/// What the user did in the go-to window.
+pub enum GotoAction {
+    None,
+    Close,
+    /// Go to a data row (0-based), and a column for cell references.
+    Row {
+        row: usize,
+        column: Option<usize>,
+    },
+    /// Find the first row whose `column` holds `value`.
+    Find {
+        column: usize,
+        value: String,
+    },
+}
+
+/// Ctrl+G window that jumps to a row number, a cell reference such as
+/// `C1234`, or the row holding a value in a key column.
+pub struct GotoDialog {
+    text: String,
+    key_column: usize,
+    /// Searches the key column even for input that reads as a row number,
+    /// for numeric keys such as chip indices.
+    always_find: bool,
+    focused: bool,
+}
+
+impl GotoDialog {
+    /// Starts with the first column named like an index as the key column.
+    pub fn new(headers: &[String]) -> Self {
+        let key_column = headers
+            .iter()
+            .position(|header| header.to_lowercase().contains("index"))
+            .unwrap_or(0);
+        Self {
+            text: String::new(),
+            key_column,
+            always_find: false,
+            focused: false,
+        }
+    }
+
+    /// `busy` is set while a search or a page load is running; `message`
+    /// tells why the last target could not be reached.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        headers: &[String],
+        busy: bool,
+        message: Option<&str>,
+    ) -> GotoAction {
+        let palette = theme::palette(ctx);
+        let mut action = GotoAction::None;
+        let mut open = true;
+        self.key_column = self.key_column.min(headers.len().saturating_sub(1));
+
+        egui::Window::new(tr!("goto-title"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(340.0)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 80.0))
+            .show(ctx, |ui| {
+                let field = ui.add(
+                    egui::TextEdit::singleline(&mut self.text)
+                        .hint_text(tr!("goto-hint"))
+                        .desired_width(f32::INFINITY),
+                );
+                if !self.focused {
+                    field.request_focus();
+                    self.focused = true;
+                }
+                let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                ui.horizontal(|ui| {
+                    ui.label(tr!("goto-key-column"));
+                    let selected = headers.get(self.key_column).cloned().unwrap_or_default();
+                    egui::ComboBox::from_id_source("goto_key_column")
+                        .selected_text(selected)
+                        .width(180.0)
+                        .show_ui(ui, |ui| {
+                            for (index, header) in headers.iter().enumerate() {
+                                ui.selectable_value(&mut self.key_column, index, header);
+                            }
+                        });
+                });
+                ui.checkbox(&mut self.always_find, tr!("goto-always-find"));
+
+                let target = parse_target(&self.text, headers.len()).filter(|_| !self.always_find);
+                let hint = match target {
+                    _ if self.text.trim().is_empty() => String::new(),
+                    Some((row, Some(column))) => tr!(
+                        "goto-preview-cell",
+                        row = row + 1,
+                        column = headers[column].as_str()
+                    ),
+                    Some((row, None)) => tr!("goto-preview-row", row = row + 1),
+                    None => tr!(
+                        "goto-preview-find",
+                        column = headers.get(self.key_column).map_or("", String::as_str)
+                    ),
+                };
+                ui.label(egui::RichText::new(hint).color(palette.muted).size(12.0));
+                if let Some(message) = message {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", message))
+                            .color(palette.warning)
+                            .size(12.0),
+                    );
+                }
+
+                ui.horizontal(|ui| {
+                    let ready = !self.text.trim().is_empty() && !busy;
+                    let go = ui
+                        .add_enabled(ready, egui::Button::new(tr!("goto-go")))
+                        .clicked();
+                    if busy {
+                        ui.spinner();
+                    }
+                    if (go || submitted) && ready {
+                        action = match target {
+                            Some((row, column)) => GotoAction::Row { row, column },
+                            None => GotoAction::Find {
+                                column: self.key_column,
+                                value: self.text.trim().to_owned(),
+                            },
+                        };
+                        // Enter again right away jumps again
+                        self.focused = false;
+                    }
+                });
+            });
+
+        if !open {
+            action = GotoAction::Close;
+        }
+        action
+    }
+}
+
+/// Reads a row number (`1234`) or a cell reference (`C1234`), numbered as
+/// in the grid, as a 0-based data row and column. Columns are lettered in
+/// file order from `A`.
+pub fn parse_target(text: &str, column_count: usize) -> Option<(usize, Option<usize>)> {
+    let text = text.trim();
+    let digits = text.find(|c: char| c.is_ascii_digit())?;
+    let (letters, number) = text.split_at(digits);
+    let row = number.parse::<usize>().ok()?.checked_sub(1)?;
+    if letters.is_empty() {
+        return Some((row, None));
+    }
+    if !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    // Bijective base 26: A = 1, Z = 26, AA = 27
+    let column = letters.chars().try_fold(0usize, |column, c| {
+        column
+            .checked_mul(26)?
+            .checked_add((c.to_ascii_uppercase() as u8 - b'A') as usize + 1)
+    })? - 1;
+    (column < column_count).then_some((row, Some(column)))
+}
@@ -0,0 +1,223 @@
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use rust_excel_reader_core::LoadError;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+/// Looks up a message of the catalog, with optional arguments:
+/// `tr!("row-count", count = rows.len())`.
+macro_rules! tr {
+    ($id:expr) => {
+        $crate::i18n::message($id, None)
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::message($id, Some(&args))
+    }};
+}
+pub(crate) use tr;
+
+/// Language of the interface, persisted in the settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Language {
+    /// Follows the locale of the environment (`LC_ALL`, `LC_MESSAGES`,
+    /// `LANG`), English when it names no supported language.
+    #[default]
+    System,
+    TraditionalChinese,
+    SimplifiedChinese,
+    English,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::System,
+        Language::TraditionalChinese,
+        Language::SimplifiedChinese,
+        Language::English,
+    ];
+
+    /// The name of the language in itself, so it can be found whichever
+    /// language is in use.
+    pub fn label(self) -> String {
+        match self {
+            Language::System => tr!("language-system"),
+            Language::TraditionalChinese => "繁體中文".to_owned(),
+            Language::SimplifiedChinese => "简体中文".to_owned(),
+            Language::English => "English".to_owned(),
+        }
+    }
+
+    fn resolve(self) -> Language {
+        if self != Language::System {
+            return self;
+        }
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default()
+            .to_lowercase()
+            .replace('-', "_");
+        if !locale.starts_with("zh") {
+            Language::English
+        } else if ["zh_tw", "zh_hk", "zh_mo", "zh_hant"]
+            .iter()
+            .any(|prefix| locale.starts_with(prefix))
+        {
+            Language::TraditionalChinese
+        } else {
+            Language::SimplifiedChinese
+        }
+    }
+
+    fn catalog(self) -> (&'static str, &'static str) {
+        match self.resolve() {
+            Language::TraditionalChinese => ("zh-TW", include_str!("../locales/zh-TW.ftl")),
+            Language::SimplifiedChinese => ("zh-CN", include_str!("../locales/zh-CN.ftl")),
+            _ => ("en", ENGLISH),
+        }
+    }
+}
+
+const ENGLISH: &str = include_str!("../locales/en.ftl");
+
+/// Messages of the selected language, with the English ones for anything a
+/// translation lacks.
+struct Catalog {
+    language: Language,
+    bundle: FluentBundle<FluentResource>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+impl Catalog {
+    fn new(language: Language) -> Self {
+        let (id, source) = language.catalog();
+        Self {
+            language,
+            bundle: bundle(id, source),
+            fallback: bundle("en", ENGLISH),
+        }
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+        [&self.bundle, &self.fallback]
+            .into_iter()
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = Vec::new();
+                Some(
+                    bundle
+                        .format_pattern(pattern, args, &mut errors)
+                        .into_owned(),
+                )
+            })
+    }
+}
+
+fn bundle(id: &str, source: &str) -> FluentBundle<FluentResource> {
+    // The catalogs are compiled in, so a syntax error is a bug
+    let resource = FluentResource::try_new(source.to_owned())
+        .unwrap_or_else(|_| panic!("invalid message catalog {}", id));
+    let mut bundle = FluentBundle::new(vec![id.parse().expect("valid language id")]);
+    // Direction marks around arguments show up as boxes in egui
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .unwrap_or_else(|_| panic!("duplicate messages in catalog {}", id));
+    bundle
+}
+
+thread_local! {
+    // The interface is drawn on one thread, which owns the catalog
+    static CATALOG: RefCell<Catalog> = RefCell::new(Catalog::new(Language::System));
+}
+
+/// Switches the interface to `language`; a no-op if it is in use.
+pub fn set_language(language: Language) {
+    CATALOG.with(|catalog| {
+        if catalog.borrow().language != language {
+            *catalog.borrow_mut() = Catalog::new(language);
+        }
+    });
+}
+
+/// The message `id` in the current language; see [`tr!`].
+pub fn message(id: &str, args: Option<&FluentArgs>) -> String {
+    CATALOG
+        .with(|catalog| catalog.borrow().format(id, args))
+        .unwrap_or_else(|| id.to_owned())
+}
+
+/// Describes a load failure in the current language.
+pub fn load_error(error: &LoadError) -> String {
+    match error {
+        LoadError::Task(detail) => tr!("error-task", detail = detail.as_str()),
+        LoadError::Io(detail) => tr!("error-io", detail = detail.as_str()),
+        LoadError::EmptyFile => tr!("error-empty-file"),
+        LoadError::Parse(detail) => tr!("error-parse", detail = detail.as_str()),
+        LoadError::OpenWorkbook(detail) => tr!("error-open-workbook", detail = detail.as_str()),
+        LoadError::NoWorksheets => tr!("error-no-worksheets"),
+        LoadError::ReadWorksheet { sheet, detail } => tr!(
+            "error-read-worksheet",
+            sheet = sheet.as_str(),
+            detail = detail.as_str()
+        ),
+        LoadError::ArchiveNeedsEntry => tr!("error-archive-entry"),
+        LoadError::UnsupportedFormat => tr!("error-unsupported-format"),
+        LoadError::OpenArchive(detail) => tr!("error-open-archive", detail = detail.as_str()),
+        LoadError::ExtractEntry { entry, detail } => tr!(
+            "error-extract-entry",
+            entry = entry.as_str(),
+            detail = detail.as_str()
+        ),
+        LoadError::InvalidEntryName(entry) => {
+            tr!("error-invalid-entry-name", entry = entry.as_str())
+        }
+        LoadError::InvalidJson(detail) => tr!("error-invalid-json", detail = detail.as_str()),
+        LoadError::InvalidJsonLine { line, detail } => tr!(
+            "error-invalid-json-line",
+            line = *line,
+            detail = detail.as_str()
+        ),
+        LoadError::OpenDatabase(detail) => tr!("error-open-database", detail = detail.as_str()),
+        LoadError::NoTables => tr!("error-no-tables"),
+        LoadError::ReadTable { table, detail } => tr!(
+            "error-read-table",
+            table = table.as_str(),
+            detail = detail.as_str()
+        ),
+        LoadError::CreateFile { path, detail } => tr!(
+            "error-create-file",
+            path = path.as_str(),
+            detail = detail.as_str()
+        ),
+        LoadError::NoCommonColumns => tr!("error-no-common-columns"),
+        LoadError::NoKeyColumns => tr!("error-no-key-columns"),
+        LoadError::MissingKeyColumn { column, file } => tr!(
+            "error-missing-key-column",
+            column = column.as_str(),
+            file = file.as_str()
+        ),
+        LoadError::KeyCountMismatch {
+            file,
+            count,
+            first_file,
+            first_count,
+        } => tr!(
+            "error-key-count-mismatch",
+            file = file.as_str(),
+            count = *count,
+            first_file = first_file.as_str(),
+            first_count = *first_count
+        ),
+        LoadError::TooFewInputs => tr!("error-too-few-inputs"),
+        LoadError::QueryEngine(detail) => tr!("error-query-engine", detail = detail.as_str()),
+        LoadError::AttachDatabase { path, detail } => tr!(
+            "error-attach-database",
+            path = path.as_str(),
+            detail = detail.as_str()
+        ),
+        LoadError::Other(message) => message.clone(),
+    }
+}
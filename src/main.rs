@@ -1,8 +1,24 @@
 mod app;
-mod csv_loader;
-mod data_loader;
-mod excel_loader;
+mod cell_viewer;
+mod computed_column_dialog;
+mod derived_table;
+mod diff_panel;
+mod fixed_width_dialog;
+mod folder_dialog;
 mod font_setup;
+mod format_panel;
+mod goto_dialog;
+mod i18n;
+mod merge_panel;
+mod parse_issues_panel;
+mod pivot_panel;
+mod quality_panel;
+mod query_console;
+mod row_inspector;
+mod settings;
+mod sheet_picker;
+mod table_grid;
+mod theme;
 
 use app::MyApp;
 
@@ -13,6 +29,8 @@ fn main() -> Result<(), eframe::Error> {
             .with_inner_size([1000.0, 700.0])  // 初始視窗大小
             .with_min_inner_size([800.0, 600.0])  // 最小視窗大小
             .with_resizable(true),  // 可調整大小
+        // Lets the System theme follow the OS on every platform
+        follow_system_theme: true,
         ..eframe::NativeOptions::default()
     };
 
@@ -0,0 +1,262 @@
+use egui::Context;
+use rust_excel_reader_core::data_loader::spawn_task;
+use rust_excel_reader_core::join::{
+    join_sheets, JoinInput, JoinKind, JoinSpec, DEFAULT_MEMORY_LIMIT,
+};
+use rust_excel_reader_core::table_reader::SheetSource;
+use rust_excel_reader_core::LoadError;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use crate::i18n::{self, tr};
+use crate::pivot_panel::column_list;
+use crate::sheet_picker::{current_source, sheet_buttons, CurrentSheet, PickedSheet};
+use crate::theme;
+
+type MergeOutcome = Result<PathBuf, LoadError>;
+
+struct MergeInput {
+    sheet: PickedSheet,
+    /// Key columns, as indexes into the sheet's headers.
+    keys: Vec<usize>,
+}
+
+/// Window for joining several files on key columns, e.g. EPI, AOI and
+/// final-test results of one wafer on the chip INDEX. The join runs in the
+/// background and its result is opened in the file view like any file, so
+/// it can be browsed, filtered and exported.
+pub struct MergePanel {
+    pub open: bool,
+    inputs: Vec<MergeInput>,
+    kind: JoinKind,
+    tx: mpsc::Sender<MergeOutcome>,
+    rx: mpsc::Receiver<MergeOutcome>,
+    running: bool,
+    error: Option<String>,
+}
+
+impl MergePanel {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            open: false,
+            inputs: Vec::new(),
+            kind: JoinKind::Inner,
+            tx,
+            rx,
+            running: false,
+            error: None,
+        }
+    }
+
+    /// Shows the window if it is open and returns the merged file once a
+    /// merge has finished.
+    pub fn show(&mut self, ctx: &Context, current: Option<CurrentSheet>) -> Option<PathBuf> {
+        let palette = theme::palette(ctx);
+        let finished = self.handle_outcome();
+        if !self.open {
+            return finished;
+        }
+        if self.inputs.is_empty() {
+            if let Some(current) = current {
+                self.add_input(current_source(current));
+            }
+        }
+
+        let mut open = true;
+        let mut merge = false;
+
+        egui::Window::new(tr!("merge-title"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                self.input_list(ui);
+
+                ui.horizontal(|ui| {
+                    ui.label(tr!("merge-add"));
+                    if let Some(source) = sheet_buttons(ui, "merge_add", None, current) {
+                        self.add_input(source);
+                    }
+                });
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label(tr!("merge-join"));
+                    for kind in JoinKind::ALL {
+                        ui.radio_value(&mut self.kind, kind, join_kind_label(kind));
+                    }
+                });
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let ready =
+                        self.inputs.len() >= 2 && self.inputs.iter().all(|i| !i.keys.is_empty());
+                    merge = ui
+                        .add_enabled(ready && !self.running, egui::Button::new(tr!("merge-run")))
+                        .clicked();
+                    if self.running {
+                        ui.spinner();
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .color(palette.error)
+                            .size(12.0),
+                    );
+                }
+            });
+
+        if !open {
+            self.open = false;
+        }
+        if merge {
+            self.start(ctx);
+        }
+
+        finished
+    }
+
+    fn input_list(&mut self, ui: &mut egui::Ui) {
+        let mut removed = None;
+        let mut replaced = None;
+        for (index, input) in self.inputs.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").clicked() {
+                    removed = Some(index);
+                }
+                ui.label(egui::RichText::new(input.sheet.name()).strong());
+            });
+            if let Some(source) =
+                sheet_buttons(ui, ("merge_sheet", index), Some(&input.sheet), None)
+            {
+                replaced = Some((index, source));
+            }
+            column_list(
+                ui,
+                &tr!("merge-key"),
+                &format!("merge_keys_{}", index),
+                &input.sheet.headers,
+                &mut input.keys,
+            );
+            ui.separator();
+        }
+
+        if let Some(index) = removed {
+            self.inputs.remove(index);
+        }
+        if let Some((index, source)) = replaced {
+            match PickedSheet::probe(source) {
+                Ok(sheet) => {
+                    let keys = self.matching_keys(&sheet.headers);
+                    self.inputs[index] = MergeInput { sheet, keys };
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+    }
+
+    fn add_input(&mut self, source: SheetSource) {
+        match PickedSheet::probe(source) {
+            Ok(sheet) => {
+                let keys = self.matching_keys(&sheet.headers);
+                self.inputs.push(MergeInput { sheet, keys });
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// Preselects the columns named like the key columns of the first input.
+    fn matching_keys(&self, headers: &[String]) -> Vec<usize> {
+        let Some(first) = self.inputs.first() else {
+            return Vec::new();
+        };
+        first
+            .keys
+            .iter()
+            .filter_map(|&key| {
+                let name = first.sheet.headers.get(key)?.trim();
+                headers
+                    .iter()
+                    .position(|h| h.trim().eq_ignore_ascii_case(name))
+            })
+            .collect()
+    }
+
+    fn start(&mut self, ctx: &Context) {
+        let inputs: Vec<JoinInput> = self
+            .inputs
+            .iter()
+            .map(|input| JoinInput {
+                source: input.sheet.source.clone(),
+                keys: input
+                    .keys
+                    .iter()
+                    .filter_map(|&key| input.sheet.headers.get(key).cloned())
+                    .collect(),
+            })
+            .collect();
+        let stems: Vec<String> = inputs
+            .iter()
+            .map(|input| {
+                input
+                    .source
+                    .path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        let target_dir = std::env::temp_dir()
+            .join("rust_excel_reader")
+            .join("merged");
+        let target = target_dir.join(format!("{}_{}.csv", stems.join("+"), self.kind.label()));
+        let spec = JoinSpec {
+            inputs,
+            kind: self.kind,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        };
+
+        self.running = true;
+        self.error = None;
+        let egui_ctx = ctx.clone();
+        spawn_task(
+            move || {
+                std::fs::create_dir_all(&target_dir).map_err(|e| LoadError::CreateFile {
+                    path: target_dir.display().to_string(),
+                    detail: e.to_string(),
+                })?;
+                join_sheets(&spec, &target).map(|_| target)
+            },
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_outcome(&mut self) -> Option<PathBuf> {
+        let result = self.rx.try_recv().ok()?;
+        self.running = false;
+        match result {
+            Ok(path) => {
+                self.open = false;
+                Some(path)
+            }
+            Err(e) => {
+                self.error = Some(i18n::load_error(&e));
+                None
+            }
+        }
+    }
+}
+
+fn join_kind_label(kind: JoinKind) -> String {
+    match kind {
+        JoinKind::Inner => tr!("join-inner"),
+        JoinKind::Left => tr!("join-left"),
+        JoinKind::Outer => tr!("join-outer"),
+    }
+}
@@ -0,0 +1,282 @@
+use egui::Context;
+use rust_excel_reader_core::csv_loader::{
+    scan_parse_issues, IssueKind, IssueScan, ParseIssue, Tolerance,
+};
+use rust_excel_reader_core::data_loader::spawn_task;
+use rust_excel_reader_core::progress::Progress;
+use rust_excel_reader_core::table_reader::SheetSource;
+use std::collections::HashSet;
+use std::sync::mpsc;
+
+use crate::i18n::tr;
+use crate::theme;
+
+/// Issues listed by a whole-file scan; the rest are only counted.
+const SCAN_LIMIT: usize = 10_000;
+
+/// A whole-file scan, tagged with the file it belongs to.
+type ScanOutcome = (u64, Result<IssueScan, String>);
+
+/// What the user did in the parse issues panel.
+pub enum ParseIssuesAction {
+    None,
+    /// Go to a data row of the file.
+    GoTo(usize),
+    /// Read the file again with another tolerance.
+    SetTolerance(Tolerance),
+}
+
+/// Side panel listing the CSV records that did not parse cleanly: those of
+/// the loaded pages as they arrive, or those of the whole file once it has
+/// been scanned in the background.
+pub struct ParseIssuesPanel {
+    pub open: bool,
+    /// Whether the rows with issues are marked in the table.
+    pub mark_rows: bool,
+    /// Issues of the loaded pages.
+    loaded: Vec<ParseIssue>,
+    /// Rows kept in the table although they have an issue.
+    flagged: HashSet<usize>,
+    scan: Option<IssueScan>,
+    /// Bumped for every file, so a scan of a previous file is dropped.
+    generation: u64,
+    tx: mpsc::Sender<ScanOutcome>,
+    rx: mpsc::Receiver<ScanOutcome>,
+    scanning: bool,
+    progress: Progress,
+    error: Option<String>,
+}
+
+impl ParseIssuesPanel {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            open: false,
+            mark_rows: true,
+            loaded: Vec::new(),
+            flagged: HashSet::new(),
+            scan: None,
+            generation: 0,
+            tx,
+            rx,
+            scanning: false,
+            progress: Progress::new(),
+            error: None,
+        }
+    }
+
+    /// Forgets every issue, e.g. when another file is opened or the file is
+    /// read with another tolerance.
+    pub fn clear(&mut self) {
+        self.generation += 1;
+        self.scanning = false;
+        self.scan = None;
+        self.error = None;
+        self.clear_loaded();
+    }
+
+    /// Forgets the issues of the loaded pages, before they are loaded again.
+    pub fn clear_loaded(&mut self) {
+        self.loaded.clear();
+        self.flagged.clear();
+    }
+
+    /// Adds the issues of a page that has been loaded.
+    pub fn add_page(&mut self, issues: &[ParseIssue]) {
+        for issue in issues {
+            if !issue.skipped {
+                self.flagged.insert(issue.row);
+            }
+        }
+        self.loaded.extend_from_slice(issues);
+    }
+
+    /// Data rows to mark in the table, `None` when rows are not marked.
+    pub fn flagged_rows(&self) -> Option<&HashSet<usize>> {
+        self.mark_rows.then_some(&self.flagged)
+    }
+
+    pub fn issue_count(&self) -> usize {
+        self.scan.as_ref().map_or(self.loaded.len(), |scan| scan.total)
+    }
+
+    /// Shows the panel if it is open. `source` is the open CSV file, and the
+    /// tolerance can only be changed while `tolerance_locked` is `false`.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        source: Option<SheetSource>,
+        tolerance_locked: bool,
+    ) -> ParseIssuesAction {
+        self.handle_scan();
+        let mut action = ParseIssuesAction::None;
+        if !self.open {
+            return action;
+        }
+        let Some(source) = source else {
+            return action;
+        };
+        if self.scanning {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
+        let palette = theme::palette(ctx);
+        egui::SidePanel::right("parse_issues")
+            .resizable(true)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(tr!("issues-title"));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").clicked() {
+                            self.open = false;
+                        }
+                    });
+                });
+                ui.separator();
+
+                let tolerance = source.options.tolerance;
+                ui.horizontal(|ui| {
+                    ui.label(tr!("issues-tolerance"));
+                    ui.add_enabled_ui(!tolerance_locked, |ui| {
+                        egui::ComboBox::from_id_source("parse_tolerance")
+                            .selected_text(tolerance_label(tolerance))
+                            .show_ui(ui, |ui| {
+                                for option in [Tolerance::Strict, Tolerance::Skip, Tolerance::Repair]
+                                {
+                                    if ui
+                                        .selectable_label(option == tolerance, tolerance_label(option))
+                                        .clicked()
+                                        && option != tolerance
+                                    {
+                                        action = ParseIssuesAction::SetTolerance(option);
+                                    }
+                                }
+                            });
+                    })
+                    .response
+                    .on_disabled_hover_text(tr!("issues-tolerance-locked"));
+                });
+                ui.checkbox(&mut self.mark_rows, tr!("issues-mark-rows"));
+
+                ui.horizontal(|ui| {
+                    let scan = ui.add_enabled(!self.scanning, egui::Button::new(tr!("issues-scan")));
+                    if scan.clicked() {
+                        self.start_scan(ctx, source.clone());
+                    }
+                    if self.scanning {
+                        ui.spinner();
+                        ui.label(
+                            egui::RichText::new(tr!(
+                                "issues-scanning",
+                                rows = self.progress.snapshot().rows
+                            ))
+                            .color(palette.muted)
+                            .size(12.0),
+                        );
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .color(palette.warning)
+                            .size(12.0),
+                    );
+                }
+
+                let (issues, summary) = match &self.scan {
+                    Some(scan) => (
+                        &scan.issues,
+                        tr!("issues-file-summary", count = scan.total, shown = scan.issues.len()),
+                    ),
+                    None => (&self.loaded, tr!("issues-loaded-summary", count = self.loaded.len())),
+                };
+                ui.label(egui::RichText::new(summary).color(palette.muted).size(12.0));
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show_rows(ui, 20.0, issues.len(), |ui, range| {
+                        for issue in &issues[range] {
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .small_button(tr!("issues-line", line = issue.line))
+                                    .on_hover_text(tr!(
+                                        "issues-go-to",
+                                        row = issue.row + 1,
+                                        byte = issue.byte
+                                    ))
+                                    .clicked()
+                                {
+                                    action = ParseIssuesAction::GoTo(issue.row);
+                                }
+                                let color = if issue.skipped {
+                                    palette.muted
+                                } else {
+                                    palette.warning
+                                };
+                                let mut text = issue_text(issue.kind);
+                                if issue.skipped {
+                                    text = tr!("issues-skipped", issue = text);
+                                }
+                                ui.label(egui::RichText::new(text).color(color).size(12.0));
+                            });
+                        }
+                    });
+            });
+        action
+    }
+
+    fn start_scan(&mut self, ctx: &Context, source: SheetSource) {
+        self.scanning = true;
+        self.error = None;
+        self.progress = Progress::new();
+        let progress = self.progress.clone();
+        let generation = self.generation;
+        let egui_ctx = ctx.clone();
+        spawn_task(
+            move || {
+                let scan = scan_parse_issues(
+                    &source.path,
+                    source.options.tolerance,
+                    SCAN_LIMIT,
+                    &progress,
+                );
+                (generation, scan)
+            },
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_scan(&mut self) {
+        while let Ok((generation, result)) = self.rx.try_recv() {
+            if generation != self.generation {
+                continue;
+            }
+            self.scanning = false;
+            match result {
+                Ok(scan) => self.scan = Some(scan),
+                Err(e) => self.error = Some(e),
+            }
+        }
+    }
+}
+
+fn tolerance_label(tolerance: Tolerance) -> String {
+    match tolerance {
+        Tolerance::Strict => tr!("tolerance-strict"),
+        Tolerance::Skip => tr!("tolerance-skip"),
+        Tolerance::Repair => tr!("tolerance-repair"),
+    }
+}
+
+fn issue_text(kind: IssueKind) -> String {
+    match kind {
+        IssueKind::UnterminatedQuote { lines } => tr!("issue-unterminated-quote", lines = lines),
+        IssueKind::InvalidUtf8 => tr!("issue-invalid-utf8"),
+        IssueKind::FieldCount { expected, found } => {
+            tr!("issue-field-count", found = found, expected = expected)
+        }
+    }
+}
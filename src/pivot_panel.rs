@@ -0,0 +1,269 @@
+use egui::Context;
+use rust_excel_reader_core::data_loader::spawn_task;
+use rust_excel_reader_core::pivot::{compute_pivot, Aggregate, PivotSpec, PivotTable};
+use rust_excel_reader_core::LoadOptions;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::derived_table::DerivedTable;
+use crate::i18n::tr;
+use crate::theme;
+
+/// Sheet the pivot is computed over.
+pub struct PivotSource<'a> {
+    pub path: &'a Path,
+    pub sheet_index: usize,
+    pub options: &'a LoadOptions,
+    pub headers: &'a [String],
+}
+
+type PivotOutcome = (String, Result<PivotTable, String>);
+
+/// Window for building a group-by / pivot table over the open sheet. The
+/// result is computed over every row in the background and opens as a tab.
+pub struct PivotPanel {
+    pub open: bool,
+    spec: PivotSpec,
+    /// Value column and aggregate picked for the next "Add".
+    new_value: (usize, Aggregate),
+    /// The file the spec was built for; the spec is reset for other files.
+    spec_path: Option<PathBuf>,
+    tx: mpsc::Sender<PivotOutcome>,
+    rx: mpsc::Receiver<PivotOutcome>,
+    running: bool,
+    error: Option<String>,
+    pivot_count: usize,
+}
+
+impl PivotPanel {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            open: false,
+            spec: PivotSpec::default(),
+            new_value: (0, Aggregate::Mean),
+            spec_path: None,
+            tx,
+            rx,
+            running: false,
+            error: None,
+            pivot_count: 0,
+        }
+    }
+
+    /// Shows the window if it is open and returns a pivot table that finished
+    /// computing since the last frame.
+    pub fn show(&mut self, ctx: &Context, source: Option<PivotSource>) -> Option<DerivedTable> {
+        let palette = theme::palette(ctx);
+        let finished = self.handle_outcome();
+        let Some(source) = source else {
+            return finished;
+        };
+        if self.spec_path.as_deref() != Some(source.path) {
+            self.spec = PivotSpec::default();
+            self.spec_path = Some(source.path.to_path_buf());
+            self.error = None;
+        }
+        if !self.open {
+            return finished;
+        }
+
+        let headers = source.headers;
+        let mut open = true;
+        let mut compute = false;
+
+        egui::Window::new(tr!("pivot-title"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(tr!("pivot-whole-sheet"))
+                        .color(palette.muted)
+                        .size(12.0),
+                );
+                ui.add_space(6.0);
+
+                column_list(ui, &tr!("pivot-rows"), "pivot_rows", headers, &mut self.spec.row_groups);
+                column_list(
+                    ui,
+                    &tr!("pivot-columns"),
+                    "pivot_columns",
+                    headers,
+                    &mut self.spec.column_groups,
+                );
+                self.value_list(ui, headers);
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    compute = ui
+                        .add_enabled(!self.running, egui::Button::new(tr!("pivot-compute")))
+                        .clicked();
+                    if ui.button(tr!("pivot-clear")).clicked() {
+                        self.spec = PivotSpec::default();
+                    }
+                    if self.running {
+                        ui.spinner();
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .color(palette.error)
+                            .size(12.0),
+                    );
+                }
+            });
+
+        if !open {
+            self.open = false;
+        }
+        if compute {
+            self.start(ctx, &source);
+        }
+
+        finished
+    }
+
+    fn value_list(&mut self, ui: &mut egui::Ui, headers: &[String]) {
+        ui.label(egui::RichText::new(tr!("pivot-values")).strong());
+        let mut removed = None;
+        for (index, &(column, aggregate)) in self.spec.values.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").clicked() {
+                    removed = Some(index);
+                }
+                ui.label(format!(
+                    "{}({})",
+                    aggregate.label(),
+                    headers.get(column).map(String::as_str).unwrap_or("?")
+                ));
+            });
+        }
+        if let Some(index) = removed {
+            self.spec.values.remove(index);
+        }
+
+        ui.horizontal(|ui| {
+            let (column, aggregate) = &mut self.new_value;
+            column_combo(ui, "pivot_value_column", headers, column);
+            egui::ComboBox::from_id_source("pivot_value_aggregate")
+                .selected_text(aggregate.label())
+                .width(90.0)
+                .show_ui(ui, |ui| {
+                    for option in Aggregate::ALL {
+                        ui.selectable_value(aggregate, option, option.label());
+                    }
+                });
+            if ui.button(tr!("pivot-add")).clicked() && *column < headers.len() {
+                self.spec.values.push(self.new_value);
+            }
+        });
+        ui.add_space(6.0);
+    }
+
+    fn start(&mut self, ctx: &Context, source: &PivotSource) {
+        self.running = true;
+        self.error = None;
+
+        let title = pivot_title(&self.spec, source.headers);
+        let path = source.path.to_path_buf();
+        let sheet_index = source.sheet_index;
+        let options = source.options.clone();
+        let spec = self.spec.clone();
+        let egui_ctx = ctx.clone();
+        spawn_task(
+            move || (title, compute_pivot(&path, sheet_index, &options, &spec)),
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_outcome(&mut self) -> Option<DerivedTable> {
+        let (title, result) = self.rx.try_recv().ok()?;
+        self.running = false;
+        match result {
+            Ok(table) => {
+                self.pivot_count += 1;
+                Some(DerivedTable::new(
+                    tr!("pivot-tab", number = self.pivot_count),
+                    title,
+                    table.headers,
+                    table.rows,
+                    table.column_types,
+                ))
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+}
+
+/// A list of chosen group columns with remove buttons and a picker to add one.
+pub(crate) fn column_list(ui: &mut egui::Ui, label: &str, id: &str, headers: &[String], columns: &mut Vec<usize>) {
+    ui.label(egui::RichText::new(label).strong());
+    let mut removed = None;
+    for (index, &column) in columns.iter().enumerate() {
+        ui.horizontal(|ui| {
+            if ui.small_button("✖").clicked() {
+                removed = Some(index);
+            }
+            ui.label(headers.get(column).map(String::as_str).unwrap_or("?"));
+        });
+    }
+    if let Some(index) = removed {
+        columns.remove(index);
+    }
+
+    let mut picked = usize::MAX;
+    column_combo(ui, id, headers, &mut picked);
+    if picked < headers.len() && !columns.contains(&picked) {
+        columns.push(picked);
+    }
+    ui.add_space(6.0);
+}
+
+/// Column picker; `selected` is left alone until the user picks a column.
+pub(crate) fn column_combo(ui: &mut egui::Ui, id: &str, headers: &[String], selected: &mut usize) {
+    let text = headers
+        .get(*selected)
+        .cloned()
+        .unwrap_or_else(|| tr!("column-add"));
+    egui::ComboBox::from_id_source(id)
+        .selected_text(text)
+        .width(180.0)
+        .show_ui(ui, |ui| {
+            for (index, header) in headers.iter().enumerate() {
+                ui.selectable_value(selected, index, header);
+            }
+        });
+}
+
+/// Describes the pivot for the tab tooltip, e.g. "mean(WP) by WAFER, BIN".
+fn pivot_title(spec: &PivotSpec, headers: &[String]) -> String {
+    let name = |column: usize| headers.get(column).cloned().unwrap_or_default();
+    let values: Vec<String> = spec
+        .values
+        .iter()
+        .map(|&(column, aggregate)| format!("{}({})", aggregate.label(), name(column)))
+        .collect();
+    let groups: Vec<String> = spec
+        .row_groups
+        .iter()
+        .chain(&spec.column_groups)
+        .map(|&column| name(column))
+        .collect();
+
+    match (values.is_empty(), groups.is_empty()) {
+        (true, _) => tr!("pivot-description-rows", groups = groups.join(", ")),
+        (false, true) => values.join(", "),
+        (false, false) => tr!(
+            "pivot-description",
+            values = values.join(", "),
+            groups = groups.join(", ")
+        ),
+    }
+}
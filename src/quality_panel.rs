@@ -0,0 +1,362 @@
+use egui::Context;
+use rust_excel_reader_core::data_loader::spawn_task;
+use rust_excel_reader_core::formatting::SpecLimit;
+use rust_excel_reader_core::progress::Progress;
+use rust_excel_reader_core::quality::{
+    scan_quality, Finding, FindingKind, QualityOptions, QualityReport,
+};
+use rust_excel_reader_core::table_reader::SheetSource;
+use rust_excel_reader_core::values::format_number;
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+
+use crate::i18n::tr;
+use crate::theme;
+
+/// A whole-sheet scan, tagged with the sheet it belongs to.
+type ScanOutcome = (u64, Result<QualityReport, String>);
+
+/// What the user did in the quality panel.
+pub enum QualityAction {
+    None,
+    /// Go to a data row of the file, with the cursor on a column.
+    GoTo(usize, usize),
+}
+
+/// The open sheet, as handed to the panel each frame.
+pub struct QualitySource<'a> {
+    pub source: SheetSource,
+    pub headers: &'a [String],
+    /// Ranges of coordinate and measurement columns, from the row inspector.
+    pub limits: &'a BTreeMap<String, SpecLimit>,
+    pub total_rows: Option<usize>,
+}
+
+/// Side panel with the quality report of the open sheet: the checks a
+/// tester file goes through before it is trusted, run over every row in the
+/// background. Findings link to their example rows, and the report can be
+/// exported as JSON or HTML.
+pub struct QualityPanel {
+    pub open: bool,
+    /// Columns checked for duplicate keys.
+    key_columns: Vec<usize>,
+    report: Option<QualityReport>,
+    /// Bumped for every sheet, so a scan of a previous sheet is dropped.
+    generation: u64,
+    tx: mpsc::Sender<ScanOutcome>,
+    rx: mpsc::Receiver<ScanOutcome>,
+    scanning: bool,
+    progress: Progress,
+    error: Option<String>,
+    /// Outcome of the last export, shown under the export buttons.
+    export_status: Option<String>,
+}
+
+impl QualityPanel {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            open: false,
+            key_columns: Vec::new(),
+            report: None,
+            generation: 0,
+            tx,
+            rx,
+            scanning: false,
+            progress: Progress::new(),
+            error: None,
+            export_status: None,
+        }
+    }
+
+    /// Forgets the report and key columns, e.g. when another sheet is opened.
+    pub fn clear(&mut self) {
+        self.generation += 1;
+        self.scanning = false;
+        self.key_columns.clear();
+        self.report = None;
+        self.error = None;
+        self.export_status = None;
+    }
+
+    pub fn finding_count(&self) -> Option<usize> {
+        self.report.as_ref().map(QualityReport::finding_count)
+    }
+
+    /// Shows the panel if it is open and a sheet is loaded.
+    pub fn show(&mut self, ctx: &Context, sheet: Option<QualitySource>) -> QualityAction {
+        self.handle_scan();
+        let mut action = QualityAction::None;
+        if !self.open {
+            return action;
+        }
+        let Some(sheet) = sheet else {
+            return action;
+        };
+        if self.scanning {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
+        let palette = theme::palette(ctx);
+        egui::SidePanel::right("quality_report")
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(tr!("quality-title"));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").clicked() {
+                            self.open = false;
+                        }
+                    });
+                });
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label(tr!("quality-keys"));
+                    let selected = if self.key_columns.is_empty() {
+                        tr!("quality-no-keys")
+                    } else {
+                        self.key_columns
+                            .iter()
+                            .filter_map(|&index| sheet.headers.get(index).cloned())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    ui.menu_button(selected, |ui| {
+                        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                            for (index, header) in sheet.headers.iter().enumerate() {
+                                let mut checked = self.key_columns.contains(&index);
+                                if ui.checkbox(&mut checked, header).changed() {
+                                    if checked {
+                                        self.key_columns.push(index);
+                                    } else {
+                                        self.key_columns.retain(|&key| key != index);
+                                    }
+                                }
+                            }
+                        });
+                    });
+                });
+                let ranges = sheet.limits.values().filter(|limit| !limit.is_empty()).count();
+                ui.label(
+                    egui::RichText::new(tr!("quality-ranges", count = ranges))
+                        .color(palette.muted)
+                        .size(12.0),
+                );
+
+                ui.horizontal(|ui| {
+                    let run = ui
+                        .add_enabled(!self.scanning, egui::Button::new(tr!("quality-run")))
+                        .on_hover_text(tr!("quality-run-hint"));
+                    if run.clicked() {
+                        self.start_scan(ctx, &sheet);
+                    }
+                    if self.scanning {
+                        let progress = self.progress.snapshot();
+                        let label = tr!("quality-scanning", rows = progress.rows);
+                        match progress.fraction() {
+                            Some(fraction) => {
+                                ui.add(
+                                    egui::ProgressBar::new(fraction)
+                                        .desired_width(180.0)
+                                        .text(egui::RichText::new(label).size(11.0)),
+                                );
+                            }
+                            None => {
+                                ui.spinner();
+                                ui.label(
+                                    egui::RichText::new(label).color(palette.muted).size(12.0),
+                                );
+                            }
+                        }
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .color(palette.warning)
+                            .size(12.0),
+                    );
+                }
+
+                let Some(report) = &self.report else {
+                    return;
+                };
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(tr!(
+                        "quality-summary",
+                        rows = report.rows,
+                        findings = report.finding_count()
+                    ));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button(tr!("quality-export-html")).clicked() {
+                            self.export_status = export(&sheet.source, report, "html");
+                        }
+                        if ui.small_button(tr!("quality-export-json")).clicked() {
+                            self.export_status = export(&sheet.source, report, "json");
+                        }
+                    });
+                });
+                if let Some(status) = &self.export_status {
+                    ui.label(egui::RichText::new(status).color(palette.muted).size(12.0));
+                }
+                let clean = report
+                    .columns
+                    .iter()
+                    .filter(|column| column.findings.is_empty())
+                    .count();
+                if clean > 0 {
+                    ui.label(
+                        egui::RichText::new(tr!("quality-clean-columns", count = clean))
+                            .color(palette.muted)
+                            .size(12.0),
+                    );
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for (index, column) in report.columns.iter().enumerate() {
+                            if column.findings.is_empty() {
+                                continue;
+                            }
+                            egui::CollapsingHeader::new(
+                                egui::RichText::new(&column.name).strong(),
+                            )
+                            .id_source(("quality_column", index))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                ui.label(
+                                    egui::RichText::new(tr!(
+                                        "quality-filled",
+                                        filled = column.filled,
+                                        empty = column.empty
+                                    ))
+                                    .color(palette.muted)
+                                    .size(11.0),
+                                );
+                                for finding in &column.findings {
+                                    let text = egui::RichText::new(finding_text(finding))
+                                        .color(palette.warning);
+                                    let first = finding.example_rows.first().copied();
+                                    let response = ui.add_enabled(
+                                        first.is_some(),
+                                        egui::Link::new(text),
+                                    );
+                                    if let Some(row) = first {
+                                        if response.on_hover_text(tr!("quality-go-to")).clicked() {
+                                            action = QualityAction::GoTo(row, index);
+                                        }
+                                    }
+                                    if finding.example_rows.is_empty() {
+                                        continue;
+                                    }
+                                    ui.horizontal_wrapped(|ui| {
+                                        ui.add_space(12.0);
+                                        for &row in &finding.example_rows {
+                                            if ui
+                                                .small_button(tr!("quality-row", row = row + 1))
+                                                .clicked()
+                                            {
+                                                action = QualityAction::GoTo(row, index);
+                                            }
+                                        }
+                                    });
+                                }
+                            });
+                        }
+                    });
+            });
+        action
+    }
+
+    fn start_scan(&mut self, ctx: &Context, sheet: &QualitySource) {
+        self.scanning = true;
+        self.error = None;
+        self.export_status = None;
+        self.progress = Progress::new();
+        if let Some(total) = sheet.total_rows {
+            self.progress.set_total_rows(total);
+        }
+        let progress = self.progress.clone();
+        let options = QualityOptions {
+            key_columns: self.key_columns.clone(),
+            limits: sheet.limits.clone(),
+        };
+        let source = sheet.source.clone();
+        let generation = self.generation;
+        let egui_ctx = ctx.clone();
+        spawn_task(
+            move || (generation, scan_quality(&source, &options, &progress)),
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_scan(&mut self) {
+        while let Ok((generation, result)) = self.rx.try_recv() {
+            if generation != self.generation {
+                continue;
+            }
+            self.scanning = false;
+            match result {
+                Ok(report) => self.report = Some(report),
+                Err(e) => self.error = Some(e),
+            }
+        }
+    }
+}
+
+/// Asks for a target file and writes the report to it as `extension`
+/// ("json" or "html"). Returns the message to show, `None` if cancelled.
+fn export(source: &SheetSource, report: &QualityReport, extension: &str) -> Option<String> {
+    let stem = source
+        .path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let path = rfd::FileDialog::new()
+        .add_filter(extension.to_uppercase(), &[extension])
+        .set_file_name(format!("{}_quality.{}", stem, extension))
+        .save_file()?;
+    let content = match extension {
+        "html" => report.to_html(&format!("Quality report: {}", stem)),
+        _ => report.to_json(),
+    };
+    Some(match std::fs::write(&path, content) {
+        Ok(()) => tr!("saved-file", path = path.display().to_string()),
+        Err(e) => format!("⚠ {}", e),
+    })
+}
+
+fn finding_text(finding: &Finding) -> String {
+    match &finding.kind {
+        FindingKind::Empty => tr!("finding-empty"),
+        FindingKind::Constant { value } => tr!("finding-constant", value = value.clone()),
+        FindingKind::DuplicateKeys { columns, values } => tr!(
+            "finding-duplicate-keys",
+            count = finding.count,
+            columns = columns.join(", "),
+            values = *values
+        ),
+        FindingKind::MixedTypes { numbers, text } => {
+            tr!("finding-mixed-types", numbers = *numbers, text = *text)
+        }
+        FindingKind::OutOfRange { low, high } => {
+            let bound = |value: &Option<f64>| value.map(format_number).unwrap_or_default();
+            tr!(
+                "finding-out-of-range",
+                count = finding.count,
+                low = bound(low),
+                high = bound(high)
+            )
+        }
+        FindingKind::TrailingWhitespace => {
+            tr!("finding-trailing-whitespace", count = finding.count)
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use egui::{Context, TopBottomPanel};
+use rust_excel_reader_core::data_loader::spawn_task;
+use rust_excel_reader_core::query::{QueryEngine, QueryResult, RegisteredTable};
+use rust_excel_reader_core::{LoadError, LoadOptions};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::derived_table::DerivedTable;
+use crate::i18n::{self, tr};
+use crate::theme;
+
+/// Result of one background task of the console.
+struct TaskOutcome {
+    tables: Vec<RegisteredTable>,
+    /// `None` when the task only registered files.
+    result: Option<Result<QueryResult, String>>,
+    /// Registration errors; they do not stop the query.
+    errors: Vec<LoadError>,
+    sql: String,
+    elapsed: Duration,
+}
+
+/// Bottom panel for running SQL over the open file and any files added to
+/// the console. Query results open in their own tab.
+pub struct QueryConsole {
+    pub open: bool,
+    sql: String,
+    /// Created on first use, on the worker thread.
+    engine: Arc<Mutex<Option<QueryEngine>>>,
+    tables: Vec<RegisteredTable>,
+    tx: mpsc::Sender<TaskOutcome>,
+    rx: mpsc::Receiver<TaskOutcome>,
+    running: bool,
+    error: Option<String>,
+    status: Option<String>,
+    query_count: usize,
+}
+
+impl QueryConsole {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            open: false,
+            sql: String::new(),
+            engine: Arc::new(Mutex::new(None)),
+            tables: Vec::new(),
+            tx,
+            rx,
+            running: false,
+            error: None,
+            status: None,
+            query_count: 0,
+        }
+    }
+
+    /// Shows the console if it is open. `current_file` is registered before
+    /// every query, so the open file can always be queried by name.
+    ///
+    /// Returns the table of a query that finished since the last frame.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        current_file: Option<(&Path, &LoadOptions)>,
+    ) -> Option<DerivedTable> {
+        let palette = theme::palette(ctx);
+        let finished = self.handle_outcome();
+        if !self.open {
+            return finished;
+        }
+
+        let mut run = false;
+        let mut add_file = false;
+
+        TopBottomPanel::bottom("query_console")
+            .resizable(true)
+            .default_height(220.0)
+            .min_height(120.0)
+            .show(ctx, |ui| {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(tr!("sql-title")).strong());
+                    ui.add_space(12.0);
+                    run = ui
+                        .add_enabled(!self.running, egui::Button::new(tr!("sql-run")))
+                        .on_hover_text("Ctrl+Enter")
+                        .clicked();
+                    add_file = ui
+                        .add_enabled(!self.running, egui::Button::new(tr!("sql-add-file")))
+                        .clicked();
+                    if self.running {
+                        ui.spinner();
+                    }
+                    if let Some(status) = &self.status {
+                        ui.label(
+                            egui::RichText::new(status)
+                                .color(palette.muted)
+                                .size(12.0),
+                        );
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .color(palette.error)
+                            .size(12.0),
+                    );
+                }
+
+                ui.horizontal_top(|ui| {
+                    let tables_width = 220.0;
+                    let editor_width = (ui.available_width() - tables_width - 12.0).max(200.0);
+                    egui::ScrollArea::vertical()
+                        .id_source("query_editor")
+                        .max_width(editor_width)
+                        .show(ui, |ui| {
+                            let editor = ui.add(
+                                egui::TextEdit::multiline(&mut self.sql)
+                                    .code_editor()
+                                    .desired_width(editor_width)
+                                    .desired_rows(6)
+                                    .hint_text("SELECT * FROM my_file WHERE value > 10"),
+                            );
+                            if editor.has_focus()
+                                && ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Enter))
+                            {
+                                run = !self.running;
+                            }
+                        });
+
+                    ui.separator();
+                    self.render_tables(ui);
+                });
+            });
+
+        if add_file {
+            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                self.start_task(ctx, vec![(path, LoadOptions::default())], String::new());
+            }
+        } else if run && !self.sql.trim().is_empty() {
+            let files = current_file
+                .map(|(path, options)| vec![(path.to_path_buf(), options.clone())])
+                .unwrap_or_default();
+            self.start_task(ctx, files, self.sql.clone());
+        }
+
+        finished
+    }
+
+    /// Lists the registered tables; clicking one inserts its name.
+    fn render_tables(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        egui::ScrollArea::vertical()
+            .id_source("query_tables")
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(tr!("sql-tables"))
+                        .color(palette.muted)
+                        .size(12.0),
+                );
+                if self.tables.is_empty() {
+                    ui.label(
+                        egui::RichText::new(tr!("sql-no-tables"))
+                            .color(palette.faint)
+                            .size(11.0),
+                    );
+                }
+                for table in &self.tables {
+                    let file_name = table.path.file_name().unwrap_or_default().to_string_lossy();
+                    let source = if table.sheet.is_empty() {
+                        file_name.into_owned()
+                    } else {
+                        format!("{} › {}", file_name, table.sheet)
+                    };
+                    let response = ui
+                        .selectable_label(false, egui::RichText::new(&table.name).monospace())
+                        .on_hover_text(format!("{}\n\n{}", source, table.columns.join(", ")));
+                    if response.clicked() {
+                        self.sql.push_str(&table.name);
+                    }
+                }
+            });
+    }
+
+    fn start_task(&mut self, ctx: &Context, files: Vec<(PathBuf, LoadOptions)>, sql: String) {
+        self.running = true;
+        self.error = None;
+        self.status = None;
+
+        let engine = self.engine.clone();
+        let egui_ctx = ctx.clone();
+        spawn_task(
+            move || {
+                let started = Instant::now();
+                let mut guard = engine.lock().unwrap_or_else(|e| e.into_inner());
+                if guard.is_none() {
+                    match QueryEngine::new() {
+                        Ok(new_engine) => *guard = Some(new_engine),
+                        Err(e) => {
+                            return TaskOutcome {
+                                tables: Vec::new(),
+                                result: None,
+                                errors: vec![e],
+                                sql,
+                                elapsed: started.elapsed(),
+                            }
+                        }
+                    }
+                }
+                let engine = guard.as_mut().expect("engine was just created");
+
+                let errors = files
+                    .iter()
+                    .filter_map(|(path, options)| engine.register_file(path, options).err())
+                    .collect();
+                let result = (!sql.trim().is_empty()).then(|| engine.query(&sql));
+
+                TaskOutcome {
+                    tables: engine.tables().to_vec(),
+                    result,
+                    errors,
+                    sql,
+                    elapsed: started.elapsed(),
+                }
+            },
+            self.tx.clone(),
+            move || egui_ctx.request_repaint(),
+        );
+    }
+
+    fn handle_outcome(&mut self) -> Option<DerivedTable> {
+        let outcome = self.rx.try_recv().ok()?;
+        self.running = false;
+        if !outcome.tables.is_empty() {
+            self.tables = outcome.tables;
+        }
+        self.error = (!outcome.errors.is_empty()).then(|| {
+            let messages: Vec<String> = outcome.errors.iter().map(i18n::load_error).collect();
+            messages.join("\n")
+        });
+
+        match outcome.result? {
+            Ok(result) => {
+                self.query_count += 1;
+                self.status = Some(tr!(
+                    "sql-status",
+                    rows = result.rows.len(),
+                    ms = outcome.elapsed.as_millis() as u64
+                ));
+                Some(DerivedTable::new(
+                    tr!("sql-tab", number = self.query_count),
+                    outcome.sql,
+                    result.headers,
+                    result.rows,
+                    result.column_types,
+                ))
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+}
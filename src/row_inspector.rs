@@ -0,0 +1,356 @@
+use egui::Context;
+use rust_excel_reader_core::edits::RowId;
+use rust_excel_reader_core::export::row_json;
+use rust_excel_reader_core::formatting::{LimitCheck, SpecLimit};
+use rust_excel_reader_core::values::{format_number, parse_number};
+use std::collections::BTreeMap;
+
+use crate::i18n::tr;
+use crate::theme;
+
+/// The row under the cursor, as handed to the inspector each frame.
+pub struct InspectedRow<'a> {
+    /// The row in the file, or a row inserted since the last save; the grid
+    /// numbers rows by position, which inserts and deletes shift.
+    pub row: RowId,
+    pub values: &'a [String],
+}
+
+/// Lower and upper limit being typed for one column.
+struct LimitEditor {
+    column: String,
+    low: String,
+    high: String,
+    error: bool,
+}
+
+/// Side panel listing every header / value pair of the selected row, for
+/// files too wide to read a record across the grid.
+///
+/// Rows can be pinned to compare them side by side with the selection.
+/// Values are colored against the spec limits of their column; the limits
+/// are saved per header signature by the app, like formatting rules.
+pub struct RowInspector {
+    pub open: bool,
+    search: String,
+    /// Pinned rows, with the values they had when pinned.
+    pinned: Vec<(RowId, Vec<String>)>,
+    limits: BTreeMap<String, SpecLimit>,
+    limit_editor: Option<LimitEditor>,
+    /// Row last copied as JSON, acknowledged next to the button.
+    copied: Option<RowId>,
+}
+
+impl RowInspector {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            search: String::new(),
+            pinned: Vec::new(),
+            limits: BTreeMap::new(),
+            limit_editor: None,
+            copied: None,
+        }
+    }
+
+    pub fn limits(&self) -> &BTreeMap<String, SpecLimit> {
+        &self.limits
+    }
+
+    /// Switches to a newly loaded sheet and its saved limits; pinned rows
+    /// belong to the previous sheet and are dropped.
+    pub fn set_sheet(&mut self, limits: BTreeMap<String, SpecLimit>) {
+        self.limits = limits;
+        self.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.pinned.clear();
+        self.limit_editor = None;
+        self.copied = None;
+    }
+
+    /// Shows the panel if it is open. Returns `true` when the limits changed
+    /// and should be saved.
+    pub fn show(&mut self, ctx: &Context, headers: &[String], row: Option<InspectedRow>) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        let before = self.limits.clone();
+        egui::SidePanel::right("row_inspector")
+            .resizable(true)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(tr!("inspector-title"));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").clicked() {
+                            self.open = false;
+                        }
+                    });
+                });
+                ui.separator();
+                self.toolbar(ui, headers, row.as_ref());
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.search)
+                        .hint_text(tr!("inspector-search"))
+                        .desired_width(f32::INFINITY),
+                );
+                ui.separator();
+
+                if row.is_none() && self.pinned.is_empty() {
+                    let palette = theme::palette(ui.ctx());
+                    ui.label(
+                        egui::RichText::new(tr!("inspector-no-row"))
+                            .color(palette.muted)
+                            .size(12.0),
+                    );
+                    return;
+                }
+                egui::ScrollArea::both()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| self.value_table(ui, headers, row.as_ref()));
+            });
+
+        self.limits != before
+    }
+
+    fn toolbar(&mut self, ui: &mut egui::Ui, headers: &[String], row: Option<&InspectedRow>) {
+        let palette = theme::palette(ui.ctx());
+        ui.horizontal_wrapped(|ui| {
+            let Some(row) = row else {
+                return;
+            };
+            ui.label(egui::RichText::new(row_label(row.row)).strong());
+            let pinned = self.pinned.iter().any(|(pinned, _)| *pinned == row.row);
+            if ui
+                .add_enabled(!pinned, egui::Button::new(tr!("inspector-pin")))
+                .on_hover_text(tr!("inspector-pin-hint"))
+                .clicked()
+            {
+                self.pinned.push((row.row, row.values.to_vec()));
+            }
+            if ui.button(tr!("inspector-copy-json")).clicked() {
+                ui.ctx().copy_text(row_json(headers, row.values));
+                self.copied = Some(row.row);
+            }
+            if self.copied == Some(row.row) {
+                ui.label(
+                    egui::RichText::new(tr!("inspector-copied"))
+                        .color(palette.success)
+                        .size(12.0),
+                );
+            }
+        });
+    }
+
+    /// One line per column: the header, the selected row and the pinned
+    /// rows.
+    fn value_table(&mut self, ui: &mut egui::Ui, headers: &[String], row: Option<&InspectedRow>) {
+        let palette = theme::palette(ui.ctx());
+        let search = self.search.trim().to_lowercase();
+        let shown: Vec<usize> = (0..headers.len())
+            .filter(|&column| {
+                search.is_empty()
+                    || headers[column].to_lowercase().contains(&search)
+                    || row
+                        .into_iter()
+                        .map(|row| row.values)
+                        .chain(self.pinned.iter().map(|(_, values)| values.as_slice()))
+                        .any(|values| {
+                            values
+                                .get(column)
+                                .is_some_and(|value| value.to_lowercase().contains(&search))
+                        })
+            })
+            .collect();
+        if !search.is_empty() {
+            ui.label(
+                egui::RichText::new(tr!(
+                    "inspector-matches",
+                    shown = shown.len(),
+                    total = headers.len()
+                ))
+                .color(palette.muted)
+                .size(12.0),
+            );
+        }
+
+        let mut unpinned = None;
+        let mut edit_limits = None;
+        egui::Grid::new("row_inspector_values")
+            .striped(true)
+            .num_columns(2 + self.pinned.len())
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new(tr!("inspector-column")).strong());
+                match row {
+                    Some(row) => ui.label(egui::RichText::new(row_label(row.row)).strong()),
+                    None => ui.label(""),
+                };
+                for (index, (pinned, _)) in self.pinned.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let number = match pinned {
+                            RowId::Source(index) => (index + 1).to_string(),
+                            RowId::Inserted(_) => tr!("inspector-new"),
+                        };
+                        ui.label(egui::RichText::new(format!("📌 {}", number)).strong());
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text(tr!("inspector-unpin"))
+                            .clicked()
+                        {
+                            unpinned = Some(index);
+                        }
+                    });
+                }
+                ui.end_row();
+
+                for &column in &shown {
+                    let header = &headers[column];
+                    let limit = self.limits.get(header).copied().unwrap_or_default();
+                    ui.horizontal(|ui| {
+                        let label = ui.label(header);
+                        if !limit.is_empty() {
+                            label.on_hover_text(limit_summary(&limit));
+                        }
+                        if ui
+                            .small_button("📏")
+                            .on_hover_text(tr!("inspector-limits-hint"))
+                            .clicked()
+                        {
+                            edit_limits = Some(header.clone());
+                        }
+                    });
+                    let current = row.map(|row| row.values);
+                    let pinned = self
+                        .pinned
+                        .iter()
+                        .map(|(_, values)| Some(values.as_slice()));
+                    for values in std::iter::once(current).chain(pinned) {
+                        let value = values
+                            .and_then(|values| values.get(column))
+                            .map_or("", String::as_str);
+                        let (color, hint) = match limit.check(value) {
+                            Some(LimitCheck::Within) => (palette.success, None),
+                            Some(LimitCheck::Below) => {
+                                (palette.error, Some(tr!("inspector-below")))
+                            }
+                            Some(LimitCheck::Above) => {
+                                (palette.error, Some(tr!("inspector-above")))
+                            }
+                            None => (palette.text, None),
+                        };
+                        let response = ui.label(egui::RichText::new(value).color(color));
+                        if let Some(hint) = hint {
+                            response.on_hover_text(hint);
+                        }
+                    }
+                    ui.end_row();
+
+                    if self
+                        .limit_editor
+                        .as_ref()
+                        .is_some_and(|editor| editor.column == *header)
+                    {
+                        self.limit_editor_row(ui);
+                        ui.end_row();
+                    }
+                }
+            });
+
+        if let Some(index) = unpinned {
+            self.pinned.remove(index);
+        }
+        if let Some(column) = edit_limits {
+            let limit = self.limits.get(&column).copied().unwrap_or_default();
+            let text = |bound: Option<f64>| bound.map(format_number).unwrap_or_default();
+            self.limit_editor = Some(LimitEditor {
+                column,
+                low: text(limit.low),
+                high: text(limit.high),
+                error: false,
+            });
+        }
+    }
+
+    /// Lower and upper limit fields under the column being edited.
+    fn limit_editor_row(&mut self, ui: &mut egui::Ui) {
+        let palette = theme::palette(ui.ctx());
+        let Some(editor) = &mut self.limit_editor else {
+            return;
+        };
+        ui.label(
+            egui::RichText::new(tr!("inspector-limits"))
+                .color(palette.muted)
+                .size(12.0),
+        );
+        let mut done = false;
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("inspector-low"));
+                ui.add(egui::TextEdit::singleline(&mut editor.low).desired_width(70.0));
+                ui.label(tr!("inspector-high"));
+                ui.add(egui::TextEdit::singleline(&mut editor.high).desired_width(70.0));
+            });
+            ui.horizontal(|ui| {
+                if ui.button(tr!("inspector-apply")).clicked() {
+                    // Empty fields leave that side open
+                    let bound = |text: &str| match text.trim() {
+                        "" => Some(None),
+                        text => parse_number(text).map(Some),
+                    };
+                    match (bound(&editor.low), bound(&editor.high)) {
+                        (Some(low), Some(high)) => {
+                            let limit = SpecLimit { low, high };
+                            if limit.is_empty() {
+                                self.limits.remove(&editor.column);
+                            } else {
+                                self.limits.insert(editor.column.clone(), limit);
+                            }
+                            done = true;
+                        }
+                        _ => editor.error = true,
+                    }
+                }
+                if ui.button(tr!("inspector-clear")).clicked() {
+                    self.limits.remove(&editor.column);
+                    done = true;
+                }
+                if ui.button(tr!("inspector-cancel")).clicked() {
+                    done = true;
+                }
+            });
+            if editor.error {
+                ui.label(
+                    egui::RichText::new(format!("⚠ {}", tr!("inspector-invalid-limit")))
+                        .color(palette.warning)
+                        .size(11.0),
+                );
+            }
+        });
+        if done {
+            self.limit_editor = None;
+        }
+    }
+}
+
+/// The limits of a column for its tooltip, with open sides as infinity.
+fn limit_summary(limit: &SpecLimit) -> String {
+    let bound = |value: Option<f64>, open: &str| value.map_or(open.to_owned(), format_number);
+    tr!(
+        "inspector-limit-summary",
+        low = bound(limit.low, "−∞"),
+        high = bound(limit.high, "+∞")
+    )
+}
+
+/// Heading of a row: its number in the file, from 1, as go-to and the
+/// parse issues count rows.
+fn row_label(row: RowId) -> String {
+    match row {
+        RowId::Source(index) => tr!("inspector-row", row = index + 1),
+        RowId::Inserted(_) => tr!("inspector-new-row"),
+    }
+}
@@ -0,0 +1,48 @@
+use rust_excel_reader_core::csv_loader::Tolerance;
+use rust_excel_reader_core::fixed_width_loader::FixedWidthProfile;
+use rust_excel_reader_core::formatting::{FormatRule, SpecLimit};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::font_setup::FontSettings;
+use crate::i18n::Language;
+use crate::theme::ThemeSettings;
+
+const SETTINGS_KEY: &str = "settings";
+
+/// User settings that are persisted across runs in eframe's storage.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Saved fixed-width import layouts.
+    pub fixed_width_profiles: Vec<FixedWidthProfile>,
+    /// Conditional formatting rules by header signature (see
+    /// [`rust_excel_reader_core::formatting::header_signature`]).
+    pub format_rules: BTreeMap<String, Vec<FormatRule>>,
+    /// Spec limits by header signature, then by column name.
+    pub spec_limits: BTreeMap<String, BTreeMap<String, SpecLimit>>,
+    /// How malformed CSV records are handled in the files opened next.
+    pub csv_tolerance: Tolerance,
+    pub language: Language,
+    pub font: FontSettings,
+    pub theme: ThemeSettings,
+}
+
+impl Settings {
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        storage
+            .and_then(|storage| eframe::get_value(storage, SETTINGS_KEY))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SETTINGS_KEY, self);
+    }
+
+    /// Adds `profile`, replacing any profile with the same header line.
+    pub fn save_fixed_width_profile(&mut self, profile: FixedWidthProfile) {
+        self.fixed_width_profiles
+            .retain(|existing| existing.header_line.trim_end() != profile.header_line.trim_end());
+        self.fixed_width_profiles.push(profile);
+    }
+}
@@ -0,0 +1,114 @@
+use rust_excel_reader_core::data_loader::load_data_sync;
+use rust_excel_reader_core::table_reader::SheetSource;
+use rust_excel_reader_core::LoadOptions;
+use std::path::Path;
+
+use crate::i18n::tr;
+
+/// The sheet open in the file view: path, sheet index and load options.
+pub type CurrentSheet<'a> = (&'a Path, usize, &'a LoadOptions);
+
+/// A sheet picked in the compare or merge window, with its sheet names and
+/// headers so the window can offer them.
+pub struct PickedSheet {
+    pub source: SheetSource,
+    pub sheet_names: Vec<String>,
+    pub headers: Vec<String>,
+}
+
+impl PickedSheet {
+    /// Reads the first row of a sheet to learn its sheets and columns.
+    pub fn probe(source: SheetSource) -> Result<Self, String> {
+        let page = load_data_sync(
+            source.path.clone(),
+            0,
+            1,
+            source.sheet_index,
+            &source.options,
+        )?;
+        Ok(Self {
+            source,
+            sheet_names: page.sheet_names,
+            headers: page.headers,
+        })
+    }
+
+    /// File name, with the sheet name for workbooks.
+    pub fn name(&self) -> String {
+        let file = self
+            .source
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        match self.sheet_names.get(self.source.sheet_index) {
+            Some(sheet) if self.sheet_names.len() > 1 => format!("{} › {}", file, sheet),
+            _ => file.into_owned(),
+        }
+    }
+}
+
+pub fn current_source((path, sheet_index, options): CurrentSheet) -> SheetSource {
+    SheetSource {
+        path: path.to_path_buf(),
+        sheet_index,
+        options: options.clone(),
+    }
+}
+
+/// A sheet selector for `picked` plus "Current sheet" and "Choose file…"
+/// buttons. Returns the source the user switched to, if any.
+pub fn sheet_buttons(
+    ui: &mut egui::Ui,
+    id: impl std::hash::Hash,
+    picked: Option<&PickedSheet>,
+    current: Option<CurrentSheet>,
+) -> Option<SheetSource> {
+    let mut chosen = None;
+    ui.horizontal(|ui| {
+        if let Some(picked) = picked.filter(|p| p.sheet_names.len() > 1) {
+            let mut sheet_index = picked.source.sheet_index;
+            egui::ComboBox::from_id_source(id)
+                .selected_text(
+                    picked
+                        .sheet_names
+                        .get(sheet_index)
+                        .cloned()
+                        .unwrap_or_default(),
+                )
+                .width(150.0)
+                .show_ui(ui, |ui| {
+                    for (index, name) in picked.sheet_names.iter().enumerate() {
+                        ui.selectable_value(&mut sheet_index, index, name);
+                    }
+                });
+            if sheet_index != picked.source.sheet_index {
+                chosen = Some(SheetSource {
+                    sheet_index,
+                    ..picked.source.clone()
+                });
+            }
+        }
+        if let Some(current) = current {
+            if ui.small_button(tr!("sheet-current")).clicked() {
+                chosen = Some(current_source(current));
+            }
+        }
+        if ui.small_button(tr!("sheet-choose-file")).clicked() {
+            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                // The open file keeps its import settings, e.g. a
+                // fixed-width layout
+                let options = match current {
+                    Some((current_path, _, options)) if current_path == path => options.clone(),
+                    _ => LoadOptions::default(),
+                };
+                chosen = Some(SheetSource {
+                    path,
+                    sheet_index: 0,
+                    options,
+                });
+            }
+        }
+    });
+    chosen
+}
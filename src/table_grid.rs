@@ -0,0 +1,985 @@
+use egui::{pos2, vec2, Align2, Color32, CursorIcon, FontId, Id, Rect, Rounding, Sense};
+use rust_excel_reader_core::pretty::is_structured;
+use rust_excel_reader_core::values::SortOrder;
+use rust_excel_reader_core::ColumnType;
+use std::borrow::Cow;
+
+use crate::i18n::tr;
+use crate::theme::{self, Palette};
+
+/// Gap between neighbouring cells.
+const SPACING: f32 = 1.0;
+const ROW_HEIGHT: f32 = 22.0;
+/// Lines of text shown in the cells of a wrapping column, and the height
+/// each line adds to the rows.
+const WRAP_LINES: usize = 3;
+const LINE_HEIGHT: f32 = 15.0;
+const CELL_PADDING: f32 = 6.0;
+const MIN_COLUMN_WIDTH: f32 = 30.0;
+/// Width range of a column fitted when it is first shown.
+const DEFAULT_WIDTH: std::ops::RangeInclusive<f32> = 80.0..=250.0;
+/// Rows measured when a column is first shown; a double-click on its edge
+/// measures up to `FIT_ROWS` loaded rows.
+const INITIAL_FIT_ROWS: usize = 50;
+const FIT_ROWS: usize = 2000;
+/// Width of the grip on the right edge of a header.
+const RESIZE_GRIP: f32 = 6.0;
+/// Characters shown before a cell is cut short, until the column is set
+/// otherwise.
+const DEFAULT_TRUNCATE: usize = 50;
+
+/// How a table is drawn and what can be done with it.
+pub struct GridOptions<'a> {
+    /// Indices into the rows in display order; `None` shows every row in
+    /// order.
+    pub order: Option<&'a [usize]>,
+    /// With `Some`, headers are clickable and show the current sort order.
+    pub sort: Option<Option<SortOrder>>,
+    /// Style of a cell, given its row index and column.
+    pub cell_style: &'a dyn Fn(usize, usize) -> CellStyle,
+    /// Number of leading columns that can be edited; `0` makes the table
+    /// read-only. Computed columns come after the editable ones.
+    pub editable_columns: usize,
+    /// Why rows cannot be inserted or deleted, shown on the disabled row
+    /// menu items; `None` allows it in an editable table.
+    pub row_lock: Option<&'a str>,
+    /// The cell being edited, if any.
+    pub editor: Option<&'a mut CellEditor>,
+    /// Widths, order and visibility of the columns, changed by the user.
+    pub layout: &'a mut ColumnLayout,
+    /// Row number of the first row, less one, when the rows are a window
+    /// into a longer sheet.
+    pub first_row: usize,
+    /// The cell under the keyboard cursor, as (display index, column).
+    pub cursor: Option<(usize, usize)>,
+    /// Scrolls the cursor into view this frame.
+    pub scroll_to_cursor: bool,
+}
+
+impl<'a> GridOptions<'a> {
+    /// A read-only table in row order.
+    pub fn new(
+        layout: &'a mut ColumnLayout,
+        cell_style: &'a dyn Fn(usize, usize) -> CellStyle,
+    ) -> Self {
+        Self {
+            order: None,
+            sort: None,
+            cell_style,
+            editable_columns: 0,
+            row_lock: None,
+            editor: None,
+            layout,
+            first_row: 0,
+            cursor: None,
+            scroll_to_cursor: false,
+        }
+    }
+}
+
+/// How a cell is drawn, beyond the default striping.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CellStyle {
+    /// Background in place of the striping.
+    pub fill: Option<Color32>,
+    /// A bar drawn over the cell from the left, as a fraction of its width.
+    pub bar: Option<(f32, Color32)>,
+}
+
+impl CellStyle {
+    pub fn fill(fill: Option<Color32>) -> Self {
+        Self { fill, bar: None }
+    }
+}
+
+/// Column widths, display order, hidden columns, frozen columns and how
+/// cell text is fitted, kept for as long as the table is shown.
+///
+/// Columns are referred to by their index in the rows; the layout only
+/// changes where and how wide they are drawn. Frozen columns are the
+/// leading entries of the display order and stay in place, next to the row
+/// index, during horizontal scrolling.
+#[derive(Default)]
+pub struct ColumnLayout {
+    /// Width of each column; `None` until it is fitted to the first rows.
+    widths: Vec<Option<f32>>,
+    /// Every column, hidden ones included, in display order.
+    order: Vec<usize>,
+    hidden: Vec<bool>,
+    /// Number of leading entries of `order` that are frozen.
+    frozen: usize,
+    /// Characters shown before a cell of each column is cut short.
+    truncate: Vec<usize>,
+    /// Columns whose text wraps over several lines instead; rows get taller
+    /// while one of them is visible.
+    wrap: Vec<bool>,
+    /// Column whose header is being dragged to a new place.
+    dragging: Option<usize>,
+    /// Filter of the column menu.
+    filter: String,
+}
+
+impl ColumnLayout {
+    /// Matches the layout to a table with `columns` columns. Columns added
+    /// later, such as computed ones, go at the end.
+    fn sync(&mut self, columns: usize) {
+        if self.widths.len() == columns {
+            return;
+        }
+        self.widths.resize(columns, None);
+        self.hidden.resize(columns, false);
+        self.truncate.resize(columns, DEFAULT_TRUNCATE);
+        self.wrap.resize(columns, false);
+        let mut listed = vec![false; columns];
+        self.order.retain(|&column| column < columns);
+        for &column in &self.order {
+            listed[column] = true;
+        }
+        self.order
+            .extend((0..columns).filter(|&column| !listed[column]));
+        self.frozen = self.frozen.min(self.order.len());
+        self.dragging = None;
+    }
+
+    pub fn hidden_count(&self) -> usize {
+        self.hidden.iter().filter(|&&hidden| hidden).count()
+    }
+
+    fn width(&self, column: usize) -> f32 {
+        self.widths[column].unwrap_or(*DEFAULT_WIDTH.start())
+    }
+
+    fn position(&self, column: usize) -> Option<usize> {
+        self.order.iter().position(|&c| c == column)
+    }
+
+    /// Visible columns in display order, frozen ones first.
+    pub fn visible_order(&self) -> Vec<usize> {
+        let (mut frozen, scrolling) = self.visible_columns();
+        frozen.extend(scrolling);
+        frozen
+    }
+
+    /// Height of the rows: taller while a visible column wraps.
+    fn row_height(&self) -> f32 {
+        let wraps = self
+            .order
+            .iter()
+            .any(|&column| self.wrap[column] && !self.hidden[column]);
+        if wraps {
+            ROW_HEIGHT + (WRAP_LINES - 1) as f32 * LINE_HEIGHT
+        } else {
+            ROW_HEIGHT
+        }
+    }
+
+    /// Visible columns in display order, as (frozen, scrolling).
+    fn visible_columns(&self) -> (Vec<usize>, Vec<usize>) {
+        let visible = |columns: &[usize]| -> Vec<usize> {
+            columns
+                .iter()
+                .copied()
+                .filter(|&column| !self.hidden[column])
+                .collect()
+        };
+        (
+            visible(&self.order[..self.frozen]),
+            visible(&self.order[self.frozen..]),
+        )
+    }
+
+    /// Freezes every column up to and including `column`.
+    fn freeze_through(&mut self, column: usize) {
+        if let Some(position) = self.position(column) {
+            self.frozen = position + 1;
+        }
+    }
+
+    /// Moves `column` in front of `before`, or to the end of the frozen or
+    /// scrolling columns if `before` is `None`.
+    fn move_column(&mut self, column: usize, before: Option<usize>, frozen: bool) {
+        let Some(from) = self.position(column) else {
+            return;
+        };
+        if before == Some(column) {
+            return;
+        }
+        self.order.remove(from);
+        if from < self.frozen {
+            self.frozen -= 1;
+        }
+        let end = if frozen {
+            self.frozen
+        } else {
+            self.order.len()
+        };
+        let to = before.and_then(|c| self.position(c)).unwrap_or(end);
+        let to = if frozen {
+            to.min(self.frozen)
+        } else {
+            to.max(self.frozen)
+        };
+        self.order.insert(to, column);
+        if frozen {
+            self.frozen += 1;
+        }
+    }
+}
+
+/// A cell being edited in place.
+pub struct CellEditor {
+    /// Row index into the table's rows.
+    pub row: usize,
+    pub column: usize,
+    pub text: String,
+    focused: bool,
+}
+
+impl CellEditor {
+    pub fn new(row: usize, column: usize, text: String) -> Self {
+        Self {
+            row,
+            column,
+            text,
+            focused: false,
+        }
+    }
+}
+
+/// A row operation picked from the row context menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowAction {
+    InsertAbove,
+    InsertBelow,
+    Delete,
+}
+
+/// How editing a cell ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorOutcome {
+    Commit,
+    Cancel,
+}
+
+/// What the user did in the grid this frame.
+#[derive(Default)]
+pub struct GridEvents {
+    /// Header clicked to sort.
+    pub sort_clicked: Option<usize>,
+    /// Cell double-clicked to edit in place, as (row index, column).
+    pub edit_requested: Option<(usize, usize)>,
+    /// Cell to show in full in the cell viewer, as (row index, column):
+    /// double-clicked when it is read-only or holds several lines, JSON or
+    /// XML, or picked from its context menu.
+    pub view_requested: Option<(usize, usize)>,
+    pub row_action: Option<(usize, RowAction)>,
+    pub editor: Option<EditorOutcome>,
+    /// Cell clicked, as (display index, column).
+    pub cell_clicked: Option<(usize, usize)>,
+    /// Number of rows that fit in the visible area.
+    pub page_rows: usize,
+}
+
+/// Screen positions of the parts of the table, for one frame.
+///
+/// The header row sticks to the top of the visible area and the row index
+/// and frozen columns to its left; every part is drawn clipped to its own
+/// region so scrolled cells never show through the sticky ones.
+struct Geometry {
+    grid_id: Id,
+    palette: Palette,
+    /// Left edge of the row index column.
+    index_left: f32,
+    index_width: f32,
+    row_height: f32,
+    header_top: f32,
+    header_height: f32,
+    /// Top of the first data row.
+    body_top: f32,
+    corner_clip: Rect,
+    header_clip: Rect,
+    frozen_clip: Rect,
+    body_clip: Rect,
+    /// Left edge and width of the visible columns, frozen ones first.
+    columns: Vec<(usize, f32, f32)>,
+    frozen_count: usize,
+    /// Right edge of the frozen columns.
+    frozen_right: f32,
+}
+
+impl Geometry {
+    /// Clip region of the cells of the `index`-th visible column.
+    fn column_clip(&self, index: usize, header: bool) -> Rect {
+        match (index < self.frozen_count, header) {
+            (true, true) => self.corner_clip,
+            (true, false) => self.frozen_clip,
+            (false, true) => self.header_clip,
+            (false, false) => self.body_clip,
+        }
+    }
+}
+
+pub fn show(
+    ui: &mut egui::Ui,
+    headers: &[String],
+    column_types: &[ColumnType],
+    rows: &[Vec<String>],
+    options: GridOptions,
+) -> egui::InnerResponse<GridEvents> {
+    let layout = &mut *options.layout;
+    layout.sync(headers.len());
+    for (column, header) in headers.iter().enumerate() {
+        if layout.widths[column].is_none() {
+            let sample = rows.iter().take(INITIAL_FIT_ROWS);
+            let width = fit_width(ui, header, sample, column, layout.truncate[column])
+                .clamp(*DEFAULT_WIDTH.start(), *DEFAULT_WIDTH.end());
+            layout.widths[column] = Some(width);
+        }
+    }
+
+    let row_count = options.order.map_or(rows.len(), |order| order.len());
+    let last_number = (options.first_row + rows.len()).to_string();
+    let index_width =
+        (text_width(ui, &last_number, index_font()) + 2.0 * CELL_PADDING).max(36.0);
+    let header_height = if column_types.is_empty() { 26.0 } else { 38.0 };
+    let row_height = layout.row_height();
+    let (frozen, scrolling) = layout.visible_columns();
+    let span = |columns: &[usize]| -> f32 {
+        columns
+            .iter()
+            .map(|&column| layout.width(column) + SPACING)
+            .sum()
+    };
+    let (frozen_width, scrolling_width) = (span(&frozen), span(&scrolling));
+    let size = vec2(
+        index_width + SPACING + frozen_width + scrolling_width,
+        header_height + SPACING + row_count as f32 * (row_height + SPACING),
+    );
+    let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+
+    let clip = ui.clip_rect();
+    let index_left = rect.left().max(clip.left());
+    let header_top = rect.top().max(clip.top());
+    let frozen_right = index_left + index_width + SPACING + frozen_width;
+    let header_bottom = header_top + header_height + SPACING;
+    let region = |left: f32, top: f32, right: f32, bottom: f32| {
+        Rect::from_min_max(pos2(left, top), pos2(right, bottom)).intersect(clip)
+    };
+
+    let mut columns = Vec::with_capacity(frozen.len() + scrolling.len());
+    let mut x = index_left + index_width + SPACING;
+    for &column in &frozen {
+        columns.push((column, x, layout.width(column)));
+        x += layout.width(column) + SPACING;
+    }
+    let mut x = rect.left() + index_width + SPACING + frozen_width;
+    for &column in &scrolling {
+        columns.push((column, x, layout.width(column)));
+        x += layout.width(column) + SPACING;
+    }
+
+    let geometry = Geometry {
+        grid_id: ui.id().with("table_grid"),
+        palette: theme::palette(ui.ctx()),
+        index_left,
+        index_width,
+        row_height,
+        header_top,
+        header_height,
+        body_top: rect.top() + header_height + SPACING,
+        corner_clip: region(clip.left(), clip.top(), frozen_right, header_bottom),
+        header_clip: region(frozen_right, clip.top(), clip.right(), header_bottom),
+        frozen_clip: region(clip.left(), header_bottom, frozen_right, clip.bottom()),
+        body_clip: region(frozen_right, header_bottom, clip.right(), clip.bottom()),
+        columns,
+        frozen_count: frozen.len(),
+        frozen_right,
+    };
+
+    let events = GridEvents {
+        sort_clicked: render_headers(
+            ui,
+            &geometry,
+            headers,
+            column_types,
+            rows,
+            options.sort,
+            layout,
+        ),
+        ..GridEvents::default()
+    };
+    let cursor = options.cursor.filter(|_| options.scroll_to_cursor);
+    let events = render_rows(ui, &geometry, rows, options, events);
+    if let Some(cursor) = cursor {
+        scroll_to_cell(ui, &geometry, cursor);
+    }
+    egui::InnerResponse::new(events, response)
+}
+
+/// Scrolls so the cell at `(display index, column)` is clear of the sticky
+/// header and frozen columns.
+fn scroll_to_cell(ui: &mut egui::Ui, geometry: &Geometry, (display_index, column): (usize, usize)) {
+    let Some(index) = geometry.columns.iter().position(|&(c, _, _)| c == column) else {
+        return;
+    };
+    let (_, left, width) = geometry.columns[index];
+    let clip = ui.clip_rect();
+    let top = geometry.body_top + display_index as f32 * (geometry.row_height + SPACING);
+    let sticky_height = geometry.header_height + SPACING;
+    let x_range = if index < geometry.frozen_count {
+        // Frozen columns are always in view
+        clip.x_range()
+    } else {
+        (left - (geometry.frozen_right - geometry.index_left)..=left + width).into()
+    };
+    let target = Rect::from_x_y_ranges(x_range, top - sticky_height..=top + geometry.row_height);
+    ui.scroll_to_rect(target, None);
+}
+
+/// Renders the header row: the index corner, then a header per visible
+/// column with a grip to resize it. Headers can be dragged to reorder the
+/// columns and have a context menu to hide or freeze them. With `sort`
+/// set, a click sorts by the column, which is returned.
+fn render_headers(
+    ui: &mut egui::Ui,
+    geometry: &Geometry,
+    headers: &[String],
+    column_types: &[ColumnType],
+    rows: &[Vec<String>],
+    sort: Option<Option<SortOrder>>,
+    layout: &mut ColumnLayout,
+) -> Option<usize> {
+    if headers.is_empty() {
+        return None;
+    }
+    let mut clicked = None;
+
+    // Index header with special styling - more compact
+    let corner = Rect::from_min_size(
+        pos2(geometry.index_left, geometry.header_top),
+        vec2(geometry.index_width, geometry.header_height),
+    );
+    let painter = ui.painter().with_clip_rect(geometry.corner_clip);
+    let palette = &geometry.palette;
+    painter.rect_filled(corner, Rounding::same(4.0), palette.accent);
+    painter.text(
+        pos2(corner.left() + 8.0, corner.center().y),
+        Align2::LEFT_CENTER,
+        "#",
+        FontId::proportional(12.0),
+        palette.header_text,
+    );
+
+    // Column headers - more compact
+    for (index, &(column, left, width)) in geometry.columns.iter().enumerate() {
+        let region = geometry.column_clip(index, true);
+        let rect = Rect::from_min_size(
+            pos2(left, geometry.header_top),
+            vec2(width, geometry.header_height),
+        );
+        if !rect.intersects(region) {
+            continue;
+        }
+        let arrow = match sort.flatten() {
+            Some(order) if order.column == column && order.ascending => " ▲",
+            Some(order) if order.column == column => " ▼",
+            _ => "",
+        };
+        let painter = ui.painter().with_clip_rect(rect.intersect(region));
+        painter.rect_filled(rect, Rounding::same(4.0), palette.header);
+        let name = format!(
+            "{}{}{}",
+            if index < geometry.frozen_count {
+                "🔒 "
+            } else {
+                ""
+            },
+            headers[column],
+            arrow
+        );
+        // Declared type from the file schema (Parquet / Arrow)
+        if let Some(column_type) = column_types.get(column) {
+            painter.text(
+                pos2(rect.left() + 8.0, rect.top() + 4.0),
+                Align2::LEFT_TOP,
+                name,
+                FontId::proportional(12.0),
+                palette.header_text,
+            );
+            painter.text(
+                pos2(rect.left() + 8.0, rect.bottom() - 4.0),
+                Align2::LEFT_BOTTOM,
+                column_type.label(),
+                FontId::monospace(10.0),
+                palette.header_text.gamma_multiply(0.85),
+            );
+        } else {
+            painter.text(
+                pos2(rect.left() + 8.0, rect.center().y),
+                Align2::LEFT_CENTER,
+                name,
+                FontId::proportional(12.0),
+                palette.header_text,
+            );
+        }
+
+        let mut target = rect;
+        target.max.x -= RESIZE_GRIP / 2.0;
+        let response = ui.interact(
+            target.intersect(region),
+            geometry.grid_id.with(("header", column)),
+            Sense::click_and_drag(),
+        );
+        if sort.is_some() {
+            let response = response.clone().on_hover_cursor(CursorIcon::PointingHand);
+            if response.clicked() {
+                clicked = Some(column);
+            }
+        }
+        if response.drag_started() {
+            layout.dragging = Some(column);
+        }
+        response.context_menu(|ui| header_menu(ui, headers, rows, column, layout));
+
+        let grip = Rect::from_x_y_ranges(
+            rect.right() - RESIZE_GRIP / 2.0..=rect.right() + RESIZE_GRIP / 2.0,
+            rect.y_range(),
+        );
+        let grip = ui
+            .interact(
+                grip.intersect(region),
+                geometry.grid_id.with(("resize", column)),
+                Sense::click_and_drag(),
+            )
+            .on_hover_cursor(CursorIcon::ResizeColumn)
+            .on_hover_text(tr!("grid-resize-hint"));
+        if grip.dragged() {
+            ui.ctx().set_cursor_icon(CursorIcon::ResizeColumn);
+            layout.widths[column] = Some((width + grip.drag_delta().x).max(MIN_COLUMN_WIDTH));
+        }
+        if grip.double_clicked() {
+            let sample = rows.iter().take(FIT_ROWS);
+            let width = fit_width(ui, &headers[column], sample, column, layout.truncate[column]);
+            layout.widths[column] = Some(width.max(MIN_COLUMN_WIDTH));
+        }
+    }
+
+    if let Some(column) = layout.dragging {
+        drag_column(ui, geometry, layout, column);
+    }
+    clicked
+}
+
+/// Shows where a dragged header would go and moves the column there when
+/// it is dropped. Dropping it over the frozen columns freezes it.
+fn drag_column(ui: &mut egui::Ui, geometry: &Geometry, layout: &mut ColumnLayout, column: usize) {
+    let Some(pointer) = ui.input(|i| i.pointer.interact_pos()) else {
+        return;
+    };
+    let frozen = pointer.x < geometry.frozen_right;
+    let (start, end) = if frozen {
+        (0, geometry.frozen_count)
+    } else {
+        (geometry.frozen_count, geometry.columns.len())
+    };
+    let candidates = &geometry.columns[start..end];
+    let before = candidates
+        .iter()
+        .find(|&&(_, left, width)| left + width / 2.0 > pointer.x);
+    let x = match (before, candidates.last()) {
+        (Some(&(_, left, _)), _) => left - SPACING / 2.0,
+        (None, Some(&(_, left, width))) => left + width + SPACING / 2.0,
+        (None, None) => geometry.frozen_right,
+    };
+
+    if ui.input(|i| i.pointer.primary_down()) {
+        ui.ctx().set_cursor_icon(CursorIcon::Grabbing);
+        let region = geometry.header_clip.union(geometry.corner_clip);
+        ui.painter().with_clip_rect(region).vline(
+            x,
+            geometry.header_top..=geometry.header_top + geometry.header_height,
+            egui::Stroke::new(3.0, geometry.palette.warning),
+        );
+    } else {
+        layout.move_column(column, before.map(|&(c, _, _)| c), frozen);
+        layout.dragging = None;
+    }
+}
+
+fn header_menu(
+    ui: &mut egui::Ui,
+    headers: &[String],
+    rows: &[Vec<String>],
+    column: usize,
+    layout: &mut ColumnLayout,
+) {
+    let frozen = layout
+        .position(column)
+        .is_some_and(|position| position < layout.frozen);
+    if ui.button(tr!("grid-hide-column")).clicked() {
+        layout.hidden[column] = true;
+        ui.close_menu();
+    }
+    if ui.button(tr!("grid-freeze")).clicked() {
+        layout.freeze_through(column);
+        ui.close_menu();
+    }
+    if frozen && ui.button(tr!("grid-unfreeze")).clicked() {
+        layout.frozen = 0;
+        ui.close_menu();
+    }
+    if ui.button(tr!("grid-fit")).clicked() {
+        let sample = rows.iter().take(FIT_ROWS);
+        let width = fit_width(ui, &headers[column], sample, column, layout.truncate[column]);
+        layout.widths[column] = Some(width.max(MIN_COLUMN_WIDTH));
+        ui.close_menu();
+    }
+    if layout.hidden_count() > 0 && ui.button(tr!("grid-show-all-columns")).clicked() {
+        layout.hidden.fill(false);
+        ui.close_menu();
+    }
+
+    ui.separator();
+    ui.checkbox(&mut layout.wrap[column], tr!("grid-wrap-text"));
+    ui.add_enabled_ui(!layout.wrap[column], |ui| {
+        ui.horizontal(|ui| {
+            ui.label(tr!("grid-truncate-after"));
+            ui.add(egui::DragValue::new(&mut layout.truncate[column]).range(4..=10_000));
+            ui.label(tr!("grid-characters"));
+        });
+    });
+}
+
+/// Renders the visible part of `rows`, or of the rows listed in `order` in
+/// that order. The index column always shows the row's position in the
+/// table.
+fn render_rows(
+    ui: &mut egui::Ui,
+    geometry: &Geometry,
+    rows: &[Vec<String>],
+    mut options: GridOptions,
+    mut events: GridEvents,
+) -> GridEvents {
+    let palette = &geometry.palette;
+    let row_count = options.order.map_or(rows.len(), |order| order.len());
+    let pitch = geometry.row_height + SPACING;
+    let clip = ui.clip_rect();
+    let first = ((clip.top() - geometry.body_top) / pitch).floor().max(0.0) as usize;
+    let last = (((clip.bottom() - geometry.body_top) / pitch)
+        .ceil()
+        .max(0.0) as usize)
+        .min(row_count);
+    events.page_rows = ((clip.height() - geometry.header_height) / pitch).floor().max(1.0) as usize;
+
+    for display_index in first..last {
+        let row_index = options
+            .order
+            .map_or(display_index, |order| order[display_index]);
+        let row = &rows[row_index];
+        let top = geometry.body_top + display_index as f32 * pitch;
+        let is_even = display_index % 2 == 0;
+        let row_bg = if is_even {
+            palette.stripe_even
+        } else {
+            palette.stripe_odd
+        };
+
+        // Row index cell - more compact
+        let index_cell = Rect::from_min_size(
+            pos2(geometry.index_left, top),
+            vec2(geometry.index_width, geometry.row_height),
+        );
+        let cursor_row = options.cursor.is_some_and(|(row, _)| row == display_index);
+        let painter = ui.painter().with_clip_rect(geometry.frozen_clip);
+        painter.rect_filled(index_cell, Rounding::same(3.0), row_bg);
+        painter.text(
+            pos2(index_cell.left() + CELL_PADDING, index_cell.center().y),
+            Align2::LEFT_CENTER,
+            (options.first_row + row_index + 1).to_string(),
+            index_font(),
+            if cursor_row {
+                palette.accent
+            } else {
+                palette.muted
+            },
+        );
+        let index_target = index_cell.intersect(geometry.frozen_clip);
+        if options.editable_columns > 0 && index_target.is_positive() {
+            ui.interact(
+                index_target,
+                geometry.grid_id.with(("row", row_index)),
+                Sense::click(),
+            )
+            .context_menu(|ui| {
+                let actions = [
+                    (tr!("grid-insert-above"), RowAction::InsertAbove),
+                    (tr!("grid-insert-below"), RowAction::InsertBelow),
+                    (tr!("grid-delete-row"), RowAction::Delete),
+                ];
+                for (label, action) in actions {
+                    let button = ui.add_enabled(options.row_lock.is_none(), egui::Button::new(label));
+                    let button = match options.row_lock {
+                        Some(reason) => button.on_disabled_hover_text(reason),
+                        None => button,
+                    };
+                    if button.clicked() {
+                        events.row_action = Some((row_index, action));
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+
+        // Data cells - more compact
+        for (index, &(col_index, left, width)) in geometry.columns.iter().enumerate() {
+            let region = geometry.column_clip(index, false);
+            let cell_rect =
+                Rect::from_min_size(pos2(left, top), vec2(width, geometry.row_height));
+            let target = cell_rect.intersect(region);
+            if !target.is_positive() {
+                continue;
+            }
+            let cell = row.get(col_index).map_or("", String::as_str);
+            let style = (options.cell_style)(row_index, col_index);
+            let cell_bg = if let Some(fill) = style.fill {
+                fill
+            } else if index % 2 == 0 {
+                row_bg
+            } else {
+                palette.column_tint(row_bg)
+            };
+            let painter = ui.painter().with_clip_rect(target);
+            painter.rect_filled(cell_rect, Rounding::same(3.0), cell_bg);
+
+            let editing = options
+                .editor
+                .as_deref_mut()
+                .filter(|e| e.row == row_index && e.column == col_index);
+            if let Some(editor) = editing {
+                let mut child = ui.child_ui_with_id_source(
+                    cell_rect.shrink2(vec2(2.0, 1.0)),
+                    egui::Layout::left_to_right(egui::Align::Center),
+                    geometry.grid_id.with("editor"),
+                    None,
+                );
+                child.set_clip_rect(target);
+                events.editor = render_editor(&mut child, editor);
+                continue;
+            }
+
+            if let Some((fraction, color)) = style.bar {
+                let mut bar = cell_rect.shrink(2.0);
+                bar.set_width(bar.width() * fraction);
+                painter.rect_filled(bar, Rounding::same(2.0), color.gamma_multiply(0.45));
+            }
+            let text_painter =
+                painter.with_clip_rect(target.shrink2(vec2(CELL_PADDING / 2.0, 0.0)));
+            let text_color = text_on(cell_bg, palette);
+            let truncated = if options.layout.wrap[col_index] {
+                let mut job = egui::text::LayoutJob::simple(
+                    cell.to_owned(),
+                    cell_font(),
+                    text_color,
+                    width - 2.0 * CELL_PADDING,
+                );
+                job.wrap.max_rows = WRAP_LINES;
+                let galley = ui.fonts(|fonts| fonts.layout_job(job));
+                let elided = galley.elided;
+                let text_top = cell_rect.center().y - galley.size().y / 2.0;
+                text_painter.galley(
+                    pos2(cell_rect.left() + CELL_PADDING, text_top),
+                    galley,
+                    text_color,
+                );
+                elided
+            } else {
+                let (text, truncated) = display_text(cell, options.layout.truncate[col_index]);
+                text_painter.text(
+                    pos2(cell_rect.left() + CELL_PADDING, cell_rect.center().y),
+                    Align2::LEFT_CENTER,
+                    text,
+                    cell_font(),
+                    text_color,
+                );
+                truncated
+            };
+
+            if options.cursor == Some((display_index, col_index)) {
+                painter.rect_stroke(
+                    cell_rect.shrink(1.0),
+                    Rounding::same(3.0),
+                    egui::Stroke::new(2.0, palette.accent),
+                );
+            }
+
+            let editable = col_index < options.editable_columns;
+            // Show full text on hover if truncated
+            let mut response = ui.interact(
+                target,
+                geometry.grid_id.with(("cell", row_index, col_index)),
+                Sense::click(),
+            );
+            if truncated {
+                response = response.on_hover_text(cell);
+            }
+            if response.clicked() {
+                events.cell_clicked = Some((display_index, col_index));
+            }
+            if response.double_clicked() {
+                if editable && !is_structured(cell) {
+                    events.edit_requested = Some((row_index, col_index));
+                } else {
+                    events.view_requested = Some((row_index, col_index));
+                }
+            }
+            response.context_menu(|ui| {
+                if ui.button(tr!("grid-view-cell")).clicked() {
+                    events.view_requested = Some((row_index, col_index));
+                    ui.close_menu();
+                }
+            });
+        }
+    }
+    events
+}
+
+/// A text field in place of the cell. Enter or clicking elsewhere keeps the
+/// new value, Escape drops it.
+fn render_editor(ui: &mut egui::Ui, editor: &mut CellEditor) -> Option<EditorOutcome> {
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut editor.text)
+            .desired_width(ui.available_width())
+            .font(egui::TextStyle::Body),
+    );
+    if !editor.focused {
+        response.request_focus();
+        editor.focused = true;
+    }
+    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+        Some(EditorOutcome::Cancel)
+    } else if response.lost_focus() {
+        Some(EditorOutcome::Commit)
+    } else {
+        None
+    }
+}
+
+/// Menu button listing the columns of a table, to show, hide and freeze
+/// them. For tables with many columns the list can be filtered.
+pub fn column_menu(ui: &mut egui::Ui, headers: &[String], layout: &mut ColumnLayout) {
+    layout.sync(headers.len());
+    let label = match layout.hidden_count() {
+        0 => tr!("columns-menu"),
+        hidden => tr!("columns-menu-hidden", count = hidden),
+    };
+    ui.menu_button(label, |ui| {
+        ui.horizontal(|ui| {
+            if ui.button(tr!("columns-show-all")).clicked() {
+                layout.hidden.fill(false);
+            }
+            if ui.button(tr!("columns-hide-all")).clicked() {
+                layout.hidden.fill(true);
+            }
+            if layout.frozen > 0 && ui.button(tr!("columns-unfreeze")).clicked() {
+                layout.frozen = 0;
+            }
+            if ui
+                .button(tr!("columns-reset"))
+                .on_hover_text(tr!("columns-reset-hint"))
+                .clicked()
+            {
+                *layout = ColumnLayout::default();
+                layout.sync(headers.len());
+            }
+        });
+        ui.add(
+            egui::TextEdit::singleline(&mut layout.filter)
+                .hint_text(tr!("columns-find"))
+                .desired_width(220.0),
+        );
+        ui.separator();
+
+        let filter = layout.filter.to_lowercase();
+        egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+            for position in 0..layout.order.len() {
+                let column = layout.order[position];
+                if !headers[column].to_lowercase().contains(&filter) {
+                    continue;
+                }
+                let mut shown = !layout.hidden[column];
+                let name = if position < layout.frozen {
+                    format!("🔒 {}", headers[column])
+                } else {
+                    headers[column].clone()
+                };
+                if ui.checkbox(&mut shown, name).changed() {
+                    layout.hidden[column] = !shown;
+                }
+            }
+        });
+        ui.separator();
+        ui.label(
+            egui::RichText::new(tr!("columns-help"))
+                .color(theme::palette(ui.ctx()).muted)
+                .size(11.0),
+        );
+    });
+}
+
+/// Text color readable on `background`, which may be a highlight of any
+/// brightness.
+fn text_on(background: Color32, palette: &Palette) -> Color32 {
+    let luminance = 0.299 * background.r() as f32
+        + 0.587 * background.g() as f32
+        + 0.114 * background.b() as f32;
+    match (luminance > 140.0, palette.dark) {
+        (true, true) => Color32::from_rgb(24, 25, 28),
+        (false, false) => Color32::from_rgb(235, 235, 235),
+        _ => palette.text,
+    }
+}
+
+fn cell_font() -> FontId {
+    FontId::proportional(12.0)
+}
+
+fn index_font() -> FontId {
+    FontId::monospace(11.0)
+}
+
+/// The text shown in a cell: its first line, cut short after `limit`
+/// characters (Unicode-safe). The flag is `true` when text was left out.
+fn display_text(cell: &str, limit: usize) -> (Cow<'_, str>, bool) {
+    let line = cell.lines().next().unwrap_or_default();
+    if line.chars().nth(limit).is_some() {
+        let kept: String = line.chars().take(limit.saturating_sub(3)).collect();
+        (format!("{}...", kept).into(), true)
+    } else if line.len() < cell.trim_end().len() {
+        (format!("{}...", line).into(), true)
+    } else {
+        (line.into(), false)
+    }
+}
+
+fn text_width(ui: &egui::Ui, text: &str, font: FontId) -> f32 {
+    ui.painter()
+        .layout_no_wrap(text.to_owned(), font, Color32::WHITE)
+        .size()
+        .x
+}
+
+/// Width that shows the header and the cells of `column` in `rows` in full.
+fn fit_width<'a>(
+    ui: &egui::Ui,
+    header: &str,
+    rows: impl Iterator<Item = &'a Vec<String>>,
+    column: usize,
+    limit: usize,
+) -> f32 {
+    let header = text_width(ui, &format!("🔒 {} ▲", header), FontId::proportional(12.0));
+    let cells = rows
+        .filter_map(|row| row.get(column))
+        .map(|cell| text_width(ui, &display_text(cell, limit).0, cell_font()))
+        .fold(0.0, f32::max);
+    header.max(cells) + 2.0 * CELL_PADDING + 4.0
+}
@@ -0,0 +1,299 @@
+use egui::{Color32, Context, Id, Margin, Rounding, Stroke, Vec2, Visuals};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+/// An sRGB color, as stored in the settings.
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThemeMode {
+    #[default]
+    Dark,
+    Light,
+    /// Follows the light or dark setting of the operating system.
+    System,
+}
+
+impl ThemeMode {
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::Dark, ThemeMode::Light, ThemeMode::System];
+
+    pub fn label(self) -> String {
+        match self {
+            ThemeMode::Dark => tr!("theme-dark"),
+            ThemeMode::Light => tr!("theme-light"),
+            ThemeMode::System => tr!("theme-system"),
+        }
+    }
+}
+
+/// Theme choices of the user, persisted in the settings.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub mode: ThemeMode,
+    /// Black or white backgrounds with stronger text and borders, for
+    /// monitors read from a distance.
+    pub high_contrast: bool,
+    /// Colors picked by the user in place of the theme's own.
+    pub stripe: Option<Rgb>,
+    pub header: Option<Rgb>,
+    pub accent: Option<Rgb>,
+}
+
+/// Colors of the interface, resolved from the theme settings and shared
+/// through the egui context (see [`palette`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub dark: bool,
+    pub high_contrast: bool,
+    pub panel: Color32,
+    /// Windows and the frame around tables.
+    pub window: Color32,
+    /// Cards and other raised areas.
+    pub card: Color32,
+    pub extreme_bg: Color32,
+    pub text: Color32,
+    /// Secondary text, such as hints and counts.
+    pub muted: Color32,
+    pub faint: Color32,
+    /// Backgrounds of even and odd table rows.
+    pub stripe_even: Color32,
+    pub stripe_odd: Color32,
+    /// Background of column headers.
+    pub header: Color32,
+    pub header_text: Color32,
+    /// Buttons, the row index header and highlights.
+    pub accent: Color32,
+    /// Text on `accent` and `success` backgrounds, such as the loading and
+    /// end-of-file badges.
+    pub on_accent: Color32,
+    pub success: Color32,
+    /// Border of buttons filled with `success`.
+    pub success_stroke: Color32,
+    pub warning: Color32,
+    /// Error text on the panel background.
+    pub error: Color32,
+    /// Background of the error banner, and the text on it.
+    pub error_bg: Color32,
+    pub on_error: Color32,
+}
+
+impl Palette {
+    /// Resolves `settings`; `system_dark` is the operating system setting,
+    /// if known.
+    pub fn new(settings: &ThemeSettings, system_dark: Option<bool>) -> Self {
+        let dark = match settings.mode {
+            ThemeMode::Dark => true,
+            ThemeMode::Light => false,
+            ThemeMode::System => system_dark.unwrap_or(true),
+        };
+        let mut palette = match (dark, settings.high_contrast) {
+            (true, false) => Self::dark(),
+            (false, false) => Self::light(),
+            (true, true) => Self::dark_high_contrast(),
+            (false, true) => Self::light_high_contrast(),
+        };
+        let rgb = |[r, g, b]: Rgb| Color32::from_rgb(r, g, b);
+        if let Some(stripe) = settings.stripe {
+            palette.stripe_even = rgb(stripe);
+            palette.stripe_odd = shade(rgb(stripe), if dark { -4 } else { -10 });
+        }
+        if let Some(header) = settings.header {
+            palette.header = rgb(header);
+        }
+        if let Some(accent) = settings.accent {
+            palette.accent = rgb(accent);
+        }
+        palette
+    }
+
+    fn dark() -> Self {
+        Self {
+            dark: true,
+            high_contrast: false,
+            panel: Color32::from_rgb(32, 33, 36),
+            window: Color32::from_rgb(40, 42, 46),
+            card: Color32::from_rgb(48, 50, 54),
+            extreme_bg: Color32::from_rgb(24, 25, 28),
+            text: Color32::from_rgb(220, 220, 220),
+            muted: Color32::from_rgb(156, 163, 175),
+            faint: Color32::from_rgb(120, 120, 120),
+            stripe_even: Color32::from_rgb(48, 50, 54),
+            stripe_odd: Color32::from_rgb(44, 46, 50),
+            header: Color32::from_rgb(76, 175, 80),
+            header_text: Color32::WHITE,
+            accent: Color32::from_rgb(66, 133, 244),
+            on_accent: Color32::WHITE,
+            success: Color32::from_rgb(76, 175, 80),
+            success_stroke: Color32::from_rgb(56, 142, 60),
+            warning: Color32::from_rgb(255, 193, 7),
+            error: Color32::from_rgb(255, 120, 120),
+            error_bg: Color32::from_rgb(220, 53, 69),
+            on_error: Color32::WHITE,
+        }
+    }
+
+    fn light() -> Self {
+        Self {
+            dark: false,
+            high_contrast: false,
+            panel: Color32::from_rgb(243, 244, 246),
+            window: Color32::from_rgb(252, 252, 253),
+            card: Color32::from_rgb(255, 255, 255),
+            extreme_bg: Color32::from_rgb(232, 234, 237),
+            text: Color32::from_rgb(32, 33, 36),
+            muted: Color32::from_rgb(95, 99, 104),
+            faint: Color32::from_rgb(128, 134, 139),
+            stripe_even: Color32::from_rgb(255, 255, 255),
+            stripe_odd: Color32::from_rgb(245, 246, 248),
+            header: Color32::from_rgb(46, 125, 50),
+            header_text: Color32::WHITE,
+            accent: Color32::from_rgb(26, 115, 232),
+            on_accent: Color32::WHITE,
+            success: Color32::from_rgb(46, 125, 50),
+            success_stroke: Color32::from_rgb(27, 94, 32),
+            warning: Color32::from_rgb(176, 110, 0),
+            error: Color32::from_rgb(197, 34, 31),
+            error_bg: Color32::from_rgb(220, 53, 69),
+            on_error: Color32::WHITE,
+        }
+    }
+
+    fn dark_high_contrast() -> Self {
+        Self {
+            high_contrast: true,
+            panel: Color32::BLACK,
+            window: Color32::BLACK,
+            card: Color32::from_rgb(20, 20, 20),
+            extreme_bg: Color32::BLACK,
+            text: Color32::WHITE,
+            muted: Color32::from_rgb(220, 220, 220),
+            faint: Color32::from_rgb(190, 190, 190),
+            stripe_even: Color32::BLACK,
+            stripe_odd: Color32::from_rgb(30, 30, 30),
+            header: Color32::from_rgb(0, 100, 0),
+            accent: Color32::from_rgb(0, 90, 200),
+            success: Color32::from_rgb(80, 230, 80),
+            success_stroke: Color32::WHITE,
+            warning: Color32::from_rgb(255, 220, 0),
+            error: Color32::from_rgb(255, 100, 100),
+            error_bg: Color32::from_rgb(170, 0, 0),
+            ..Self::dark()
+        }
+    }
+
+    fn light_high_contrast() -> Self {
+        Self {
+            high_contrast: true,
+            panel: Color32::WHITE,
+            window: Color32::WHITE,
+            card: Color32::WHITE,
+            extreme_bg: Color32::from_rgb(235, 235, 235),
+            text: Color32::BLACK,
+            muted: Color32::from_rgb(40, 40, 40),
+            faint: Color32::from_rgb(70, 70, 70),
+            stripe_even: Color32::WHITE,
+            stripe_odd: Color32::from_rgb(225, 225, 225),
+            header: Color32::from_rgb(0, 80, 0),
+            accent: Color32::from_rgb(0, 60, 170),
+            success: Color32::from_rgb(0, 100, 0),
+            success_stroke: Color32::BLACK,
+            warning: Color32::from_rgb(130, 80, 0),
+            error: Color32::from_rgb(170, 0, 0),
+            error_bg: Color32::from_rgb(170, 0, 0),
+            ..Self::light()
+        }
+    }
+
+    /// Background of the odd columns of a row, a little apart from `row`.
+    pub fn column_tint(&self, row: Color32) -> Color32 {
+        shade(row, if self.dark { 4 } else { -4 })
+    }
+
+    /// Applies the palette to the egui style and shares it with the rest of
+    /// the interface.
+    pub fn apply(&self, ctx: &Context) {
+        let mut style = (*ctx.style()).clone();
+        style.visuals = if self.dark {
+            Visuals::dark()
+        } else {
+            Visuals::light()
+        };
+
+        style.visuals.override_text_color = Some(self.text);
+        style.visuals.panel_fill = self.panel;
+        style.visuals.window_fill = self.window;
+        style.visuals.extreme_bg_color = self.extreme_bg;
+        style.visuals.code_bg_color = self.card;
+        style.visuals.warn_fg_color = self.warning;
+        style.visuals.error_fg_color = self.error;
+
+        // Button styling; light themes use a pale accent so the dark text
+        // stays readable
+        let button = if self.dark {
+            self.accent
+        } else {
+            blend(self.accent, Color32::WHITE, 0.7)
+        };
+        style.visuals.widgets.inactive.bg_fill = button;
+        style.visuals.widgets.hovered.bg_fill = shade(button, 20);
+        style.visuals.widgets.active.bg_fill = shade(button, -20);
+        style.visuals.selection.bg_fill = self.accent.gamma_multiply(0.6);
+        if self.high_contrast {
+            let stroke = Stroke::new(1.5, self.text);
+            style.visuals.widgets.noninteractive.bg_stroke = stroke;
+            style.visuals.widgets.inactive.bg_stroke = stroke;
+            style.visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, self.warning);
+            style.visuals.selection.stroke = Stroke::new(2.0, self.text);
+        }
+
+        // Rounded corners
+        style.visuals.widgets.inactive.rounding = Rounding::same(8.0);
+        style.visuals.widgets.hovered.rounding = Rounding::same(8.0);
+        style.visuals.widgets.active.rounding = Rounding::same(8.0);
+        style.visuals.window_rounding = Rounding::same(12.0);
+
+        // Spacing
+        style.spacing.item_spacing = Vec2::new(8.0, 6.0);
+        style.spacing.button_padding = Vec2::new(16.0, 8.0);
+        style.spacing.menu_margin = Margin::same(8.0);
+
+        ctx.set_style(style);
+        ctx.data_mut(|data| data.insert_temp(Id::new(PALETTE_ID), *self));
+    }
+}
+
+/// A color picker for one palette color, starting from the color in use,
+/// with a button to go back to the theme's own.
+pub fn color_setting(ui: &mut egui::Ui, label: &str, value: &mut Option<Rgb>, current: Color32) {
+    ui.horizontal(|ui| {
+        let mut rgb = value.unwrap_or([current.r(), current.g(), current.b()]);
+        if ui.color_edit_button_srgb(&mut rgb).changed() {
+            *value = Some(rgb);
+        }
+        ui.label(label);
+        if value.is_some() && ui.small_button("↺").on_hover_text(tr!("theme-reset")).clicked() {
+            *value = None;
+        }
+    });
+}
+
+const PALETTE_ID: &str = "theme_palette";
+
+/// The palette last applied, or the dark one before any was.
+pub fn palette(ctx: &Context) -> Palette {
+    ctx.data(|data| data.get_temp(Id::new(PALETTE_ID)))
+        .unwrap_or_else(Palette::dark)
+}
+
+/// `color` made lighter (positive `amount`) or darker.
+fn shade(color: Color32, amount: i16) -> Color32 {
+    let channel = |c: u8| (c as i16 + amount).clamp(0, 255) as u8;
+    Color32::from_rgb(channel(color.r()), channel(color.g()), channel(color.b()))
+}
+
+fn blend(a: Color32, b: Color32, t: f32) -> Color32 {
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    Color32::from_rgb(mix(a.r(), b.r()), mix(a.g(), b.g()), mix(a.b(), b.b()))
+}